@@ -6,7 +6,7 @@ use tokio::{join, task::spawn_blocking, time::sleep};
 #[tokio::main]
 async fn main() -> Result<(), MinusError> {
     let mut output = minus::Pager::new();
-    let output2 = output.clone();
+    let pager_handle = minus::dynamic_paging(output.clone());
 
     let increment = async {
         for i in 0..=100_u32 {
@@ -16,10 +16,7 @@ async fn main() -> Result<(), MinusError> {
         Result::<_, MinusError>::Ok(())
     };
 
-    let (res1, res2) = join!(
-        spawn_blocking(move || minus::dynamic_paging(output2)),
-        increment
-    );
+    let (res1, res2) = join!(spawn_blocking(move || pager_handle.join()), increment);
     res1.unwrap()?;
     res2?;
     Ok(())
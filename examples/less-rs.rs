@@ -10,7 +10,6 @@
 use std::env::args;
 use std::fs::File;
 use std::io::{BufReader, Read};
-use std::thread;
 
 fn read_file(name: String, pager: minus::Pager) -> Result<(), Box<dyn std::error::Error>> {
     let file = File::open(name)?;
@@ -22,10 +21,9 @@ fn read_file(name: String, pager: minus::Pager) -> Result<(), Box<dyn std::error
         Result::<(), Box<dyn std::error::Error>>::Ok(())
     };
 
-    let pager = pager.clone();
-    let res1 = thread::spawn(|| minus::dynamic_paging(pager));
+    let pager_handle = minus::dynamic_paging(pager.clone());
     let res2 = changes();
-    res1.join().unwrap()?;
+    pager_handle.join()?;
     res2?;
     Ok(())
 }
@@ -15,11 +15,8 @@ async fn main() -> Result<(), MinusError> {
         Result::<_, MinusError>::Ok(())
     };
 
-    let output = output.clone();
-    let (res1, res2) = join!(
-        spawn_blocking(move || minus::dynamic_paging(output)),
-        increment
-    );
+    let pager_handle = minus::dynamic_paging(output.clone());
+    let (res1, res2) = join!(spawn_blocking(move || pager_handle.join()), increment);
     res1.unwrap()?;
     res2?;
     Ok(())
@@ -0,0 +1,15 @@
+use minus::{error::MinusError, ExitReason};
+
+fn main() -> Result<(), MinusError> {
+    let colors = ["red", "green", "blue", "yellow", "magenta", "cyan"];
+
+    let output = minus::Pager::new();
+    output.set_text(colors.join("\n"))?;
+    output.set_prompt("Pick a color")?;
+    output.set_picker_mode(true)?;
+
+    if let ExitReason::Picked(idx) = minus::page_all(output)? {
+        println!("You picked {}", colors[idx]);
+    }
+    Ok(())
+}
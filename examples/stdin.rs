@@ -0,0 +1,9 @@
+// Pipe some data into this example, for example `ls -l | cargo run --example stdin --features=dynamic_output`
+use minus::error::MinusError;
+
+fn main() -> Result<(), MinusError> {
+    let output = minus::Pager::new();
+    output.set_prompt("stdin")?;
+    minus::page_stdin(output)?;
+    Ok(())
+}
@@ -0,0 +1,186 @@
+//! Provides paging several texts in one pager session
+//!
+//! Only the displayed buffer keeps its contents in the [`PagerState`]. The contents of the other
+//! buffers are stored away and exchanged with it whenever a different buffer is displayed. See
+//! [`Pager::add_buffer`](crate::Pager::add_buffer) for more info.
+
+#[cfg(feature = "search")]
+use std::collections::BTreeSet;
+use std::{collections::BTreeMap, mem, ops::Range};
+
+use crossterm::style::ContentStyle;
+
+use crate::{
+    diff::Diff,
+    minus_core::utils::{display::Frame, LinesRowMap},
+    screen::{Folds, LineProvider, Rows, Text},
+    sources::Sources,
+    state::JumpList,
+    table::Table,
+    PagerState, Sign,
+};
+
+/// The buffers of the pager
+pub struct Buffers {
+    /// Names of the buffers in the order in which they were added
+    names: Vec<String>,
+    /// Contents of the buffers which aren't loaded into the state
+    ///
+    /// The entry of the loaded buffer is empty as its contents are in the state.
+    contents: Vec<Contents>,
+    /// Index of the buffer whose contents are in the state
+    loaded: usize,
+}
+
+impl Default for Buffers {
+    fn default() -> Self {
+        Self {
+            names: vec![String::new()],
+            contents: vec![Contents::default()],
+            loaded: 0,
+        }
+    }
+}
+
+impl Buffers {
+    /// Add an empty buffer called `name` after all others, unless there already is one
+    pub fn add(&mut self, name: String) {
+        if self.position(&name).is_none() {
+            self.names.push(name);
+            self.contents.push(Contents::default());
+        }
+    }
+
+    /// Index of the buffer called `name`
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    /// Number of buffers
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Index of the buffer whose contents are in the state
+    pub const fn loaded(&self) -> usize {
+        self.loaded
+    }
+
+    /// Name of the buffer whose contents are in the state
+    pub fn loaded_name(&self) -> &str {
+        &self.names[self.loaded]
+    }
+
+    /// Rename the buffer whose contents are in the state, unless `name` is taken
+    pub fn rename_loaded(&mut self, name: String) {
+        if self.position(&name).is_none() {
+            self.names[self.loaded] = name;
+        }
+    }
+}
+
+/// Everything that belongs to the text of a single buffer
+#[derive(Default)]
+struct Contents {
+    orig_text: Text,
+    formatted_lines: Rows,
+    line_count: usize,
+    max_line_length: usize,
+    unterminated: usize,
+    line_number_anchor: usize,
+    discarded_lines: usize,
+    signs: BTreeMap<usize, Sign>,
+    folds: Folds,
+    line_provider: Option<Box<dyn LineProvider>>,
+    provider_window_start: usize,
+    upper_mark: usize,
+    left_mark: usize,
+    selected_line: Option<usize>,
+    lines_to_row_map: LinesRowMap,
+    table: Table,
+    diff: Diff,
+    sources: Sources,
+    line_highlights: Vec<(Range<usize>, ContentStyle)>,
+    sections: BTreeMap<usize, String>,
+    data_finished: bool,
+    jump_list: JumpList,
+    displayed_frame: Option<Frame>,
+    #[cfg(feature = "search")]
+    search_idx: BTreeSet<usize>,
+    #[cfg(feature = "search")]
+    search_mark: usize,
+}
+
+impl Contents {
+    /// Exchange these contents with the ones in the state
+    fn swap(&mut self, ps: &mut PagerState) {
+        mem::swap(&mut self.orig_text, &mut ps.screen.orig_text);
+        mem::swap(&mut self.formatted_lines, &mut ps.screen.formatted_lines);
+        mem::swap(&mut self.line_count, &mut ps.screen.line_count);
+        mem::swap(&mut self.max_line_length, &mut ps.screen.max_line_length);
+        mem::swap(&mut self.unterminated, &mut ps.screen.unterminated);
+        mem::swap(
+            &mut self.line_number_anchor,
+            &mut ps.screen.line_number_anchor,
+        );
+        mem::swap(&mut self.discarded_lines, &mut ps.screen.discarded_lines);
+        mem::swap(&mut self.signs, &mut ps.screen.signs);
+        mem::swap(&mut self.folds, &mut ps.screen.folds);
+        mem::swap(&mut self.line_provider, &mut ps.screen.line_provider);
+        mem::swap(
+            &mut self.provider_window_start,
+            &mut ps.screen.provider_window_start,
+        );
+        mem::swap(&mut self.upper_mark, &mut ps.upper_mark);
+        mem::swap(&mut self.left_mark, &mut ps.left_mark);
+        mem::swap(&mut self.selected_line, &mut ps.selected_line);
+        mem::swap(&mut self.lines_to_row_map, &mut ps.lines_to_row_map);
+        mem::swap(&mut self.table, &mut ps.table);
+        mem::swap(&mut self.diff, &mut ps.diff);
+        mem::swap(&mut self.sources, &mut ps.sources);
+        mem::swap(&mut self.line_highlights, &mut ps.line_highlights);
+        mem::swap(&mut self.sections, &mut ps.sections);
+        mem::swap(&mut self.data_finished, &mut ps.data_finished);
+        mem::swap(&mut self.jump_list, &mut ps.jump_list);
+        mem::swap(&mut self.displayed_frame, &mut ps.displayed_frame);
+        #[cfg(feature = "search")]
+        mem::swap(&mut self.search_idx, &mut ps.search_state.search_idx);
+        #[cfg(feature = "search")]
+        mem::swap(&mut self.search_mark, &mut ps.search_state.search_mark);
+    }
+}
+
+impl PagerState {
+    /// Exchange the contents in the state with the ones of the buffer at `index`
+    ///
+    /// Nothing is formatted again, so this is cheap enough to do for every command that is sent
+    /// to a buffer which isn't displayed.
+    pub(crate) fn load_buffer(&mut self, index: usize) {
+        if index == self.buffers.loaded || index >= self.buffers.len() {
+            return;
+        }
+        let loaded = self.buffers.loaded;
+        let mut contents = mem::take(&mut self.buffers.contents[loaded]);
+        contents.swap(self);
+        self.buffers.contents[loaded] = contents;
+
+        let mut contents = mem::take(&mut self.buffers.contents[index]);
+        contents.swap(self);
+        self.buffers.contents[index] = contents;
+        self.buffers.loaded = index;
+    }
+
+    /// Display the buffer at `index`
+    ///
+    /// Returns whether a different buffer is displayed now.
+    pub(crate) fn display_buffer(&mut self, index: usize) -> bool {
+        if index == self.buffers.loaded || index >= self.buffers.len() {
+            return false;
+        }
+        self.load_buffer(index);
+        // The terminal or the options might have changed while the buffer was stored away
+        self.format_lines();
+        self.displayed_frame = None;
+        true
+    }
+}
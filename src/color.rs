@@ -0,0 +1,245 @@
+//! Adapts the colors of the styles used by minus to what the terminal can display
+//!
+//! See [`Pager::set_color_support`](crate::Pager::set_color_support) for more info.
+
+use crossterm::style::{Color, ContentStyle};
+use std::convert::TryFrom;
+
+/// Colors that the terminal is able to display
+///
+/// Colors of the styles used by minus, like the styles of the prompt, highlights and signs, that
+/// the terminal can't display are replaced by the closest color that it can. The text itself is
+/// displayed as is.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum ColorSupport {
+    /// Any RGB color.
+    ///
+    /// **This is the default** if `COLORTERM` is set to `truecolor` or `24bit`.
+    TrueColor,
+    /// The 256 colors of the xterm palette.
+    Ansi256,
+    /// The 16 basic colors, which are usually picked by the theme of the terminal.
+    Ansi16,
+    /// No colors at all. Text attributes like bold or reversed text are still displayed.
+    ///
+    /// **This is the default** if `TERM` is `dumb` or `NO_COLOR` is set to a non-empty value.
+    None,
+}
+
+/// RGB values of the 16 basic colors as displayed by xterm
+const BASIC_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Levels of each component of the colors in the 6x6x6 cube of the xterm palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Returns `true` if the user asked for output without colors by setting `NO_COLOR`
+pub fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").map_or(false, |val| !val.is_empty())
+}
+
+impl ColorSupport {
+    /// Guess the colors that the terminal supports from the environment variables
+    pub(crate) fn detect() -> Self {
+        let term = std::env::var("TERM").unwrap_or_default();
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        if no_color() || term == "dumb" {
+            Self::None
+        } else if colorterm == "truecolor"
+            || colorterm == "24bit"
+            || term.ends_with("-direct")
+            || (term.is_empty() && cfg!(windows))
+        {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// Replace the colors of `style` that the terminal can't display
+    pub(crate) fn adapt(self, style: ContentStyle) -> ContentStyle {
+        ContentStyle {
+            foreground_color: style.foreground_color.and_then(|c| self.adapt_color(c)),
+            background_color: style.background_color.and_then(|c| self.adapt_color(c)),
+            underline_color: style.underline_color.and_then(|c| self.adapt_color(c)),
+            attributes: style.attributes,
+        }
+    }
+
+    fn adapt_color(self, color: Color) -> Option<Color> {
+        match (self, color) {
+            (Self::None, _) => None,
+            (Self::Ansi256, Color::Rgb { r, g, b }) => Some(Color::AnsiValue(nearest_256(r, g, b))),
+            (Self::Ansi16, Color::Rgb { r, g, b }) => Some(basic_color(nearest_16(r, g, b))),
+            (Self::Ansi16, Color::AnsiValue(value)) => {
+                let (r, g, b) = palette_rgb(value);
+                Some(basic_color(nearest_16(r, g, b)))
+            }
+            _ => Some(color),
+        }
+    }
+}
+
+/// Returns the 16 color palette entry closest to the given color
+fn nearest_16(r: u8, g: u8, b: u8) -> u8 {
+    (0..16)
+        .min_by_key(|idx| distance(BASIC_COLORS[usize::from(*idx)], (r, g, b)))
+        .unwrap_or(0)
+}
+
+/// Returns the entry of the color cube or the grayscale ramp of the 256 color palette closest to
+/// the given color
+///
+/// The first 16 entries are left out since their actual colors depend on the theme of the
+/// terminal.
+fn nearest_256(r: u8, g: u8, b: u8) -> u8 {
+    let cube_idx = |c: u8| {
+        (0..6u8)
+            .min_by_key(|idx| CUBE_LEVELS[usize::from(*idx)].abs_diff(c))
+            .unwrap_or(0)
+    };
+    let cube = 16 + 36 * cube_idx(r) + 6 * cube_idx(g) + cube_idx(b);
+    let avg = u8::try_from((u16::from(r) + u16::from(g) + u16::from(b)) / 3).unwrap_or(u8::MAX);
+    let gray = 232 + (avg.saturating_sub(3) / 10).min(23);
+    if distance(palette_rgb(gray), (r, g, b)) < distance(palette_rgb(cube), (r, g, b)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// Returns the RGB values of an entry of the 256 color palette
+fn palette_rgb(value: u8) -> (u8, u8, u8) {
+    match value {
+        0..=15 => BASIC_COLORS[usize::from(value)],
+        16..=231 => {
+            let idx = value - 16;
+            (
+                CUBE_LEVELS[usize::from(idx / 36)],
+                CUBE_LEVELS[usize::from(idx / 6 % 6)],
+                CUBE_LEVELS[usize::from(idx % 6)],
+            )
+        }
+        _ => {
+            let level = 8 + (value - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let diff = |x: u8, y: u8| u32::from(x.abs_diff(y)).pow(2);
+    diff(a.0, b.0) + diff(a.1, b.1) + diff(a.2, b.2)
+}
+
+/// Returns the named color of an entry of the 16 color palette
+const fn basic_color(value: u8) -> Color {
+    match value {
+        0 => Color::Black,
+        1 => Color::DarkRed,
+        2 => Color::DarkGreen,
+        3 => Color::DarkYellow,
+        4 => Color::DarkBlue,
+        5 => Color::DarkMagenta,
+        6 => Color::DarkCyan,
+        7 => Color::Grey,
+        8 => Color::DarkGrey,
+        9 => Color::Red,
+        10 => Color::Green,
+        11 => Color::Yellow,
+        12 => Color::Blue,
+        13 => Color::Magenta,
+        14 => Color::Cyan,
+        _ => Color::White,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ColorSupport;
+    use crossterm::style::{Color, ContentStyle, Stylize};
+
+    #[test]
+    fn true_color() {
+        let style = ContentStyle::new().with(Color::Rgb { r: 1, g: 2, b: 3 });
+        assert_eq!(ColorSupport::TrueColor.adapt(style), style);
+    }
+
+    #[test]
+    fn ansi_256() {
+        let style = ContentStyle::new()
+            .with(Color::Rgb {
+                r: 255,
+                g: 135,
+                b: 0,
+            })
+            .on(Color::Rgb {
+                r: 100,
+                g: 100,
+                b: 102,
+            })
+            .bold();
+        assert_eq!(
+            ColorSupport::Ansi256.adapt(style),
+            ContentStyle::new()
+                .with(Color::AnsiValue(208))
+                .on(Color::AnsiValue(241))
+                .bold()
+        );
+        // Colors of the palette are kept
+        let style = ContentStyle::new()
+            .with(Color::AnsiValue(100))
+            .on(Color::Red);
+        assert_eq!(ColorSupport::Ansi256.adapt(style), style);
+    }
+
+    #[test]
+    fn ansi_16() {
+        let style = ContentStyle::new()
+            .with(Color::Rgb {
+                r: 250,
+                g: 10,
+                b: 20,
+            })
+            .on(Color::AnsiValue(21))
+            .underline(Color::DarkGreen);
+        assert_eq!(
+            ColorSupport::Ansi16.adapt(style),
+            ContentStyle::new()
+                .with(Color::Red)
+                .on(Color::DarkBlue)
+                .underline(Color::DarkGreen)
+        );
+    }
+
+    #[test]
+    fn no_colors() {
+        let style = ContentStyle::new()
+            .with(Color::Black)
+            .on(Color::Rgb { r: 1, g: 2, b: 3 })
+            .reverse();
+        assert_eq!(
+            ColorSupport::None.adapt(style),
+            ContentStyle::new().reverse()
+        );
+    }
+}
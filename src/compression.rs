@@ -0,0 +1,101 @@
+//! Provides transparent decompression of compressed files
+//!
+//! Compressed files are recognized by the magic bytes at their start, so their names don't need
+//! to end in a particular extension. They are decompressed while they are being read instead of
+//! all at once.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+};
+
+use flate2::read::MultiGzDecoder;
+use ruzstd::decoding::StreamingDecoder;
+
+/// Magic bytes at the start of gzip files
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+/// Magic bytes at the start of zstd frames
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Open the file at `path` for decompressing it if it is compressed
+///
+/// This returns [`None`] if the file doesn't start with the magic bytes of a supported
+/// compression format.
+pub fn open_compressed(path: &Path) -> io::Result<Option<Box<dyn Read + Send>>> {
+    let mut file = File::open(path)?;
+    let mut magic = Vec::with_capacity(ZSTD_MAGIC.len());
+    file.by_ref()
+        .take(ZSTD_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+    let is_gzip = magic.starts_with(GZIP_MAGIC);
+    let is_zstd = magic.starts_with(ZSTD_MAGIC);
+    // Put the magic bytes back in front of the rest of the file for the decoders
+    let reader = BufReader::new(io::Cursor::new(magic).chain(file));
+
+    if is_gzip {
+        // Rotated logs are sometimes made up of several concatenated gzip members
+        Ok(Some(Box::new(MultiGzDecoder::new(reader))))
+    } else if is_zstd {
+        let decoder = StreamingDecoder::new(reader)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Some(Box::new(decoder)))
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::open_compressed;
+    use std::io::{Read, Write};
+
+    fn decompress(name: &str, contents: &[u8]) -> Option<String> {
+        let path = std::env::temp_dir().join(format!(
+            "minus-compression-test-{}-{name}",
+            std::process::id()
+        ));
+        std::fs::write(&path, contents).unwrap();
+        let reader = open_compressed(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        reader.map(|mut reader| {
+            let mut text = String::new();
+            reader.read_to_string(&mut text).unwrap();
+            text
+        })
+    }
+
+    #[test]
+    fn gzip() {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"first\nsecond\n").unwrap();
+        let mut compressed = encoder.finish().unwrap();
+        // Concatenated gzip files are valid gzip files too
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"third\n").unwrap();
+        compressed.extend(encoder.finish().unwrap());
+
+        assert_eq!(
+            decompress("gz", &compressed).as_deref(),
+            Some("first\nsecond\nthird\n")
+        );
+    }
+
+    #[test]
+    fn zstd() {
+        let compressed = ruzstd::encoding::compress_to_vec(
+            &b"first\nsecond\n"[..],
+            ruzstd::encoding::CompressionLevel::Fastest,
+        );
+        assert_eq!(
+            decompress("zst", &compressed).as_deref(),
+            Some("first\nsecond\n")
+        );
+    }
+
+    #[test]
+    fn uncompressed() {
+        assert_eq!(decompress("txt", b"plain text\n"), None);
+        assert_eq!(decompress("empty", b""), None);
+    }
+}
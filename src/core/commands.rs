@@ -3,93 +3,403 @@
 //! This module only declares the [Command] type. To know how they are handled internally see
 //! the [`ev_handler`](super::ev_handler).
 
-use std::fmt::Debug;
+use std::{fmt::Debug, io::Write, ops::Range, time::Duration};
 
 use crate::{
-    input::{InputClassifier, InputEvent},
-    ExitStrategy, LineNumbers,
+    diff::Diff,
+    input::{EventSource, InputClassifier, InputEvent},
+    screen::{LineFormatter, LineNumberFormatter, LineProvider, Sign},
+    state::{CustomCommandCallback, ExitVeto, SavePathHook, UnhandledKeyCallback},
+    Cell, ColorSupport, ControlChars, ExitStrategy, InterruptStrategy, LineNumberContinuation,
+    LineNumbers, MessageLevel, Position, PromptMode, Theme, Viewport,
 };
+use crossterm::{event::KeyEvent, style::ContentStyle};
+use parking_lot::Mutex;
 
 #[cfg(feature = "search")]
 use crate::search::SearchOpts;
+#[cfg(feature = "search")]
+use regex::Regex;
 
 /// Different events that can be encountered while the pager is running
 #[non_exhaustive]
 pub enum Command {
     // User input
     UserInput(InputEvent),
+    RunCustom(String),
+    Stop,
 
     // Data related
     AppendData(String),
     SetData(String),
+    Truncate(usize),
+    EditLines(Range<usize>, String),
+    PushRow(Vec<Cell>),
+    AddSource(String, ContentStyle),
+    AppendSourceData(String, String),
+    ShowSource(String, bool),
+    AddBuffer(String),
+    InBuffer(String, Box<Self>),
+    ShowBuffer(String),
+    SetDiff(Diff),
+    SetBufferName(String),
+    DataFinished,
+    SetLineProvider(Box<dyn LineProvider>),
+    BeginUpdate,
+    EndUpdate,
 
     // Prompt related
-    SendMessage(String),
+    SendMessage(MessageLevel, String, Option<Duration>),
     ShowPrompt(bool),
+    ShowPercentage(bool),
+    ShowScrollbar(bool),
+    SetSplitView(bool),
+    SetProgress(Option<f32>),
+    SetWaitingPlaceholder(Option<String>, bool),
+    SetFiller(Option<String>),
+    SetScrollOff(usize),
+    SetPageOverlap(usize),
+    SetScrollAmounts(usize, usize),
+    SetMouseCapture(bool),
+    SetUnfocusedPause(bool, bool),
+    SetInitialPosition(Position),
+    SetStatus(Vec<String>),
     SetPrompt(String),
+    SetPromptFormat(String),
+    SetPromptStyle(PromptMode, ContentStyle),
+    // Boxed as the theme is much larger than the other commands
+    SetTheme(Box<Theme>),
 
     // Screen output configurations
     LineWrapping(bool),
     SetLineNumbers(LineNumbers),
+    SetLineNumberFormat(LineNumberFormatter),
+    SetLineFormatter(LineFormatter),
+    SetLineNumberContinuation(LineNumberContinuation),
+    SetLineNumberStart(usize),
+    SetTabWidth(usize),
+    SetControlChars(ControlChars),
+    SetStripAnsi(bool),
+    SetNormalizeLineEndings(bool),
+    SetColorSupport(ColorSupport),
+    ShowInvisibles(bool),
+    SetWrapIndicator(String, String),
     FollowOutput(bool),
+    SetCursorLine(bool),
+    SetPickerMode(bool),
+    HighlightLines(Range<usize>, ContentStyle),
+    ClearHighlights,
+    SetHeaderLines(usize),
+    SetFrozenColumns(usize),
+    SetSigns(Vec<(usize, Sign)>),
+    ClearSigns,
+    AddFolds(Vec<Range<usize>>),
+    SetFoldByIndent(bool),
+    ClearFolds,
+    AddSections(Vec<(usize, String)>),
+    ClearSections,
+    #[cfg(feature = "search")]
+    AddColorRule(Regex, ContentStyle),
+    #[cfg(feature = "search")]
+    ClearColorRules,
 
     // Configuration options
     SetExitStrategy(ExitStrategy),
+    SetInterruptStrategy(InterruptStrategy),
     SetInputClassifier(Box<dyn InputClassifier + Send + Sync + 'static>),
+    PushInputClassifier(Box<dyn InputClassifier + Send + Sync + 'static>),
+    PopInputClassifier,
     AddExitCallback(Box<dyn FnMut() + Send + Sync + 'static>),
+    SetExitVeto(ExitVeto),
+    SetUnhandledKeyCallback(UnhandledKeyCallback),
+    AddCustom(String, KeyEvent, CustomCommandCallback),
+    SetSavePathHook(SavePathHook),
     #[cfg(feature = "static_output")]
     SetRunNoOverflow(bool),
+    SetPassthroughOnPipe(bool),
+    SetAlternateScreen(bool),
+    SetViewport(Option<Viewport>),
+    // The output and the input are wrapped in a mutex so that they don't need to be Sync
+    SetOutput(Mutex<Box<dyn Write + Send>>, u16, u16),
+    SetInput(Mutex<Box<dyn EventSource>>),
+    #[cfg(feature = "dynamic_output")]
+    SetQuitIfOneScreen(bool),
+    SetQuitAtEof(bool),
+    SetAppendBatching(Duration, usize),
+    SetResizePollInterval(Option<Duration>),
+    SetSynchronizedOutput(bool),
+    SetMaxRefreshRate(u32),
+    SetMaxLines(usize),
     #[cfg(feature = "search")]
     IncrementalSearchCondition(Box<dyn Fn(&SearchOpts) -> bool + Send + Sync + 'static>),
 
     // Internal commands
     FormatRedrawPrompt,
     FormatRedrawDisplay,
+    /// The time that the message on the prompt is shown for has passed
+    ExpireMessage,
+    /// The spinner shown while waiting for the data should move to its next frame
+    MoveSpinner,
+    /// The size of the terminal should be checked in case its resize wasn't reported
+    CheckTermSize,
+    /// The user moved down past the end of the data while quitting there is turned on
+    QuitAtEof,
+    /// The terminal gained or lost the focus
+    SetFocused(bool),
 }
 
 impl PartialEq for Command {
+    #[allow(clippy::too_many_lines)]
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::SetData(d1), Self::SetData(d2))
             | (Self::AppendData(d1), Self::AppendData(d2))
             | (Self::SetPrompt(d1), Self::SetPrompt(d2))
-            | (Self::SendMessage(d1), Self::SendMessage(d2)) => d1 == d2,
+            | (Self::SetPromptFormat(d1), Self::SetPromptFormat(d2))
+            | (Self::RunCustom(d1), Self::RunCustom(d2)) => d1 == d2,
+            (Self::PushRow(d1), Self::PushRow(d2)) => d1 == d2,
+            (Self::Truncate(d1), Self::Truncate(d2)) => d1 == d2,
+            (Self::EditLines(l1, t1), Self::EditLines(l2, t2)) => l1 == l2 && t1 == t2,
+            (Self::AddSource(n1, s1), Self::AddSource(n2, s2)) => n1 == n2 && s1 == s2,
+            (Self::AppendSourceData(n1, t1), Self::AppendSourceData(n2, t2)) => {
+                n1 == n2 && t1 == t2
+            }
+            (Self::ShowSource(n1, s1), Self::ShowSource(n2, s2)) => n1 == n2 && s1 == s2,
+            (Self::AddBuffer(n1), Self::AddBuffer(n2))
+            | (Self::ShowBuffer(n1), Self::ShowBuffer(n2))
+            | (Self::SetBufferName(n1), Self::SetBufferName(n2)) => n1 == n2,
+            (Self::InBuffer(n1, c1), Self::InBuffer(n2, c2)) => n1 == n2 && c1 == c2,
+            (Self::SendMessage(l1, t1, d1), Self::SendMessage(l2, t2, d2)) => {
+                l1 == l2 && t1 == t2 && d1 == d2
+            }
+            (Self::SetStatus(d1), Self::SetStatus(d2)) => d1 == d2,
+            (Self::SetProgress(d1), Self::SetProgress(d2)) => d1 == d2,
+            (Self::SetWaitingPlaceholder(t1, s1), Self::SetWaitingPlaceholder(t2, s2)) => {
+                t1 == t2 && s1 == s2
+            }
             (Self::LineWrapping(d1), Self::LineWrapping(d2)) => d1 == d2,
             (Self::SetLineNumbers(d1), Self::SetLineNumbers(d2)) => d1 == d2,
-            (Self::ShowPrompt(d1), Self::ShowPrompt(d2)) => d1 == d2,
+            (Self::SetLineNumberContinuation(d1), Self::SetLineNumberContinuation(d2)) => d1 == d2,
+            (Self::SetLineNumberStart(d1), Self::SetLineNumberStart(d2))
+            | (Self::SetTabWidth(d1), Self::SetTabWidth(d2)) => d1 == d2,
+            (Self::SetControlChars(d1), Self::SetControlChars(d2)) => d1 == d2,
+            (Self::SetColorSupport(d1), Self::SetColorSupport(d2)) => d1 == d2,
+            (Self::UserInput(d1), Self::UserInput(d2)) => d1 == d2,
+            (Self::AddCustom(n1, k1, _), Self::AddCustom(n2, k2, _)) => n1 == n2 && k1 == k2,
+            (Self::SetViewport(d1), Self::SetViewport(d2)) => d1 == d2,
+            (Self::SetOutput(_, c1, r1), Self::SetOutput(_, c2, r2)) => c1 == c2 && r1 == r2,
+            (Self::ShowPrompt(d1), Self::ShowPrompt(d2))
+            | (Self::ShowPercentage(d1), Self::ShowPercentage(d2))
+            | (Self::ShowScrollbar(d1), Self::ShowScrollbar(d2))
+            | (Self::SetSplitView(d1), Self::SetSplitView(d2))
+            | (Self::SetStripAnsi(d1), Self::SetStripAnsi(d2))
+            | (Self::SetNormalizeLineEndings(d1), Self::SetNormalizeLineEndings(d2))
+            | (Self::ShowInvisibles(d1), Self::ShowInvisibles(d2)) => d1 == d2,
+            (Self::SetWrapIndicator(p1, s1), Self::SetWrapIndicator(p2, s2)) => {
+                p1 == p2 && s1 == s2
+            }
+            (Self::SetPromptStyle(m1, s1), Self::SetPromptStyle(m2, s2)) => m1 == m2 && s1 == s2,
+            (Self::SetTheme(t1), Self::SetTheme(t2)) => t1 == t2,
+            (Self::HighlightLines(r1, s1), Self::HighlightLines(r2, s2)) => r1 == r2 && s1 == s2,
+            (Self::SetSigns(s1), Self::SetSigns(s2)) => s1 == s2,
+            (Self::AddFolds(r1), Self::AddFolds(r2)) => r1 == r2,
+            (Self::AddSections(s1), Self::AddSections(s2)) => s1 == s2,
+            (Self::SetDiff(d1), Self::SetDiff(d2)) => d1 == d2,
+            #[cfg(feature = "search")]
+            (Self::AddColorRule(r1, s1), Self::AddColorRule(r2, s2)) => {
+                r1.as_str() == r2.as_str() && s1 == s2
+            }
             (Self::SetExitStrategy(d1), Self::SetExitStrategy(d2)) => d1 == d2,
+            (Self::SetSynchronizedOutput(d1), Self::SetSynchronizedOutput(d2))
+            | (Self::SetPassthroughOnPipe(d1), Self::SetPassthroughOnPipe(d2))
+            | (Self::SetAlternateScreen(d1), Self::SetAlternateScreen(d2))
+            | (Self::SetCursorLine(d1), Self::SetCursorLine(d2))
+            | (Self::SetPickerMode(d1), Self::SetPickerMode(d2))
+            | (Self::SetFoldByIndent(d1), Self::SetFoldByIndent(d2)) => d1 == d2,
+            (Self::SetHeaderLines(d1), Self::SetHeaderLines(d2))
+            | (Self::SetFrozenColumns(d1), Self::SetFrozenColumns(d2)) => d1 == d2,
+            #[cfg(feature = "dynamic_output")]
+            (Self::SetQuitIfOneScreen(d1), Self::SetQuitIfOneScreen(d2)) => d1 == d2,
+            (Self::SetQuitAtEof(d1), Self::SetQuitAtEof(d2)) => d1 == d2,
+            (Self::SetMaxRefreshRate(d1), Self::SetMaxRefreshRate(d2)) => d1 == d2,
+            (Self::SetMaxLines(d1), Self::SetMaxLines(d2)) => d1 == d2,
+            (Self::SetAppendBatching(i1, b1), Self::SetAppendBatching(i2, b2)) => {
+                i1 == i2 && b1 == b2
+            }
+            (Self::SetResizePollInterval(d1), Self::SetResizePollInterval(d2)) => d1 == d2,
             #[cfg(feature = "static_output")]
             (Self::SetRunNoOverflow(d1), Self::SetRunNoOverflow(d2)) => d1 == d2,
+            (Self::FollowOutput(f1), Self::FollowOutput(f2)) => f1 == f2,
+            (Self::SetFiller(f1), Self::SetFiller(f2)) => f1 == f2,
+            (Self::SetScrollOff(d1), Self::SetScrollOff(d2)) => d1 == d2,
+            (Self::SetPageOverlap(d1), Self::SetPageOverlap(d2)) => d1 == d2,
+            (Self::SetScrollAmounts(a1, w1), Self::SetScrollAmounts(a2, w2)) => {
+                a1 == a2 && w1 == w2
+            }
+            (Self::SetMouseCapture(d1), Self::SetMouseCapture(d2))
+            | (Self::SetFocused(d1), Self::SetFocused(d2)) => d1 == d2,
+            (Self::SetUnfocusedPause(r1, f1), Self::SetUnfocusedPause(r2, f2)) => {
+                r1 == r2 && f1 == f2
+            }
+            (Self::SetInitialPosition(p1), Self::SetInitialPosition(p2)) => p1 == p2,
             (Self::SetInputClassifier(_), Self::SetInputClassifier(_))
-            | (Self::AddExitCallback(_), Self::AddExitCallback(_)) => true,
+            | (Self::PushInputClassifier(_), Self::PushInputClassifier(_))
+            | (Self::PopInputClassifier, Self::PopInputClassifier)
+            | (Self::Stop, Self::Stop)
+            | (Self::ExpireMessage, Self::ExpireMessage)
+            | (Self::MoveSpinner, Self::MoveSpinner)
+            | (Self::CheckTermSize, Self::CheckTermSize)
+            | (Self::BeginUpdate, Self::BeginUpdate)
+            | (Self::EndUpdate, Self::EndUpdate)
+            | (Self::QuitAtEof, Self::QuitAtEof)
+            | (Self::ClearHighlights, Self::ClearHighlights)
+            | (Self::ClearSigns, Self::ClearSigns)
+            | (Self::ClearFolds, Self::ClearFolds)
+            | (Self::ClearSections, Self::ClearSections)
+            | (Self::DataFinished, Self::DataFinished)
+            | (Self::SetLineNumberFormat(_), Self::SetLineNumberFormat(_))
+            | (Self::SetLineFormatter(_), Self::SetLineFormatter(_))
+            | (Self::SetLineProvider(_), Self::SetLineProvider(_))
+            | (Self::SetInput(_), Self::SetInput(_))
+            | (Self::AddExitCallback(_), Self::AddExitCallback(_))
+            | (Self::SetExitVeto(_), Self::SetExitVeto(_))
+            | (Self::SetUnhandledKeyCallback(_), Self::SetUnhandledKeyCallback(_))
+            | (Self::SetInterruptStrategy(_), Self::SetInterruptStrategy(_))
+            | (Self::SetSavePathHook(_), Self::SetSavePathHook(_)) => true,
             #[cfg(feature = "search")]
-            (Self::IncrementalSearchCondition(_), Self::IncrementalSearchCondition(_)) => true,
+            (Self::IncrementalSearchCondition(_), Self::IncrementalSearchCondition(_))
+            | (Self::ClearColorRules, Self::ClearColorRules) => true,
             _ => false,
         }
     }
 }
 
 impl Debug for Command {
+    #[allow(clippy::too_many_lines)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::SetData(text) => write!(f, "SetData({:?})", text),
+            Self::Truncate(lines) => write!(f, "Truncate({lines:?})"),
+            Self::EditLines(lines, text) => write!(f, "EditLines({lines:?}, {text:?})"),
             Self::AppendData(text) => write!(f, "AppendData({:?})", text),
+            Self::PushRow(cells) => write!(f, "PushRow({cells:?})"),
+            Self::AddSource(name, style) => write!(f, "AddSource({name:?}, {style:?})"),
+            Self::AppendSourceData(name, text) => {
+                write!(f, "AppendSourceData({name:?}, {text:?})")
+            }
+            Self::ShowSource(name, show) => write!(f, "ShowSource({name:?}, {show:?})"),
+            Self::AddBuffer(name) => write!(f, "AddBuffer({name:?})"),
+            Self::InBuffer(name, command) => write!(f, "InBuffer({name:?}, {command:?})"),
+            Self::ShowBuffer(name) => write!(f, "ShowBuffer({name:?})"),
+            Self::SetDiff(diff) => write!(f, "SetDiff({diff:?})"),
+            Self::SetBufferName(name) => write!(f, "SetBufferName({name:?})"),
             Self::SetPrompt(text) => write!(f, "SetPrompt({:?})", text),
-            Self::SendMessage(text) => write!(f, "SendMessage({:?})", text),
+            Self::SetPromptFormat(format) => write!(f, "SetPromptFormat({format:?})"),
+            Self::SendMessage(level, text, timeout) => {
+                write!(f, "SendMessage({level:?}, {text:?}, {timeout:?})")
+            }
             Self::SetLineNumbers(ln) => write!(f, "SetLineNumbers({:?})", ln),
+            Self::SetLineNumberFormat(_) => write!(f, "SetLineNumberFormat"),
+            Self::SetLineFormatter(_) => write!(f, "SetLineFormatter"),
+            Self::SetLineProvider(_) => write!(f, "SetLineProvider"),
+            Self::SetLineNumberContinuation(c) => write!(f, "SetLineNumberContinuation({c:?})"),
+            Self::SetLineNumberStart(start) => write!(f, "SetLineNumberStart({start:?})"),
+            Self::SetTabWidth(width) => write!(f, "SetTabWidth({width:?})"),
+            Self::SetControlChars(cc) => write!(f, "SetControlChars({cc:?})"),
+            Self::SetStripAnsi(strip) => write!(f, "SetStripAnsi({strip:?})"),
+            Self::SetNormalizeLineEndings(normalize) => {
+                write!(f, "SetNormalizeLineEndings({normalize:?})")
+            }
+            Self::SetColorSupport(support) => write!(f, "SetColorSupport({support:?})"),
+            Self::ShowInvisibles(show) => write!(f, "ShowInvisibles({show:?})"),
+            Self::SetWrapIndicator(prefix, suffix) => {
+                write!(f, "SetWrapIndicator({prefix:?}, {suffix:?})")
+            }
             Self::LineWrapping(lw) => write!(f, "LineWrapping({:?})", lw),
             Self::SetExitStrategy(es) => write!(f, "SetExitStrategy({:?})", es),
+            Self::SetInterruptStrategy(is) => write!(f, "SetInterruptStrategy({is:?})"),
+            Self::SetSynchronizedOutput(sync) => write!(f, "SetSynchronizedOutput({sync:?})"),
+            Self::SetPassthroughOnPipe(val) => write!(f, "SetPassthroughOnPipe({val:?})"),
+            Self::SetAlternateScreen(val) => write!(f, "SetAlternateScreen({val:?})"),
+            Self::SetViewport(viewport) => write!(f, "SetViewport({viewport:?})"),
+            Self::SetOutput(_, cols, rows) => write!(f, "SetOutput({cols:?}, {rows:?})"),
+            Self::SetInput(_) => write!(f, "SetInput"),
+            #[cfg(feature = "dynamic_output")]
+            Self::SetQuitIfOneScreen(val) => write!(f, "SetQuitIfOneScreen({val:?})"),
+            Self::SetQuitAtEof(val) => write!(f, "SetQuitAtEof({val:?})"),
+            Self::DataFinished => write!(f, "DataFinished"),
+            Self::SetMaxRefreshRate(rate) => write!(f, "SetMaxRefreshRate({rate:?})"),
+            Self::SetMaxLines(max_lines) => write!(f, "SetMaxLines({max_lines:?})"),
+            Self::SetAppendBatching(interval, max_batch) => {
+                write!(f, "SetAppendBatching({interval:?}, {max_batch:?})")
+            }
             Self::SetInputClassifier(_) => write!(f, "SetInputClassifier"),
+            Self::PushInputClassifier(_) => write!(f, "PushInputClassifier"),
+            Self::PopInputClassifier => write!(f, "PopInputClassifier"),
+            Self::SetPromptStyle(mode, style) => write!(f, "SetPromptStyle({mode:?}, {style:?})"),
+            Self::SetTheme(theme) => write!(f, "SetTheme({theme:?})"),
             Self::ShowPrompt(show) => write!(f, "ShowPrompt({show:?})"),
+            Self::ShowPercentage(show) => write!(f, "ShowPercentage({show:?})"),
+            Self::ShowScrollbar(show) => write!(f, "ShowScrollbar({show:?})"),
+            Self::SetSplitView(split) => write!(f, "SetSplitView({split:?})"),
+            Self::SetStatus(lines) => write!(f, "SetStatus({lines:?})"),
+            Self::SetProgress(progress) => write!(f, "SetProgress({progress:?})"),
             Self::FormatRedrawPrompt => write!(f, "FormatRedrawPrompt"),
+            Self::ExpireMessage => write!(f, "ExpireMessage"),
+            Self::MoveSpinner => write!(f, "MoveSpinner"),
+            Self::SetResizePollInterval(interval) => {
+                write!(f, "SetResizePollInterval({interval:?})")
+            }
+            Self::CheckTermSize => write!(f, "CheckTermSize"),
+            Self::SetFiller(filler) => write!(f, "SetFiller({filler:?})"),
+            Self::SetScrollOff(rows) => write!(f, "SetScrollOff({rows:?})"),
+            Self::SetPageOverlap(rows) => write!(f, "SetPageOverlap({rows:?})"),
+            Self::SetScrollAmounts(arrow, wheel) => {
+                write!(f, "SetScrollAmounts({arrow:?}, {wheel:?})")
+            }
+            Self::SetMouseCapture(val) => write!(f, "SetMouseCapture({val:?})"),
+            Self::SetUnfocusedPause(repaints, follow_output) => {
+                write!(f, "SetUnfocusedPause({repaints:?}, {follow_output:?})")
+            }
+            Self::SetFocused(focused) => write!(f, "SetFocused({focused:?})"),
+            Self::SetInitialPosition(position) => write!(f, "SetInitialPosition({position:?})"),
+            Self::QuitAtEof => write!(f, "QuitAtEof"),
+            Self::BeginUpdate => write!(f, "BeginUpdate"),
+            Self::EndUpdate => write!(f, "EndUpdate"),
+            Self::SetWaitingPlaceholder(text, spinner) => {
+                write!(f, "SetWaitingPlaceholder({text:?}, {spinner:?})")
+            }
             Self::FormatRedrawDisplay => write!(f, "FormatRedrawDisplay"),
             #[cfg(feature = "search")]
             Self::IncrementalSearchCondition(_) => write!(f, "IncrementalSearchCondition"),
             Self::AddExitCallback(_) => write!(f, "AddExitCallback"),
+            Self::SetExitVeto(_) => write!(f, "SetExitVeto"),
+            Self::SetUnhandledKeyCallback(_) => write!(f, "SetUnhandledKeyCallback"),
+            Self::AddCustom(name, key, _) => write!(f, "AddCustom({name:?}, {key:?})"),
+            Self::SetSavePathHook(_) => write!(f, "SetSavePathHook"),
             #[cfg(feature = "static_output")]
             Self::SetRunNoOverflow(val) => write!(f, "SetRunNoOverflow({val:?})"),
             Self::UserInput(input) => write!(f, "UserInput({input:?})"),
+            Self::RunCustom(name) => write!(f, "RunCustom({name:?})"),
+            Self::Stop => write!(f, "Stop"),
             Self::FollowOutput(follow_output) => write!(f, "FollowOutput({follow_output:?})"),
+            Self::SetCursorLine(val) => write!(f, "SetCursorLine({val:?})"),
+            Self::SetPickerMode(val) => write!(f, "SetPickerMode({val:?})"),
+            Self::HighlightLines(lines, style) => write!(f, "HighlightLines({lines:?}, {style:?})"),
+            Self::ClearHighlights => write!(f, "ClearHighlights"),
+            Self::SetHeaderLines(lines) => write!(f, "SetHeaderLines({lines:?})"),
+            Self::SetFrozenColumns(cols) => write!(f, "SetFrozenColumns({cols:?})"),
+            Self::SetSigns(signs) => write!(f, "SetSigns({signs:?})"),
+            Self::ClearSigns => write!(f, "ClearSigns"),
+            Self::AddFolds(regions) => write!(f, "AddFolds({regions:?})"),
+            Self::SetFoldByIndent(val) => write!(f, "SetFoldByIndent({val:?})"),
+            Self::ClearFolds => write!(f, "ClearFolds"),
+            Self::AddSections(sections) => write!(f, "AddSections({sections:?})"),
+            Self::ClearSections => write!(f, "ClearSections"),
+            #[cfg(feature = "search")]
+            Self::AddColorRule(regex, style) => write!(f, "AddColorRule({regex:?}, {style:?})"),
+            #[cfg(feature = "search")]
+            Self::ClearColorRules => write!(f, "ClearColorRules"),
         }
     }
 }
@@ -97,11 +407,23 @@ impl Debug for Command {
 impl Command {
     #[allow(dead_code)]
     pub(crate) const fn is_exit_event(&self) -> bool {
-        matches!(self, Self::UserInput(InputEvent::Exit))
+        matches!(self, Self::UserInput(InputEvent::Exit) | Self::Stop)
     }
 
     #[allow(dead_code)]
     pub(crate) const fn is_movement(&self) -> bool {
         matches!(self, Self::UserInput(InputEvent::UpdateUpperMark(_)))
     }
+
+    /// Returns true if handling this command waits for the user to type something
+    pub(crate) const fn waits_for_input(&self) -> bool {
+        #[cfg(feature = "search")]
+        if matches!(self, Self::UserInput(InputEvent::Search(_))) {
+            return true;
+        }
+        matches!(
+            self,
+            Self::UserInput(InputEvent::Save | InputEvent::Pipe(_) | InputEvent::CommandPrompt)
+        )
+    }
 }
@@ -1,18 +1,35 @@
 //! Provides the [`handle_event`] function
 
-use std::convert::TryInto;
-use std::io::Write;
+use std::convert::TryFrom;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::{self, ChildStdin, Stdio};
+use std::time::{Duration, Instant};
+
+use crossterm::{
+    event::{Event, KeyEvent, KeyEventKind},
+    terminal,
+};
 use std::sync::{atomic::AtomicBool, Arc};
 
-#[cfg(feature = "search")]
 use parking_lot::{Condvar, Mutex};
 
-use super::utils::display::{self, AppendStyle};
+use super::utils::{
+    command_line,
+    display::{self, AppendStyle},
+    input_field, picker, LinesRowMap,
+};
 use super::CommandQueue;
 use super::{commands::Command, utils::term};
 #[cfg(feature = "search")]
 use crate::search;
-use crate::{error::MinusError, input::InputEvent, PagerState};
+use crate::{
+    error::{CleanupError, MinusError, SetupError},
+    input::{InputEvent, PipeRange},
+    state::CustomCommand,
+    ExitReason, ExitStrategy, InterruptStrategy, MessageLevel, PagerState,
+};
 
 /// Respond based on the type of command
 ///
@@ -28,26 +45,108 @@ pub fn handle_event(
     p: &mut PagerState,
     command_queue: &mut CommandQueue,
     is_exited: &Arc<AtomicBool>,
-    #[cfg(feature = "search")] user_input_active: &Arc<(Mutex<bool>, Condvar)>,
+    user_input_active: &Arc<(Mutex<bool>, Condvar)>,
 ) -> Result<(), MinusError> {
     match ev {
-        Command::SetData(text) => {
-            p.screen.orig_text = text;
+        Command::SetData(mut text) => {
+            p.normalize_line_endings_of(&mut text);
+            p.screen.line_provider = None;
+            p.table.clear();
+            p.diff.clear();
+            p.sources.clear_lines();
+            p.screen.folds.open_all();
+            p.screen.discarded_lines = 0;
+            p.screen.orig_text = text.into();
+            p.format_lines();
+            p.screen.line_count = p.screen.orig_text.line_count();
+            p.enforce_max_lines();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(&mut out, p)?;
+            }
+        }
+        Command::SetDiff(diff) => {
+            p.screen.line_provider = None;
+            p.table.clear();
+            p.sources.clear_lines();
+            p.screen.folds.open_all();
+            p.screen.discarded_lines = 0;
+            p.diff = diff;
+            // The rows of the diff are laid out into the text
             p.format_lines();
-            p.screen.line_count = p.screen.orig_text.lines().count();
+            p.screen.line_count = p.screen.orig_text.line_count();
+            p.enforce_max_lines();
             if !p.running.lock().is_uninitialized() {
-                display::draw_full(&mut out, p)?;
+                display::draw_changed(&mut out, p)?;
+            }
+        }
+        Command::EditLines(lines, mut text) => {
+            p.normalize_line_endings_of(&mut text);
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            if p.edit_lines(lines, &text) && !p.running.lock().is_uninitialized() {
+                display::draw_changed(&mut out, p)?;
+            }
+        }
+        Command::Truncate(lines) => {
+            if p.truncate_lines(lines) && !p.running.lock().is_uninitialized() {
+                display::draw_changed(&mut out, p)?;
             }
         }
         Command::UserInput(InputEvent::Exit) => {
-            p.exit();
-            is_exited.store(true, std::sync::atomic::Ordering::SeqCst);
-            term::cleanup(&mut out, &p.exit_strategy, true)?;
+            let es = p.exit_strategy.clone();
+            quit(out, p, command_queue, is_exited, ExitReason::UserQuit, &es)?;
+        }
+        Command::QuitAtEof => {
+            let es = p.exit_strategy.clone();
+            quit(out, p, command_queue, is_exited, ExitReason::EndOfData, &es)?;
+        }
+        Command::UserInput(InputEvent::Interrupt) => {
+            let es = match &mut p.interrupt_strategy {
+                InterruptStrategy::Exit => p.exit_strategy.clone(),
+                InterruptStrategy::PagerQuit => ExitStrategy::PagerQuit,
+                InterruptStrategy::ProcessQuit => ExitStrategy::ProcessQuit,
+                InterruptStrategy::Forward(func) => {
+                    func();
+                    return Ok(());
+                }
+            };
+            quit(
+                out,
+                p,
+                command_queue,
+                is_exited,
+                ExitReason::Interrupted,
+                &es,
+            )?;
         }
+        Command::RunCustom(name) => {
+            // The commands are taken out so that the callback can look at the whole state
+            let mut commands = std::mem::take(&mut p.custom_commands);
+            match commands.iter_mut().find(|c| c.name == name) {
+                Some(command) => (command.callback)(p),
+                None => command_queue.push_back(Command::SendMessage(
+                    MessageLevel::Error,
+                    format!("Unknown command: {name}"),
+                    None,
+                )),
+            }
+            p.custom_commands = commands;
+        }
+        // Stopping the pager from the application never exits the process
+        Command::Stop => quit(
+            out,
+            p,
+            command_queue,
+            is_exited,
+            ExitReason::Stopped,
+            &ExitStrategy::PagerQuit,
+        )?,
         Command::UserInput(InputEvent::UpdateUpperMark(mut um)) => {
             let line_count = p.screen.formatted_lines_count();
-            // Reduce one row for prompt/messages
-            let writable_rows = p.rows.saturating_sub(1);
+            // Reduce the rows for the prompt/messages and the status area, and the other pane of
+            // the split view
+            let writable_rows = p.text_rows();
             // Calculate the lower_mark by adding either the rows or line_count depending
             // on the minimality
             let lower_mark = p.upper_mark.saturating_add(writable_rows.min(line_count));
@@ -57,22 +156,194 @@ pub fn handle_event(
                 p.upper_mark = line_count.saturating_sub(writable_rows);
             }
 
+            // Remember the current position if we are jumping beyond the current screen
+            let target = um.min(line_count.saturating_sub(writable_rows));
+            if target.abs_diff(p.upper_mark) > writable_rows {
+                p.jump_list.record(p.upper_mark);
+            }
+
             display::draw_for_change(out, p, &mut um)?;
             p.upper_mark = um;
+            if p.cursor_line_into_view() {
+                display::draw_changed(out, p)?;
+            }
+            queue_relative_line_numbers_update(p, command_queue);
+        }
+        Command::UserInput(InputEvent::SelectLine(line)) if p.selected_line.is_some() => {
+            // The cursor moves past the lines hidden in folds in the direction it was moving
+            let forward = p.selected_line.map_or(true, |old| line > old);
+            let line_count = p.screen.line_count();
+            let line = p.screen.folds.skip_hidden(line, forward, line_count);
+            // The lines of the header can't be selected
+            let line = line.max(p.header_lines).min(p.last_line());
+            p.selected_line = Some(line);
+            // Scroll just enough to show all the rows of the line below the header, along with
+            // the scroll-off margin around them
+            let rows = p.rows_of_line(line);
+            let writable_rows = p.text_rows();
+            let header_rows = p.header_rows();
+            let margin = p.scroll_off_rows();
+            if rows.start < p.upper_mark.saturating_add(header_rows + margin) {
+                p.upper_mark = p.upper_mark_for_jump(rows.start);
+            } else if rows.end + margin > p.upper_mark.saturating_add(writable_rows) {
+                p.upper_mark = (rows.end + margin)
+                    .saturating_sub(writable_rows)
+                    .min(p.upper_mark_for_jump(rows.start));
+            }
+            display::draw_changed(out, p)?;
+            queue_relative_line_numbers_update(p, command_queue);
+        }
+        Command::UserInput(InputEvent::PickLine) => {
+            if let Some(line) = p.selected_line {
+                // The application has to receive the line, so the process is never quit here
+                quit(
+                    out,
+                    p,
+                    command_queue,
+                    is_exited,
+                    ExitReason::Picked(line),
+                    &ExitStrategy::PagerQuit,
+                )?;
+            }
+        }
+        Command::HighlightLines(lines, style) => {
+            p.line_highlights.push((lines, style));
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::ClearHighlights => {
+            p.line_highlights.clear();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::SetHeaderLines(lines) => {
+            p.header_lines = lines;
+            p.cursor_line_into_view();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::SetFrozenColumns(cols) => {
+            p.screen.frozen_columns = cols;
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::SetSigns(signs) => {
+            p.screen.signs.extend(signs);
+            p.format_lines();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::ClearSigns => {
+            p.screen.signs.clear();
+            p.format_lines();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::AddFolds(regions) => p.screen.folds.add_regions(regions),
+        Command::AddSections(sections) => p.sections.extend(sections),
+        Command::ClearSections => p.sections.clear(),
+        Command::SetFoldByIndent(val) => p.screen.folds.set_by_indent(val),
+        Command::ClearFolds => {
+            let folded = !p.screen.folds.all_open();
+            p.screen.folds.clear();
+            if folded {
+                p.format_lines();
+                if !p.running.lock().is_uninitialized() {
+                    display::draw_changed(out, p)?;
+                }
+            }
+        }
+        #[cfg(feature = "search")]
+        Command::AddColorRule(regex, style) => {
+            p.screen.color_rules.push((regex, style));
+            p.format_lines();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        #[cfg(feature = "search")]
+        Command::ClearColorRules => {
+            p.screen.color_rules.clear();
+            p.format_lines();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::SetCursorLine(val) | Command::SetPickerMode(val) => {
+            // Lines can't be picked without the cursor line
+            if matches!(ev, Command::SetPickerMode(_)) || !val {
+                p.picker_mode = val;
+            }
+            p.selected_line = val.then(|| p.selected_line.unwrap_or(0));
+            p.cursor_line_into_view();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::UserInput(iev @ (InputEvent::JumpBack | InputEvent::JumpForward)) => {
+            let position = if iev == InputEvent::JumpBack {
+                p.jump_list.back(p.upper_mark)
+            } else {
+                p.jump_list.forward(p.upper_mark)
+            };
+            if let Some(mut um) = position {
+                display::draw_for_change(out, p, &mut um)?;
+                p.upper_mark = um;
+                if p.cursor_line_into_view() {
+                    display::draw_changed(out, p)?;
+                }
+                queue_relative_line_numbers_update(p, command_queue);
+            }
         }
         Command::UserInput(InputEvent::UpdateLeftMark(lm)) if !p.screen.line_wrapping => {
-            if lm.saturating_add(p.cols) > p.screen.get_max_line_length() && lm > p.left_mark {
+            if lm.saturating_add(p.text_cols()) > p.screen.get_max_line_length() && lm > p.left_mark
+            {
                 return Ok(());
             }
             p.left_mark = lm;
-            display::draw_full(out, p)?;
+            display::draw_changed(out, p)?;
         }
         Command::UserInput(InputEvent::RestorePrompt) => {
             // Set the message to None and new messages to false as all messages have been shown
             p.message = None;
-            command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+            p.message_expiry = None;
+            // The rows of the full prompt are given back to the text
+            if !p.full_prompt.is_empty() {
+                p.full_prompt.clear();
+                display::draw_changed(out, p)?;
+            }
+            command_queue.push_back(Command::FormatRedrawPrompt);
+        }
+        Command::UserInput(InputEvent::ShowFullPrompt) => {
+            if p.show_full_prompt() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        // The size of the viewport doesn't change with the terminal, but it should still be
+        // redrawn in case the terminal has cleared it
+        Command::UserInput(InputEvent::UpdateTermArea(c, r)) if p.viewport.is_some() => {
+            if p.output_size.is_some() {
+                p.output_size = Some((
+                    u16::try_from(c).unwrap_or(u16::MAX),
+                    u16::try_from(r).unwrap_or(u16::MAX),
+                ));
+            }
+            p.displayed_frame = None;
+            command_queue.push_back(Command::FormatRedrawDisplay);
         }
         Command::UserInput(InputEvent::UpdateTermArea(c, r)) => {
+            if p.output_size.is_some() {
+                p.output_size = Some((
+                    u16::try_from(c).unwrap_or(u16::MAX),
+                    u16::try_from(r).unwrap_or(u16::MAX),
+                ));
+            }
             p.rows = r;
             p.cols = c;
             // Readjust the text wrapping for the new number of columns
@@ -90,23 +361,18 @@ pub fn handle_event(
             // less matches in this search than last time
             p.search_state.search_mark = 0;
 
-            // Pause the main user input thread, read search query and then restart the main input thread
-            let (lock, cvar) = (&user_input_active.0, &user_input_active.1);
-            let mut active = lock.lock();
-            *active = false;
-            drop(active);
-            // let string = search::fetch_input(&mut out, p.search_mode, p.rows)?;
-            let search_result = search::fetch_input(&mut out, p)?;
-            let mut active = lock.lock();
-            *active = true;
-            drop(active);
-            cvar.notify_one();
-
-            command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+            let search_result =
+                with_input_paused(user_input_active, || search::fetch_input(&mut out, p))?;
+            // Incremental search may have redrawn the screen
+            p.displayed_frame = None;
+
+            command_queue.push_back(Command::FormatRedrawPrompt);
             // If we have incremental search cache directly use it and return
             if let Some(incremental_search_result) = search_result.incremental_search_result {
+                p.jump_list.record(p.upper_mark);
                 p.search_state.search_term = search_result.compiled_regex;
-                p.upper_mark = incremental_search_result.upper_mark;
+                // Show the match below the header
+                p.upper_mark = p.upper_mark_for_jump(incremental_search_result.upper_mark);
                 p.search_state.search_mark = incremental_search_result.search_mark;
                 p.search_state.search_idx = incremental_search_result.search_idx;
                 p.screen.formatted_lines = incremental_search_result.formatted_lines;
@@ -120,8 +386,10 @@ pub fn handle_event(
             } else if !search_result.string.is_empty() {
                 let compiled_regex = regex::Regex::new(&search_result.string).ok();
                 if compiled_regex.is_none() {
-                    command_queue.push_back_unchecked(Command::SendMessage(
+                    command_queue.push_back(Command::SendMessage(
+                        MessageLevel::Error,
                         "Invalid regular expression. Press Enter".to_string(),
+                        None,
                     ));
                     return Ok(());
                 }
@@ -134,24 +402,50 @@ pub fn handle_event(
             p.format_lines();
         }
         #[cfg(feature = "search")]
+        Command::UserInput(
+            iev @ (InputEvent::NextMatch
+            | InputEvent::PrevMatch
+            | InputEvent::MoveToNextMatch(_)
+            | InputEvent::MoveToPrevMatch(_)),
+        ) if p.search_state.search_term.is_some() && p.screen.line_provider.is_some() => {
+            // Search through the lines of the provider as they aren't all available
+            let n = match iev {
+                InputEvent::MoveToNextMatch(n) | InputEvent::MoveToPrevMatch(n) => n,
+                _ => 1,
+            };
+            let forward = matches!(iev, InputEvent::NextMatch | InputEvent::MoveToNextMatch(_));
+            if let (Some(provider), Some(regex)) = (
+                p.screen.line_provider.as_deref(),
+                p.search_state.search_term.as_ref(),
+            ) {
+                // The current match is shown below the scroll-off margin
+                let from = p.upper_mark.saturating_add(p.scroll_off_rows());
+                if let Some(line) = search::find_provider_match(provider, regex, from, forward, n) {
+                    command_queue.push_back(Command::UserInput(InputEvent::UpdateUpperMark(
+                        p.upper_mark_for_jump(line),
+                    )));
+                }
+            }
+        }
+        #[cfg(feature = "search")]
         Command::UserInput(InputEvent::NextMatch | InputEvent::MoveToNextMatch(1))
             if p.search_state.search_term.is_some() =>
         {
-            // Move to next search match after the current upper_mark
+            // Move to next search match after the rows covered by the header
             let position_of_next_match =
-                search::next_nth_match(&p.search_state.search_idx, p.upper_mark, 1);
+                search::next_nth_match(&p.search_state.search_idx, p.next_match_search_start(), 1);
             if let Some(pnm) = position_of_next_match {
                 p.search_state.search_mark = pnm;
-                let upper_mark = *p
-                    .search_state
-                    .search_idx
-                    .iter()
-                    .nth(p.search_state.search_mark)
-                    .unwrap();
-                command_queue.push_back_unchecked(Command::UserInput(InputEvent::UpdateUpperMark(
-                    upper_mark,
-                )));
-                command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+                let upper_mark = p.upper_mark_for_jump(
+                    *p.search_state
+                        .search_idx
+                        .iter()
+                        .nth(p.search_state.search_mark)
+                        .unwrap(),
+                );
+                command_queue
+                    .push_back(Command::UserInput(InputEvent::UpdateUpperMark(upper_mark)));
+                command_queue.push_back(Command::FormatRedrawPrompt);
             }
         }
         #[cfg(feature = "search")]
@@ -164,19 +458,25 @@ pub fn handle_event(
             }
             // Decrement the s_mark and get the preceding index
             p.search_state.search_mark = p.search_state.search_mark.saturating_sub(1);
-            if let Some(y) = p
+            let first_row = p
+                .upper_mark
+                .saturating_add(p.header_rows() + p.scroll_off_rows());
+            if let Some(&y) = p
                 .search_state
                 .search_idx
                 .iter()
                 .nth(p.search_state.search_mark)
             {
-                // If the index is less than or equal to the upper_mark, then set y to the new upper_mark
-                if *y < p.upper_mark {
-                    p.upper_mark = *y;
-                    command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
-                    command_queue.push_back_unchecked(Command::UserInput(
-                        InputEvent::UpdateUpperMark(p.upper_mark),
-                    ));
+                // If the index is above the rows below the header, then show y below the header
+                if y < first_row {
+                    let upper_mark = p.upper_mark_for_jump(y);
+                    command_queue
+                        .push_back(Command::UserInput(InputEvent::UpdateUpperMark(upper_mark)));
+                    command_queue.push_back(Command::FormatRedrawPrompt);
+                } else if !p.running.lock().is_uninitialized() {
+                    // The current match has moved to another row already on the screen
+                    p.format_prompt();
+                    display::draw_changed(out, p)?;
                 }
             }
         }
@@ -184,24 +484,23 @@ pub fn handle_event(
         Command::UserInput(InputEvent::MoveToNextMatch(n))
             if p.search_state.search_term.is_some() =>
         {
-            // Move to next nth search match after the current upper_mark
+            // Move to next nth search match after the rows covered by the header
             let position_of_next_match =
-                search::next_nth_match(&p.search_state.search_idx, p.upper_mark, n);
+                search::next_nth_match(&p.search_state.search_idx, p.next_match_search_start(), n);
             if let Some(pnm) = position_of_next_match {
                 p.search_state.search_mark = pnm;
-                let upper_mark = *p
-                    .search_state
-                    .search_idx
-                    .iter()
-                    .nth(p.search_state.search_mark)
-                    .unwrap();
+                let upper_mark = p.upper_mark_for_jump(
+                    *p.search_state
+                        .search_idx
+                        .iter()
+                        .nth(p.search_state.search_mark)
+                        .unwrap(),
+                );
 
                 // Ensure there is enough text available after location corresponding to
                 // position_of_next_match so that we can display a pagefull of data. If not,
                 // reduce it so that a pagefull of text can be accommodated.
-                // NOTE: Add 1 to total number of lines to avoid off-by-one errors
-                while p.upper_mark.saturating_add(p.rows)
-                    > p.screen.formatted_lines_count().saturating_add(1)
+                while p.upper_mark.saturating_add(p.text_rows()) > p.screen.formatted_lines_count()
                 {
                     p.search_state.search_mark = p.search_state.search_mark.saturating_sub(1);
                     p.upper_mark = *p
@@ -211,10 +510,9 @@ pub fn handle_event(
                         .nth(p.search_state.search_mark)
                         .unwrap();
                 }
-                command_queue.push_back_unchecked(Command::UserInput(InputEvent::UpdateUpperMark(
-                    upper_mark,
-                )));
-                command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+                command_queue
+                    .push_back(Command::UserInput(InputEvent::UpdateUpperMark(upper_mark)));
+                command_queue.push_back(Command::FormatRedrawPrompt);
             }
         }
         #[cfg(feature = "search")]
@@ -227,42 +525,178 @@ pub fn handle_event(
             }
             // Decrement the s_mark and get the preceding index
             p.search_state.search_mark = p.search_state.search_mark.saturating_sub(n);
-            if let Some(y) = p
+            let first_row = p
+                .upper_mark
+                .saturating_add(p.header_rows() + p.scroll_off_rows());
+            if let Some(&y) = p
                 .search_state
                 .search_idx
                 .iter()
                 .nth(p.search_state.search_mark)
             {
-                // If the index is less than or equal to the upper_mark, then set y to the new upper_mark
-                if *y < p.upper_mark {
-                    let upper_mark = *y;
-                    command_queue.push_back_unchecked(Command::UserInput(
-                        InputEvent::UpdateUpperMark(upper_mark),
-                    ));
-                    command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+                // If the index is above the rows below the header, then show y below the header
+                if y < first_row {
+                    let upper_mark = p.upper_mark_for_jump(y);
+                    command_queue
+                        .push_back(Command::UserInput(InputEvent::UpdateUpperMark(upper_mark)));
+                    command_queue.push_back(Command::FormatRedrawPrompt);
+                } else if !p.running.lock().is_uninitialized() {
+                    // The current match has moved to another row already on the screen
+                    p.format_prompt();
+                    display::draw_changed(out, p)?;
                 }
             }
         }
 
         Command::UserInput(InputEvent::HorizontalScroll(val)) => {
-            p.screen.line_wrapping = val;
-            command_queue.push_back_unchecked(Command::FormatRedrawDisplay);
+            p.screen.line_wrapping = val && p.screen.line_provider.is_none();
+            command_queue.push_back(Command::FormatRedrawDisplay);
+        }
+        Command::UserInput(InputEvent::Pipe(range)) => {
+            with_input_paused(user_input_active, || {
+                pipe_text(&mut out, p, range, command_queue)
+            })?;
+        }
+        #[cfg(unix)]
+        Command::UserInput(InputEvent::Suspend) if p.terminal_input => {
+            with_input_paused(user_input_active, || suspend(&mut out, p))?;
+            // The terminal may have been resized while the process was suspended
+            if p.output_size.is_none() {
+                if let Ok((cols, rows)) = terminal::size() {
+                    command_queue.push_back(Command::UserInput(InputEvent::UpdateTermArea(
+                        cols.into(),
+                        rows.into(),
+                    )));
+                }
+            }
+            p.displayed_frame = None;
+            command_queue.push_back(Command::FormatRedrawDisplay);
+            command_queue.push_back(Command::FormatRedrawPrompt);
+        }
+        Command::UserInput(InputEvent::Save) => {
+            let name = with_input_paused(user_input_active, || {
+                input_field::read_input(&mut out, p, "Save to: ")
+            })?;
+            command_queue.push_back(Command::FormatRedrawPrompt);
+            let Some(name) = name.filter(|name| !name.is_empty()) else {
+                return Ok(());
+            };
+            let path = match p.save_path_hook.as_ref() {
+                Some(hook) => match hook(PathBuf::from(name)) {
+                    Ok(path) => path,
+                    Err(message) => {
+                        command_queue.push_back(Command::SendMessage(
+                            MessageLevel::Error,
+                            message,
+                            None,
+                        ));
+                        return Ok(());
+                    }
+                },
+                None => PathBuf::from(name),
+            };
+            // An existing file is only overwritten once the user agrees to it
+            let file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    let prompt = format!("{} exists. Overwrite it? (y/n) ", path.display());
+                    let answer = with_input_paused(user_input_active, || {
+                        input_field::read_input(&mut out, p, &prompt)
+                    })?;
+                    if !answer.map_or(false, |answer| answer.eq_ignore_ascii_case("y")) {
+                        return Ok(());
+                    }
+                    File::create(&path)
+                }
+                file => file,
+            };
+            let (level, message) = match file.and_then(|file| save_text(p, file)) {
+                Ok(()) => (MessageLevel::Info, format!("Saved to {}", path.display())),
+                Err(e) => (
+                    MessageLevel::Error,
+                    format!("Could not save to {}: {e}", path.display()),
+                ),
+            };
+            command_queue.push_back(Command::SendMessage(level, message, None));
+        }
+        Command::UserInput(InputEvent::CommandPrompt) => {
+            let line = with_input_paused(user_input_active, || {
+                input_field::read_input(&mut out, p, ":")
+            })?;
+            command_queue.push_back(Command::FormatRedrawPrompt);
+            match line.map(|line| command_line::parse(&line, p)) {
+                Some(Ok(Some(command))) => command_queue.push_back(command),
+                Some(Err(message)) => {
+                    command_queue.push_back(Command::SendMessage(
+                        MessageLevel::Error,
+                        message,
+                        None,
+                    ));
+                }
+                Some(Ok(None)) | None => {}
+            }
+        }
+        Command::UserInput(InputEvent::ShowInvisibles(show)) => {
+            p.screen.show_invisibles = show;
+            command_queue.push_back(Command::FormatRedrawDisplay);
         }
 
         Command::FormatRedrawDisplay => {
             p.format_lines();
-            display::draw_full(&mut out, p)?;
+            display::draw_changed(&mut out, p)?;
+        }
+        Command::SetLineProvider(provider) => {
+            p.screen.line_provider = Some(provider);
+            p.screen.orig_text.clear();
+            p.screen.discarded_lines = 0;
+            p.lines_to_row_map = LinesRowMap::new();
+            p.upper_mark = 0;
+            // Each line must take exactly one row
+            p.screen.line_wrapping = false;
+            p.format_lines();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        // The lines are served by the line provider, so appended text has nowhere to go
+        Command::AppendData(_) | Command::PushRow(_) if p.screen.line_provider.is_some() => {}
+        Command::PushRow(cells) => {
+            let table_width = p.table_width();
+            if let Some(text) = p.table.push(cells, table_width) {
+                command_queue.push_back(Command::AppendData(text));
+                return Ok(());
+            }
+            // The columns have been resized so every row has to be laid out again
+            p.format_lines();
+            p.screen.line_count = p.screen.orig_text.line_count();
+            p.enforce_max_lines();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
         }
         Command::AppendData(text) => {
             let prev_unterminated = p.screen.unterminated;
             let prev_fmt_lines_count = p.screen.formatted_lines_count();
             let is_running = !p.running.lock().is_uninitialized();
             let rows = p.rows;
+            let in_viewport = p.viewport.is_some();
+            let has_status = p.status_rows() > 0;
+            // The placeholder shown while waiting for the data also counts as an overlay
+            let has_row_overlays = p.has_row_overlays();
+            let repaints_paused = p.repaints_paused();
+            let minimal_layout = p.minimal_layout();
             let append_style = p.append_str(text.as_str());
 
             if is_running {
-                if append_style == AppendStyle::FullRedraw {
-                    return display::draw_full(out, p);
+                // Text can't be appended by writing new lines inside a viewport, above the status
+                // area or on the rows that are drawn over
+                if append_style == AppendStyle::FullRedraw
+                    || in_viewport
+                    || has_status
+                    || has_row_overlays
+                    || repaints_paused
+                    || minimal_layout
+                {
+                    return display::draw_changed(out, p);
                 }
 
                 display::draw_append_text(
@@ -272,94 +706,814 @@ pub fn handle_event(
                     prev_fmt_lines_count,
                     &append_style,
                 )?;
+                // The rows written for the appended text aren't tracked
+                p.displayed_frame = None;
 
-                if p.follow_output {
-                    command_queue.push_back_unchecked(Command::UserInput(
-                        InputEvent::UpdateUpperMark(p.screen.formatted_lines_count()),
-                    ));
+                if p.follow_output && !p.following_paused() {
+                    command_queue.push_back(Command::UserInput(InputEvent::UpdateUpperMark(
+                        p.screen.formatted_lines_count(),
+                    )));
                 }
             }
         }
 
-        Command::SetPrompt(ref text) | Command::SendMessage(ref text) => {
-            if let Command::SetPrompt(_) = ev {
-                p.prompt = text.to_string();
-            } else {
-                p.message = Some(text.to_string());
+        Command::SetPrompt(text) => {
+            p.prompt = text;
+            p.format_prompt();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_prompt(out, p)?;
+            }
+        }
+        Command::SendMessage(level, text, timeout) => {
+            p.message = Some(text);
+            p.message_level = level;
+            p.message_expiry = timeout.map(|timeout| Instant::now() + timeout);
+            p.format_prompt();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_prompt(out, p)?;
+            }
+        }
+        // A newer message may have replaced the one that expired
+        Command::ExpireMessage => {
+            if p.message_expiry
+                .map_or(false, |expiry| expiry <= Instant::now())
+            {
+                command_queue.push_back(Command::UserInput(InputEvent::RestorePrompt));
+            }
+        }
+        Command::ShowPercentage(show) => {
+            p.show_percentage = show;
+            p.format_prompt();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_prompt(out, p)?;
+            }
+        }
+        Command::SetProgress(progress) => {
+            p.progress = progress;
+            p.format_prompt();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_prompt(out, p)?;
+            }
+        }
+        Command::SetWaitingPlaceholder(text, spinner) => {
+            p.waiting_text = text;
+            p.waiting_spinner = spinner;
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::SetInitialPosition(position) => p.initial_position = Some(position),
+        Command::SetScrollOff(rows) => p.scroll_off = rows,
+        Command::SetPageOverlap(rows) => p.page_overlap = rows,
+        Command::SetScrollAmounts(arrow, wheel) => {
+            p.arrow_scroll_lines = arrow;
+            p.wheel_scroll_lines = wheel;
+        }
+        Command::SetFiller(filler) => {
+            p.filler = filler;
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::MoveSpinner => {
+            if p.next_spinner_move()
+                .map_or(false, |next_move| next_move <= Instant::now())
+            {
+                p.spinner_frame = p.spinner_frame.wrapping_add(1);
+                p.spinner_moved = Instant::now();
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::SetResizePollInterval(interval) => {
+            p.resize_poll_interval = interval;
+            p.size_checked = Instant::now();
+        }
+        // Resizes that the terminal didn't report are handled like the reported ones
+        Command::CheckTermSize => {
+            p.size_checked = Instant::now();
+            if let Ok((cols, rows)) = terminal::size() {
+                let (cols, rows) = (usize::from(cols), usize::from(rows));
+                if p.next_size_check().is_some() && (cols, rows) != (p.cols, p.rows) {
+                    command_queue
+                        .push_back(Command::UserInput(InputEvent::UpdateTermArea(cols, rows)));
+                }
             }
+        }
+        Command::SetPromptFormat(format) => {
+            p.prompt_format = Some(format);
             p.format_prompt();
             if !p.running.lock().is_uninitialized() {
-                display::write_prompt(out, &p.displayed_prompt, p.rows.try_into().unwrap())?;
+                display::draw_prompt(out, p)?;
             }
         }
         Command::SetLineNumbers(ln) => {
             p.line_numbers = ln;
             p.format_lines();
             if !p.running.lock().is_uninitialized() {
-                display::write_prompt(out, &p.displayed_prompt, p.rows.try_into().unwrap())?;
+                display::draw_prompt(out, p)?;
+            }
+        }
+        Command::SetLineNumberFormat(formatter) => {
+            p.screen.line_number_formatter = Some(formatter);
+            p.format_lines();
+        }
+        Command::SetLineFormatter(formatter) => {
+            p.screen.line_formatter = Some(formatter);
+            p.format_lines();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::SetWrapIndicator(prefix, suffix) => {
+            p.screen.wrap_prefix = prefix;
+            p.screen.wrap_suffix = suffix;
+            p.format_lines();
+        }
+        Command::ShowInvisibles(show) => {
+            p.screen.show_invisibles = show;
+            p.format_lines();
+        }
+        Command::SetControlChars(control_chars) => {
+            p.screen.control_chars = control_chars;
+            p.format_lines();
+        }
+        Command::SetStripAnsi(strip) => {
+            p.screen.strip_ansi = strip;
+            p.format_lines();
+        }
+        Command::SetNormalizeLineEndings(normalize) => p.normalize_line_endings = normalize,
+        Command::SetColorSupport(support) => {
+            p.screen.color_support = support;
+            p.format_lines();
+        }
+        Command::SetTabWidth(width) => {
+            p.screen.tab_width = width;
+            p.format_lines();
+        }
+        Command::SetLineNumberStart(start) => {
+            p.screen.line_number_start = start;
+            p.format_lines();
+        }
+        Command::SetLineNumberContinuation(continuation) => {
+            p.screen.line_number_continuation = continuation;
+            p.format_lines();
+        }
+        Command::SetPromptStyle(mode, style) => {
+            p.screen.theme.set_prompt_style(mode, style);
+            p.format_prompt();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_prompt(out, p)?;
+            }
+        }
+        Command::SetStatus(lines) => {
+            p.status_lines = lines;
+            // The number of rows left for the text may have changed
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::ShowScrollbar(show) => {
+            p.show_scrollbar = show;
+            // The text has one column less to fit into while the scrollbar is shown
+            p.format_lines();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_full(out, p)?;
+            }
+        }
+        Command::SetSplitView(split) => {
+            p.set_split(split);
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::SetTheme(theme) => {
+            p.screen.theme = *theme;
+            p.format_lines();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_full(out, p)?;
             }
         }
         Command::FormatRedrawPrompt => {
             p.format_prompt();
-            display::write_prompt(out, &p.displayed_prompt, p.rows.try_into().unwrap())?;
+            display::draw_prompt(out, p)?;
         }
         Command::SetExitStrategy(es) => p.exit_strategy = es,
+        Command::SetInterruptStrategy(is) => p.interrupt_strategy = is,
         Command::LineWrapping(lw) => {
-            p.screen.line_wrapping = lw;
+            p.screen.line_wrapping = lw && p.screen.line_provider.is_none();
             p.format_lines();
         }
         #[cfg(feature = "static_output")]
         Command::SetRunNoOverflow(val) => p.run_no_overflow = val,
-        #[cfg(feature = "search")]
-        Command::IncrementalSearchCondition(cb) => p.search_state.incremental_search_condition = cb,
-        Command::SetInputClassifier(clf) => p.input_classifier = clf,
-        Command::AddExitCallback(cb) => p.exit_callbacks.push(cb),
-        Command::ShowPrompt(show) => p.show_prompt = show,
-        Command::FollowOutput(follow_output)
-        | Command::UserInput(InputEvent::FollowOutput(follow_output)) => {
-            p.follow_output = follow_output;
-            command_queue.push_back(Command::UserInput(InputEvent::UpdateUpperMark(
-                p.screen.formatted_lines_count(),
-            )));
-            command_queue.push_back(Command::FormatRedrawPrompt);
+        Command::SetPassthroughOnPipe(val) => p.passthrough_on_pipe = val,
+        // The output and input can't be switched once the pager has started using them
+        Command::SetOutput(output, cols, rows) => {
+            if p.running.lock().is_uninitialized() {
+                p.output = Some(output.into_inner());
+                p.output_size = Some((cols, rows));
+                if p.viewport.is_none() {
+                    p.cols = cols.into();
+                    p.rows = rows.into();
+                    // The text may have been formatted for the size of the terminal already
+                    p.format_lines();
+                }
+            }
         }
-        Command::UserInput(_) => {}
-    }
-    Ok(())
-}
-
-#[cfg(test)]
-mod tests {
-    use super::super::commands::Command;
-    use super::handle_event;
-    use crate::{minus_core::CommandQueue, ExitStrategy, PagerState, RunMode};
-    use std::sync::{atomic::AtomicBool, Arc};
-    #[cfg(feature = "search")]
-    use {
-        once_cell::sync::Lazy,
-        parking_lot::{Condvar, Mutex},
-    };
-
-    // Tests constants
-    #[cfg(feature = "search")]
-    static UIA: Lazy<Arc<(Mutex<bool>, Condvar)>> =
-        Lazy::new(|| Arc::new((Mutex::new(true), Condvar::new())));
-    const TEST_STR: &str = "This is some sample text";
-
-    // Tests for event emitting functions of Pager
-    #[test]
-    #[cfg(any(feature = "dynamic_output", feature = "static_output"))]
-    fn set_data() {
-        let mut ps = PagerState::new().unwrap();
-        let ev = Command::SetData(TEST_STR.to_string());
-        let mut out = Vec::new();
-        #[cfg(feature = "dynamic_output")]
-        {
-            *crate::minus_core::RUNMODE.lock() = RunMode::Dynamic;
+        Command::SetInput(source) => {
+            if p.running.lock().is_uninitialized() {
+                p.event_source = Arc::new(source);
+                p.terminal_input = false;
+            }
         }
-        #[cfg(feature = "static_output")]
+        Command::SetViewport(viewport) => {
+            let is_running = !p.running.lock().is_uninitialized();
+            if let Some(vp) = viewport {
+                p.rows = vp.height.into();
+                p.cols = vp.width.into();
+            } else if is_running {
+                let (cols, rows) = match p.output_size {
+                    Some(size) => size,
+                    None => crossterm::terminal::size()?,
+                };
+                p.rows = rows.into();
+                p.cols = cols.into();
+            }
+            p.viewport = viewport;
+            p.format_lines();
+            if is_running {
+                display::draw_full(out, p)?;
+            }
+        }
+        // The screen can't be switched once the terminal has been set up
+        Command::SetAlternateScreen(val) => {
+            if p.running.lock().is_uninitialized() {
+                p.alternate_screen = val;
+            }
+        }
+        Command::SetMouseCapture(val) => set_mouse_capture(&mut out, p, val)?,
+        Command::SetUnfocusedPause(repaints, follow_output) => {
+            let was_paused = p.repaints_paused() || p.following_paused();
+            p.pause_repaints_unfocused = repaints;
+            p.pause_follow_unfocused = follow_output;
+            resume_if_unpaused(&mut out, p, was_paused)?;
+        }
+        Command::SetFocused(focused) => {
+            let was_paused = p.repaints_paused() || p.following_paused();
+            p.focused = focused;
+            resume_if_unpaused(&mut out, p, was_paused)?;
+        }
+        Command::BeginUpdate => p.open_batches += 1,
+        Command::EndUpdate => {
+            let was_paused = p.repaints_paused() || p.following_paused();
+            p.open_batches = p.open_batches.saturating_sub(1);
+            resume_if_unpaused(&mut out, p, was_paused)?;
+        }
+        Command::AddBuffer(name) => p.buffers.add(name),
+        // Commands about the buffers themselves don't belong to the buffer they were sent to
+        Command::InBuffer(_, command)
+            if matches!(
+                *command,
+                Command::AddBuffer(_) | Command::ShowBuffer(_) | Command::InBuffer(..)
+            ) =>
         {
-            *crate::minus_core::RUNMODE.lock() = RunMode::Static;
+            handle_event(
+                *command,
+                out,
+                p,
+                command_queue,
+                is_exited,
+                user_input_active,
+            )?;
+        }
+        Command::InBuffer(name, command) => {
+            let Some(index) = p.buffers.position(&name) else {
+                return Ok(());
+            };
+            let displayed = p.buffers.loaded();
+            if index == displayed {
+                return handle_event(
+                    *command,
+                    out,
+                    p,
+                    command_queue,
+                    is_exited,
+                    user_input_active,
+                );
+            }
+            // The buffer isn't on the screen, so nothing that is drawn for it may reach the
+            // terminal
+            p.load_buffer(index);
+            let mut queued = CommandQueue::new_zero();
+            let res = handle_event(
+                *command,
+                &mut io::sink(),
+                p,
+                &mut queued,
+                is_exited,
+                user_input_active,
+            );
+            // The commands queued by it belong to the buffer too. It might have been renamed.
+            let name = p.buffers.loaded_name().to_string();
+            while let Some(command) = queued.pop_front() {
+                command_queue.push_back(Command::InBuffer(name.clone(), Box::new(command)));
+            }
+            p.load_buffer(displayed);
+            p.format_prompt();
+            res?;
+        }
+        Command::ShowBuffer(name) => {
+            if let Some(index) = p.buffers.position(&name) {
+                if p.display_buffer(index) && !p.running.lock().is_uninitialized() {
+                    display::draw_changed(&mut out, p)?;
+                }
+            }
+        }
+        Command::SetBufferName(name) => {
+            p.buffers.rename_loaded(name);
+            p.format_prompt();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_prompt(&mut out, p)?;
+            }
+        }
+        Command::UserInput(InputEvent::SwitchBuffer(index)) => {
+            if p.display_buffer(index) {
+                display::draw_changed(&mut out, p)?;
+                let name = p.buffers.loaded_name();
+                let position = format!("buffer {} of {}", index + 1, p.buffers.len());
+                let message = if name.is_empty() {
+                    position
+                } else {
+                    format!("{name} ({position})")
+                };
+                command_queue.push_back(Command::SendMessage(MessageLevel::Info, message, None));
+            }
+        }
+        Command::UserInput(InputEvent::SplitView(split)) => {
+            // Only the lines around the screen are fetched from a line provider
+            if split && p.screen.line_provider.is_some() {
+                command_queue.push_back(Command::SendMessage(
+                    MessageLevel::Error,
+                    "The screen can't be split for this text".to_string(),
+                    None,
+                ));
+            } else {
+                p.set_split(split);
+                display::draw_changed(&mut out, p)?;
+            }
+        }
+        Command::UserInput(InputEvent::ToggleFold(line)) => {
+            let line_count = p.screen.line_count();
+            let toggled = p.screen.line_provider.is_none()
+                && p.screen.folds.toggle(line, &p.screen.orig_text, line_count);
+            if toggled {
+                let folds = &p.screen.folds;
+                p.selected_line = p
+                    .selected_line
+                    .map(|line| folds.skip_hidden(line, false, line_count));
+                p.format_lines();
+                p.cursor_line_into_view();
+                display::draw_changed(&mut out, p)?;
+            } else {
+                command_queue.push_back(Command::SendMessage(
+                    MessageLevel::Error,
+                    "There is nothing to fold at this line".to_string(),
+                    None,
+                ));
+            }
+        }
+        Command::UserInput(InputEvent::FoldAll(fold)) if p.screen.line_provider.is_none() => {
+            if fold {
+                let line_count = p.screen.line_count();
+                p.screen.folds.close_all(&p.screen.orig_text, line_count);
+                let folds = &p.screen.folds;
+                p.selected_line = p
+                    .selected_line
+                    .map(|line| folds.skip_hidden(line, false, line_count));
+            } else {
+                p.screen.folds.open_all();
+            }
+            p.format_lines();
+            p.cursor_line_into_view();
+            display::draw_changed(&mut out, p)?;
+        }
+        Command::UserInput(iev @ (InputEvent::NextSection(_) | InputEvent::PrevSection(_))) => {
+            let (forward, count) = match iev {
+                InputEvent::NextSection(count) => (true, count),
+                InputEvent::PrevSection(count) => (false, count),
+                _ => unreachable!(),
+            };
+            if let Some(line) = p.section_line(forward, count) {
+                command_queue.push_back(jump_to_line(p, line));
+            } else {
+                let message = if forward {
+                    "No next section"
+                } else {
+                    "No previous section"
+                };
+                command_queue.push_back(Command::SendMessage(
+                    MessageLevel::Error,
+                    message.to_string(),
+                    None,
+                ));
+            }
+        }
+        Command::UserInput(InputEvent::SectionPicker) => {
+            if p.sections.is_empty() {
+                command_queue.push_back(Command::SendMessage(
+                    MessageLevel::Error,
+                    "There are no sections".to_string(),
+                    None,
+                ));
+                return Ok(());
+            }
+            // Start from the section that the current line is in
+            let selected = p
+                .sections
+                .range(..=p.current_line())
+                .count()
+                .saturating_sub(1);
+            let picked = {
+                let labels = p.sections.values().map(String::as_str).collect::<Vec<_>>();
+                with_input_paused(user_input_active, || {
+                    picker::pick(&mut out, p, "Go to section: ", &labels, selected)
+                })?
+            };
+            // The list was drawn over the text
+            p.displayed_frame = None;
+            display::draw_full(&mut out, p)?;
+            if let Some(line) = picked.and_then(|idx| p.sections.keys().nth(idx).copied()) {
+                command_queue.push_back(jump_to_line(p, line));
+            }
+        }
+        Command::UserInput(InputEvent::SwitchPane) if p.split.is_some() => {
+            p.switch_pane();
+            p.cursor_line_into_view();
+            display::draw_changed(&mut out, p)?;
+        }
+        Command::UserInput(InputEvent::MouseCapture(val)) => {
+            set_mouse_capture(&mut out, p, val)?;
+            let message = if val {
+                "Mouse capture on"
+            } else {
+                "Mouse capture off, the mouse selects text"
+            };
+            command_queue.push_back(Command::SendMessage(
+                MessageLevel::Info,
+                message.to_string(),
+                None,
+            ));
+        }
+        #[cfg(feature = "dynamic_output")]
+        Command::SetQuitIfOneScreen(val) => p.quit_if_one_screen = val,
+        Command::SetQuitAtEof(val) => p.quit_at_eof = val,
+        Command::AddSource(name, style) => p.sources.set_style(&name, style),
+        Command::AppendSourceData(name, text) => {
+            let text = p.sources.push(&name, &text, p.max_lines);
+            if !text.is_empty() {
+                handle_event(
+                    Command::AppendData(text),
+                    out,
+                    p,
+                    command_queue,
+                    is_exited,
+                    user_input_active,
+                )?;
+            }
+        }
+        Command::ShowSource(name, show) => {
+            if p.sources.set_shown(&name, show) {
+                p.screen.orig_text = p.sources.text().into();
+                p.format_lines();
+                p.screen.line_count = p.screen.orig_text.line_count();
+                p.enforce_max_lines();
+                if !p.running.lock().is_uninitialized() {
+                    display::draw_changed(out, p)?;
+                }
+            }
+        }
+        Command::DataFinished => {
+            p.data_finished = true;
+            // There is nothing more to follow. Stay at the end of the data
+            if p.follow_output {
+                command_queue.push_back(Command::FollowOutput(false));
+            }
+            p.format_prompt();
+            // The placeholder is replaced and the prompt may show that the end has been reached
+            if !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        #[cfg(feature = "search")]
+        Command::IncrementalSearchCondition(cb) => p.search_state.incremental_search_condition = cb,
+        Command::SetInputClassifier(clf) => p.input_classifier = clf,
+        Command::PushInputClassifier(clf) => {
+            let prev = std::mem::replace(&mut p.input_classifier, clf);
+            p.input_classifier_stack.push(prev);
+            p.prefix_num.clear();
+        }
+        Command::PopInputClassifier => {
+            if let Some(clf) = p.input_classifier_stack.pop() {
+                p.input_classifier = clf;
+                p.prefix_num.clear();
+            }
+        }
+        Command::AddExitCallback(cb) => p.exit_callbacks.push(cb),
+        Command::SetSavePathHook(hook) => p.save_path_hook = Some(hook),
+        Command::SetExitVeto(veto) => p.exit_veto = Some(veto),
+        Command::SetUnhandledKeyCallback(cb) => p.unhandled_key_callback = Some(cb),
+        Command::AddCustom(name, key, callback) => {
+            p.custom_commands.retain(|c| c.name != name);
+            p.custom_commands.push(CustomCommand {
+                name,
+                key,
+                callback,
+            });
+        }
+        Command::SetSynchronizedOutput(sync) => p.synchronized_output = sync,
+        Command::SetMaxRefreshRate(rate) => p.max_refresh_rate = rate,
+        Command::SetMaxLines(max_lines) => {
+            p.max_lines = max_lines;
+            if p.enforce_max_lines() && !p.running.lock().is_uninitialized() {
+                display::draw_changed(out, p)?;
+            }
+        }
+        Command::SetAppendBatching(interval, max_batch) => {
+            p.append_flush_interval = interval;
+            p.append_max_batch = max_batch;
+        }
+        Command::ShowPrompt(show) => p.show_prompt = show,
+        Command::FollowOutput(follow_output)
+        | Command::UserInput(InputEvent::FollowOutput(follow_output)) => {
+            p.follow_output = follow_output;
+            command_queue.push_back(Command::UserInput(InputEvent::UpdateUpperMark(
+                p.screen.formatted_lines_count(),
+            )));
+            command_queue.push_back(Command::FormatRedrawPrompt);
+        }
+        Command::UserInput(_) => {}
+    }
+    Ok(())
+}
+
+/// Capture the mouse or release it so that the terminal can select text with it
+///
+/// The terminal is only changed if it has already been set up. Otherwise the setting is applied
+/// when the pager starts.
+fn set_mouse_capture(
+    out: &mut impl Write,
+    p: &mut PagerState,
+    val: bool,
+) -> Result<(), MinusError> {
+    if p.mouse_capture == val {
+        return Ok(());
+    }
+    p.mouse_capture = val;
+    if !p.running.lock().is_uninitialized() {
+        if val {
+            crossterm::execute!(out, crossterm::event::EnableMouseCapture)
+                .map_err(|e| SetupError::EnableMouseCapture(e.into()))?;
+        } else {
+            crossterm::execute!(out, crossterm::event::DisableMouseCapture)
+                .map_err(|e| CleanupError::DisableMouseCapture(e.into()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Draw the screen again completely if drawing or following the output was paused and isn't
+/// anymore
+fn resume_if_unpaused(
+    out: &mut impl Write,
+    p: &mut PagerState,
+    was_paused: bool,
+) -> Result<(), MinusError> {
+    if !was_paused
+        || p.repaints_paused()
+        || p.following_paused()
+        || p.running.lock().is_uninitialized()
+    {
+        return Ok(());
+    }
+    // Catch up with the output that arrived in the meantime
+    if p.follow_output {
+        p.upper_mark = p.screen.formatted_lines_count();
+    }
+    p.displayed_frame = None;
+    display::draw_changed(out, p)
+}
+
+/// Reformat the text if relative line numbers are active and the line at the top of the screen
+/// has changed since the text was last formatted
+fn queue_relative_line_numbers_update(p: &PagerState, command_queue: &mut CommandQueue) {
+    if p.line_numbers.is_relative()
+        && p.lines_to_row_map.line_of_row(p.upper_mark) != p.screen.line_number_anchor
+    {
+        command_queue.push_back(Command::FormatRedrawDisplay);
+    }
+}
+
+/// Get the command which brings `line` to the top of the screen or moves the cursor line to it
+fn jump_to_line(p: &PagerState, line: usize) -> Command {
+    Command::UserInput(if p.selected_line.is_some() {
+        InputEvent::SelectLine(line)
+    } else {
+        InputEvent::UpdateUpperMark(p.upper_mark_for_jump(p.rows_of_line(line).start))
+    })
+}
+
+/// Pause the main user input thread while `f` reads the input by itself and restart it afterwards
+fn with_input_paused<T>(
+    user_input_active: &Arc<(Mutex<bool>, Condvar)>,
+    f: impl FnOnce() -> T,
+) -> T {
+    let (lock, cvar) = (&user_input_active.0, &user_input_active.1);
+    *lock.lock() = false;
+    let result = f();
+    *lock.lock() = true;
+    cvar.notify_one();
+    result
+}
+
+/// Quit the pager for `reason`, clean up the terminal and then follow `es`
+///
+/// If the user is quitting, the exit veto is asked first and the pager keeps running if it
+/// refuses.
+fn quit(
+    out: &mut impl Write,
+    p: &mut PagerState,
+    command_queue: &mut CommandQueue,
+    is_exited: &Arc<AtomicBool>,
+    reason: ExitReason,
+    es: &ExitStrategy,
+) -> Result<(), MinusError> {
+    if let (Some(veto), false) = (p.exit_veto.as_mut(), reason == ExitReason::Stopped) {
+        if let Err(message) = veto(reason) {
+            command_queue.push_back(Command::SendMessage(MessageLevel::Error, message, None));
+            return Ok(());
+        }
+    }
+    p.exit(reason);
+    is_exited.store(true, std::sync::atomic::Ordering::SeqCst);
+    term::cleanup(out, es, true, p.term_options())?;
+    Ok(())
+}
+
+/// Write the text, as it was given to the pager, to `file`
+fn save_text(p: &PagerState, file: File) -> io::Result<()> {
+    let mut file = BufWriter::new(file);
+    p.screen.write_text(&mut file)?;
+    file.flush()
+}
+
+/// Read a shell command from the user and pipe the text in `range` into it
+///
+/// The terminal is restored to its original state while the command runs so that the command
+/// can make use of it. The pager is resumed once the user presses a key afterwards.
+fn pipe_text(
+    out: &mut impl Write,
+    p: &mut PagerState,
+    range: PipeRange,
+    command_queue: &mut CommandQueue,
+) -> Result<(), MinusError> {
+    command_queue.push_back(Command::FormatRedrawPrompt);
+    let Some(command) = input_field::read_input(out, p, "|")?.filter(|c| !c.trim().is_empty())
+    else {
+        return Ok(());
+    };
+
+    let opts = p.term_options();
+    term::cleanup(&mut *out, &ExitStrategy::PagerQuit, true, opts)?;
+    let result = run_piped(&command, |stdin| match range {
+        PipeRange::All => p.screen.write_text(stdin),
+        PipeRange::Screen => p.screen.write_lines(stdin, p.visible_lines()),
+    });
+    write!(out, "\r\nPress any key to continue")?;
+    out.flush()?;
+
+    if opts.raw_mode {
+        terminal::enable_raw_mode().map_err(|e| SetupError::RawMode(e.into()))?;
+    }
+    loop {
+        let ev = p
+            .event_source
+            .lock()
+            .next_event(Duration::from_millis(100))
+            .map_err(|e| MinusError::HandleEvent(e.into()))?;
+        if let Some(Event::Key(KeyEvent {
+            kind: KeyEventKind::Press,
+            ..
+        })) = ev
+        {
+            break;
+        }
+    }
+    term::setup(out, opts)?;
+
+    // The whole screen needs to be drawn again
+    p.displayed_frame = None;
+    command_queue.push_back(Command::FormatRedrawDisplay);
+    if let Err(e) = result {
+        command_queue.push_back(Command::SendMessage(
+            MessageLevel::Error,
+            format!("Could not run {command}: {e}"),
+            None,
+        ));
+    }
+    Ok(())
+}
+
+/// Restore the terminal and stop the process group, just like the terminal does on `Ctrl+Z` when
+/// it isn't in raw mode
+///
+/// This returns once the process has been resumed and the terminal has been set up again.
+#[cfg(unix)]
+fn suspend(out: &mut impl Write, p: &PagerState) -> Result<(), MinusError> {
+    let opts = p.term_options();
+    term::cleanup(&mut *out, &ExitStrategy::PagerQuit, true, opts)?;
+    // SAFETY: Sending a signal doesn't touch any memory of this process. The stop signal is
+    // delivered before `kill` returns, so execution continues here only after being resumed.
+    unsafe {
+        libc::kill(0, libc::SIGTSTP);
+    }
+    term::setup(out, opts)?;
+    Ok(())
+}
+
+/// Run `command` in the shell and write its input with `write_input`
+///
+/// The command may exit without reading all of its input, hence a broken pipe is not treated as
+/// an error.
+fn run_piped(
+    command: &str,
+    write_input: impl FnOnce(&mut BufWriter<ChildStdin>) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut child = shell_command(command).stdin(Stdio::piped()).spawn()?;
+    if let Some(stdin) = child.stdin.take() {
+        let mut stdin = BufWriter::new(stdin);
+        match write_input(&mut stdin).and_then(|()| stdin.flush()) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e),
+            _ => {}
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Create a [`process::Command`] that runs `command` in the user's shell
+fn shell_command(command: &str) -> process::Command {
+    #[cfg(windows)]
+    let (shell, flag) = (
+        std::env::var_os("COMSPEC").unwrap_or_else(|| "cmd".into()),
+        "/C",
+    );
+    #[cfg(not(windows))]
+    let (shell, flag) = (
+        std::env::var_os("SHELL").unwrap_or_else(|| "sh".into()),
+        "-c",
+    );
+    let mut shell = process::Command::new(shell);
+    shell.arg(flag).arg(command);
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::commands::Command;
+    use super::handle_event;
+    use crate::{
+        input::{DefaultInputClassifier, InputEvent},
+        minus_core::CommandQueue,
+        Cell, ColorSupport, ExitStrategy, LineNumbers, MessageLevel, PagerState, PromptMode,
+        RunMode, Theme,
+    };
+    use crossterm::{
+        event::{Event, KeyCode, KeyEvent},
+        style::{Color, ContentStyle, Stylize},
+    };
+    use once_cell::sync::Lazy;
+    use parking_lot::{Condvar, Mutex};
+    use std::sync::{atomic::AtomicBool, Arc};
+    use std::time::Duration;
+
+    // Tests constants
+    static UIA: Lazy<Arc<(Mutex<bool>, Condvar)>> =
+        Lazy::new(|| Arc::new((Mutex::new(true), Condvar::new())));
+    const TEST_STR: &str = "This is some sample text";
+
+    // Tests for event emitting functions of Pager
+    #[test]
+    #[cfg(any(feature = "dynamic_output", feature = "static_output"))]
+    fn set_data() {
+        let mut ps = PagerState::new().unwrap();
+        let ev = Command::SetData(TEST_STR.to_string());
+        let mut out = Vec::new();
+        #[cfg(feature = "dynamic_output")]
+        {
+            *ps.running.lock() = RunMode::Dynamic;
+        }
+        #[cfg(feature = "static_output")]
+        {
+            *ps.running.lock() = RunMode::Static;
         }
         let mut command_queue = CommandQueue::new_zero();
 
@@ -369,7 +1523,6 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
             &UIA,
         )
         .unwrap();
@@ -390,7 +1543,6 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
             &UIA,
         )
         .unwrap();
@@ -400,7 +1552,6 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
             &UIA,
         )
         .unwrap();
@@ -419,11 +1570,11 @@ mod tests {
         let mut command_queue = CommandQueue::new_zero();
         #[cfg(feature = "dynamic_output")]
         {
-            *crate::minus_core::RUNMODE.lock() = RunMode::Dynamic;
+            *ps.running.lock() = RunMode::Dynamic;
         }
         #[cfg(feature = "static_output")]
         {
-            *crate::minus_core::RUNMODE.lock() = RunMode::Static;
+            *ps.running.lock() = RunMode::Static;
         }
 
         handle_event(
@@ -432,7 +1583,6 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
             &UIA,
         )
         .unwrap();
@@ -445,13 +1595,13 @@ mod tests {
         let mut ps = PagerState::new().unwrap();
         #[cfg(feature = "dynamic_output")]
         {
-            *crate::minus_core::RUNMODE.lock() = RunMode::Dynamic;
+            *ps.running.lock() = RunMode::Dynamic;
         }
         #[cfg(feature = "static_output")]
         {
-            *crate::minus_core::RUNMODE.lock() = RunMode::Static;
+            *ps.running.lock() = RunMode::Static;
         }
-        let ev = Command::SendMessage(TEST_STR.to_string());
+        let ev = Command::SendMessage(MessageLevel::Error, TEST_STR.to_string(), None);
         let mut out = Vec::new();
         let mut command_queue = CommandQueue::new_zero();
 
@@ -461,13 +1611,55 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
             &UIA,
         )
         .unwrap();
         assert_eq!(ps.message.unwrap(), TEST_STR.to_string());
     }
 
+    #[test]
+    fn expire_message() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        for ev in [
+            Command::SendMessage(
+                MessageLevel::Info,
+                TEST_STR.to_string(),
+                Some(Duration::ZERO),
+            ),
+            Command::ExpireMessage,
+        ] {
+            handle_event(
+                ev,
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        }
+        assert_eq!(ps.message_level, MessageLevel::Info);
+        assert_eq!(
+            command_queue.pop_front(),
+            Some(Command::UserInput(InputEvent::RestorePrompt))
+        );
+
+        // Messages without a timeout don't expire
+        ps.message_expiry = None;
+        handle_event(
+            Command::ExpireMessage,
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        assert!(command_queue.is_empty());
+    }
+
     #[test]
     #[cfg(feature = "static_output")]
     fn set_run_no_overflow() {
@@ -482,7 +1674,6 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
             &UIA,
         )
         .unwrap();
@@ -502,13 +1693,857 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
             &UIA,
         )
         .unwrap();
         assert_eq!(ps.exit_strategy, ExitStrategy::PagerQuit);
     }
 
+    #[test]
+    fn jump_back_and_forward() {
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.orig_text = "Line\n".repeat(100).into();
+        ps.format_lines();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+
+        for ev in [
+            InputEvent::UpdateUpperMark(50),
+            InputEvent::UpdateUpperMark(52),
+            InputEvent::JumpBack,
+        ] {
+            handle_event(
+                Command::UserInput(ev),
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        }
+        // Scrolling by 2 lines is not a large jump hence it should not be recorded
+        assert_eq!(ps.upper_mark, 0);
+
+        handle_event(
+            Command::UserInput(InputEvent::JumpForward),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        assert_eq!(ps.upper_mark, 52);
+    }
+
+    #[test]
+    #[cfg(feature = "search")]
+    fn jump_back_after_prev_match() {
+        let mut ps = PagerState::new().unwrap();
+        ps.search_state.search_term = Some(regex::Regex::new("match").unwrap());
+        ps.screen.orig_text = format!("match\n{}", "line\n".repeat(100)).as_str().into();
+        ps.format_lines();
+        ps.upper_mark = 60;
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |command, ps: &mut PagerState| {
+            command_queue.push_back(command);
+            while let Some(command) = command_queue.pop_front() {
+                handle_event(
+                    command,
+                    &mut out,
+                    ps,
+                    &mut command_queue,
+                    &Arc::new(AtomicBool::new(false)),
+                    &UIA,
+                )
+                .unwrap();
+            }
+        };
+
+        handle(Command::UserInput(InputEvent::PrevMatch), &mut ps);
+        assert_eq!(ps.upper_mark, 0);
+        handle(Command::UserInput(InputEvent::JumpBack), &mut ps);
+        assert_eq!(ps.upper_mark, 60);
+    }
+
+    #[test]
+    fn push_pop_input_classifier() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+
+        for ev in [
+            Command::PushInputClassifier(Box::new(DefaultInputClassifier)),
+            Command::PushInputClassifier(Box::new(DefaultInputClassifier)),
+            Command::PopInputClassifier,
+        ] {
+            handle_event(
+                ev,
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        }
+        assert_eq!(ps.input_classifier_stack.len(), 1);
+
+        // Popping more than what has been pushed should keep the base classifier
+        for _ in 0..2 {
+            handle_event(
+                Command::PopInputClassifier,
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        }
+        assert!(ps.input_classifier_stack.is_empty());
+    }
+
+    #[test]
+    fn set_prompt_style() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let style = ContentStyle::new().with(Color::White).on(Color::DarkGreen);
+        ps.message = Some("Hello".to_string());
+
+        handle_event(
+            Command::SetPromptStyle(PromptMode::Message, style),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+
+        // Only compare the part till the message as the rest of the prompt is padded
+        let styled_message = style.apply("Hello").to_string();
+        let (style_codes, _) = styled_message.split_once("Hello").unwrap();
+        assert!(ps
+            .displayed_prompt
+            .starts_with(&format!("{style_codes}Hello")));
+    }
+
+    #[test]
+    fn set_prompt_format() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        ps.rows = 11;
+        ps.prompt = "file.txt".to_string();
+        ps.screen.orig_text = (1..=40).map(|n| format!("{n}\n")).collect();
+        ps.screen.line_count = 40;
+        ps.format_lines();
+        ps.upper_mark = 10;
+
+        handle_event(
+            Command::SetPromptFormat(
+                "%prompt %line-%bottom/%total %percent%% %unknown".to_string(),
+            ),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        assert!(ps
+            .displayed_prompt
+            .contains("file.txt 11-20/40 50% %unknown"));
+
+        // Messages are still shown in place of the prompt
+        ps.message = Some("Hello".to_string());
+        ps.format_prompt();
+        assert!(!ps.displayed_prompt.contains("file.txt"));
+    }
+
+    #[test]
+    fn set_progress() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+
+        for (progress, indicator) in [
+            (0.42, " [####------]  42% "),
+            (1.5, " [##########] 100% "),
+            (f32::NAN, " [----------]   0% "),
+        ] {
+            handle_event(
+                Command::SetProgress(Some(progress)),
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+            assert!(ps.displayed_prompt.contains(indicator));
+        }
+
+        handle_event(
+            Command::SetProgress(None),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        assert!(!ps.displayed_prompt.contains('%'));
+    }
+
+    #[test]
+    #[cfg(feature = "search")]
+    fn scroll_off() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        ps.rows = 11;
+        ps.screen.orig_text = (1..=40).map(|n| format!("{n}\n")).collect();
+        ps.screen.line_count = 40;
+        ps.search_state.search_term = Some(regex::Regex::new("5").unwrap());
+        ps.scroll_off = 2;
+        ps.format_lines();
+
+        // The rows of `5`, `15` and `25` are shown with two rows above them
+        for upper_mark in [2, 12, 22] {
+            command_queue.push_back(Command::UserInput(InputEvent::NextMatch));
+            while let Some(command) = command_queue.pop_front() {
+                handle_event(
+                    command,
+                    &mut out,
+                    &mut ps,
+                    &mut command_queue,
+                    &Arc::new(AtomicBool::new(false)),
+                    &UIA,
+                )
+                .unwrap();
+            }
+            assert_eq!(ps.upper_mark, upper_mark);
+        }
+
+        // The margin is smaller if the screen can't fit it
+        ps.scroll_off = 10;
+        assert_eq!(ps.scroll_off_rows(), 4);
+    }
+
+    #[test]
+    fn end_of_data() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        ps.rows = 11;
+        ps.screen.orig_text = (1..=20).map(|n| format!("{n}\n")).collect();
+        ps.screen.line_count = 20;
+        ps.format_lines();
+        ps.format_prompt();
+        assert!(!ps.eof_reached());
+
+        handle_event(
+            Command::DataFinished,
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        // The end isn't on the screen yet
+        assert!(!ps.eof_reached());
+        assert!(!ps.displayed_prompt.contains("(END)"));
+
+        handle_event(
+            Command::UserInput(InputEvent::UpdateUpperMark(10)),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        assert!(ps.eof_reached());
+        assert!(ps.displayed_prompt.contains("(END)"));
+    }
+
+    #[test]
+    #[cfg(feature = "static_output")]
+    fn toggle_mouse_capture() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+
+        // Nothing is written before the terminal is set up
+        handle_event(
+            Command::SetMouseCapture(false),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        assert!(!ps.mouse_capture);
+        assert!(out.is_empty());
+
+        *ps.running.lock() = RunMode::Static;
+        handle_event(
+            Command::UserInput(InputEvent::MouseCapture(true)),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        assert!(ps.mouse_capture);
+        let mut enable = Vec::new();
+        crossterm::execute!(enable, crossterm::event::EnableMouseCapture).unwrap();
+        assert_eq!(out, enable);
+        assert!(matches!(
+            command_queue.pop_front(),
+            Some(Command::SendMessage(MessageLevel::Info, _, None))
+        ));
+
+        out.clear();
+        handle_event(
+            Command::SetMouseCapture(false),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        let mut disable = Vec::new();
+        crossterm::execute!(disable, crossterm::event::DisableMouseCapture).unwrap();
+        assert_eq!(out, disable);
+    }
+
+    #[test]
+    #[cfg(feature = "static_output")]
+    fn unfocused_pause() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        ps.rows = 5;
+        ps.follow_output = true;
+        *ps.running.lock() = RunMode::Static;
+        let mut handle = |command, out: &mut Vec<u8>, ps: &mut PagerState| {
+            handle_event(
+                command,
+                out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        };
+
+        handle(Command::SetUnfocusedPause(true, true), &mut out, &mut ps);
+        handle(Command::SetFocused(false), &mut out, &mut ps);
+        handle(
+            Command::AppendData("1\n2\n3\n4\n5\n6\n7\n8\n".to_string()),
+            &mut out,
+            &mut ps,
+        );
+        handle(Command::SetPrompt("Logs".to_string()), &mut out, &mut ps);
+        // Nothing is drawn and the screen doesn't follow the output
+        assert!(out.is_empty());
+        assert_eq!(ps.upper_mark, 0);
+
+        handle(Command::SetFocused(true), &mut out, &mut ps);
+        assert!(!out.is_empty());
+        assert!(ps.displayed_frame.is_some());
+        assert_eq!(ps.upper_mark, 4);
+    }
+
+    #[test]
+    #[cfg(feature = "static_output")]
+    fn batch_update() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        ps.rows = 5;
+        *ps.running.lock() = RunMode::Static;
+        let mut handle = |command, out: &mut Vec<u8>, ps: &mut PagerState| {
+            handle_event(
+                command,
+                out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        };
+
+        handle(Command::BeginUpdate, &mut out, &mut ps);
+        handle(Command::BeginUpdate, &mut out, &mut ps);
+        handle(Command::SetData("1\n2\n".to_string()), &mut out, &mut ps);
+        handle(Command::AppendData("3\n".to_string()), &mut out, &mut ps);
+        handle(Command::SetPrompt("Logs".to_string()), &mut out, &mut ps);
+        handle(Command::EndUpdate, &mut out, &mut ps);
+        // Nothing is drawn until the outermost batch is closed
+        assert!(out.is_empty());
+
+        handle(Command::EndUpdate, &mut out, &mut ps);
+        assert!(!out.is_empty());
+        assert!(ps.displayed_frame.is_some());
+        assert_eq!(ps.prompt, "Logs");
+    }
+
+    #[test]
+    fn switch_buffers() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |command, ps: &mut PagerState| {
+            handle_event(
+                command,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        };
+        let in_buffer = |name: &str, command| Command::InBuffer(name.into(), Box::new(command));
+
+        handle(Command::SetData("first\n".into()), &mut ps);
+        handle(Command::AddBuffer("second".into()), &mut ps);
+        handle(
+            in_buffer("second", Command::AppendData("a\nb\n".into())),
+            &mut ps,
+        );
+        // Text sent to a buffer that isn't displayed doesn't show up
+        assert_eq!(ps.screen.orig_text, "first\n");
+        assert_eq!(ps.screen.formatted_lines, vec!["first"]);
+
+        handle(Command::ShowBuffer("second".into()), &mut ps);
+        assert_eq!(ps.screen.formatted_lines, vec!["a", "b"]);
+        assert_eq!(ps.buffers.loaded_name(), "second");
+        handle(in_buffer("", Command::AppendData("last\n".into())), &mut ps);
+        handle(
+            in_buffer("second", Command::AppendData("c\n".into())),
+            &mut ps,
+        );
+        assert_eq!(ps.screen.formatted_lines, vec!["a", "b", "c"]);
+
+        handle(Command::UserInput(InputEvent::SwitchBuffer(0)), &mut ps);
+        assert_eq!(ps.screen.formatted_lines, vec!["first", "last"]);
+        assert_eq!(ps.buffers.loaded(), 0);
+    }
+
+    #[test]
+    fn queued_commands_stay_in_their_buffer() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new();
+        let in_buffer = |name: &str, command| Command::InBuffer(name.into(), Box::new(command));
+
+        for command in [
+            Command::SetData("first\n".into()),
+            Command::AddBuffer("table".into()),
+            in_buffer("table", Command::PushRow(vec![Cell::new("a")])),
+            // A row which fits the columns is appended by a queued command
+            in_buffer("table", Command::PushRow(vec![Cell::new("b")])),
+        ] {
+            command_queue.push_back(command);
+            while let Some(command) = command_queue.pop_front() {
+                handle_event(
+                    command,
+                    &mut out,
+                    &mut ps,
+                    &mut command_queue,
+                    &Arc::new(AtomicBool::new(false)),
+                    &UIA,
+                )
+                .unwrap();
+            }
+        }
+        assert_eq!(ps.screen.orig_text, "first\n");
+
+        command_queue.push_back(Command::ShowBuffer("table".into()));
+        while let Some(command) = command_queue.pop_front() {
+            handle_event(
+                command,
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        }
+        assert_eq!(ps.screen.orig_text, "a\nb\n");
+    }
+
+    #[test]
+    fn discarded_lines_keep_numbers() {
+        let mut ps = PagerState::new().unwrap();
+        ps.max_lines = 3;
+        ps.line_numbers = LineNumbers::Enabled;
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |command, ps: &mut PagerState| {
+            handle_event(
+                command,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        };
+
+        handle(Command::AppendData("L1\nL2\nL3\nL4\nL5\n".into()), &mut ps);
+        assert_eq!(ps.screen.formatted_lines[0], "     3. L3");
+
+        // Another buffer is numbered from the start
+        handle(Command::AddBuffer("other".into()), &mut ps);
+        handle(Command::ShowBuffer("other".into()), &mut ps);
+        handle(Command::AppendData("A\n".into()), &mut ps);
+        assert_eq!(ps.screen.formatted_lines, vec!["     1. A"]);
+        handle(Command::ShowBuffer(String::new()), &mut ps);
+        assert_eq!(ps.screen.formatted_lines[0], "     3. L3");
+
+        // New text starts again from the first line number
+        handle(Command::SetData("A\nB\n".into()), &mut ps);
+        assert_eq!(ps.screen.formatted_lines, vec!["     1. A", "     2. B"]);
+    }
+
+    #[test]
+    fn split_view_panes() {
+        let mut ps = PagerState::new().unwrap();
+        ps.rows = 8;
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |command, ps: &mut PagerState| {
+            handle_event(
+                command,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        };
+
+        handle(Command::SetData("a\n".repeat(20)), &mut ps);
+        handle(Command::UserInput(InputEvent::SplitView(true)), &mut ps);
+        // Only the focused pane moves and it can be scrolled to the end of the text by itself
+        handle(
+            Command::UserInput(InputEvent::UpdateUpperMark(100)),
+            &mut ps,
+        );
+        assert_eq!(ps.upper_mark, 17);
+        handle(Command::UserInput(InputEvent::SwitchPane), &mut ps);
+        assert_eq!(ps.upper_mark, 0);
+        assert_eq!(ps.split.map(|split| split.other_mark), Some(17));
+
+        handle(Command::UserInput(InputEvent::SplitView(false)), &mut ps);
+        assert!(ps.split.is_none());
+        assert_eq!(ps.upper_mark, 0);
+    }
+
+    #[test]
+    fn fold_lines() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |command, ps: &mut PagerState| {
+            handle_event(
+                command,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        };
+
+        handle(
+            Command::SetData("a\n  b\n  c\n  d\ne\n".to_string()),
+            &mut ps,
+        );
+        handle(Command::SetFoldByIndent(true), &mut ps);
+        handle(Command::SetCursorLine(true), &mut ps);
+        handle(Command::UserInput(InputEvent::ToggleFold(2)), &mut ps);
+        assert_eq!(ps.screen.formatted_lines.len(), 2);
+        assert!(ps.screen.formatted_lines[0].starts_with("a "));
+        assert!(ps.screen.formatted_lines[0].contains("3 more lines"));
+        assert_eq!(ps.lines_to_row_map.line_of_row(1), 4);
+        assert_eq!(ps.rows_of_line(2), 1..1);
+
+        // The cursor skips the hidden lines
+        handle(Command::UserInput(InputEvent::SelectLine(1)), &mut ps);
+        assert_eq!(ps.selected_line, Some(4));
+        handle(Command::UserInput(InputEvent::SelectLine(3)), &mut ps);
+        assert_eq!(ps.selected_line, Some(0));
+
+        handle(Command::UserInput(InputEvent::ToggleFold(0)), &mut ps);
+        assert_eq!(ps.screen.formatted_lines.len(), 5);
+        handle(Command::UserInput(InputEvent::FoldAll(true)), &mut ps);
+        assert_eq!(ps.screen.formatted_lines.len(), 2);
+        handle(Command::UserInput(InputEvent::FoldAll(false)), &mut ps);
+        assert_eq!(ps.screen.formatted_lines.len(), 5);
+    }
+
+    #[test]
+    fn sections() {
+        let mut ps = PagerState::new().unwrap();
+        ps.rows = 5;
+        let (events, source) = std::sync::mpsc::channel();
+        ps.event_source = Arc::new(Mutex::new(Box::new(source)));
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |command, ps: &mut PagerState| {
+            command_queue.push_back(command);
+            while let Some(command) = command_queue.pop_front() {
+                if matches!(command, Command::SendMessage(..)) {
+                    continue;
+                }
+                handle_event(
+                    command,
+                    &mut out,
+                    ps,
+                    &mut command_queue,
+                    &Arc::new(AtomicBool::new(false)),
+                    &UIA,
+                )
+                .unwrap();
+            }
+        };
+
+        handle(Command::SetData("a\n".repeat(30)), &mut ps);
+        handle(
+            Command::AddSections(vec![
+                (20, "Third".to_string()),
+                (5, "First".to_string()),
+                (10, "Second".to_string()),
+            ]),
+            &mut ps,
+        );
+        handle(Command::UserInput(InputEvent::NextSection(1)), &mut ps);
+        assert_eq!(ps.upper_mark, 5);
+        handle(Command::UserInput(InputEvent::NextSection(5)), &mut ps);
+        assert_eq!(ps.upper_mark, 20);
+        handle(Command::UserInput(InputEvent::PrevSection(1)), &mut ps);
+        assert_eq!(ps.upper_mark, 10);
+        handle(Command::UserInput(InputEvent::PrevSection(1)), &mut ps);
+        assert_eq!(ps.upper_mark, 5);
+        // Nothing happens without a section in that direction
+        handle(Command::UserInput(InputEvent::PrevSection(1)), &mut ps);
+        assert_eq!(ps.upper_mark, 5);
+
+        // The picker starts at the current section
+        for code in [KeyCode::Down, KeyCode::Down, KeyCode::Enter] {
+            events.send(Event::Key(KeyEvent::from(code))).unwrap();
+        }
+        handle(Command::UserInput(InputEvent::SectionPicker), &mut ps);
+        assert_eq!(ps.upper_mark, 20);
+
+        // The cursor line is moved instead of the screen
+        handle(Command::SetCursorLine(true), &mut ps);
+        events
+            .send(Event::Key(KeyEvent::from(KeyCode::Up)))
+            .unwrap();
+        events
+            .send(Event::Key(KeyEvent::from(KeyCode::Enter)))
+            .unwrap();
+        handle(Command::UserInput(InputEvent::SectionPicker), &mut ps);
+        assert_eq!(ps.selected_line, Some(10));
+    }
+
+    #[test]
+    fn merge_sources() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |command, ps: &mut PagerState| {
+            handle_event(
+                command,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        };
+        let append = |name: &str, text: &str| Command::AppendSourceData(name.into(), text.into());
+
+        handle(
+            Command::AddSource("web".into(), ContentStyle::new()),
+            &mut ps,
+        );
+        handle(
+            Command::AddSource("db".into(), ContentStyle::new()),
+            &mut ps,
+        );
+        handle(append("web", "GET /in"), &mut ps);
+        handle(append("db", "ready\n"), &mut ps);
+        handle(append("web", "dex.html\nGET /"), &mut ps);
+        handle(append("db", "query\n"), &mut ps);
+        assert_eq!(
+            ps.screen.orig_text,
+            "db  | ready\nweb | GET /index.html\ndb  | query\n"
+        );
+
+        handle(Command::ShowSource("db".into(), false), &mut ps);
+        assert_eq!(ps.screen.orig_text, "web | GET /index.html\n");
+        // Lines of hidden sources are kept for when they are shown again
+        handle(append("db", "done\n"), &mut ps);
+        handle(append("web", "\n"), &mut ps);
+        assert_eq!(ps.screen.orig_text, "web | GET /index.html\nweb | GET /\n");
+        handle(Command::ShowSource("db".into(), true), &mut ps);
+        assert_eq!(
+            ps.screen.orig_text,
+            "db  | ready\nweb | GET /index.html\ndb  | query\ndb  | done\nweb | GET /\n"
+        );
+    }
+
+    #[test]
+    fn sources_keep_in_step_with_text() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |command, ps: &mut PagerState| {
+            handle_event(
+                command,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        };
+        let append = |name: &str, text: &str| Command::AppendSourceData(name.into(), text.into());
+        handle(
+            Command::AddSource("web".into(), ContentStyle::new()),
+            &mut ps,
+        );
+        handle(
+            Command::AddSource("db".into(), ContentStyle::new()),
+            &mut ps,
+        );
+        handle(Command::SetMaxLines(2), &mut ps);
+        handle(append("web", "1\n2\n3\n"), &mut ps);
+        assert_eq!(ps.screen.orig_text, "web | 2\nweb | 3\n");
+
+        // Only the last lines of a hidden source are kept
+        handle(Command::ShowSource("db".into(), false), &mut ps);
+        let db_lines: String = (0..10).map(|n| format!("{n}\n")).collect();
+        handle(append("db", &db_lines), &mut ps);
+        handle(Command::ShowSource("db".into(), true), &mut ps);
+        assert_eq!(ps.screen.orig_text, "db  | 8\ndb  | 9\n");
+
+        // Discarded lines don't come back when the text is rebuilt
+        handle(Command::SetMaxLines(0), &mut ps);
+        handle(Command::ShowSource("web".into(), false), &mut ps);
+        handle(Command::ShowSource("web".into(), true), &mut ps);
+        assert_eq!(ps.screen.orig_text, "db  | 8\ndb  | 9\n");
+
+        // Neither do truncated lines
+        handle(Command::Truncate(1), &mut ps);
+        handle(Command::ShowSource("web".into(), false), &mut ps);
+        handle(Command::ShowSource("web".into(), true), &mut ps);
+        assert_eq!(ps.screen.orig_text, "db  | 8\n");
+
+        // Lines of sources can't be edited
+        handle(Command::EditLines(0..1, "edited\n".into()), &mut ps);
+        assert_eq!(ps.screen.orig_text, "db  | 8\n");
+    }
+
+    #[test]
+    fn finish_data_stops_following() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        ps.follow_output = true;
+
+        handle_event(
+            Command::DataFinished,
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        assert_eq!(
+            command_queue.pop_front(),
+            Some(Command::FollowOutput(false))
+        );
+    }
+
+    #[test]
+    fn show_percentage() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        ps.rows = 11;
+        ps.screen.orig_text = (1..=40).map(|n| format!("{n}\n")).collect();
+        ps.screen.line_count = 40;
+        ps.format_lines();
+        ps.upper_mark = 10;
+
+        handle_event(
+            Command::ShowPercentage(true),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        assert!(ps.displayed_prompt.contains(" 50% "));
+    }
+
+    #[test]
+    fn set_theme() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        ps.screen.orig_text = "First\nSecond\n".into();
+        ps.format_lines();
+        ps.selected_line = Some(1);
+        ps.screen.color_support = ColorSupport::TrueColor;
+
+        let mut theme = Theme::default();
+        theme.prompt = ContentStyle::new().with(Color::White).on(Color::DarkBlue);
+        theme.selection = ContentStyle::new().on(Color::DarkGrey);
+        handle_event(
+            Command::SetTheme(Box::new(theme)),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+
+        assert_eq!(ps.screen.theme, theme);
+        assert_eq!(ps.row_highlight(1), Some(theme.selection));
+        let styled_prompt = theme.prompt.apply("minus").to_string();
+        let (style_codes, _) = styled_prompt.split_once("minus").unwrap();
+        assert!(ps.displayed_prompt.starts_with(style_codes));
+    }
+
     #[test]
     fn add_exit_callback() {
         let mut ps = PagerState::new().unwrap();
@@ -522,7 +2557,6 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
             &UIA,
         )
         .unwrap();
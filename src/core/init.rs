@@ -11,7 +11,7 @@
 #[cfg(feature = "static_output")]
 use crate::minus_core::utils::display;
 use crate::{
-    error::MinusError,
+    error::{MinusError, SetupError},
     input::InputEvent,
     minus_core::{
         commands::Command,
@@ -19,28 +19,159 @@ use crate::{
         utils::{display::draw_full, term},
         RunMode,
     },
-    Pager, PagerState,
+    screen, ExitReason, Pager, PagerState,
 };
 
-use crossbeam_channel::{Receiver, Sender, TrySendError};
-use crossterm::event;
+use crossbeam_channel::{Receiver, RecvError, RecvTimeoutError, Sender, TrySendError};
+use crossterm::{
+    event::{Event, KeyEventKind},
+    execute, queue,
+    terminal::{BeginSynchronizedUpdate, EndSynchronizedUpdate},
+};
 use std::{
-    io::{stdout, Stdout},
+    io::{stdout, Write},
     panic,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::Duration,
 };
 
-#[cfg(feature = "static_output")]
-use {super::utils::display::write_raw_lines, crossterm::tty::IsTty};
+use super::utils::display::write_raw_lines;
+use crossterm::tty::IsTty;
+#[cfg(feature = "dynamic_output")]
+use std::time::Instant;
 
-#[cfg(feature = "search")]
 use parking_lot::Condvar;
 use parking_lot::Mutex;
 
-use super::{utils::display::draw_for_change, CommandQueue, RUNMODE};
+use super::{utils::display::draw_for_change, CommandQueue, RunModeGuard, TerminalGuard};
+
+/// Returns `true` if minus is running in a CI or under a terminal with `TERM=dumb`
+///
+/// A full screen pager cannot work properly in these environments.
+#[cfg(feature = "static_output")]
+fn is_dumb_environment() -> bool {
+    std::env::var_os("CI").is_some() || std::env::var("TERM").as_deref() == Ok("dumb")
+}
+
+// `PanicHookInfo`, which replaces it, is only available since Rust 1.81
+#[allow(deprecated)]
+type PanicHook = Box<dyn Fn(&panic::PanicInfo<'_>) + Sync + Send + 'static>;
+
+/// Terminal cleanups of all the pagers that are running
+///
+/// Pagers running at the same time share a single panic hook which runs all of these cleanups.
+/// The panic hook that was set before is called after them and put back once the last pager
+/// quits. Raw mode isn't counted here, as at most one of these pagers reads its input from the
+/// terminal of the process.
+struct PanicCleanups {
+    previous_hook: Option<Arc<PanicHook>>,
+    next_id: usize,
+    cleanups: Vec<(usize, Box<dyn Fn() + Send>)>,
+}
+
+static PANIC_CLEANUPS: Mutex<PanicCleanups> = parking_lot::const_mutex(PanicCleanups {
+    previous_hook: None,
+    next_id: 0,
+    cleanups: Vec::new(),
+});
+
+/// Removes a cleanup added by [`on_panic`] when dropped
+struct PanicCleanupGuard(usize);
+
+/// Run `cleanup` if any thread panics until the returned guard is dropped
+fn on_panic(cleanup: impl Fn() + Send + 'static) -> PanicCleanupGuard {
+    let mut registry = PANIC_CLEANUPS.lock();
+    if registry.previous_hook.is_none() {
+        let previous_hook = Arc::new(panic::take_hook());
+        registry.previous_hook = Some(previous_hook.clone());
+        panic::set_hook(Box::new(move |pinfo| {
+            // The registry might be locked by the thread that panicked
+            if let Some(registry) = PANIC_CLEANUPS.try_lock() {
+                for (_, cleanup) in &registry.cleanups {
+                    cleanup();
+                }
+            }
+            previous_hook(pinfo);
+        }));
+    }
+    let id = registry.next_id;
+    registry.next_id += 1;
+    registry.cleanups.push((id, Box::new(cleanup)));
+    drop(registry);
+    PanicCleanupGuard(id)
+}
+
+impl Drop for PanicCleanupGuard {
+    fn drop(&mut self) {
+        let mut registry = PANIC_CLEANUPS.lock();
+        registry.cleanups.retain(|(id, _)| *id != self.0);
+        if registry.cleanups.is_empty() {
+            if let Some(previous_hook) = registry.previous_hook.take() {
+                panic::set_hook(Box::new(move |pinfo| previous_hook(pinfo)));
+            }
+        }
+    }
+}
+
+/// Write all the formatted lines to the main screen and run the exit callbacks
+fn write_inline(out: &mut impl Write, ps: &mut PagerState) -> crate::Result {
+    ps.fetch_provider_lines(0);
+    let count = ps.screen.formatted_lines_count();
+    write_raw_lines(
+        out,
+        ps.screen.get_formatted_lines_with_bounds(0, count),
+        Some("\r"),
+    )?;
+    ps.exit(ExitReason::FitsOneScreen);
+    Ok(())
+}
+
+/// Write the text of the pager straight to `out` instead of paging it
+///
+/// If `rx` is given, the data that is sent to the pager afterwards is also written as it arrives
+/// until all the senders have been dropped.
+fn passthrough(
+    out: &mut impl Write,
+    ps: &mut PagerState,
+    rx: Option<Receiver<Command>>,
+) -> crate::Result {
+    ps.screen.write_text(out)?;
+    out.flush()?;
+
+    for command in rx.into_iter().flatten() {
+        match command {
+            Command::AppendData(text) | Command::SetData(text) => {
+                out.write_all(text.as_bytes())?;
+            }
+            Command::SetLineProvider(provider) => {
+                screen::write_provider_lines(out, provider.as_ref())?;
+            }
+            Command::AddExitCallback(cb) => ps.exit_callbacks.push(cb),
+            Command::Stop => break,
+            _ => continue,
+        }
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// Set the run mode of `pager` to `rm`
+///
+/// The returned guard must be given to [`init_core`].
+///
+/// # Panics
+/// This function panics if the pager is already running.
+pub fn mark_running(pager: &Pager, rm: RunMode) -> RunModeGuard {
+    let runmode = RunModeGuard::set(&pager.running, rm);
+    assert!(
+        runmode.previous().is_uninitialized(),
+        "Failed to start the pager. This is caused probably because it is already running"
+    );
+    runmode
+}
 
 /// The main entry point of minus
 ///
@@ -50,9 +181,16 @@ use super::{utils::display::draw_for_change, CommandQueue, RUNMODE};
 /// It first receives all events present inside the [`Pager`]'s receiver
 /// and creates the initial state that to be stored inside the [`PagerState`]
 ///
+/// If standard output is not a terminal screen, that is if it is a file or a pipe, minus will
+/// write all the data straight to the stdout without starting the pager. In dynamic mode the data
+/// sent to the [`Pager`] keeps being written until it and all its clones are dropped. This
+/// can be turned off with [`Pager::set_passthrough_on_pipe(false)`](Pager::set_passthrough_on_pipe).
+///
 /// Then it checks if the minus is running in static mode and does some checks:-
-/// * If standard output is not a terminal screen, that is if it is a file or block
-/// device, minus will write all the data at once to the stdout and quit
+///
+/// * If minus is running in a CI or under a terminal with `TERM=dumb`, minus will write the data
+///   in chunks with a `--More--` prompt between them if standard input is a terminal. Otherwise
+///   it will write all the data at once and quit
 ///
 /// * If the size of the data is less than the available number of rows in the terminal
 /// then it displays everything on the main stdout screen at once and quits. This
@@ -76,100 +214,166 @@ use super::{utils::display::draw_for_change, CommandQueue, RUNMODE};
 /// [`event reader`]: event_reader
 #[allow(clippy::module_name_repetitions)]
 #[allow(clippy::too_many_lines)]
-pub fn init_core(pager: &Pager, rm: RunMode) -> std::result::Result<(), MinusError> {
-    #[allow(unused_mut)]
-    let mut out = stdout();
+pub fn init_core(
+    pager: Pager,
+    _runmode: RunModeGuard,
+) -> std::result::Result<ExitReason, MinusError> {
+    // The run mode is reset once `_runmode` is dropped, so that the pager can be started again
+    // after it has quit
+    let rm = *pager.running.lock();
+
     // Is the event reader running
-    #[cfg(feature = "search")]
     let input_thread_running = Arc::new((Mutex::new(true), Condvar::new()));
 
     #[allow(unused_mut)]
-    let mut ps = crate::state::PagerState::generate_initial_state(&pager.rx, &mut out)?;
+    let mut ps = crate::state::PagerState::generate_initial_state(&pager.rx, &mut stdout())?;
+
+    // Draw to the output set by the application if there is one. It is always assumed to be a
+    // terminal
+    let custom_output = ps.output.is_some();
+    let is_terminal = custom_output || stdout().is_tty();
+    let mut out: Box<dyn Write + Send> = ps.output.take().unwrap_or_else(|| Box::new(stdout()));
+    // Only one pager at a time can use the terminal of the process. It is released once
+    // `_terminal` is dropped
+    let _terminal = (!custom_output || ps.terminal_input).then(|| TerminalGuard::acquire(rm));
+
+    // Like with static data, write the text to the main screen and quit if it turns out to fit
+    // on it once the data is finished
+    #[cfg(feature = "dynamic_output")]
+    if rm == RunMode::Dynamic && ps.quit_if_one_screen && is_terminal {
+        // Our own pager never stops being able to send data, so stop waiting once no other pager
+        // is left to send any
+        ps.wait_for_overflow(&pager.rx, &mut out, || pager.has_other_clones())?;
+        if ps.all_data_fits_one_screen() {
+            write_inline(&mut out, &mut ps)?;
+            return Ok(ExitReason::FitsOneScreen);
+        }
+    }
 
-    {
-        let mut runmode = super::RUNMODE.lock();
-        assert!(runmode.is_uninitialized(), "Failed to set the RUNMODE. This is caused probably because another instance of minus is already running");
-        *runmode = rm;
-        drop(runmode);
+    // Everything has been handled as if the pager had not started till now
+    ps.running = pager.running.clone();
+
+    // If stdout is not a tty, write everything straight to it
+    if ps.passthrough_on_pipe && !is_terminal {
+        // More data can only arrive later in dynamic mode
+        #[cfg(feature = "static_output")]
+        let rx = (rm != RunMode::Static).then(|| pager.rx.clone());
+        #[cfg(not(feature = "static_output"))]
+        let rx = Some(pager.rx.clone());
+        // Our own sender must be dropped to know when no more data can be sent. The callbacks for
+        // the user's keys are never called here and might hold other clones of it.
+        drop(pager);
+        ps.custom_commands.clear();
+        ps.unhandled_key_callback = None;
+        let res = passthrough(&mut out, &mut ps, rx);
+        ps.exit(ExitReason::Passthrough);
+        return res.map(|()| ExitReason::Passthrough);
     }
 
     // Static mode checks
     #[cfg(feature = "static_output")]
-    if *RUNMODE.lock() == RunMode::Static {
-        // If stdout is not a tty, write everything and quit
-        if !out.is_tty() {
-            write_raw_lines(&mut out, &[ps.screen.orig_text], None)?;
-            let mut rm = RUNMODE.lock();
-            *rm = RunMode::Uninitialized;
-            drop(rm);
-            return Ok(());
+    if rm == RunMode::Static {
+        // All of the data is given at once in static mode
+        ps.data_finished = true;
+
+        // If we are running in a CI or a dumb terminal, a full screen pager cannot work properly.
+        // Write everything in chunks if the user can respond to the --More-- prompt, otherwise
+        // write everything at once
+        if !custom_output && is_dumb_environment() {
+            let stdin = std::io::stdin();
+            if stdin.is_tty() {
+                // Keep a row for the --More-- prompt. If the terminal size is unknown, assume the
+                // standard 24 rows
+                let chunk_size = if ps.rows > 1 { ps.rows - 1 } else { 23 };
+                display::write_in_chunks(
+                    &mut out,
+                    &mut stdin.lock(),
+                    ps.screen.orig_text.lines(),
+                    chunk_size,
+                )?;
+            } else {
+                ps.screen.orig_text.write_to(&mut out)?;
+                writeln!(out)?;
+            }
+            ps.exit(ExitReason::Passthrough);
+            return Ok(ExitReason::Passthrough);
         }
         // If number of lines of text is less than available rows, write everything and quit
         // unless run_no_overflow is set to true
-        if ps.screen.formatted_lines_count() <= ps.rows && !ps.run_no_overflow {
-            write_raw_lines(&mut out, &ps.screen.formatted_lines, Some("\r"))?;
-            ps.exit();
-            let mut rm = RUNMODE.lock();
-            *rm = RunMode::Uninitialized;
-            drop(rm);
-            return Ok(());
+        if ps.all_data_fits_one_screen() && !ps.run_no_overflow {
+            write_inline(&mut out, &mut ps)?;
+            return Ok(ExitReason::FitsOneScreen);
         }
     }
 
+    if !is_terminal {
+        return Err(SetupError::InvalidTerminal.into());
+    }
+
     // Setup terminal, adjust line wraps and get rows
-    term::setup(&out)?;
+    let term_options = ps.term_options();
+    term::setup(&mut out, term_options)?;
+    let out = Arc::new(Mutex::new(out));
 
     // Has the user quit
     let is_exited = Arc::new(AtomicBool::new(false));
     let is_exited2 = is_exited.clone();
 
-    {
-        let panic_hook = panic::take_hook();
-        panic::set_hook(Box::new(move |pinfo| {
+    // Restore the terminal if any thread panics while the pager is running
+    let _panic_cleanup = {
+        let out = out.clone();
+        on_panic(move || {
             is_exited2.store(true, std::sync::atomic::Ordering::SeqCst);
             // While silently ignoring error is considered a bad practice, we are forced to do it here
             // as we cannot use the ? and panicking here will cause UB.
-            drop(term::cleanup(
-                stdout(),
-                &crate::ExitStrategy::PagerQuit,
-                true,
-            ));
-            panic_hook(pinfo);
-        }));
-    }
+            // The output might be locked by the thread that panicked, so stdout is used instead
+            // when it is the output
+            if let Some(mut out) = out.try_lock() {
+                drop(term::cleanup(
+                    &mut *out,
+                    &crate::ExitStrategy::PagerQuit,
+                    true,
+                    term_options,
+                ));
+            } else if !custom_output {
+                drop(term::cleanup(
+                    stdout(),
+                    &crate::ExitStrategy::PagerQuit,
+                    true,
+                    term_options,
+                ));
+            } else if term_options.raw_mode {
+                drop(crossterm::terminal::disable_raw_mode());
+            }
+        })
+    };
 
     let ps_mutex = Arc::new(Mutex::new(ps));
 
     let evtx = pager.tx.clone();
     let rx = pager.rx.clone();
-    let out = stdout();
 
     let p1 = ps_mutex.clone();
+    let p2 = ps_mutex.clone();
 
-    #[cfg(feature = "search")]
     let input_thread_running2 = input_thread_running.clone();
 
-    std::thread::scope(|s| -> crate::Result {
-        let out = Arc::new(out);
+    let res = std::thread::scope(|s| -> crate::Result {
         let out_copy = out.clone();
         let is_exited3 = is_exited.clone();
         let is_exited4 = is_exited.clone();
 
         let t1 = s.spawn(move || {
-            let res = event_reader(
-                &evtx,
-                &p1,
-                #[cfg(feature = "search")]
-                &input_thread_running2,
-                &is_exited3,
-            );
+            let res = event_reader(&evtx, &p1, &input_thread_running2, &is_exited3);
 
             if res.is_err() {
                 is_exited3.store(true, std::sync::atomic::Ordering::SeqCst);
-                let mut rm = RUNMODE.lock();
-                *rm = RunMode::Uninitialized;
-                drop(rm);
-                term::cleanup(out.as_ref(), &crate::ExitStrategy::PagerQuit, true)?;
+                term::cleanup(
+                    &mut *out.lock(),
+                    &crate::ExitStrategy::PagerQuit,
+                    true,
+                    term_options,
+                )?;
             }
             res
         });
@@ -178,17 +382,18 @@ pub fn init_core(pager: &Pager, rm: RunMode) -> std::result::Result<(), MinusErr
                 &rx,
                 &ps_mutex,
                 &out_copy,
-                #[cfg(feature = "search")]
                 &input_thread_running,
                 &is_exited4,
             );
 
             if res.is_err() {
                 is_exited4.store(true, std::sync::atomic::Ordering::SeqCst);
-                let mut rm = RUNMODE.lock();
-                *rm = RunMode::Uninitialized;
-                drop(rm);
-                term::cleanup(out_copy.as_ref(), &crate::ExitStrategy::PagerQuit, true)?;
+                term::cleanup(
+                    &mut *out_copy.lock(),
+                    &crate::ExitStrategy::PagerQuit,
+                    true,
+                    term_options,
+                )?;
             }
             res
         });
@@ -199,7 +404,13 @@ pub fn init_core(pager: &Pager, rm: RunMode) -> std::result::Result<(), MinusErr
         r1?;
         r2?;
         Ok(())
-    })
+    });
+    res?;
+    let exit_reason = p2.lock().exit_reason;
+    Ok(exit_reason.expect(
+        "The pager quit without a reason. This is most likely a bug. Please open an issue to the \
+developers",
+    ))
 }
 
 /// Continuously displays the output and reacts to events
@@ -219,49 +430,84 @@ pub fn init_core(pager: &Pager, rm: RunMode) -> std::result::Result<(), MinusErr
 fn start_reactor(
     rx: &Receiver<Command>,
     ps: &Arc<Mutex<PagerState>>,
-    out: &Stdout,
-    #[cfg(feature = "search")] input_thread_running: &Arc<(Mutex<bool>, Condvar)>,
+    out: &Mutex<Box<dyn Write + Send>>,
+    input_thread_running: &Arc<(Mutex<bool>, Condvar)>,
     is_exited: &Arc<AtomicBool>,
 ) -> Result<(), MinusError> {
-    let mut out_lock = out.lock();
     let mut command_queue = CommandQueue::new();
 
     {
         let mut p = ps.lock();
+        let mut out_lock = out.lock();
 
-        draw_full(&mut out_lock, &mut p)?;
+        if p.synchronized_output {
+            queue!(out_lock, BeginSynchronizedUpdate)?;
+        }
+        p.go_to_initial_position();
+        draw_full(&mut *out_lock, &mut p)?;
 
         if p.follow_output {
-            draw_for_change(&mut out_lock, &mut p, &mut (usize::MAX - 1))?;
+            draw_for_change(&mut *out_lock, &mut p, &mut (usize::MAX - 1))?;
+        }
+        if p.synchronized_output {
+            execute!(out_lock, EndSynchronizedUpdate)?;
+        }
+
+        for command in std::mem::take(&mut p.pending_commands) {
+            command_queue.push_back(command);
         }
     }
 
-    let run_mode = *RUNMODE.lock();
+    // Command received while batching appends which must be handled after the batched text
+    #[cfg(feature = "dynamic_output")]
+    let mut held_back_command = None;
+    // When appended text was last drawn
+    #[cfg(feature = "dynamic_output")]
+    let mut last_append_draw: Option<Instant> = None;
+
+    let run_mode = *ps.lock().running.lock();
     match run_mode {
         #[cfg(feature = "dynamic_output")]
         RunMode::Dynamic => loop {
             if is_exited.load(Ordering::SeqCst) {
-                let mut rm = RUNMODE.lock();
-                *rm = RunMode::Uninitialized;
-                drop(rm);
                 break;
             }
 
-            let next_command = if command_queue.is_empty() {
-                rx.recv()
+            let next_command = command_queue
+                .pop_front()
+                .or_else(|| held_back_command.take())
+                .map_or_else(|| recv_command(rx, ps), Ok);
+
+            // Merge the following appends so that they are formatted and drawn at once
+            let next_command = if let Ok(Command::AppendData(text)) = next_command {
+                let (deadline, max_batch) = {
+                    let p = ps.lock();
+                    let mut deadline = Instant::now() + p.append_flush_interval;
+                    // Keep collecting the text until the next refresh if appended text has
+                    // been drawn recently
+                    if let (Some(last_draw), Some(refresh_interval)) =
+                        (last_append_draw, p.refresh_interval())
+                    {
+                        deadline = deadline.max(last_draw + refresh_interval);
+                    }
+                    (deadline, p.append_max_batch)
+                };
+                let (text, other_command) = batch_appends(rx, text, deadline, max_batch);
+                held_back_command = other_command;
+                last_append_draw = Some(Instant::now());
+                Ok(Command::AppendData(text))
             } else {
-                Ok(command_queue.pop_front().unwrap())
+                next_command
             };
 
             if let Ok(command) = next_command {
                 let mut p = ps.lock();
-                handle_event(
+                handle_synchronized(
                     command,
-                    &mut out_lock,
+                    &mut *out.lock(),
                     &mut p,
                     &mut command_queue,
                     is_exited,
-                    #[cfg(feature = "search")]
                     input_thread_running,
                 )?;
             }
@@ -271,7 +517,7 @@ fn start_reactor(
             {
                 let mut p = ps.lock();
                 if p.follow_output {
-                    display::draw_for_change(&mut out_lock, &mut p, &mut (usize::MAX - 1))?;
+                    display::draw_for_change(&mut *out.lock(), &mut p, &mut (usize::MAX - 1))?;
                 }
             }
 
@@ -280,54 +526,197 @@ fn start_reactor(
                     // Cleanup the screen
                     //
                     // This is not needed in dynamic paging because this is already handled by handle_event
-                    term::cleanup(&mut out_lock, &ps.lock().exit_strategy, true)?;
-
-                    let mut rm = RUNMODE.lock();
-                    *rm = RunMode::Uninitialized;
-                    drop(rm);
+                    let p = ps.lock();
+                    term::cleanup(&mut *out.lock(), &p.exit_strategy, true, p.term_options())?;
+                    drop(p);
 
                     break;
                 }
                 let next_command = if command_queue.is_empty() {
-                    rx.recv()
+                    recv_command(rx, ps)
                 } else {
                     Ok(command_queue.pop_front().unwrap())
                 };
 
                 if let Ok(command) = next_command {
                     let mut p = ps.lock();
-                    handle_event(
+                    handle_synchronized(
                         command,
-                        &mut out_lock,
+                        &mut *out.lock(),
                         &mut p,
                         &mut command_queue,
                         is_exited,
-                        #[cfg(feature = "search")]
                         input_thread_running,
                     )?;
                 }
             }
         }
         RunMode::Uninitialized => panic!(
-            "Static variable RUNMODE set to uninitialized.\
+            "The run mode of the pager is set to uninitialized.\
 This is most likely a bug. Please open an issue to the developers"
         ),
     }
     Ok(())
 }
 
+/// Wait for the next command from `rx`
+///
+/// If the message on the prompt is only shown for some time and that time passes before a command
+/// arrives, [`Command::ExpireMessage`] is returned instead. Likewise [`Command::MoveSpinner`] is
+/// returned when the spinner shown while waiting for the data should move and
+/// [`Command::CheckTermSize`] when the size of the terminal should be polled.
+fn recv_command(rx: &Receiver<Command>, ps: &Mutex<PagerState>) -> Result<Command, RecvError> {
+    let timeouts = {
+        let p = ps.lock();
+        [
+            p.message_expiry
+                .map(|expiry| (expiry, Command::ExpireMessage)),
+            p.next_spinner_move()
+                .map(|spinner_move| (spinner_move, Command::MoveSpinner)),
+            p.next_size_check()
+                .map(|size_check| (size_check, Command::CheckTermSize)),
+        ]
+    };
+    // The earliest one is returned if none of the commands arrives before it
+    let Some((deadline, timeout_command)) = IntoIterator::into_iter(timeouts)
+        .flatten()
+        .min_by_key(|(deadline, _)| *deadline)
+    else {
+        return rx.recv();
+    };
+    match rx.recv_deadline(deadline) {
+        Ok(command) => Ok(command),
+        Err(RecvTimeoutError::Timeout) => Ok(timeout_command),
+        Err(RecvTimeoutError::Disconnected) => Err(RecvError),
+    }
+}
+
+/// Handle `command` as a single synchronized update of the terminal
+///
+/// If [`PagerState::synchronized_output`] is set, the terminal holds back displaying anything that
+/// is drawn while handling the command and then displays it all at once. This prevents tearing
+/// when the screen is updated rapidly. Commands that wait for the user's input and exit commands
+/// aren't synchronized as the terminal would not display anything until they are finished.
+fn handle_synchronized(
+    command: Command,
+    out: &mut impl Write,
+    p: &mut PagerState,
+    command_queue: &mut CommandQueue,
+    is_exited: &Arc<AtomicBool>,
+    input_thread_running: &Arc<(Mutex<bool>, Condvar)>,
+) -> Result<(), MinusError> {
+    let synchronize =
+        p.synchronized_output && !command.waits_for_input() && !command.is_exit_event();
+    if synchronize {
+        queue!(out, BeginSynchronizedUpdate)?;
+    }
+    handle_event(
+        command,
+        out,
+        p,
+        command_queue,
+        is_exited,
+        input_thread_running,
+    )?;
+    if synchronize {
+        execute!(out, EndSynchronizedUpdate)?;
+    }
+    Ok(())
+}
+
+/// Merge the text of the [`Command::AppendData`] commands waiting in `rx` into `text`
+///
+/// If `deadline` hasn't passed yet, this waits until then for more commands to arrive. At most
+/// `max_batch` appends are merged. If a command of any other kind is received, batching stops
+/// and that command is returned as it must be handled after the merged text.
+#[cfg(feature = "dynamic_output")]
+fn batch_appends(
+    rx: &Receiver<Command>,
+    mut text: String,
+    deadline: Instant,
+    max_batch: usize,
+) -> (String, Option<Command>) {
+    for _ in 1..max_batch {
+        let command = if deadline <= Instant::now() {
+            rx.try_recv().ok()
+        } else {
+            rx.recv_deadline(deadline).ok()
+        };
+        match command {
+            Some(Command::AppendData(more)) => text.push_str(&more),
+            Some(command) => return (text, Some(command)),
+            None => break,
+        }
+    }
+    (text, None)
+}
+
+/// Get the [`Command`] that `ev` stands for and update the number prefix accordingly
+///
+/// The keys of the commands added by the application take precedence over the input classifier.
+/// Key presses that don't stand for any [`InputEvent`], or stand for [`InputEvent::Ignore`], are
+/// given to the unhandled key callback. Moving down once the end of the data is on the screen
+/// quits if [`Pager::set_quit_at_eof`](crate::Pager::set_quit_at_eof) is turned on.
+///
+/// Text pasted into the terminal is ignored. It is only inserted when a prompt is open so that
+/// pasting doesn't trigger the keybindings of the characters in it. The terminal gaining or losing
+/// the focus is always handled by the pager itself.
+pub fn classify_event(ev: Event, ps: &mut PagerState) -> Option<Command> {
+    match ev {
+        Event::Paste(_) => return None,
+        Event::FocusGained => return Some(Command::SetFocused(true)),
+        Event::FocusLost => return Some(Command::SetFocused(false)),
+        _ => {}
+    }
+    let key = match ev {
+        Event::Key(key) if key.kind != KeyEventKind::Release => Some(key),
+        _ => None,
+    };
+    let custom_command = key.and_then(|key| ps.custom_commands.iter().find(|c| c.key == key));
+    let command = if let Some(custom_command) = custom_command {
+        Some(Command::RunCustom(custom_command.name.clone()))
+    } else {
+        let input = ps.input_classifier.classify_input(ev, ps);
+        if let (None | Some(InputEvent::Ignore), Some(key), Some(cb)) =
+            (input, key, ps.unhandled_key_callback.as_mut())
+        {
+            cb(key);
+        }
+        match input {
+            Some(InputEvent::UpdateUpperMark(um))
+                if ps.quit_at_eof && um > ps.upper_mark && ps.eof_reached() =>
+            {
+                Some(Command::QuitAtEof)
+            }
+            _ => input.map(Command::UserInput),
+        }
+    };
+    match command {
+        Some(Command::UserInput(InputEvent::Number(n))) => {
+            ps.prefix_num.push(n);
+            ps.format_prompt();
+        }
+        _ if !ps.prefix_num.is_empty() => {
+            ps.prefix_num.clear();
+            ps.format_prompt();
+        }
+        _ => {}
+    }
+    command
+}
+
 fn event_reader(
     evtx: &Sender<Command>,
     ps: &Arc<Mutex<PagerState>>,
-    #[cfg(feature = "search")] user_input_active: &Arc<(Mutex<bool>, Condvar)>,
+    user_input_active: &Arc<(Mutex<bool>, Condvar)>,
     is_exited: &Arc<AtomicBool>,
 ) -> Result<(), MinusError> {
+    let source = ps.lock().event_source.clone();
     loop {
         if is_exited.load(Ordering::SeqCst) {
             break;
         }
 
-        #[cfg(feature = "search")]
         {
             let (lock, cvar) = (&user_input_active.0, &user_input_active.1);
             let mut active = lock.lock();
@@ -336,29 +725,200 @@ fn event_reader(
             }
         }
 
-        if event::poll(std::time::Duration::from_millis(100))
-            .map_err(|e| MinusError::HandleEvent(e.into()))?
-        {
-            let ev = event::read().map_err(|e| MinusError::HandleEvent(e.into()))?;
-            let mut guard = ps.lock();
-            // Get the events
-            let input = guard.input_classifier.classify_input(ev, &guard);
-            if let Some(iev) = input {
-                if let InputEvent::Number(n) = iev {
-                    guard.prefix_num.push(n);
-                    guard.format_prompt();
-                } else if !guard.prefix_num.is_empty() {
-                    guard.prefix_num.clear();
-                    guard.format_prompt();
-                }
-                if let Err(TrySendError::Disconnected(_)) = evtx.try_send(Command::UserInput(iev)) {
+        let ev = source
+            .lock()
+            .next_event(Duration::from_millis(100))
+            .map_err(|e| MinusError::HandleEvent(e.into()))?;
+        if let Some(ev) = ev {
+            let command = classify_event(ev, &mut ps.lock());
+            if let Some(command) = command {
+                if let Err(TrySendError::Disconnected(_)) = evtx.try_send(command) {
                     break;
                 }
-            } else if !guard.prefix_num.is_empty() {
-                guard.prefix_num.clear();
-                guard.format_prompt();
             }
         }
     }
     Result::<(), MinusError>::Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{classify_event, handle_synchronized, passthrough, recv_command};
+    use crate::{
+        minus_core::{commands::Command, CommandQueue},
+        MessageLevel, PagerState,
+    };
+    use std::sync::{atomic::AtomicBool, Arc};
+    #[cfg(feature = "dynamic_output")]
+    use {
+        super::batch_appends,
+        std::time::{Duration, Instant},
+    };
+
+    #[test]
+    fn passthrough_streams_data() {
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.orig_text = "initial\n".into();
+        let (tx, rx) = crossbeam_channel::unbounded();
+        tx.send(Command::AppendData("first ".to_string())).unwrap();
+        tx.send(Command::SendMessage(
+            MessageLevel::Error,
+            "ignored".to_string(),
+            None,
+        ))
+        .unwrap();
+        tx.send(Command::AppendData("line\n".to_string())).unwrap();
+        drop(tx);
+
+        let mut out = Vec::new();
+        passthrough(&mut out, &mut ps, Some(rx)).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "initial\nfirst line\n");
+    }
+
+    #[test]
+    fn quit_at_eof() {
+        use crossterm::event::{Event, KeyCode, KeyEvent};
+
+        let mut ps = PagerState::new().unwrap();
+        ps.rows = 6;
+        ps.screen.orig_text = (1..=10).map(|n| format!("{n}\n")).collect();
+        ps.screen.line_count = 10;
+        ps.format_lines();
+        ps.quit_at_eof = true;
+        ps.data_finished = true;
+        let down = || Event::Key(KeyEvent::from(KeyCode::Down));
+
+        // Moving down doesn't quit until the end of the data is on the screen
+        assert!(matches!(
+            classify_event(down(), &mut ps),
+            Some(Command::UserInput(_))
+        ));
+        ps.upper_mark = 5;
+        assert_eq!(classify_event(down(), &mut ps), Some(Command::QuitAtEof));
+
+        // More data might still arrive
+        ps.data_finished = false;
+        assert!(matches!(
+            classify_event(down(), &mut ps),
+            Some(Command::UserInput(_))
+        ));
+    }
+
+    #[test]
+    fn paste_is_ignored() {
+        use crossterm::event::Event;
+
+        let mut ps = PagerState::new().unwrap();
+        ps.prefix_num = String::from("5");
+        assert_eq!(
+            classify_event(Event::Paste(String::from("q/G")), &mut ps),
+            None
+        );
+        // A number typed before the paste isn't lost
+        assert_eq!(ps.prefix_num, "5");
+    }
+
+    #[test]
+    fn focus_events() {
+        use crossterm::event::Event;
+
+        let mut ps = PagerState::new().unwrap();
+        assert_eq!(
+            classify_event(Event::FocusLost, &mut ps),
+            Some(Command::SetFocused(false))
+        );
+        assert_eq!(
+            classify_event(Event::FocusGained, &mut ps),
+            Some(Command::SetFocused(true))
+        );
+    }
+
+    #[test]
+    fn poll_term_size() {
+        let ps = parking_lot::Mutex::new(PagerState::new().unwrap());
+        ps.lock().resize_poll_interval = Some(std::time::Duration::ZERO);
+        let (tx, rx) = crossbeam_channel::unbounded();
+        assert_eq!(recv_command(&rx, &ps), Ok(Command::CheckTermSize));
+
+        // The size isn't polled when minus doesn't draw to the terminal of the process
+        ps.lock().output_size = Some((80, 10));
+        tx.send(Command::SetPrompt("Prompt".to_string())).unwrap();
+        assert_eq!(
+            recv_command(&rx, &ps),
+            Ok(Command::SetPrompt("Prompt".to_string()))
+        );
+    }
+
+    #[test]
+    fn synchronized_update() {
+        let mut ps = PagerState::new().unwrap();
+        ps.synchronized_output = true;
+        let mut out = Vec::new();
+        handle_synchronized(
+            Command::SetPrompt("Prompt".to_string()),
+            &mut out,
+            &mut ps,
+            &mut CommandQueue::new_zero(),
+            &Arc::new(AtomicBool::new(false)),
+            &Arc::new((parking_lot::Mutex::new(true), parking_lot::Condvar::new())),
+        )
+        .unwrap();
+        // Everything should be written between the begin and end synchronized update sequences
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("\x1b[?2026h"));
+        assert!(out.ends_with("\x1b[?2026l"));
+        assert_eq!(ps.prompt, "Prompt");
+    }
+
+    #[test]
+    #[cfg(feature = "dynamic_output")]
+    fn batch_waiting_appends() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        tx.send(Command::AppendData("B".to_string())).unwrap();
+        tx.send(Command::AppendData("C".to_string())).unwrap();
+        tx.send(Command::SetPrompt("Prompt".to_string())).unwrap();
+        tx.send(Command::AppendData("D".to_string())).unwrap();
+
+        let (text, other) = batch_appends(&rx, "A".to_string(), Instant::now(), 256);
+        assert_eq!(text, "ABC");
+        assert_eq!(other, Some(Command::SetPrompt("Prompt".to_string())));
+
+        // The batch is limited to max_batch appends including the first one
+        let (text, other) = batch_appends(&rx, "C".to_string(), Instant::now(), 1);
+        assert_eq!(text, "C");
+        assert_eq!(other, None);
+        let (text, other) = batch_appends(&rx, String::new(), Instant::now(), 256);
+        assert_eq!(text, "D");
+        assert_eq!(other, None);
+    }
+
+    #[test]
+    #[cfg(feature = "dynamic_output")]
+    fn batch_appends_within_interval() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let sender = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            tx.send(Command::AppendData("B".to_string())).unwrap();
+        });
+
+        let (text, _) = batch_appends(
+            &rx,
+            "A".to_string(),
+            Instant::now() + Duration::from_secs(5),
+            2,
+        );
+        assert_eq!(text, "AB");
+        sender.join().unwrap();
+    }
+
+    #[test]
+    fn terminal_is_used_by_one_pager_at_a_time() {
+        use crate::minus_core::{RunMode, TerminalGuard};
+
+        let guard = TerminalGuard::acquire(RunMode::Dynamic);
+        assert!(std::panic::catch_unwind(|| TerminalGuard::acquire(RunMode::Static)).is_err());
+        drop(guard);
+        // The terminal can be used again once the pager using it has quit
+        drop(TerminalGuard::acquire(RunMode::Static));
+    }
+}
@@ -1,11 +1,72 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, sync::Arc};
+
+use parking_lot::Mutex;
 
 pub mod commands;
 pub mod ev_handler;
 #[cfg(any(feature = "dynamic_output", feature = "static_output"))]
 pub mod init;
 pub mod utils;
-pub static RUNMODE: parking_lot::Mutex<RunMode> = parking_lot::const_mutex(RunMode::Uninitialized);
+
+/// The [RunMode] of the pager which uses the terminal of the process
+///
+/// Raw mode and the alternate screen are settings of the whole terminal, so only one pager at a
+/// time can use it. Pagers with their own output and input don't touch it and are not tracked
+/// here.
+static RUNMODE: Mutex<RunMode> = parking_lot::const_mutex(RunMode::Uninitialized);
+
+/// Sets the [RunMode] of a pager and restores the previous value of it when dropped
+///
+/// This makes sure that the [RunMode] is reset however the pager quits, even if it panics, so
+/// that it can be started again afterwards.
+pub struct RunModeGuard {
+    running: Arc<Mutex<RunMode>>,
+    previous: RunMode,
+}
+
+impl RunModeGuard {
+    pub fn set(running: &Arc<Mutex<RunMode>>, rm: RunMode) -> Self {
+        Self {
+            previous: std::mem::replace(&mut *running.lock(), rm),
+            running: running.clone(),
+        }
+    }
+
+    /// The [RunMode] before it was set by this guard
+    pub const fn previous(&self) -> RunMode {
+        self.previous
+    }
+}
+
+impl Drop for RunModeGuard {
+    fn drop(&mut self) {
+        *self.running.lock() = self.previous;
+    }
+}
+
+/// Marks the terminal of the process as used by a pager till it is dropped
+pub struct TerminalGuard(());
+
+impl TerminalGuard {
+    /// # Panics
+    /// This function panics if another pager is already using the terminal.
+    pub fn acquire(rm: RunMode) -> Self {
+        let mut runmode = RUNMODE.lock();
+        assert!(
+            runmode.is_uninitialized(),
+            "Failed to start the pager. This is caused probably because another pager is already using the terminal"
+        );
+        *runmode = rm;
+        drop(runmode);
+        Self(())
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        *RUNMODE.lock() = RunMode::Uninitialized;
+    }
+}
 
 use commands::Command;
 
@@ -16,10 +77,6 @@ use commands::Command;
 /// requires the text data to be reformatted and repainted on the screen. Hence it can push that
 /// command to this to be executed once it itself has completed executing.
 ///
-/// This also takes into account [RUNMODE] before inserting data. The means that it will ensure that
-/// [RUNMODE] is not uninitialized before pushing any data into the queue. Hence it is best used
-/// case is while declaring handlers for [Command::UserInput].
-///
 /// This is a FIFO type hence the command that enters first gets executed first.
 pub struct CommandQueue(VecDeque<Command>);
 
@@ -36,24 +93,15 @@ impl CommandQueue {
         Self(VecDeque::with_capacity(0))
     }
     /// Returns true if the queue is empty.
+    #[cfg(feature = "static_output")]
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
-    /// Store `value` only if [RUNMODE] is not unintialized.
+    /// Store `value` to be executed after the commands that are already in the queue.
     ///
-    /// # Panics
-    /// This function will panic if it is called in an environment where [RUNMODE] is
-    /// uninitialized.
+    /// The commands are only executed while the pager is running. Those pushed while handling
+    /// the commands before it starts are discarded.
     pub fn push_back(&mut self, value: Command) {
-        assert!(!RUNMODE.lock().is_uninitialized(), "CommandQueue::push_back() caled when  RUNMODE is not set. This is most likely a bug. Please report the issue on minus's issue tracker on Github.");
-        self.0.push_back(value);
-    }
-    /// Store `value` without checking [RUNMODE].
-    ///
-    /// This is only meant to be used as an optimization over [push_back](CommandQueue::push_back)
-    /// when it is absolutely sure that [RUNMODE] isn't uninitialized. Hence calling this in an
-    /// enviroment where [RUNMODE] is uninitialized can lead to unexpect slowdowns.
-    pub fn push_back_unchecked(&mut self, value: Command) {
         self.0.push_back(value);
     }
     pub fn pop_front(&mut self) -> Option<Command> {
@@ -0,0 +1,178 @@
+//! Parses the ex-style commands typed at the `:` prompt
+//!
+//! The built-in commands are
+//! - `q` or `quit` to quit the pager
+//! - A line number, like `123`, to go to that line
+//! - `set <option>` to change an option. The options are `number` (or `nu`) and `nonumber` (or
+//!   `nonu`) for line numbers, `wrap` and `nowrap` for line wrapping, `list` and `nolist` for
+//!   showing invisible characters and `mouse` and `nomouse` for capturing the mouse
+//! - `n` or `next` and `p` or `prev` to display the next or previous buffer and `b <buffer>` or
+//!   `buffer <buffer>` to display a buffer by its name or number. These are only built in while
+//!   there is more than one [buffer](crate::Pager::add_buffer).
+//!
+//! Any other command is run as one of the commands added by the application with
+//! [`Pager::add_command`](crate::Pager::add_command).
+
+use crate::{input::InputEvent, minus_core::commands::Command, LineNumbers, PagerState};
+
+/// Get the [Command] that runs the `line` typed at the `:` prompt
+///
+/// Returns `Ok(None)` if nothing was typed and a message for the user if the command cannot be
+/// run.
+pub fn parse(line: &str, ps: &PagerState) -> Result<Option<Command>, String> {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else {
+        return Ok(None);
+    };
+    let input = match name {
+        "q" | "quit" => InputEvent::Exit,
+        "set" => set(words.next(), ps)?,
+        "n" | "next" | "p" | "prev" | "b" | "buffer" if ps.buffers.len() > 1 => {
+            buffer(name, words.next(), ps)?
+        }
+        _ if name.bytes().all(|b| b.is_ascii_digit()) => {
+            // Line numbering starts from 1 while the lines are indexed from 0
+            let line = name
+                .parse::<usize>()
+                .unwrap_or(usize::MAX)
+                .saturating_sub(1);
+            // Go to the bottom of the text if the line doesn't exist
+            let row = ps
+                .lines_to_row_map
+                .get(line)
+                .map_or(usize::MAX - 1, |row| *row);
+            InputEvent::UpdateUpperMark(row)
+        }
+        _ => return Ok(Some(Command::RunCustom(name.to_string()))),
+    };
+    Ok(Some(Command::UserInput(input)))
+}
+
+/// Get the [InputEvent] that sets `option`
+fn set(option: Option<&str>, ps: &PagerState) -> Result<InputEvent, String> {
+    let input = match option {
+        // Line numbers that are forced on or off by the application are left alone
+        Some("number" | "nu") if ps.line_numbers == LineNumbers::Disabled => {
+            InputEvent::UpdateLineNumber(LineNumbers::Enabled)
+        }
+        Some("nonumber" | "nonu") if ps.line_numbers == LineNumbers::Enabled => {
+            InputEvent::UpdateLineNumber(LineNumbers::Disabled)
+        }
+        Some("number" | "nu" | "nonumber" | "nonu") => InputEvent::Ignore,
+        Some("wrap") => InputEvent::HorizontalScroll(true),
+        Some("nowrap") => InputEvent::HorizontalScroll(false),
+        Some("list") => InputEvent::ShowInvisibles(true),
+        Some("nolist") => InputEvent::ShowInvisibles(false),
+        Some("mouse") => InputEvent::MouseCapture(true),
+        Some("nomouse") => InputEvent::MouseCapture(false),
+        Some(option) => return Err(format!("Unknown option: {option}")),
+        None => return Err(String::from("Missing option for set")),
+    };
+    Ok(input)
+}
+
+/// Get the [InputEvent] that displays the buffer chosen with the command `name`
+fn buffer(name: &str, arg: Option<&str>, ps: &PagerState) -> Result<InputEvent, String> {
+    let current = ps.buffers.loaded();
+    let index = match name {
+        "n" | "next" if current + 1 < ps.buffers.len() => current + 1,
+        "n" | "next" => return Err(String::from("No next buffer")),
+        "p" | "prev" if current > 0 => current - 1,
+        "p" | "prev" => return Err(String::from("No previous buffer")),
+        _ => {
+            let arg = arg.ok_or_else(|| String::from("Missing buffer"))?;
+            // Buffers are numbered from 1 like lines
+            ps.buffers
+                .position(arg)
+                .or_else(|| {
+                    arg.parse::<usize>()
+                        .ok()
+                        .filter(|n| (1..=ps.buffers.len()).contains(n))
+                        .map(|n| n - 1)
+                })
+                .ok_or_else(|| format!("No such buffer: {arg}"))?
+        }
+    };
+    Ok(InputEvent::SwitchBuffer(index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::{input::InputEvent, minus_core::commands::Command, LineNumbers, PagerState};
+
+    fn parse_input(line: &str, ps: &PagerState) -> Option<InputEvent> {
+        match parse(line, ps) {
+            Ok(Some(Command::UserInput(input))) => Some(input),
+            res => panic!("{:?} was parsed into {:?}", line, res),
+        }
+    }
+
+    #[test]
+    fn built_in_commands() {
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.orig_text = "a\nb\nc\n".into();
+        ps.format_lines();
+
+        assert!(matches!(parse("  ", &ps), Ok(None)));
+        assert_eq!(parse_input("q", &ps), Some(InputEvent::Exit));
+        assert_eq!(parse_input("2", &ps), Some(InputEvent::UpdateUpperMark(1)));
+        // Lines that don't exist go to the bottom
+        assert_eq!(
+            parse_input("100", &ps),
+            Some(InputEvent::UpdateUpperMark(usize::MAX - 1))
+        );
+        assert_eq!(
+            parse_input("set nu", &ps),
+            Some(InputEvent::UpdateLineNumber(LineNumbers::Enabled))
+        );
+        assert_eq!(
+            parse_input("set nowrap", &ps),
+            Some(InputEvent::HorizontalScroll(false))
+        );
+        assert_eq!(
+            parse_input("set nomouse", &ps),
+            Some(InputEvent::MouseCapture(false))
+        );
+        ps.line_numbers = LineNumbers::AlwaysOff;
+        assert_eq!(parse_input("set number", &ps), Some(InputEvent::Ignore));
+    }
+
+    #[test]
+    fn buffer_commands() {
+        let mut ps = PagerState::new().unwrap();
+        // Without other buffers, the names are left to the application
+        assert_eq!(
+            parse("n", &ps),
+            Ok(Some(Command::RunCustom("n".to_string())))
+        );
+
+        ps.buffers.add("build".to_string());
+        ps.buffers.add("test".to_string());
+        assert_eq!(parse_input("n", &ps), Some(InputEvent::SwitchBuffer(1)));
+        assert_eq!(parse("prev", &ps), Err("No previous buffer".to_string()));
+        assert_eq!(
+            parse_input("b test", &ps),
+            Some(InputEvent::SwitchBuffer(2))
+        );
+        assert_eq!(
+            parse_input("buffer 1", &ps),
+            Some(InputEvent::SwitchBuffer(0))
+        );
+        assert_eq!(parse("b 4", &ps), Err("No such buffer: 4".to_string()));
+    }
+
+    #[test]
+    fn other_commands() {
+        let ps = PagerState::new().unwrap();
+        assert_eq!(
+            parse("refresh now", &ps),
+            Ok(Some(Command::RunCustom("refresh".to_string())))
+        );
+        assert_eq!(
+            parse("set colors", &ps),
+            Err("Unknown option: colors".to_string())
+        );
+        assert!(parse("set", &ps).is_err());
+    }
+}
@@ -3,14 +3,22 @@
 use crossterm::{
     cursor::MoveTo,
     execute, queue,
+    style::{Attribute, ContentStyle, SetAttributes, SetBackgroundColor, SetForegroundColor},
     terminal::{Clear, ClearType},
+    Command,
 };
 
-use std::{cmp::Ordering, convert::TryInto, io::Write};
+use std::{
+    cmp::Ordering,
+    convert::TryInto,
+    io::{BufRead, Write},
+};
 
 use super::term;
 use crate::screen::Row;
-use crate::{error::MinusError, minus_core, LineNumbers, PagerState};
+#[cfg(feature = "search")]
+use crate::search;
+use crate::{error::MinusError, PagerState, Viewport};
 
 /// How should the incoming text be drawn on the screen
 #[derive(Debug, PartialEq, Eq)]
@@ -22,6 +30,237 @@ pub enum AppendStyle<'a> {
     FullRedraw,
 }
 
+/// The rows of text that were last drawn on the terminal
+///
+/// This is used by [`draw_changed`] to find the rows that need to be rewritten.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Text of each row, exactly as it was written to the terminal
+    rows: Vec<String>,
+    /// Number of columns of the terminal when the frame was drawn
+    cols: usize,
+    /// Number of rows of the terminal when the frame was drawn
+    term_rows: usize,
+}
+
+impl Frame {
+    /// Make a frame of the rows that should currently be displayed for `ps`
+    pub fn new(ps: &PagerState) -> Self {
+        if ps.is_too_small() {
+            return Self {
+                rows: if ps.rows == 0 {
+                    Vec::new()
+                } else {
+                    vec![ps.too_small_row()]
+                },
+                cols: ps.cols,
+                term_rows: ps.rows,
+            };
+        }
+        if ps.is_waiting_for_data() {
+            return Self {
+                rows: vec![ps.waiting_row()],
+                cols: ps.cols,
+                term_rows: ps.rows,
+            };
+        }
+        let rows = if let Some((split, (top_rows, bottom_rows))) = ps.split.zip(ps.split_rows()) {
+            let (top_mark, bottom_mark) = if split.top_focused {
+                (ps.upper_mark, split.other_mark)
+            } else {
+                (split.other_mark, ps.upper_mark)
+            };
+            // Neither pane scrolls past the last row, like the screen when it isn't split
+            let row_count = ps.screen.formatted_lines_count();
+            let top_mark = top_mark.min(row_count.saturating_sub(top_rows));
+            let bottom_mark = bottom_mark.min(row_count.saturating_sub(bottom_rows));
+            // The separator stays in place even if the text doesn't fill the top pane
+            let mut rows = pane_rows(ps, top_mark, top_rows);
+            rows.resize(top_rows, String::new());
+            rows.push(split_separator(ps, split.top_focused));
+            rows.extend(pane_rows(ps, bottom_mark, bottom_rows));
+            rows
+        } else {
+            let rows = pane_rows(ps, ps.upper_mark, ps.writable_rows());
+            match scrollbar(ps) {
+                Some(cells) => add_scrollbar(rows, &cells, ps.text_cols()),
+                None => rows,
+            }
+        };
+        Self {
+            rows,
+            cols: ps.cols,
+            term_rows: ps.rows,
+        }
+    }
+
+    /// Get the text of each row
+    pub fn rows(&self) -> &[String] {
+        &self.rows
+    }
+}
+
+/// Get the rows of a pane of `pane_rows` rows whose first row is the `upper_mark`th row of the text
+///
+/// The header, the highlights and the filler are drawn on the rows. While the screen isn't split,
+/// the whole screen is a single pane.
+fn pane_rows(ps: &PagerState, upper_mark: usize, pane_rows: usize) -> Vec<String> {
+    // The header is drawn over the rows at the top of the screen
+    let header_rows = ps.header_rows();
+    let header = ps.screen.get_formatted_lines_with_bounds(0, header_rows);
+    let body_start = upper_mark.saturating_add(header_rows);
+    let body = ps
+        .screen
+        .get_formatted_lines_with_bounds(body_start, upper_mark.saturating_add(pane_rows));
+    let lines = (0..).zip(header).chain((body_start..).zip(body));
+    let gutter_width = ps.screen.fixed_width(ps.displayed_line_numbers());
+    #[cfg(feature = "search")]
+    let current_match = ps.current_match_row();
+    let rows = lines
+        .map(|(row, line)| {
+            let text = if ps.screen.line_wrapping {
+                line.clone()
+            } else {
+                let (gutter, text) =
+                    horizontal_scroll_row(line, ps.text_cols(), ps.left_mark, gutter_width);
+                format!("{gutter}{text}")
+            };
+            #[cfg(feature = "search")]
+            let text = if current_match == Some(row) {
+                let color_support = ps.screen.color_support;
+                search::restyle_matches(
+                    &text,
+                    color_support.adapt(ps.screen.theme.search_match),
+                    color_support.adapt(ps.screen.theme.current_search_match),
+                )
+            } else {
+                text
+            };
+            match ps.row_highlight(row) {
+                Some(style) => highlight_row(&text, ps.text_cols(), style),
+                None => text,
+            }
+        })
+        .collect();
+    match &ps.filler {
+        Some(filler) => add_filler(rows, filler, pane_rows, ps),
+        None => rows,
+    }
+}
+
+/// Get the row between the panes of the split view
+///
+/// The arrow at its start points at the focused pane.
+fn split_separator(ps: &PagerState, top_focused: bool) -> String {
+    let arrow = if top_focused { '▲' } else { '▼' };
+    let line = format!("{arrow}{}", "─".repeat(ps.cols.saturating_sub(1)));
+    ps.screen
+        .color_support
+        .adapt(ps.screen.theme.split_separator)
+        .apply(line)
+        .to_string()
+}
+
+/// Get the cell of the scrollbar on each row of the screen
+///
+/// The thumb covers the rows of the track that correspond to the part of the text on the screen.
+/// Rows of the track that correspond to rows with search matches are marked. Returns `None` if
+/// the scrollbar is turned off or all of the text fits on the screen.
+fn scrollbar(ps: &PagerState) -> Option<Vec<String>> {
+    let track_len = ps.writable_rows();
+    let total_rows = ps.screen.formatted_lines_count();
+    if !ps.show_scrollbar || track_len == 0 || total_rows <= track_len {
+        return None;
+    }
+    // First row of the text that is shown by the `idx`th row of the track
+    #[cfg(feature = "search")]
+    let first_row_of = |idx: usize| (idx * total_rows + track_len - 1) / track_len;
+    let thumb_start = ps.upper_mark * track_len / total_rows;
+    let thumb_end = (((ps.upper_mark + track_len) * track_len + total_rows - 1) / total_rows)
+        .clamp(thumb_start + 1, track_len);
+
+    let theme = &ps.screen.theme;
+    let color_support = ps.screen.color_support;
+    let cells = (0..track_len)
+        .map(|idx| {
+            #[cfg(feature = "search")]
+            let has_match = ps
+                .search_state
+                .search_idx
+                .range(first_row_of(idx)..first_row_of(idx + 1))
+                .next()
+                .is_some();
+            #[cfg(not(feature = "search"))]
+            let has_match = false;
+            let on_thumb = (thumb_start..thumb_end).contains(&idx);
+            let (style, symbol) = match (on_thumb, has_match) {
+                (true, true) => (theme.scrollbar_thumb, '-'),
+                (true, false) => (theme.scrollbar_thumb, ' '),
+                #[cfg(feature = "search")]
+                (false, true) => (theme.scrollbar_search_mark, '-'),
+                _ => (theme.scrollbar, '│'),
+            };
+            format!(
+                "{}{}",
+                Attribute::Reset,
+                color_support.adapt(style).apply(symbol)
+            )
+        })
+        .collect();
+    Some(cells)
+}
+
+/// Put the `cells` of the scrollbar after the `text_cols` columns of the `rows`
+fn add_scrollbar(rows: Vec<String>, cells: &[String], text_cols: usize) -> Vec<String> {
+    rows.into_iter()
+        .chain(std::iter::repeat(String::new()))
+        .zip(cells)
+        .map(|(row, cell)| {
+            let padding = text_cols.saturating_sub(textwrap::core::display_width(&row));
+            format!("{row}{:padding$}{cell}", "")
+        })
+        .collect()
+}
+
+/// Fill the rows of a pane of `pane_rows` rows after the end of the text with `filler`
+fn add_filler(
+    mut rows: Vec<String>,
+    filler: &str,
+    pane_rows: usize,
+    ps: &PagerState,
+) -> Vec<String> {
+    let filler = ps
+        .screen
+        .color_support
+        .adapt(ps.screen.theme.filler)
+        .apply(super::truncate_with_ellipsis(filler, ps.text_cols()))
+        .to_string();
+    let empty_rows = pane_rows.saturating_sub(rows.len());
+    rows.extend(std::iter::repeat(filler).take(empty_rows));
+    rows
+}
+
+/// Draw `row` with `style` across all of the `cols` columns
+fn highlight_row(row: &str, cols: usize, style: ContentStyle) -> String {
+    let padding = cols.saturating_sub(textwrap::core::display_width(row));
+    let mut set_style = String::new();
+    // Writing the escape sequences to a String can't fail
+    if let Some(fg) = style.foreground_color {
+        let _ = SetForegroundColor(fg).write_ansi(&mut set_style);
+    }
+    if let Some(bg) = style.background_color {
+        let _ = SetBackgroundColor(bg).write_ansi(&mut set_style);
+    }
+    if !style.attributes.is_empty() {
+        let _ = SetAttributes(style.attributes).write_ansi(&mut set_style);
+    }
+    // Resetting all the attributes inside the row would end the highlight early
+    let row = row
+        .replace("\x1b[0m", &format!("\x1b[0m{set_style}"))
+        .replace("\x1b[m", &format!("\x1b[m{set_style}"));
+    format!("{set_style}{row}{:padding$}{}", "", Attribute::Reset)
+}
+
 /// Handles drawing of screen based on movement
 ///
 /// Refreshing the entire terminal can be costly, especially on high resolution displays and this cost can turns out to be
@@ -33,13 +272,19 @@ pub fn draw_for_change(
     ps: &mut PagerState,
     new_upper_mark: &mut usize,
 ) -> Result<(), MinusError> {
+    if ps.repaints_paused() {
+        ps.upper_mark = *new_upper_mark;
+        ps.displayed_frame = None;
+        return Ok(());
+    }
     let line_count = ps.screen.formatted_lines_count();
 
-    // Reduce one row for prompt/messages
+    // Reduce the rows for the prompt/messages and the status area, and the other pane of the
+    // split view
     //
     // NOTE This should be the value of rows that should be used throughout this function.
     // Don't use PagerState::rows, it might lead to wrong output
-    let writable_rows = ps.rows.saturating_sub(1);
+    let writable_rows = ps.text_rows();
 
     // Calculate the lower_bound for current and new upper marks
     // by adding either the rows or line_count depending on the minimality
@@ -51,6 +296,17 @@ pub fn draw_for_change(
     if new_lower_bound > line_count {
         *new_upper_mark = line_count.saturating_sub(writable_rows);
     }
+    ps.fetch_provider_lines(*new_upper_mark);
+
+    // Scrolling the terminal would also move everything around the viewport and the status area.
+    // Highlights, like the cursor bar, may also have to be drawn on the rows that are already
+    // displayed. The rows are also written in place in the minimal layout as there is no prompt
+    // row to scroll the new rows in from.
+    if ps.viewport.is_some() || ps.status_rows() > 0 || ps.has_row_overlays() || ps.minimal_layout()
+    {
+        ps.upper_mark = *new_upper_mark;
+        return draw_changed(out, ps);
+    }
 
     let delta = new_upper_mark.abs_diff(ps.upper_mark);
     // Sometimes the value of delta is too large that we can rather use the value of the writable rows to
@@ -114,17 +370,21 @@ pub fn draw_for_change(
     write_lines(
         out,
         lines,
-        ps.cols,
+        ps.text_cols(),
         ps.screen.line_wrapping,
         ps.left_mark,
-        ps.line_numbers.is_on(),
-        ps.screen.line_count(),
+        ps.screen.fixed_width(ps.displayed_line_numbers()),
     )?;
 
     ps.upper_mark = *new_upper_mark;
+    // The terminal only moved the rows that were already displayed so the frame is only known if
+    // it was known before scrolling
+    if ps.displayed_frame.is_some() {
+        ps.displayed_frame = Some(Frame::new(ps));
+    }
 
     if ps.show_prompt {
-        super::display::write_prompt(out, &ps.displayed_prompt, ps.rows.try_into().unwrap())?;
+        draw_prompt(out, ps)?;
     }
     out.flush()?;
 
@@ -138,6 +398,88 @@ pub fn write_prompt(out: &mut impl Write, text: &str, rows: u16) -> Result<(), M
     Ok(())
 }
 
+/// Write the displayed prompt of `ps` at the prompt site, which is the last row of the pager
+///
+/// The lines of the status area are written on the rows above it. If the prompt shows the
+/// position in the text, like with a [format](crate::Pager::set_prompt_format), it is formatted
+/// again first.
+pub fn draw_prompt(out: &mut impl Write, ps: &mut PagerState) -> Result<(), MinusError> {
+    if ps.repaints_paused() {
+        return Ok(());
+    }
+    // There is no prompt row in the minimal layout. The last row of the text is written again
+    // instead as the prompts for the user's input are drawn over it.
+    if ps.minimal_layout() {
+        let last_row = ps.writable_rows().saturating_sub(1);
+        if ps.rows > 0 {
+            clear_row(out, ps.viewport, last_row)?;
+            if let Some(row) = Frame::new(ps).rows().get(last_row) {
+                write!(out, "{row}")?;
+            }
+        }
+        out.flush()?;
+        return Ok(());
+    }
+    if ps.prompt_shows_position() {
+        ps.format_prompt();
+    }
+    let (column, first_row) = ps.viewport.map_or((0, 0), |vp| (vp.column, vp.row));
+    let status_row: u16 = ps
+        .writable_rows()
+        .try_into()
+        .map_err(|_| MinusError::Conversion)?;
+    let status: Vec<String> = ps.displayed_status().collect();
+    write_status(out, &status, column, first_row.saturating_add(status_row))?;
+    if let Some(vp) = ps.viewport {
+        let row = vp.row.saturating_add(vp.height.saturating_sub(1));
+        write!(out, "{}{}", MoveTo(vp.column, row), ps.displayed_prompt)?;
+        out.flush()?;
+        return Ok(());
+    }
+    let rows: u16 = ps.rows.try_into().map_err(|_| MinusError::Conversion)?;
+    write_prompt(out, &ps.displayed_prompt, rows)
+}
+
+/// Write the lines of the status area on the rows below the text
+///
+/// `column` and `first_row` are the position of the top left corner of the status area on the
+/// terminal. The lines should already be padded to the width of the pager so that they overwrite
+/// everything that was there before.
+pub fn write_status(
+    out: &mut impl Write,
+    lines: &[String],
+    column: u16,
+    first_row: u16,
+) -> crate::Result {
+    for (row, line) in (first_row..).zip(lines) {
+        write!(out, "{}{line}", MoveTo(column, row))?;
+    }
+    Ok(())
+}
+
+/// Move the cursor to the start of the given `row` of the pager and clear that row
+///
+/// Inside a [`Viewport`], the row is cleared by overwriting it with spaces so that the rest of the
+/// terminal row is left untouched.
+fn clear_row(out: &mut impl Write, viewport: Option<Viewport>, row: usize) -> crate::Result {
+    let row: u16 = row.try_into().map_err(|_| MinusError::Conversion)?;
+    if let Some(vp) = viewport {
+        let row = vp.row.saturating_add(row);
+        write!(
+            out,
+            "{}{:width$}{}",
+            MoveTo(vp.column, row),
+            "",
+            MoveTo(vp.column, row),
+            width = usize::from(vp.width)
+        )?;
+    } else {
+        term::move_cursor(out, 0, row, false)?;
+        queue!(out, Clear(ClearType::CurrentLine))?;
+    }
+    Ok(())
+}
+
 // The below functions are just a subset of functionality of the above draw_for_change function.
 // Although, separate they are tightly coupled together.
 
@@ -150,15 +492,82 @@ pub fn write_prompt(out: &mut impl Write, text: &str, rows: u16) -> Result<(), M
 ///   - If there is one, it will display it at the prompt site
 ///   - If there isn't one, it will display the prompt in place of it
 pub fn draw_full(out: &mut impl Write, ps: &mut PagerState) -> Result<(), MinusError> {
+    // Everything is drawn once the terminal regains the focus
+    if ps.repaints_paused() {
+        ps.displayed_frame = None;
+        return Ok(());
+    }
+    // Clearing the screen would also clear everything around the viewport. Without the prompt row,
+    // writing a line break after the last row would scroll the text
+    if ps.viewport.is_some() || ps.minimal_layout() {
+        ps.displayed_frame = None;
+        return draw_changed(out, ps);
+    }
     super::term::move_cursor(out, 0, 0, false)?;
     queue!(out, Clear(ClearType::All))?;
 
     write_from_pagerstate(out, ps)?;
+    ps.displayed_frame = Some(Frame::new(ps));
 
-    let pager_rows: u16 = ps.rows.try_into().map_err(|_| MinusError::Conversion)?;
+    if ps.show_prompt {
+        draw_prompt(out, ps)?;
+    }
+
+    out.flush().map_err(MinusError::Draw)
+}
+
+/// Redraws only the rows of the screen that have changed since they were last drawn
+///
+/// The rows that are displayed are compared with [`PagerState::displayed_frame`] and only the ones
+/// that differ are rewritten. This avoids the flicker and the output of clearing and redrawing
+/// the entire screen, which matters a lot over slow connections like SSH.
+///
+/// If the frame on the terminal is not known or the terminal has been resized since, this falls
+/// back to [`draw_full`]. Inside a [`Viewport`], all of its rows are rewritten instead.
+pub fn draw_changed(out: &mut impl Write, ps: &mut PagerState) -> Result<(), MinusError> {
+    if ps.repaints_paused() {
+        ps.displayed_frame = None;
+        return Ok(());
+    }
+    // Make sure that we never scroll past the last line
+    let line_count = ps.screen.formatted_lines_count();
+    let writable_rows = ps.writable_rows();
+    let text_rows = ps.text_rows();
+    if ps.upper_mark.saturating_add(text_rows.min(line_count)) > line_count {
+        ps.upper_mark = line_count.saturating_sub(text_rows);
+    }
+    ps.fetch_provider_lines(ps.upper_mark);
+
+    let frame = Frame::new(ps);
+    let old_frame = ps
+        .displayed_frame
+        .take()
+        .filter(|old| old.cols == frame.cols && old.term_rows == frame.term_rows);
+    // Rows of the old frame, or None if every row has to be rewritten
+    let old_rows = match old_frame {
+        Some(old) => Some(old.rows),
+        None if ps.viewport.is_some() || ps.minimal_layout() => None,
+        None => return draw_full(out, ps),
+    };
+
+    for (idx, row) in frame.rows.iter().enumerate() {
+        if old_rows
+            .as_ref()
+            .map_or(true, |old| old.get(idx) != Some(row))
+        {
+            clear_row(out, ps.viewport, idx)?;
+            write!(out, "{row}")?;
+        }
+    }
+    // Clear the rows that aren't used anymore
+    let prev_rows_len = old_rows.map_or(writable_rows, |old| old.len());
+    for idx in frame.rows.len()..prev_rows_len {
+        clear_row(out, ps.viewport, idx)?;
+    }
+    ps.displayed_frame = Some(frame);
 
     if ps.show_prompt {
-        write_prompt(out, &ps.displayed_prompt, pager_rows)?;
+        draw_prompt(out, ps)?;
     }
 
     out.flush().map_err(MinusError::Draw)
@@ -221,11 +630,12 @@ pub fn draw_append_text(
 /// If you want to write directly to a file without this preassumption, then use the [write_lines]
 /// function.
 ///
-/// Draws (at most) `rows -1` lines, where the first line to display is
+/// Draws (at most) `writable_rows` lines, where the first line to display is
 /// [`PagerState::upper_mark`]. This function will always try to display as much lines as
-/// possible within `rows -1`.
+/// possible within `writable_rows`.
 ///
-/// It always skips one row at the bottom as a site for the prompt or any message that may be sent.
+/// The rows below them are left as a site for the status area and the prompt or any message that
+/// may be sent.
 ///
 /// This function ensures that upper mark never exceeds a value such that adding upper mark and available rows exceeds
 /// the number of lines of text data. This rule is disobeyed in only one special case which is if number of lines of
@@ -235,18 +645,14 @@ pub fn write_text_checked(
     out: &mut impl Write,
     lines: &[String],
     mut upper_mark: usize,
-    rows: usize,
+    writable_rows: usize,
     cols: usize,
     line_wrapping: bool,
     left_mark: usize,
-    line_numbers: LineNumbers,
-    total_line_count: usize,
+    gutter_width: usize,
 ) -> Result<(), MinusError> {
     let line_count = lines.len();
 
-    // Reduce one row for prompt/messages
-    let writable_rows = rows.saturating_sub(1);
-
     // Calculate the lower_mark by adding either the rows or line_count depending
     // on the minimality
     let mut lower_mark = upper_mark.saturating_add(writable_rows.min(line_count));
@@ -270,8 +676,7 @@ pub fn write_text_checked(
         cols,
         line_wrapping,
         left_mark,
-        line_numbers.is_on(),
-        total_line_count,
+        gutter_width,
     )
 }
 
@@ -279,7 +684,7 @@ pub fn write_from_pagerstate(out: &mut impl Write, ps: &mut PagerState) -> Resul
     let line_count = ps.screen.formatted_lines_count();
 
     // Reduce one row for prompt/messages
-    let writable_rows = ps.rows.saturating_sub(1);
+    let writable_rows = ps.text_rows();
 
     // Calculate the lower_mark by adding either the rows or line_count depending
     // on the minimality
@@ -287,20 +692,24 @@ pub fn write_from_pagerstate(out: &mut impl Write, ps: &mut PagerState) -> Resul
     if lower_mark > line_count {
         ps.upper_mark = line_count.saturating_sub(writable_rows);
     }
+    ps.fetch_provider_lines(ps.upper_mark);
 
     // Add \r to ensure cursor is placed at the beginning of each row
     let display_lines: &[String] = ps
         .screen
         .get_formatted_lines_with_bounds(ps.upper_mark, lower_mark);
 
+    // The rows of a frame already have the highlights drawn on them
+    if ps.has_row_overlays() {
+        return write_raw_lines(out, Frame::new(ps).rows(), Some("\r"));
+    }
     write_lines(
         out,
         display_lines,
-        ps.cols,
+        ps.text_cols(),
         ps.screen.line_wrapping,
         ps.left_mark,
-        ps.line_numbers.is_on(),
-        ps.screen.line_count(),
+        ps.screen.fixed_width(ps.displayed_line_numbers()),
     )
 }
 
@@ -310,58 +719,82 @@ pub fn write_lines(
     cols: usize,
     line_wrapping: bool,
     left_mark: usize,
-    line_numbers: bool,
-    line_count: usize,
+    gutter_width: usize,
 ) -> crate::Result {
     if line_wrapping {
         write_raw_lines(out, lines, Some("\r"))
     } else {
-        write_lines_in_horizontal_scroll(out, lines, cols, left_mark, line_numbers, line_count)
+        write_lines_in_horizontal_scroll(out, lines, cols, left_mark, gutter_width)
     }
 }
 
+/// Write lines to the output while skipping the first `start` bytes of the text of each line
+///
+/// `gutter_width` is the number of columns taken by the line number gutter and the frozen columns
+/// at the beginning of each line. They are always written as is, irrespective of `start`.
 pub fn write_lines_in_horizontal_scroll(
     out: &mut impl Write,
     lines: &[String],
     cols: usize,
     start: usize,
-    line_numbers: bool,
-    line_count: usize,
+    gutter_width: usize,
 ) -> crate::Result {
-    let line_number_ascii_seq_len = if line_numbers { 8 } else { 0 };
-    let line_number_padding = if line_numbers {
-        minus_core::utils::digits(line_count) + LineNumbers::EXTRA_PADDING + 3
-    } else {
-        0
-    };
-    let shifted_start = if line_numbers {
-        start + line_number_padding + line_number_ascii_seq_len
-    } else {
-        start
-    };
-
     for line in lines {
-        let end = shifted_start + cols.min(line.len().saturating_sub(shifted_start))
-            - line_number_padding;
-
-        if start < line.len() {
-            if line_numbers {
-                writeln!(
-                    out,
-                    "\r{}{}",
-                    &line[0..line_number_padding + line_number_ascii_seq_len],
-                    &line[shifted_start..end]
-                )?;
-            } else {
-                writeln!(out, "\r{}", &line[shifted_start..end])?;
-            }
-        } else {
-            writeln!(out, "\r")?;
-        }
+        let (gutter, text) = horizontal_scroll_row(line, cols, start, gutter_width);
+        writeln!(out, "\r{gutter}{text}")?;
     }
     Ok(())
 }
 
+/// Get the gutter and the part of the text of `line` that is visible when horizontally scrolled
+/// by `start` columns
+///
+/// A wide character that only partly fits at either edge is left out.
+fn horizontal_scroll_row(
+    line: &str,
+    cols: usize,
+    start: usize,
+    gutter_width: usize,
+) -> (&str, &str) {
+    let cols_avail = cols.saturating_sub(gutter_width);
+    let (gutter, text) = line.split_at(gutter_len(line, gutter_width));
+
+    let mut col = 0;
+    let mut begin = None;
+    let mut end = text.len();
+    for (idx, c) in text.char_indices() {
+        if begin.is_none() && col >= start {
+            begin = Some(idx);
+        }
+        let width = textwrap::core::display_width(&text[idx..idx + c.len_utf8()]);
+        if begin.is_some() && col + width > start + cols_avail {
+            end = idx;
+            break;
+        }
+        col += width;
+    }
+    (gutter, begin.map_or("", |begin| &text[begin..end]))
+}
+
+/// Get the length in bytes of the gutter that spans `gutter_width` columns at the start of `line`
+///
+/// Any ANSI escape sequences inside the gutter or immediately after it are counted as part of the
+/// gutter as they take up no columns on the terminal.
+fn gutter_len(line: &str, gutter_width: usize) -> usize {
+    let mut len = line
+        .char_indices()
+        .map(|(idx, _)| idx)
+        .find(|idx| textwrap::core::display_width(&line[..*idx]) >= gutter_width)
+        .unwrap_or(line.len());
+    while line[len..].starts_with("\x1b[") {
+        match line[len + 2..].find(|c| ('@'..='~').contains(&c)) {
+            Some(end) => len += end + 3,
+            None => return line.len(),
+        }
+    }
+    len
+}
+
 /// Write lines to the the output
 ///
 /// Outputs all the `lines` to `out` without any preassumption about terminals.
@@ -379,5 +812,38 @@ pub fn write_raw_lines(
     Ok(())
 }
 
+/// Write `lines` to the output in chunks of `chunk_size` lines
+///
+/// This is meant for environments where a full screen pager cannot be run but the user can still
+/// respond. After each chunk, a `--More--` prompt is written and a line is read from `input`.
+/// Writing stops if `input` reaches end-of-file or the line read is `q`.
+pub fn write_in_chunks<'a>(
+    out: &mut impl Write,
+    input: &mut impl BufRead,
+    lines: impl IntoIterator<Item = &'a str>,
+    chunk_size: usize,
+) -> Result<(), MinusError> {
+    let mut lines = lines.into_iter().peekable();
+    let mut response = String::new();
+
+    while lines.peek().is_some() {
+        for line in lines.by_ref().take(chunk_size) {
+            writeln!(out, "{line}")?;
+        }
+        if lines.peek().is_none() {
+            break;
+        }
+        write!(out, "--More--")?;
+        out.flush()?;
+
+        response.clear();
+        if input.read_line(&mut response)? == 0 || response.trim() == "q" {
+            break;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests;
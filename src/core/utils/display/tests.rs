@@ -1,7 +1,17 @@
 #![allow(clippy::shadow_unrelated)]
 #![allow(clippy::cast_possible_truncation)]
-use super::{draw_for_change, draw_full, write_from_pagerstate, write_prompt};
-use crate::{LineNumbers, PagerState};
+use super::{
+    draw_changed, draw_for_change, draw_full, write_from_pagerstate, write_in_chunks, write_prompt,
+    Frame,
+};
+use crate::{
+    ColorSupport, LineNumberContinuation, LineNumbers, PagerState, Sign, StyledLine, Viewport,
+};
+use crossterm::{
+    cursor::MoveTo,
+    style::{Color, ContentStyle, Stylize},
+    terminal::{Clear, ClearType},
+};
 use std::fmt::Write;
 
 // * In some places, where test lines are close to the row, 1 should be added
@@ -16,7 +26,7 @@ fn short_no_line_numbers() {
     let lines = "A line\nAnother line";
     let mut pager = PagerState::new().unwrap();
 
-    pager.screen.orig_text = lines.to_string();
+    pager.screen.orig_text = lines.into();
     pager.format_lines();
 
     let mut out = Vec::with_capacity(lines.len());
@@ -52,7 +62,7 @@ fn long_no_line_numbers() {
     let mut pager = PagerState::new().unwrap();
     // One extra line for prompt
     pager.rows = 4;
-    pager.screen.orig_text = lines.to_string();
+    pager.screen.orig_text = lines.into();
     pager.format_lines();
 
     assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
@@ -65,7 +75,7 @@ fn long_no_line_numbers() {
 
     // This ensures that asking for a position other than 0 works.
     let mut out = Vec::with_capacity(lines.len());
-    pager.screen.orig_text = "Another line\nThird line\nFourth line\nFifth line\n".to_string();
+    pager.screen.orig_text = "Another line\nThird line\nFourth line\nFifth line\n".into();
     pager.upper_mark = 1;
     pager.format_lines();
 
@@ -97,7 +107,7 @@ fn short_with_line_numbers() {
 
     let mut out = Vec::with_capacity(lines.len());
     let mut pager = PagerState::new().unwrap();
-    pager.screen.orig_text = lines.to_string();
+    pager.screen.orig_text = lines.into();
     pager.line_numbers = LineNumbers::Enabled;
     pager.format_lines();
 
@@ -132,7 +142,7 @@ fn long_with_line_numbers() {
     let mut out = Vec::with_capacity(lines.len());
     let mut pager = PagerState::new().unwrap();
     pager.rows = 4;
-    pager.screen.orig_text = lines.to_string();
+    pager.screen.orig_text = lines.into();
     pager.line_numbers = LineNumbers::Enabled;
     pager.format_lines();
 
@@ -184,7 +194,7 @@ fn big_line_numbers_are_padded() {
     let mut pager = PagerState::new().unwrap();
     pager.upper_mark = 95;
     pager.rows = 11;
-    pager.screen.orig_text = lines;
+    pager.screen.orig_text = lines.into();
     pager.line_numbers = LineNumbers::AlwaysOn;
     pager.format_lines();
 
@@ -199,6 +209,565 @@ fn big_line_numbers_are_padded() {
     assert_eq!(pager.upper_mark, 95);
 }
 
+#[test]
+fn relative_line_numbers() {
+    let lines = "A line\nAnother line\nThird line\nFourth line\nFifth line";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.rows = 4;
+    pager.screen.orig_text = lines.into();
+    pager.line_numbers = LineNumbers::Relative;
+    pager.format_lines();
+    // Reformat once the line at upper_mark is known
+    pager.upper_mark = 1;
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    assert_eq!(
+        "\r     0. Another line\n\r     1. Third line\n\r     2. Fourth line\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+
+    pager.line_numbers = LineNumbers::Hybrid;
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    assert_eq!(
+        "\r     2. Another line\n\r     1. Third line\n\r     2. Fourth line\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+fn custom_line_number_format() {
+    let lines = "A line\nAnother line which is quite long";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 24;
+    pager.screen.orig_text = lines.into();
+    pager.line_numbers = LineNumbers::Enabled;
+    pager.screen.line_number_formatter = Some(Box::new(|n| format!("\x1b[2m{n:>3} │\x1b[0m ")));
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    assert_eq!(
+        "\r\x1b[2m  1 │\x1b[0m A line\n\r\x1b[2m  2 │\x1b[0m Another line\n\r      which is quite\n\r      long\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+
+    // The gutter must stay in place when scrolling horizontally
+    pager.screen.line_wrapping = false;
+    pager.left_mark = 8;
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    assert_eq!(
+        "\r\x1b[2m  1 │\x1b[0m \n\r\x1b[2m  2 │\x1b[0m line which is quit\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+fn horizontal_scroll_wide_characters() {
+    let lines = "日本語".repeat(40);
+
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 5;
+    pager.screen.orig_text = lines.as_str().into();
+    pager.screen.line_wrapping = false;
+    pager.left_mark = 1;
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    // The character cut by the left edge and the one that doesn't fit the last column are left out
+    assert_eq!(
+        "\r本語\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+
+    pager.cols = 80;
+    pager.left_mark = 40;
+    pager.format_lines();
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    assert_eq!(
+        format!(
+            "\r{}\n",
+            lines.chars().skip(20).take(40).collect::<String>()
+        ),
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+fn frozen_columns() {
+    let lines = "id-1 first row\nid-2 second row";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 12;
+    pager.screen.orig_text = lines.into();
+    pager.screen.line_wrapping = false;
+    pager.screen.frozen_columns = 5;
+    pager.left_mark = 6;
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    // The first columns stay in place while the rest of the row is scrolled
+    assert_eq!(
+        "\rid-1 row\n\rid-2  row\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+fn line_number_start() {
+    let lines = "A line\nAnother line\nThird line";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.screen.orig_text = lines.into();
+    pager.line_numbers = LineNumbers::Enabled;
+    pager.screen.line_number_start = 99;
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    assert_eq!(
+        "\r      99. A line\n\r     100. Another line\n\r     101. Third line\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+fn line_number_continuation() {
+    let lines = "A line\nAnother line which is quite long";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 24;
+    pager.screen.orig_text = lines.into();
+    pager.line_numbers = LineNumbers::Enabled;
+    pager.screen.line_number_continuation = LineNumberContinuation::Arrow;
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    assert_eq!(
+        "\r     1. A line\n\r     2. Another line\n\r      ↪ which is quite\n\r      ↪ long\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+
+    pager.screen.line_number_continuation = LineNumberContinuation::RepeatDimmed;
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    assert_eq!(
+        "\r     1. A line\n\r     2. Another line\n\r     2. which is quite\n\r     2. long\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+fn wrap_indicator() {
+    let lines = "A line\nAnother line which is quite long";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 16;
+    pager.screen.orig_text = lines.into();
+    pager.screen.wrap_prefix = "↪ ".to_string();
+    pager.screen.wrap_suffix = "\\".to_string();
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    assert_eq!(
+        "\rA line\n\rAnother line\\\n\r↪ which is\\\n\r↪ quite long\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+fn sign_column() {
+    let lines = "A line\nAnother line which is long\nLast";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 24;
+    pager.screen.orig_text = lines.into();
+    pager.line_numbers = LineNumbers::Enabled;
+    pager
+        .screen
+        .signs
+        .insert(1, Sign::new("!!", ContentStyle::new()));
+    pager
+        .screen
+        .signs
+        .insert(2, Sign::new("+", ContentStyle::new().with(Color::Green)));
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    // The column is as wide as the widest sign and is left blank on wrapped rows
+    assert_eq!(
+        "\r        1. A line\n\r!!      2. Another line\n\r           which is\n\r           long\n\r\x1b[38;5;10m+\x1b[39m       3. Last\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+fn color_support() {
+    let lines = "A line\nHighlighted";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 16;
+    pager.screen.orig_text = lines.into();
+    pager.screen.color_support = ColorSupport::Ansi16;
+    pager.screen.signs.insert(
+        0,
+        Sign::new(
+            "+",
+            ContentStyle::new().with(Color::Rgb { r: 0, g: 250, b: 0 }),
+        ),
+    );
+    pager.line_highlights = vec![(1..2, ContentStyle::new().on(Color::AnsiValue(196)))];
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    // Both colors are replaced by the closest of the 16 basic colors
+    assert_eq!(
+        "\r\x1b[38;5;10m+\x1b[39m A line\n\r\x1b[48;5;9m  Highlighted   \x1b[0m\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+fn line_formatter() {
+    let lines = "fn main() {}\n// A comment that is long";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 16;
+    pager.screen.orig_text = lines.into();
+    pager.screen.line_formatter = Some(Box::new(|line, idx| {
+        let styled = StyledLine::new(line);
+        if idx == 1 {
+            styled.style(0..line.len(), ContentStyle::new().bold())
+        } else {
+            styled
+        }
+    }));
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    // The styles are applied again on each row of a wrapped line
+    assert_eq!(
+        "\rfn main() {}\n\r\x1b[1m// A comment\x1b[0m\n\r\x1b[1mthat is long\x1b[0m\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+#[cfg(feature = "search")]
+fn color_rules() {
+    let lines = "12:00 INFO started\n12:01 ERROR failed";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.screen.orig_text = lines.into();
+    pager.screen.color_rules = vec![
+        (
+            regex::Regex::new(r"\d\d:\d\d").unwrap(),
+            ContentStyle::new().bold(),
+        ),
+        (
+            regex::Regex::new("ERROR").unwrap(),
+            ContentStyle::new().red(),
+        ),
+    ];
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    assert_eq!(
+        "\r\x1b[1m12:00\x1b[0m INFO started\n\r\x1b[1m12:01\x1b[0m \x1b[38;5;9mERROR\x1b[39m failed\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+#[cfg(feature = "search")]
+fn current_search_match() {
+    let lines = "A test\nAnother test\nLast test";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.screen.color_support = ColorSupport::TrueColor;
+    pager.screen.orig_text = lines.into();
+    pager.search_state.search_term = Some(regex::Regex::new("test").unwrap());
+    pager.format_lines();
+    pager.search_state.search_mark = 1;
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    // Only the match on the row that was moved to is drawn with the current match style
+    assert_eq!(
+        "\rA \x1b[7mtest\x1b[27m\n\
+         \rAnother \x1b[38;5;0m\x1b[48;5;11mtest\x1b[39m\x1b[49m\n\
+         \rLast \x1b[7mtest\x1b[27m\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+#[cfg(feature = "search")]
+fn scrollbar() {
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 4;
+    pager.rows = 5;
+    pager.screen.color_support = ColorSupport::TrueColor;
+    pager.screen.orig_text = (1..=10).map(|n| format!("{n}\n")).collect();
+    pager.search_state.search_term = Some(regex::Regex::new("9").unwrap());
+    pager.show_scrollbar = true;
+    pager.format_lines();
+
+    let theme = pager.screen.theme;
+    let cell = |style: ContentStyle, symbol: char| format!("\x1b[0m{}", style.apply(symbol));
+    let thumb = cell(theme.scrollbar_thumb, ' ');
+    let track = cell(theme.scrollbar, '│');
+    // The last line has the only match
+    let mark = cell(theme.scrollbar_search_mark, '-');
+    assert_eq!(
+        Frame::new(&pager).rows(),
+        [
+            format!("1  {thumb}"),
+            format!("2  {thumb}"),
+            format!("3  {track}"),
+            format!("4  {mark}"),
+        ]
+    );
+
+    pager.upper_mark = 6;
+    let frame = Frame::new(&pager);
+    assert_eq!(
+        frame.rows()[..2],
+        [format!("7  {track}"), format!("8  {track}")]
+    );
+    // The row with the match is padded by its width, leaving out the escape sequences
+    assert!(frame.rows()[2].ends_with(&format!("9\x1b[39m\x1b[49m  {thumb}")));
+    assert_eq!(
+        frame.rows()[3],
+        format!("10 {}", cell(theme.scrollbar_thumb, '-'))
+    );
+
+    // Nothing is drawn when all of the text fits on the screen
+    pager.rows = 20;
+    pager.upper_mark = 0;
+    let frame = Frame::new(&pager);
+    assert_eq!(frame.rows().len(), 10);
+    assert_eq!(frame.rows()[0], "1");
+}
+
+#[test]
+#[cfg(feature = "dynamic_output")]
+fn waiting_placeholder() {
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 20;
+    pager.rows = 5;
+    pager.waiting_text = Some("Loading".to_string());
+    pager.waiting_spinner = true;
+    *pager.running.lock() = crate::RunMode::Dynamic;
+    assert_eq!(Frame::new(&pager).rows(), ["| Loading"]);
+
+    pager.spinner_frame = 1;
+    assert_eq!(Frame::new(&pager).rows(), ["/ Loading"]);
+
+    // The text replaces the placeholder as soon as it arrives
+    pager.screen.orig_text = "Data\n".into();
+    pager.screen.line_count = 1;
+    pager.format_lines();
+    assert_eq!(Frame::new(&pager).rows(), ["Data"]);
+
+    // Nothing is shown if the application has no data to send
+    pager.screen.orig_text.clear();
+    pager.screen.line_count = 0;
+    pager.format_lines();
+    pager.data_finished = true;
+    assert!(Frame::new(&pager).rows().is_empty());
+}
+
+#[test]
+fn filler() {
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 10;
+    pager.rows = 5;
+    pager.screen.color_support = ColorSupport::TrueColor;
+    pager.screen.orig_text = "A\n\nB\n".into();
+    pager.filler = Some("~".to_string());
+    pager.format_lines();
+
+    let tilde = pager.screen.theme.filler.apply("~").to_string();
+    // Blank lines in the text are kept apart from the rows past its end
+    assert_eq!(Frame::new(&pager).rows(), ["A", "", "B", &tilde]);
+
+    pager.filler = None;
+    assert_eq!(Frame::new(&pager).rows(), ["A", "", "B"]);
+}
+
+#[test]
+fn split_view() {
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 4;
+    pager.rows = 8;
+    pager.screen.color_support = ColorSupport::TrueColor;
+    pager.screen.orig_text = (0..20)
+        .fold(String::new(), |mut text, line| {
+            writeln!(text, "{line}").unwrap();
+            text
+        })
+        .into();
+    pager.header_lines = 1;
+    pager.format_lines();
+
+    pager.set_split(true);
+    pager.upper_mark = 10;
+    let style = pager.screen.theme.split_separator;
+    let separator = |arrow| style.apply(format!("{arrow}───")).to_string();
+    // The header isn't shown in either pane
+    assert_eq!(
+        Frame::new(&pager).rows(),
+        ["0", "1", "2", &separator('▼'), "10", "11", "12"]
+    );
+    assert_eq!(pager.text_rows(), 3);
+
+    pager.switch_pane();
+    assert_eq!(pager.upper_mark, 0);
+    pager.upper_mark = 100;
+    assert_eq!(
+        Frame::new(&pager).rows(),
+        ["17", "18", "19", &separator('▲'), "10", "11", "12"]
+    );
+
+    pager.set_split(false);
+    pager.upper_mark = 0;
+    assert_eq!(Frame::new(&pager).rows().len(), 7);
+    assert_eq!(pager.text_rows(), 7);
+}
+
+#[test]
+fn strip_ansi() {
+    let lines = "\x1b[1;32mgreen\x1b[0m and \x1b[4mplain\x1b[0m text";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 16;
+    pager.screen.orig_text = lines.into();
+    pager.screen.strip_ansi = true;
+    pager.format_lines();
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    assert_eq!(
+        "\rgreen and plain\n\rtext\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+fn selected_line() {
+    let lines = "A line\nAnother line which is long\nLast";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 16;
+    pager.screen.orig_text = lines.into();
+    pager.format_lines();
+    pager.selected_line = Some(1);
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    assert_eq!(
+        "\rA line\n\r\x1b[7mAnother line    \x1b[0m\n\r\x1b[7mwhich is long   \x1b[0m\n\rLast\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+fn header_lines() {
+    let lines = "NAME\nA line\nAnother line which is long\nLast";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 16;
+    pager.rows = 4;
+    pager.screen.orig_text = lines.into();
+    pager.format_lines();
+    pager.header_lines = 1;
+    pager.upper_mark = 2;
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    // The rows covered by the header are not shown
+    assert_eq!(
+        "\rNAME\n\rwhich is long\n\rLast\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+fn highlighted_lines() {
+    let lines = "A line\nAnother \x1b[1mline\x1b[0m which is long\nLast";
+
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 16;
+    pager.screen.orig_text = lines.into();
+    pager.format_lines();
+    pager.line_highlights = vec![
+        (0..2, ContentStyle::new().on(Color::Red)),
+        (0..1, ContentStyle::new().with(Color::Green)),
+    ];
+
+    let mut out = Vec::with_capacity(lines.len());
+    assert!(write_from_pagerstate(&mut out, &mut pager).is_ok());
+    // The highlight added last is drawn on top and resets inside the line don't end it
+    assert_eq!(
+        "\r\x1b[38;5;10mA line          \x1b[0m\n\
+         \r\x1b[48;5;9mAnother \x1b[1mline\x1b[0m\x1b[48;5;9m    \x1b[0m\n\
+         \r\x1b[48;5;9mwhich is long   \x1b[0m\n\
+         \rLast\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
+#[test]
+fn chunked_output() {
+    let text = "1\n2\n3\n4\n5\n";
+
+    let mut out = Vec::new();
+    let mut input = "\n\n".as_bytes();
+    write_in_chunks(&mut out, &mut input, text.lines(), 2).unwrap();
+    assert_eq!(
+        "1\n2\n--More--3\n4\n--More--5\n",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+
+    // Stop writing if the user enters q or input is closed
+    let mut out = Vec::new();
+    let mut input = "q\n".as_bytes();
+    write_in_chunks(&mut out, &mut input, text.lines(), 2).unwrap();
+    assert_eq!(
+        "1\n2\n--More--",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+
+    let mut out = Vec::new();
+    let mut input = "".as_bytes();
+    write_in_chunks(&mut out, &mut input, text.lines(), 4).unwrap();
+    assert_eq!(
+        "1\n2\n3\n4\n--More--",
+        String::from_utf8(out).expect("Should have written valid UTF-8")
+    );
+}
+
 #[test]
 fn line_numbers_not() {
     #[allow(clippy::enum_glob_use)]
@@ -206,6 +775,8 @@ fn line_numbers_not() {
 
     assert_eq!(AlwaysOn, !AlwaysOn);
     assert_eq!(AlwaysOff, !AlwaysOff);
+    assert_eq!(Relative, !Relative);
+    assert_eq!(Hybrid, !Hybrid);
     assert_eq!(Enabled, !Disabled);
     assert_eq!(Disabled, !Enabled);
 }
@@ -227,7 +798,7 @@ fn draw_short_no_line_numbers() {
 
     let mut out = Vec::with_capacity(lines.len());
     let mut pager = PagerState::new().unwrap();
-    pager.screen.orig_text = lines.to_string();
+    pager.screen.orig_text = lines.into();
     pager.line_numbers = LineNumbers::AlwaysOff;
     pager.format_lines();
 
@@ -259,7 +830,7 @@ fn draw_long_no_line_numbers() {
     let mut out = Vec::with_capacity(lines.len());
     let mut pager = PagerState::new().unwrap();
     pager.rows = 3;
-    pager.screen.orig_text = lines.to_string();
+    pager.screen.orig_text = lines.into();
     pager.format_lines();
 
     assert!(draw_full(&mut out, &mut pager).is_ok());
@@ -298,7 +869,7 @@ fn draw_short_with_line_numbers() {
     let lines = "A line\nAnother line";
     let mut out = Vec::with_capacity(lines.len());
     let mut pager = PagerState::new().unwrap();
-    pager.screen.orig_text = lines.to_string();
+    pager.screen.orig_text = lines.into();
     pager.line_numbers = LineNumbers::Enabled;
     pager.format_lines();
 
@@ -329,7 +900,7 @@ fn draw_long_with_line_numbers() {
     let mut out = Vec::with_capacity(lines.len());
     let mut pager = PagerState::new().unwrap();
     pager.rows = 3;
-    pager.screen.orig_text = lines.to_string();
+    pager.screen.orig_text = lines.into();
     pager.line_numbers = LineNumbers::Enabled;
     pager.format_lines();
 
@@ -377,7 +948,7 @@ fn draw_big_line_numbers_are_padded() {
     let mut out = Vec::with_capacity(lines.len());
     let mut pager = PagerState::new().unwrap();
     pager.upper_mark = 95;
-    pager.screen.orig_text = lines;
+    pager.screen.orig_text = lines.into();
     pager.line_numbers = LineNumbers::Enabled;
     pager.format_lines();
 
@@ -402,7 +973,7 @@ fn draw_wrapping_line_numbers() {
 
     let mut out = Vec::new();
     let mut pager = PagerState::new().unwrap();
-    pager.screen.orig_text = lines;
+    pager.screen.orig_text = lines.into();
     pager.cols = 30;
     pager.upper_mark = 2;
     pager.line_numbers = LineNumbers::Enabled;
@@ -421,7 +992,7 @@ fn draw_help_message() {
 
     let mut out = Vec::with_capacity(lines.len());
     let mut pager = PagerState::new().unwrap();
-    pager.screen.orig_text = lines.to_string();
+    pager.screen.orig_text = lines.into();
     pager.line_numbers = LineNumbers::AlwaysOff;
     pager.format_prompt();
 
@@ -436,7 +1007,7 @@ fn test_draw_no_overflow() {
     const TEXT: &str = "This is a line of text to the pager";
     let mut out = Vec::with_capacity(TEXT.len());
     let mut pager = PagerState::new().unwrap();
-    pager.screen.orig_text = TEXT.to_string();
+    pager.screen.orig_text = TEXT.into();
     pager.format_lines();
     draw_full(&mut out, &mut pager).unwrap();
     assert!(String::from_utf8(out)
@@ -444,6 +1015,73 @@ fn test_draw_no_overflow() {
         .contains(TEXT));
 }
 
+#[test]
+fn draw_only_changed_rows() {
+    let mut pager = PagerState::new().unwrap();
+    pager.screen.orig_text = "A line\nAnother line\nThird line".into();
+    pager.format_lines();
+    draw_full(&mut Vec::new(), &mut pager).unwrap();
+
+    pager.screen.orig_text = "A line\nChanged line".into();
+    pager.format_lines();
+    let mut out = Vec::new();
+    draw_changed(&mut out, &mut pager).unwrap();
+
+    let res = String::from_utf8(out).expect("Should have written valid UTF-8");
+    assert!(res.starts_with(&format!(
+        "{}{}Changed line{}{}",
+        MoveTo(0, 1),
+        Clear(ClearType::CurrentLine),
+        MoveTo(0, 2),
+        Clear(ClearType::CurrentLine),
+    )));
+    assert!(!res.contains("A line"));
+
+    // Redraw everything if the terminal has been resized
+    pager.cols = 40;
+    let mut out = Vec::new();
+    draw_changed(&mut out, &mut pager).unwrap();
+    let res = String::from_utf8(out).expect("Should have written valid UTF-8");
+    assert!(res.contains(&format!("{}", Clear(ClearType::All))));
+    assert!(res.contains("\rA line\n\rChanged line\n"));
+}
+
+#[test]
+fn draw_inside_viewport() {
+    let mut pager = PagerState::new().unwrap();
+    pager.viewport = Some(Viewport {
+        column: 10,
+        row: 2,
+        width: 20,
+        height: 4,
+    });
+    pager.cols = 20;
+    pager.rows = 4;
+    pager.screen.orig_text = "A line\nAnother line".into();
+    pager.format_lines();
+
+    let mut out = Vec::new();
+    draw_full(&mut out, &mut pager).unwrap();
+    let res = String::from_utf8(out).expect("Should have written valid UTF-8");
+    // Nothing outside the viewport is cleared or scrolled
+    assert!(!res.contains(&format!("{}", Clear(ClearType::All))));
+    assert!(!res.contains(&format!("{}", Clear(ClearType::CurrentLine))));
+    assert!(res.starts_with(&format!(
+        "{}{:20}{}A line{}{:20}{}Another line{}{:20}{}",
+        MoveTo(10, 2),
+        "",
+        MoveTo(10, 2),
+        MoveTo(10, 3),
+        "",
+        MoveTo(10, 3),
+        MoveTo(10, 4),
+        "",
+        MoveTo(10, 4),
+    )));
+    // The prompt is on the last row of the viewport
+    assert!(res.contains(&format!("{}{}", MoveTo(10, 5), pager.displayed_prompt)));
+}
+
 #[cfg(test)]
 mod draw_for_change_tests {
     use super::{draw_for_change, write_prompt};
@@ -465,7 +1103,7 @@ mod draw_for_change_tests {
         };
         let mut ps = PagerState::new().unwrap();
         ps.upper_mark = 0;
-        ps.screen.orig_text = lines;
+        ps.screen.orig_text = lines.into();
         ps.format_lines();
         ps.format_prompt();
         ps
@@ -0,0 +1,565 @@
+//! A single line text field for reading text typed by the user at the prompt site
+//!
+//! The field itself only keeps track of the text and the cursor. Drawing it is left to the
+//! callers, like the search prompt, so that they can decorate the text in their own way.
+//! [read_input] is provided for the simple cases where only a line of text is required.
+
+use std::{convert::TryFrom, io::Write, time::Duration};
+
+use crossterm::{
+    cursor,
+    event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    style::ContentStyle,
+    terminal::{Clear, ClearType},
+};
+
+use super::term;
+use crate::{error::MinusError, PagerState, Viewport};
+
+/// Column of the prompt where the cursor is placed right before the first character
+const FIRST_AVAILABLE_COLUMN: u16 = 1;
+
+/// What happened to an [InputField] after handing it an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldEvent {
+    /// `Enter` was pressed
+    Confirmed,
+    /// `Esc` was pressed
+    Cancelled,
+    /// The text was changed. The cursor may have been moved too.
+    Changed,
+    /// Only the cursor was moved
+    Moved,
+    /// Nothing happened
+    Ignored,
+}
+
+/// Text typed at the prompt along with the position of the cursor in it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputField {
+    /// Text typed so far
+    pub string: String,
+    /// Column at which the cursor is relative to the start of the text.
+    /// It can range between 1 and the number of characters in `string` + 1.
+    pub cursor_position: u16,
+    /// Column numbers where each new word starts
+    pub word_index: Vec<u16>,
+}
+
+impl InputField {
+    pub const fn new() -> Self {
+        Self {
+            string: String::new(),
+            cursor_position: FIRST_AVAILABLE_COLUMN,
+            word_index: Vec::new(),
+        }
+    }
+
+    /// Column right after the last character
+    fn last_available_column(&self) -> u16 {
+        u16::try_from(self.string.chars().count().saturating_add(1)).unwrap_or(u16::MAX)
+    }
+
+    /// Byte index of the character at the given `column`
+    fn byte_index(&self, column: u16) -> usize {
+        let idx = usize::from(column.saturating_sub(1));
+        self.string
+            .char_indices()
+            .nth(idx)
+            .map_or(self.string.len(), |(i, _)| i)
+    }
+
+    fn populate_word_index(&mut self) {
+        self.word_index = word_starts(&self.string);
+    }
+
+    /// Column where the word right before the cursor starts, along with any whitespace after it
+    fn previous_word_boundary(&self) -> u16 {
+        let chars: Vec<char> = self.string.chars().collect();
+        let mut idx = usize::from(self.cursor_position.saturating_sub(FIRST_AVAILABLE_COLUMN));
+        while idx > 0 && chars[idx - 1].is_whitespace() {
+            idx -= 1;
+        }
+        if let Some(class) = idx.checked_sub(1).map(|i| Class::of(chars[i])) {
+            idx -= 1;
+            while class != Class::Other && idx > 0 && Class::of(chars[idx - 1]) == class {
+                idx -= 1;
+            }
+        }
+        u16::try_from(idx).map_or(u16::MAX, |idx| idx.saturating_add(FIRST_AVAILABLE_COLUMN))
+    }
+
+    /// Column right after the word that follows the cursor, along with any whitespace before it
+    fn next_word_boundary(&self) -> u16 {
+        let chars: Vec<char> = self.string.chars().collect();
+        let mut idx = usize::from(self.cursor_position.saturating_sub(FIRST_AVAILABLE_COLUMN));
+        while idx < chars.len() && chars[idx].is_whitespace() {
+            idx += 1;
+        }
+        if let Some(class) = chars.get(idx).map(|c| Class::of(*c)) {
+            idx += 1;
+            while class != Class::Other && idx < chars.len() && Class::of(chars[idx]) == class {
+                idx += 1;
+            }
+        }
+        u16::try_from(idx).map_or(u16::MAX, |idx| idx.saturating_add(FIRST_AVAILABLE_COLUMN))
+    }
+
+    /// Remove the characters from column `start` up to but not including column `end`
+    ///
+    /// The cursor is placed at `start`.
+    fn remove_columns(&mut self, start: u16, end: u16) -> FieldEvent {
+        if start >= end {
+            return FieldEvent::Ignored;
+        }
+        let range = self.byte_index(start)..self.byte_index(end);
+        self.string.replace_range(range, "");
+        self.cursor_position = start;
+        self.populate_word_index();
+        FieldEvent::Changed
+    }
+
+    /// Insert `text` right before the cursor
+    ///
+    /// The field only holds a single line, so line breaks and tabs are turned into spaces, a
+    /// trailing line break is dropped and all other control characters are left out.
+    pub fn insert_str(&mut self, text: &str) -> FieldEvent {
+        let text: String = text
+            .trim_end_matches(&['\r', '\n'][..])
+            .chars()
+            .filter_map(|c| match c {
+                '\n' | '\t' => Some(' '),
+                c if c.is_control() => None,
+                c => Some(c),
+            })
+            .collect();
+        if text.is_empty() {
+            return FieldEvent::Ignored;
+        }
+        self.string
+            .insert_str(self.byte_index(self.cursor_position), &text);
+        let inserted = u16::try_from(text.chars().count()).unwrap_or(u16::MAX);
+        self.cursor_position = self.cursor_position.saturating_add(inserted);
+        self.populate_word_index();
+        FieldEvent::Changed
+    }
+
+    /// Update the field according to the given event
+    ///
+    /// Only key presses and pasted text are acted upon, everything else is
+    /// [ignored](FieldEvent::Ignored).
+    #[allow(clippy::too_many_lines)]
+    pub fn handle_event(&mut self, ev: &Event) -> FieldEvent {
+        let last_available_column = self.last_available_column();
+
+        match ev {
+            Event::Key(KeyEvent { kind, .. }) if *kind != KeyEventKind::Press => {
+                FieldEvent::Ignored
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Esc,
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => FieldEvent::Cancelled,
+            Event::Key(KeyEvent {
+                code: KeyCode::Enter,
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => FieldEvent::Confirmed,
+            Event::Key(KeyEvent {
+                code: KeyCode::Backspace,
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => {
+                // Remove the character just before the cursor, unless we are at the very first
+                // column
+                if self.cursor_position == FIRST_AVAILABLE_COLUMN {
+                    return FieldEvent::Ignored;
+                }
+                self.cursor_position = self.cursor_position.saturating_sub(1);
+                self.string.remove(self.byte_index(self.cursor_position));
+                self.populate_word_index();
+                FieldEvent::Changed
+            }
+            // Remove the word before the cursor like a shell does
+            Event::Key(
+                KeyEvent {
+                    code: KeyCode::Backspace,
+                    modifiers: KeyModifiers::CONTROL | KeyModifiers::ALT,
+                    ..
+                }
+                | KeyEvent {
+                    code: KeyCode::Char('w'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                },
+            ) => self.remove_columns(self.previous_word_boundary(), self.cursor_position),
+            // Remove the word after the cursor
+            Event::Key(
+                KeyEvent {
+                    code: KeyCode::Delete,
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                }
+                | KeyEvent {
+                    code: KeyCode::Char('d'),
+                    modifiers: KeyModifiers::ALT,
+                    ..
+                },
+            ) => self.remove_columns(self.cursor_position, self.next_word_boundary()),
+            Event::Key(KeyEvent {
+                code: KeyCode::Delete,
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => {
+                // Remove the character under the cursor, unless we are at the column right after
+                // the last character
+                if self.cursor_position >= last_available_column {
+                    return FieldEvent::Ignored;
+                }
+                self.string.remove(self.byte_index(self.cursor_position));
+                self.populate_word_index();
+                FieldEvent::Changed
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Left,
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => {
+                if self.cursor_position == FIRST_AVAILABLE_COLUMN {
+                    return FieldEvent::Ignored;
+                }
+                self.cursor_position = self.cursor_position.saturating_sub(1);
+                FieldEvent::Moved
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Left,
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            }) => {
+                // Jump to the start of the word right before the cursor or to the very first
+                // column if there is none
+                self.cursor_position = *self
+                    .word_index
+                    .iter()
+                    .rfind(|c| c < &&self.cursor_position)
+                    .unwrap_or(&FIRST_AVAILABLE_COLUMN);
+                FieldEvent::Moved
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Right,
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => {
+                if self.cursor_position >= last_available_column {
+                    return FieldEvent::Ignored;
+                }
+                self.cursor_position = self.cursor_position.saturating_add(1);
+                FieldEvent::Moved
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Right,
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            }) => {
+                // Jump to the start of the word right after the cursor or to the very last
+                // column if there is none
+                self.cursor_position = *self
+                    .word_index
+                    .iter()
+                    .find(|c| c > &&self.cursor_position)
+                    .unwrap_or(&last_available_column);
+                FieldEvent::Moved
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Home,
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => {
+                self.cursor_position = FIRST_AVAILABLE_COLUMN;
+                FieldEvent::Moved
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::End,
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => {
+                self.cursor_position = last_available_column;
+                FieldEvent::Moved
+            }
+            // Any other character is inserted right before the cursor
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(c),
+                ..
+            }) => {
+                self.string
+                    .insert(self.byte_index(self.cursor_position), *c);
+                self.cursor_position = self.cursor_position.saturating_add(1);
+                self.populate_word_index();
+                FieldEvent::Changed
+            }
+            // Text pasted into a terminal with bracketed paste enabled
+            Event::Paste(text) => self.insert_str(text),
+            _ => FieldEvent::Ignored,
+        }
+    }
+}
+
+/// Kinds of characters that make up words
+#[derive(PartialEq)]
+enum Class {
+    Word,
+    Punctuation,
+    Other,
+}
+
+impl Class {
+    fn of(c: char) -> Self {
+        if c.is_alphanumeric() || c == '_' {
+            Self::Word
+        } else if c.is_ascii_punctuation() {
+            Self::Punctuation
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// Returns the column numbers at which each word in `text` starts
+///
+/// A word is either a run of alphanumeric characters and underscores or a run of ASCII
+/// punctuation. Every other character, like a space, is a word of its own.
+fn word_starts(text: &str) -> Vec<u16> {
+    let mut prev = None;
+    let mut starts = Vec::new();
+    for (col, c) in (FIRST_AVAILABLE_COLUMN..).zip(text.chars()) {
+        let current = Class::of(c);
+        if current == Class::Other || prev.as_ref() != Some(&current) {
+            starts.push(col);
+        }
+        prev = Some(current);
+    }
+    starts
+}
+
+/// Move the cursor to the given `column` of the prompt row
+///
+/// If a `viewport` is given, the prompt row is the last row of it and `column` is taken relative
+/// to its left edge.
+pub fn move_to_prompt(
+    out: &mut impl Write,
+    rows: u16,
+    viewport: Option<Viewport>,
+    column: u16,
+    flush: bool,
+) -> crate::Result {
+    match viewport {
+        Some(vp) => term::move_cursor(
+            out,
+            vp.column.saturating_add(column),
+            vp.row.saturating_add(vp.height.saturating_sub(1)),
+            flush,
+        ),
+        None => term::move_cursor(out, column, rows, flush),
+    }
+}
+
+/// Read a line of text from the user at the prompt site
+///
+/// The `prefix` is displayed right before the text typed by the user. Returns `None` if the user
+/// cancelled the input by pressing `Esc`.
+///
+/// The caller must make sure that nothing else reads from the [`EventSource`](crate::input::EventSource)
+/// and redraw the prompt once this returns.
+pub fn read_input<O: Write>(
+    out: &mut O,
+    ps: &PagerState,
+    prefix: &str,
+) -> Result<Option<String>, MinusError> {
+    let rows = u16::try_from(ps.rows).unwrap_or(u16::MAX);
+    let cols = ps.cols;
+    let style = ps.screen.color_support.adapt(ps.screen.theme.input_prompt);
+    let prefix_len = u16::try_from(prefix.chars().count()).unwrap_or(u16::MAX);
+
+    let draw = |out: &mut O, field: &InputField| -> crate::Result {
+        draw_prompt(out, rows, ps.viewport, cols, style, prefix, &field.string)?;
+        move_to_prompt(
+            out,
+            rows,
+            ps.viewport,
+            prefix_len.saturating_add(field.cursor_position.saturating_sub(1)),
+            false,
+        )?;
+        out.flush()?;
+        Ok(())
+    };
+
+    let mut field = InputField::new();
+    draw(out, &field)?;
+    write!(out, "{}", cursor::Show)?;
+    out.flush()?;
+
+    let confirmed = loop {
+        let ev = ps
+            .event_source
+            .lock()
+            .next_event(Duration::from_millis(100))
+            .map_err(|e| MinusError::HandleEvent(e.into()))?;
+        let Some(ev) = ev else {
+            continue;
+        };
+        match field.handle_event(&ev) {
+            FieldEvent::Confirmed => break true,
+            FieldEvent::Cancelled => break false,
+            FieldEvent::Changed | FieldEvent::Moved => draw(out, &field)?,
+            FieldEvent::Ignored => {}
+        }
+    };
+
+    move_to_prompt(out, rows, ps.viewport, 0, false)?;
+    if ps.viewport.is_none() {
+        write!(out, "{}", Clear(ClearType::CurrentLine))?;
+    }
+    write!(out, "{}", cursor::Hide)?;
+    out.flush()?;
+
+    Ok(confirmed.then_some(field.string))
+}
+
+/// Writes `prefix` followed by `text` at the prompt site
+///
+/// The text is padded to take up the entire row so that the `style` covers all of it.
+pub fn draw_prompt(
+    out: &mut impl Write,
+    rows: u16,
+    viewport: Option<Viewport>,
+    cols: usize,
+    style: ContentStyle,
+    prefix: &str,
+    text: &str,
+) -> crate::Result {
+    let line = format!("{prefix}{text}");
+    move_to_prompt(out, rows, viewport, 0, false)?;
+    // The padded text already covers the whole row of a viewport, the rest of the terminal row
+    // must be left alone
+    if viewport.is_none() {
+        write!(out, "\r{}", Clear(ClearType::CurrentLine))?;
+    }
+    write!(out, "{}", style.apply(format!("{line:<cols$}")))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{word_starts, FieldEvent, InputField};
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+
+    const fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+    }
+
+    fn type_text(field: &mut InputField, text: &str) {
+        for c in text.chars() {
+            assert_eq!(
+                field.handle_event(&key(KeyCode::Char(c), KeyModifiers::NONE)),
+                FieldEvent::Changed
+            );
+        }
+    }
+
+    #[test]
+    fn words() {
+        assert_eq!(
+            word_starts("this is@complex-text_search?query"),
+            [1, 5, 6, 8, 9, 16, 17, 28, 29]
+        );
+        assert_eq!(word_starts("a  b"), [1, 2, 3, 4]);
+        assert!(word_starts("").is_empty());
+    }
+
+    #[test]
+    fn non_ascii_text() {
+        let mut field = InputField::new();
+        type_text(&mut field, "héllo wörld");
+        assert_eq!(field.cursor_position, 12);
+        assert_eq!(field.word_index, [1, 6, 7]);
+
+        field.handle_event(&key(KeyCode::Left, KeyModifiers::CONTROL));
+        assert_eq!(field.cursor_position, 7);
+        field.handle_event(&key(KeyCode::Backspace, KeyModifiers::NONE));
+        field.handle_event(&key(KeyCode::Left, KeyModifiers::NONE));
+        field.handle_event(&key(KeyCode::Left, KeyModifiers::NONE));
+        field.handle_event(&key(KeyCode::Delete, KeyModifiers::NONE));
+        type_text(&mut field, "ê");
+        assert_eq!(field.string, "hélêowörld");
+        assert_eq!(field.cursor_position, 5);
+    }
+
+    #[test]
+    fn remove_words() {
+        let mut field = InputField::new();
+        type_text(&mut field, "grep -i  foo_bar");
+        field.handle_event(&key(KeyCode::Backspace, KeyModifiers::CONTROL));
+        assert_eq!(field.string, "grep -i  ");
+        // Whitespace is removed along with the word before it
+        field.handle_event(&key(KeyCode::Char('w'), KeyModifiers::CONTROL));
+        assert_eq!(field.string, "grep -");
+        assert_eq!(field.cursor_position, 7);
+
+        field.handle_event(&key(KeyCode::Home, KeyModifiers::NONE));
+        field.handle_event(&key(KeyCode::Right, KeyModifiers::NONE));
+        field.handle_event(&key(KeyCode::Delete, KeyModifiers::CONTROL));
+        assert_eq!(field.string, "g -");
+        field.handle_event(&key(KeyCode::Char('d'), KeyModifiers::ALT));
+        assert_eq!(field.string, "g");
+        assert_eq!(
+            field.handle_event(&key(KeyCode::Delete, KeyModifiers::CONTROL)),
+            FieldEvent::Ignored
+        );
+        assert_eq!(field.word_index, [1]);
+    }
+
+    #[test]
+    fn paste() {
+        let mut field = InputField::new();
+        type_text(&mut field, "ab");
+        field.handle_event(&key(KeyCode::Left, KeyModifiers::NONE));
+        assert_eq!(
+            field.handle_event(&Event::Paste("x\ty\u{7}\n".to_string())),
+            FieldEvent::Changed
+        );
+        assert_eq!(field.string, "ax yb");
+        assert_eq!(field.cursor_position, 5);
+        assert_eq!(
+            field.handle_event(&Event::Paste("\n".to_string())),
+            FieldEvent::Ignored
+        );
+    }
+
+    #[test]
+    fn bounds() {
+        let mut field = InputField::new();
+        let ignored = [
+            key(KeyCode::Backspace, KeyModifiers::NONE),
+            key(KeyCode::Delete, KeyModifiers::NONE),
+            key(KeyCode::Left, KeyModifiers::NONE),
+            key(KeyCode::Right, KeyModifiers::NONE),
+        ];
+        for ev in &ignored {
+            assert_eq!(field.handle_event(ev), FieldEvent::Ignored);
+        }
+        assert_eq!(
+            field.handle_event(&key(KeyCode::Enter, KeyModifiers::NONE)),
+            FieldEvent::Confirmed
+        );
+        assert_eq!(
+            field.handle_event(&key(KeyCode::Esc, KeyModifiers::NONE)),
+            FieldEvent::Cancelled
+        );
+    }
+}
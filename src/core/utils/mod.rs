@@ -1,17 +1,60 @@
+pub mod command_line;
 pub mod display;
+pub mod input_field;
+pub mod picker;
 pub mod term;
 
+use std::{borrow::Cow, ops::Range};
+
 /// Return the number of digits in `num`
 pub const fn digits(num: usize) -> usize {
     (if num == 0 { 0 } else { num.ilog10() as usize }) + 1
 }
 
+/// Cut off `text` so that it takes up at most `width` columns
+///
+/// An ellipsis is put at the end if some of the text had to be cut off.
+pub fn truncate_with_ellipsis(text: &str, width: usize) -> Cow<'_, str> {
+    if textwrap::core::display_width(text) <= width {
+        return Cow::Borrowed(text);
+    }
+    if width == 0 {
+        return Cow::Borrowed("");
+    }
+    // Leave a column for the ellipsis
+    let end = text
+        .char_indices()
+        .map(|(idx, c)| idx + c.len_utf8())
+        .take_while(|end| textwrap::core::display_width(&text[..*end]) < width)
+        .last()
+        .unwrap_or(0);
+    Cow::Owned(format!("{}…", &text[..end]))
+}
+
+/// Remove and return the text from the start of `bytes` up to a UTF-8 character which is
+/// incomplete because the rest of it hasn't been read yet
+pub fn take_complete_text(bytes: &mut Vec<u8>) -> String {
+    let mut complete = bytes.len();
+    let mut checked = 0;
+    while let Err(e) = std::str::from_utf8(&bytes[checked..]) {
+        if let Some(invalid_len) = e.error_len() {
+            checked += e.valid_up_to() + invalid_len;
+        } else {
+            complete = checked + e.valid_up_to();
+            break;
+        }
+    }
+    let text = String::from_utf8_lossy(&bytes[..complete]).into_owned();
+    bytes.drain(..complete);
+    text
+}
+
 /// Stores the location of first row each line
 ///
 /// Due to line wrapping, each line may or may not occupy exactly one row on the terminal
 /// Hence we nned to keep track where the first row o each line is positioned in the entire text
 /// array.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct LinesRowMap(Vec<usize>);
 
 impl LinesRowMap {
@@ -36,4 +79,39 @@ impl LinesRowMap {
     pub fn get(&self, ln: usize) -> Option<&usize> {
         self.0.get(ln)
     }
+
+    /// Remove the first `count` lines and return the number of rows that they occupied
+    ///
+    /// The rows of the remaining lines are moved up accordingly.
+    pub fn remove_first_lines(&mut self, count: usize) -> usize {
+        let rows = self.0.get(count).copied().unwrap_or(0);
+        self.0.drain(..count.min(self.0.len()));
+        for row in &mut self.0 {
+            *row -= rows;
+        }
+        rows
+    }
+
+    /// Replace the lines in `lines` by lines whose first rows are `first_rows`
+    ///
+    /// The replaced lines occupied `removed_rows` rows and the new ones occupy `added_rows` rows.
+    /// The rows of the lines after them are moved accordingly.
+    pub fn replace_lines(
+        &mut self,
+        lines: Range<usize>,
+        first_rows: Vec<usize>,
+        removed_rows: usize,
+        added_rows: usize,
+    ) {
+        let end = lines.end.min(self.0.len());
+        for row in &mut self.0[end..] {
+            *row = *row - removed_rows + added_rows;
+        }
+        self.0.splice(lines.start.min(end)..end, first_rows);
+    }
+
+    /// Get the index of the line which occupies the row at `row`
+    pub fn line_of_row(&self, row: usize) -> usize {
+        self.0.partition_point(|r| *r <= row).saturating_sub(1)
+    }
 }
@@ -0,0 +1,193 @@
+//! A list of items drawn over the text, from which the user picks one
+//!
+//! Like [`read_input`](super::input_field::read_input), [pick] reads the events by itself until
+//! the user is done. It is used to jump to the sections added with
+//! [`Pager::add_sections`](crate::Pager::add_sections).
+
+use std::{convert::TryFrom, io::Write, time::Duration};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+
+use super::{input_field, term, truncate_with_ellipsis};
+use crate::{error::MinusError, PagerState};
+
+/// What happened to a [Picker] after handing it an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickerEvent {
+    /// `Enter` was pressed on the item at this index
+    Picked(usize),
+    /// `Esc` or `q` was pressed
+    Cancelled,
+    /// The selection was moved
+    Moved,
+    /// Nothing happened
+    Ignored,
+}
+
+/// The selected item of a list along with the part of the list that is displayed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Picker {
+    /// Index of the selected item
+    pub selected: usize,
+    /// Index of the first displayed item
+    pub top: usize,
+    /// Number of items
+    len: usize,
+    /// Number of items that can be displayed at once
+    height: usize,
+}
+
+impl Picker {
+    /// Make a picker for `len` items which shows `height` of them at once, starting with the
+    /// item at `selected`
+    pub fn new(len: usize, height: usize, selected: usize) -> Self {
+        let mut picker = Self {
+            selected: selected.min(len.saturating_sub(1)),
+            top: 0,
+            len,
+            height: height.max(1),
+        };
+        picker.scroll_to_selection();
+        picker
+    }
+
+    /// Scroll just enough to display the selected item
+    const fn scroll_to_selection(&mut self) {
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + self.height {
+            self.top = self.selected + 1 - self.height;
+        }
+    }
+
+    /// Select the item at `selected`
+    fn select(&mut self, selected: usize) -> PickerEvent {
+        let selected = selected.min(self.len.saturating_sub(1));
+        if selected == self.selected {
+            return PickerEvent::Ignored;
+        }
+        self.selected = selected;
+        self.scroll_to_selection();
+        PickerEvent::Moved
+    }
+
+    /// Update the selection according to the given event
+    pub fn handle_event(&mut self, ev: &Event) -> PickerEvent {
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        }) = ev
+        else {
+            return PickerEvent::Ignored;
+        };
+        match code {
+            KeyCode::Enter if self.len > 0 => PickerEvent::Picked(self.selected),
+            KeyCode::Esc | KeyCode::Char('q') => PickerEvent::Cancelled,
+            KeyCode::Up | KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.select(self.selected.saturating_add(1)),
+            KeyCode::PageUp => self.select(self.selected.saturating_sub(self.height)),
+            KeyCode::PageDown => self.select(self.selected.saturating_add(self.height)),
+            KeyCode::Home | KeyCode::Char('g') => self.select(0),
+            KeyCode::End | KeyCode::Char('G') => self.select(usize::MAX),
+            _ => PickerEvent::Ignored,
+        }
+    }
+}
+
+/// Let the user pick one of the `items` from a list drawn over the top of the text
+///
+/// The `title` is displayed at the prompt site and the item at `selected` is selected at first.
+/// Returns the index of the picked item or `None` if the user cancelled by pressing `Esc`.
+///
+/// The caller must make sure that nothing else reads from the
+/// [`EventSource`](crate::input::EventSource) and redraw the screen once this returns.
+pub fn pick<O: Write>(
+    out: &mut O,
+    ps: &PagerState,
+    title: &str,
+    items: &[&str],
+    selected: usize,
+) -> Result<Option<usize>, MinusError> {
+    let rows = u16::try_from(ps.rows).unwrap_or(u16::MAX);
+    let cols = ps.cols;
+    let (left, top) = ps.viewport.map_or((0, 0), |vp| (vp.column, vp.row));
+    let selection_style = ps.screen.color_support.adapt(ps.screen.theme.selection);
+    let prompt_style = ps.screen.color_support.adapt(ps.screen.theme.input_prompt);
+
+    let draw = |out: &mut O, picker: &Picker| -> crate::Result {
+        for (offset, idx) in (picker.top..items.len()).take(picker.height).enumerate() {
+            let y = top.saturating_add(u16::try_from(offset).unwrap_or(u16::MAX));
+            term::move_cursor(out, left, y, false)?;
+            let label = truncate_with_ellipsis(items[idx], cols.saturating_sub(1));
+            let padding = cols.saturating_sub(1 + textwrap::core::display_width(&label));
+            let row = format!(" {label}{:padding$}", "");
+            if idx == picker.selected {
+                write!(out, "{}", selection_style.apply(row))?;
+            } else {
+                write!(out, "{row}")?;
+            }
+        }
+        input_field::draw_prompt(out, rows, ps.viewport, cols, prompt_style, title, "")?;
+        out.flush()?;
+        Ok(())
+    };
+
+    let mut picker = Picker::new(items.len(), ps.writable_rows(), selected);
+    draw(out, &picker)?;
+
+    loop {
+        let ev = ps
+            .event_source
+            .lock()
+            .next_event(Duration::from_millis(100))
+            .map_err(|e| MinusError::HandleEvent(e.into()))?;
+        let Some(ev) = ev else {
+            continue;
+        };
+        match picker.handle_event(&ev) {
+            PickerEvent::Picked(idx) => return Ok(Some(idx)),
+            PickerEvent::Cancelled => return Ok(None),
+            PickerEvent::Moved => draw(out, &picker)?,
+            PickerEvent::Ignored => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Picker, PickerEvent};
+    use crossterm::event::{Event, KeyCode, KeyEvent};
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::from(code))
+    }
+
+    #[test]
+    fn move_selection() {
+        let mut picker = Picker::new(10, 4, 8);
+        assert_eq!((picker.selected, picker.top), (8, 5));
+
+        assert_eq!(picker.handle_event(&key(KeyCode::Down)), PickerEvent::Moved);
+        assert_eq!(
+            picker.handle_event(&key(KeyCode::Down)),
+            PickerEvent::Ignored
+        );
+        assert_eq!((picker.selected, picker.top), (9, 6));
+
+        picker.handle_event(&key(KeyCode::PageUp));
+        picker.handle_event(&key(KeyCode::Char('k')));
+        assert_eq!((picker.selected, picker.top), (4, 4));
+        picker.handle_event(&key(KeyCode::Char('g')));
+        assert_eq!((picker.selected, picker.top), (0, 0));
+
+        assert_eq!(
+            picker.handle_event(&key(KeyCode::Enter)),
+            PickerEvent::Picked(0)
+        );
+        assert_eq!(
+            picker.handle_event(&key(KeyCode::Esc)),
+            PickerEvent::Cancelled
+        );
+    }
+}
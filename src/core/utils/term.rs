@@ -6,39 +6,58 @@ use crate::error::{CleanupError, MinusError, SetupError};
 use crossterm::{
     cursor, event, execute, queue,
     terminal::{self, Clear},
-    tty::IsTty,
 };
 use std::io;
 
+/// How the terminal should be set up for the pager
+#[derive(Debug, Clone, Copy)]
+pub struct TermOptions {
+    /// Whether to switch to the [alternate screen]
+    ///
+    /// [alternate screen]: ../../../crossterm/terminal/index.html#alternate-screen
+    pub alternate_screen: bool,
+    /// Whether to enable [raw mode]. This is only needed if the input is read from the terminal.
+    ///
+    /// [raw mode]: ../../../crossterm/terminal/index.html#raw-mode
+    pub raw_mode: bool,
+    /// Whether to capture the mouse so that scrolling the mouse wheel scrolls the text
+    pub mouse_capture: bool,
+    /// Number of rows of the output if it is not the terminal of the process
+    pub output_rows: Option<u16>,
+}
+
 /// Setup the terminal
 ///
 /// It will
-/// - Switch the terminal's view to the [alternate screen]
-/// - Then enable [raw mode]
+/// - Switch the terminal's view to the [alternate screen] if requested in `opts`
+/// - Then enable [raw mode] if requested in `opts`
+/// - Capture the mouse if requested in `opts`
+/// - Enable bracketed paste so that pasted text can be told apart from typed keys
+/// - Enable focus reporting so that the pager knows when the terminal loses focus
 /// - Clear the entire screen and hide the cursor.
 ///
 /// # Errors
-/// The function will return with an error if `stdout` is not a terminal. It will qlso fail
-/// if it cannot executo commands on the terminal See [`SetupError`].
+/// The function will fail if it cannot executo commands on the terminal See [`SetupError`].
 ///
 /// [alternate screen]: ../../../crossterm/terminal/index.html#alternate-screen
 /// [raw mode]: ../../../crossterm/terminal/index.html#raw-mode
 // This function should be kept close to `cleanup` to help ensure both are
 // doing the opposite of the other.
-pub fn setup(stdout: &io::Stdout) -> std::result::Result<(), SetupError> {
-    let mut out = stdout.lock();
-
-    if out.is_tty() {
-        Ok(())
-    } else {
-        Err(SetupError::InvalidTerminal)
-    }?;
-
-    execute!(out, terminal::EnterAlternateScreen)
-        .map_err(|e| SetupError::AlternateScreen(e.into()))?;
-    terminal::enable_raw_mode().map_err(|e| SetupError::RawMode(e.into()))?;
-    execute!(out, event::EnableMouseCapture)
-        .map_err(|e| SetupError::EnableMouseCapture(e.into()))?;
+pub fn setup(out: &mut impl io::Write, opts: TermOptions) -> std::result::Result<(), SetupError> {
+    if opts.alternate_screen {
+        execute!(out, terminal::EnterAlternateScreen)
+            .map_err(|e| SetupError::AlternateScreen(e.into()))?;
+    }
+    if opts.raw_mode {
+        terminal::enable_raw_mode().map_err(|e| SetupError::RawMode(e.into()))?;
+    }
+    if opts.mouse_capture {
+        execute!(out, event::EnableMouseCapture)
+            .map_err(|e| SetupError::EnableMouseCapture(e.into()))?;
+    }
+    execute!(out, event::EnableBracketedPaste)
+        .map_err(|e| SetupError::EnableBracketedPaste(e.into()))?;
+    execute!(out, event::EnableFocusChange).map_err(|e| SetupError::EnableFocusChange(e.into()))?;
     execute!(out, cursor::Hide).map_err(|e| SetupError::HideCursor(e.into()))?;
     Ok(())
 }
@@ -48,8 +67,10 @@ pub fn setup(stdout: &io::Stdout) -> std::result::Result<(), SetupError> {
 /// The function will clean up the terminal and set it back to its original state,
 /// before the pager was setup and called.
 /// - First the cursor is displayed
+/// - Focus reporting and bracketed paste are disabled
 /// - [Raw mode] is disabled
-/// - Switch the terminal's view to the main screen
+/// - Switch the terminal's view to the main screen if the alternate screen was used. Otherwise the
+///   prompt line is cleared so that the last displayed view stays on the screen above it
 ///
 /// ## Errors
 /// The function will return with an error if it fails to do execute commands on the
@@ -60,15 +81,37 @@ pub fn cleanup(
     mut out: impl io::Write,
     es: &crate::ExitStrategy,
     cleanup_screen: bool,
+    opts: TermOptions,
 ) -> std::result::Result<(), CleanupError> {
     if cleanup_screen {
+        if !opts.alternate_screen {
+            // Leave the cursor on the prompt line so that the shell continues from there
+            let rows = opts
+                .output_rows
+                .or_else(|| terminal::size().ok().map(|(_, rows)| rows))
+                .unwrap_or(1);
+            execute!(
+                out,
+                cursor::MoveTo(0, rows.saturating_sub(1)),
+                Clear(terminal::ClearType::CurrentLine)
+            )
+            .map_err(|e| CleanupError::ShowCursor(e.into()))?;
+        }
         // Reverse order of setup.
         execute!(out, cursor::Show).map_err(|e| CleanupError::ShowCursor(e.into()))?;
+        execute!(out, event::DisableFocusChange)
+            .map_err(|e| CleanupError::DisableFocusChange(e.into()))?;
+        execute!(out, event::DisableBracketedPaste)
+            .map_err(|e| CleanupError::DisableBracketedPaste(e.into()))?;
         execute!(out, event::DisableMouseCapture)
             .map_err(|e| CleanupError::DisableMouseCapture(e.into()))?;
-        terminal::disable_raw_mode().map_err(|e| CleanupError::DisableRawMode(e.into()))?;
-        execute!(out, terminal::LeaveAlternateScreen)
-            .map_err(|e| CleanupError::LeaveAlternateScreen(e.into()))?;
+        if opts.raw_mode {
+            terminal::disable_raw_mode().map_err(|e| CleanupError::DisableRawMode(e.into()))?;
+        }
+        if opts.alternate_screen {
+            execute!(out, terminal::LeaveAlternateScreen)
+                .map_err(|e| CleanupError::LeaveAlternateScreen(e.into()))?;
+        }
     }
 
     if *es == crate::ExitStrategy::ProcessQuit {
@@ -78,6 +121,21 @@ pub fn cleanup(
     }
 }
 
+/// Check whether the terminal is expected to support synchronized output
+///
+/// Terminals that don't know about the synchronized update mode are supposed to ignore it but
+/// some old terminals and multiplexers don't handle it well, so we stay away from them. The
+/// terminal is identified by the `TERM` environment variable.
+#[must_use]
+pub fn supports_synchronized_output() -> bool {
+    std::env::var("TERM").map_or(cfg!(windows), |term| {
+        !(term == "dumb"
+            || term.starts_with("linux")
+            || term.starts_with("screen")
+            || term.starts_with("vt"))
+    })
+}
+
 /// Moves the terminal cursor to given x, y coordinates
 ///
 /// The `flush` parameter will immediately flush the buffer if it is set to `true`
@@ -0,0 +1,613 @@
+//! Lays out the differences between two texts side by side
+//!
+//! See [`Pager::set_diff`](crate::Pager::set_diff) for more info.
+
+use crossterm::style::{ContentStyle, Stylize};
+use textwrap::core::display_width;
+
+/// Separates the old text on the left from the new text on the right
+const SEPARATOR: &str = " │ ";
+
+/// A line of one of the two texts
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Line {
+    /// Number of the line in its text, starting from 1
+    number: usize,
+    text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Row {
+    /// A line of the old text next to the line of the new text that it corresponds to
+    ///
+    /// A side is missing if its line was added or removed. If both sides are there but their
+    /// texts differ, the line was changed.
+    Lines {
+        old: Option<Line>,
+        new: Option<Line>,
+    },
+    /// A line spanning both sides, like the header of a hunk of a unified diff
+    Header(String),
+}
+
+/// Styles of the parts of the diff, already adapted to the colors of the terminal
+pub struct DiffStyles {
+    pub removed: ContentStyle,
+    pub added: ContentStyle,
+    pub header: ContentStyle,
+}
+
+/// The rows set with [`Pager::set_diff`](crate::Pager::set_diff)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Diff {
+    rows: Vec<Row>,
+}
+
+impl Diff {
+    /// Compare the lines of `old` with the ones of `new`
+    pub fn new(old: &str, new: &str) -> Self {
+        let old: Vec<&str> = old.lines().collect();
+        let new: Vec<&str> = new.lines().collect();
+        let line = |lines: &[&str], idx: usize| Line {
+            number: idx + 1,
+            text: lines[idx].to_string(),
+        };
+        let mut hunk = Hunk::default();
+        for edit in diff(&old, &new) {
+            match edit {
+                Edit::Keep(old_idx, new_idx) => {
+                    hunk.push_both(line(&old, old_idx), line(&new, new_idx));
+                }
+                Edit::Remove(idx) => hunk.removed.push(line(&old, idx)),
+                Edit::Insert(idx) => hunk.added.push(line(&new, idx)),
+            }
+        }
+        hunk.finish()
+    }
+
+    /// Read the hunks of a diff in the unified format, like the output of `diff -u` or `git diff`
+    ///
+    /// Everything outside of the hunks, like the names of the files, is kept as header rows.
+    pub fn from_unified(diff: &str) -> Self {
+        let mut hunk = Hunk::default();
+        // Numbers of the next lines and the number of lines left in the current hunk
+        let (mut old_number, mut new_number) = (0, 0);
+        let (mut old_left, mut new_left) = (0_usize, 0_usize);
+        for text in diff.lines() {
+            if old_left == 0 && new_left == 0 {
+                // Like "\ No newline at end of file"
+                if text.starts_with('\\') {
+                    continue;
+                }
+                if let Some((old_start, old_len, new_start, new_len)) = parse_hunk_header(text) {
+                    (old_number, old_left) = (old_start.max(1), old_len);
+                    (new_number, new_left) = (new_start.max(1), new_len);
+                }
+                hunk.push_header(text.to_string());
+                continue;
+            }
+            let line = |number: usize| Line {
+                number,
+                text: text.get(1..).unwrap_or_default().to_string(),
+            };
+            match text.as_bytes().first() {
+                Some(b'-') => {
+                    hunk.removed.push(line(old_number));
+                    old_number += 1;
+                    old_left = old_left.saturating_sub(1);
+                }
+                Some(b'+') => {
+                    hunk.added.push(line(new_number));
+                    new_number += 1;
+                    new_left = new_left.saturating_sub(1);
+                }
+                Some(b'\\') => {}
+                _ => {
+                    hunk.push_both(line(old_number), line(new_number));
+                    old_number += 1;
+                    new_number += 1;
+                    old_left = old_left.saturating_sub(1);
+                    new_left = new_left.saturating_sub(1);
+                }
+            }
+        }
+        hunk.finish()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+
+    /// Remove the first `count` rows
+    pub fn remove_first_rows(&mut self, count: usize) {
+        self.rows.drain(..count.min(self.rows.len()));
+    }
+
+    /// Remove all the rows after the first `count` ones
+    pub fn truncate(&mut self, count: usize) {
+        self.rows.truncate(count);
+    }
+
+    /// Get the text of the diff with each row on its own line
+    ///
+    /// If `max_width` is given, both sides are as wide as each other and their lines are truncated
+    /// so that each row fits into that many columns. Otherwise the left side is as wide as its
+    /// widest line.
+    pub fn layout(
+        &self,
+        max_width: Option<usize>,
+        styles: &DiffStyles,
+        tab_width: usize,
+    ) -> String {
+        let number_width = self
+            .rows
+            .iter()
+            .filter_map(|row| match row {
+                Row::Lines { old, new } => old.iter().chain(new).map(|line| line.number).max(),
+                Row::Header(_) => None,
+            })
+            .max()
+            .unwrap_or(0)
+            .to_string()
+            .len();
+        let gutter = |line: &Line| format!("{:>number_width$} ", line.number);
+        let side_width = max_width.map_or_else(
+            || {
+                self.rows
+                    .iter()
+                    .filter_map(|row| match row {
+                        Row::Lines { old: Some(old), .. } => Some(old),
+                        _ => None,
+                    })
+                    .map(|line| {
+                        number_width + 1 + display_width(&expand_tabs(&line.text, tab_width))
+                    })
+                    .max()
+                    .unwrap_or(0)
+            },
+            |width| width.saturating_sub(display_width(SEPARATOR)) / 2,
+        );
+
+        let mut text = String::new();
+        for row in &self.rows {
+            let (old, new) = match row {
+                Row::Header(header) => {
+                    text.push_str(&styles.header.apply(header).to_string());
+                    text.push('\n');
+                    continue;
+                }
+                Row::Lines { old, new } => (old.as_ref(), new.as_ref()),
+            };
+            let old_text = old.map(|line| expand_tabs(&line.text, tab_width));
+            let new_text = new.map(|line| expand_tabs(&line.text, tab_width));
+            let (old_segments, new_segments) = match (&old_text, &new_text) {
+                (Some(old), Some(new)) if old != new => changed_segments(old, new),
+                _ => (
+                    old_text.iter().map(|t| (t.clone(), false)).collect(),
+                    new_text.iter().map(|t| (t.clone(), false)).collect(),
+                ),
+            };
+            let changed = old_text != new_text;
+            let old_side = old.map_or_else(String::new, |line| {
+                let style = changed.then_some(styles.removed);
+                render_side(&gutter(line), &old_segments, style, side_width)
+            });
+            let padding = side_width.saturating_sub(display_width(&old_side));
+            text.push_str(&old_side);
+            text.push_str(&" ".repeat(padding));
+            text.push_str(SEPARATOR);
+            if let Some(line) = new {
+                let style = changed.then_some(styles.added);
+                // The right side only has to be cut off if the rows have to fit
+                let width = max_width.map_or(usize::MAX, |_| side_width);
+                let new_side = render_side(&gutter(line), &new_segments, style, width);
+                text.push_str(&new_side);
+            }
+            // Padding after the last side would show up as trailing spaces
+            let len = text.trim_end_matches(' ').len();
+            text.truncate(len);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Collects the rows of a diff, pairing up the removed lines with the added lines after them
+#[derive(Default)]
+struct Hunk {
+    rows: Vec<Row>,
+    /// Lines removed since the last line that both texts have
+    removed: Vec<Line>,
+    /// Lines added since the last line that both texts have
+    added: Vec<Line>,
+}
+
+impl Hunk {
+    /// Put the lines removed and added since the last unchanged line next to each other
+    ///
+    /// The first removed line is shown as changed into the first added line and so on. Lines
+    /// left over on either side are shown as removed or added.
+    fn flush(&mut self) {
+        let mut removed = self.removed.drain(..);
+        let mut added = self.added.drain(..);
+        loop {
+            let (old, new) = (removed.next(), added.next());
+            if old.is_none() && new.is_none() {
+                break;
+            }
+            self.rows.push(Row::Lines { old, new });
+        }
+    }
+
+    fn push_both(&mut self, old: Line, new: Line) {
+        self.flush();
+        self.rows.push(Row::Lines {
+            old: Some(old),
+            new: Some(new),
+        });
+    }
+
+    fn push_header(&mut self, header: String) {
+        self.flush();
+        self.rows.push(Row::Header(header));
+    }
+
+    fn finish(mut self) -> Diff {
+        self.flush();
+        Diff { rows: self.rows }
+    }
+}
+
+/// Get the first line and the number of lines of both texts from the header of a hunk of a
+/// unified diff, like `@@ -12,5 +12,6 @@`
+fn parse_hunk_header(text: &str) -> Option<(usize, usize, usize, usize)> {
+    let ranges = text.strip_prefix("@@ -")?.split(" @@").next()?;
+    let (old, new) = ranges.split_once(" +")?;
+    let range = |range: &str| match range.split_once(',') {
+        Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+        // The number of lines is left out if it is one
+        None => Some((range.parse().ok()?, 1)),
+    };
+    let (old_start, old_len) = range(old)?;
+    let (new_start, new_len) = range(new)?;
+    Some((old_start, old_len, new_start, new_len))
+}
+
+/// Replace the tabs in `text` with spaces up to the next multiple of `tab_width` columns
+///
+/// The screen would expand them differently depending on where a side starts.
+fn expand_tabs(text: &str, tab_width: usize) -> String {
+    if !text.contains('\t') {
+        return text.to_string();
+    }
+    let mut expanded = String::with_capacity(text.len());
+    let mut column = 0;
+    for c in text.chars() {
+        if c == '\t' {
+            let spaces = tab_width.max(1) - column % tab_width.max(1);
+            expanded.push_str(&" ".repeat(spaces));
+            column += spaces;
+        } else {
+            expanded.push(c);
+            column += display_width(c.encode_utf8(&mut [0; 4]));
+        }
+    }
+    expanded
+}
+
+/// Parts of a line, each paired with whether it was changed
+type Segments = Vec<(String, bool)>;
+
+/// Split a changed line into the parts that both versions have and the parts that were changed
+///
+/// The lines are compared word by word. Each part is paired with whether it was changed.
+fn changed_segments(old: &str, new: &str) -> (Segments, Segments) {
+    let old_words = words(old);
+    let new_words = words(new);
+    let mut old_segments = Vec::new();
+    let mut new_segments = Vec::new();
+    for edit in diff(&old_words, &new_words) {
+        match edit {
+            Edit::Keep(old_idx, new_idx) => {
+                push_segment(&mut old_segments, old_words[old_idx], false);
+                push_segment(&mut new_segments, new_words[new_idx], false);
+            }
+            Edit::Remove(idx) => push_segment(&mut old_segments, old_words[idx], true),
+            Edit::Insert(idx) => push_segment(&mut new_segments, new_words[idx], true),
+        }
+    }
+    (old_segments, new_segments)
+}
+
+/// Append `word` to the last segment if it has been changed alike, as the words are adjacent
+fn push_segment(segments: &mut Segments, word: &str, changed: bool) {
+    match segments.last_mut() {
+        Some((last, last_changed)) if *last_changed == changed => last.push_str(word),
+        _ => segments.push((word.to_string(), changed)),
+    }
+}
+
+/// Split `text` into words, runs of whitespace and single punctuation characters
+fn words(text: &str) -> Vec<&str> {
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            0
+        } else if c.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut prev = None;
+    for (idx, c) in text.char_indices() {
+        let current = class(c);
+        if prev.map_or(false, |prev| prev != current || current == 2) {
+            words.push(&text[start..idx]);
+            start = idx;
+        }
+        prev = Some(current);
+    }
+    if start < text.len() {
+        words.push(&text[start..]);
+    }
+    words
+}
+
+/// Get the text of one side of a row, truncated with an ellipsis if it is wider than `width`
+///
+/// `style` is applied to the whole line if it was changed. The changed `segments` are
+/// emphasized by reversing it.
+fn render_side(
+    gutter: &str,
+    segments: &[(String, bool)],
+    style: Option<ContentStyle>,
+    width: usize,
+) -> String {
+    let full_width = display_width(gutter)
+        + segments
+            .iter()
+            .map(|(text, _)| display_width(text))
+            .sum::<usize>();
+    // Leave a column for the ellipsis if the line has to be truncated
+    let mut room = if full_width > width {
+        width.saturating_sub(1)
+    } else {
+        width
+    };
+    let mut side = String::new();
+    let parts = std::iter::once((gutter, false)).chain(
+        segments
+            .iter()
+            .map(|(text, emphasized)| (text.as_str(), *emphasized)),
+    );
+    for (text, emphasized) in parts {
+        let mut end = 0;
+        for (idx, c) in text.char_indices() {
+            let c_width = display_width(c.encode_utf8(&mut [0; 4]));
+            if c_width > room {
+                break;
+            }
+            room -= c_width;
+            end = idx + c.len_utf8();
+        }
+        let part = &text[..end];
+        match style {
+            Some(style) if !part.is_empty() => {
+                let style = if emphasized { style.reverse() } else { style };
+                side.push_str(&style.apply(part).to_string());
+            }
+            _ => side.push_str(part),
+        }
+        if end < text.len() {
+            break;
+        }
+    }
+    if full_width > width && width > 0 {
+        side.push('…');
+    }
+    side
+}
+
+/// A step of turning one sequence into another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    /// Keep the item at the first index of the old sequence, which is at the second index of the
+    /// new one
+    Keep(usize, usize),
+    /// Remove the item at this index of the old sequence
+    Remove(usize),
+    /// Insert the item at this index of the new sequence
+    Insert(usize),
+}
+
+/// Find the fewest edits that turn `old` into `new`
+///
+/// This is the algorithm by Eugene W. Myers, which takes time proportional to the length of the
+/// sequences times the number of edits. The items that both sequences start and end with are
+/// skipped first as most changes only touch a small part of a text.
+fn diff<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Edit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut edits: Vec<Edit> = (0..prefix).map(|idx| Edit::Keep(idx, idx)).collect();
+    let old_end = old.len() - suffix;
+    let new_end = new.len() - suffix;
+    edits.extend(
+        shortest_edit(&old[prefix..old_end], &new[prefix..new_end])
+            .into_iter()
+            .map(|edit| match edit {
+                Edit::Keep(old_idx, new_idx) => Edit::Keep(old_idx + prefix, new_idx + prefix),
+                Edit::Remove(idx) => Edit::Remove(idx + prefix),
+                Edit::Insert(idx) => Edit::Insert(idx + prefix),
+            }),
+    );
+    edits.extend((0..suffix).map(|idx| Edit::Keep(old_end + idx, new_end + idx)));
+    edits
+}
+
+#[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+fn shortest_edit<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Edit> {
+    let (old_len, new_len) = (old.len() as isize, new.len() as isize);
+    // Furthest position in the old sequence reached on each diagonal k = x - y, indexed by k + max
+    let max = old_len + new_len;
+    let mut furthest = vec![0_isize; 2 * max as usize + 2];
+    let at = |k: isize| (k + max) as usize;
+    let same = |x: isize, y: isize| old[x as usize] == new[y as usize];
+    // Only the diagonals from -d to d are kept for each number of edits d to backtrack through
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    'search: for d in 0..=max {
+        trace.push(furthest[at(-d)..=at(d)].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && furthest[at(k - 1)] < furthest[at(k + 1)]) {
+                furthest[at(k + 1)]
+            } else {
+                furthest[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < old_len && y < new_len && same(x, y) {
+                x += 1;
+                y += 1;
+            }
+            furthest[at(k)] = x;
+            if x >= old_len && y >= new_len {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (old_len, new_len);
+    for (d, previous) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        // `previous` holds the diagonals from -d to d after d - 1 edits
+        let reached = |k: isize| previous[(k + d) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && reached(k - 1) < reached(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = if d == 0 { 0 } else { reached(prev_k) };
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Keep(x as usize, y as usize));
+        }
+        if d > 0 {
+            if x == prev_x {
+                edits.push(Edit::Insert(prev_y as usize));
+            } else {
+                edits.push(Edit::Remove(prev_x as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{changed_segments, diff, Diff, DiffStyles, Edit};
+    use crossterm::style::{ContentStyle, Stylize};
+
+    /// The changed words are emphasized even without any styles
+    fn emphasized(word: &str) -> String {
+        ContentStyle::new().reverse().apply(word).to_string()
+    }
+
+    fn layout(diff: &Diff, max_width: Option<usize>) -> String {
+        let styles = DiffStyles {
+            removed: ContentStyle::new(),
+            added: ContentStyle::new(),
+            header: ContentStyle::new(),
+        };
+        diff.layout(max_width, &styles, 4)
+    }
+
+    #[test]
+    fn fewest_edits() {
+        let edits = diff(&["a", "b", "c", "d"], &["a", "c", "d", "e"]);
+        assert_eq!(
+            edits,
+            [
+                Edit::Keep(0, 0),
+                Edit::Remove(1),
+                Edit::Keep(2, 1),
+                Edit::Keep(3, 2),
+                Edit::Insert(3),
+            ]
+        );
+        assert_eq!(diff::<&str>(&[], &[]), []);
+        assert_eq!(diff(&["a"], &["b"]), [Edit::Remove(0), Edit::Insert(0)]);
+    }
+
+    #[test]
+    fn side_by_side() {
+        let diff = Diff::new("a\nold\nb\nc\n", "a\nnew\nb\nc\nd\n");
+        assert_eq!(
+            layout(&diff, Some(19)),
+            format!(
+                "1 a      │ 1 a\n2 {}    │ 2 {}\n3 b      │ 3 b\n4 c      │ 4 c\n         │ 5 d\n",
+                emphasized("old"),
+                emphasized("new")
+            )
+        );
+        // Without a limit, the right side starts after the widest line of the left side
+        let diff = Diff::new("a long line\nx\n", "x\n");
+        assert_eq!(
+            layout(&diff, None),
+            "1 a long line │\n2 x           │ 1 x\n"
+        );
+        // Lines that don't fit are truncated
+        assert_eq!(layout(&diff, Some(15)), "1 a l… │\n2 x    │ 1 x\n");
+    }
+
+    #[test]
+    fn changed_words() {
+        let (old, new) = changed_segments("let x = 1;", "let x = 23;");
+        assert_eq!(
+            old,
+            [
+                ("let x = ".to_string(), false),
+                ("1".to_string(), true),
+                (";".to_string(), false)
+            ]
+        );
+        assert_eq!(
+            new,
+            [
+                ("let x = ".to_string(), false),
+                ("23".to_string(), true),
+                (";".to_string(), false)
+            ]
+        );
+    }
+
+    #[test]
+    fn unified() {
+        let diff = Diff::from_unified(
+            "--- a/file\n+++ b/file\n@@ -9,3 +9,3 @@ fn main()\n a\n-b\n+B\n c\n\\ No newline at end of file\n",
+        );
+        assert_eq!(
+            layout(&diff, None),
+            format!(
+                "--- a/file\n+++ b/file\n@@ -9,3 +9,3 @@ fn main()\n 9 a │  9 a\n10 {} │ 10 {}\n11 c │ 11 c\n",
+                emphasized("b"),
+                emphasized("B")
+            )
+        );
+    }
+}
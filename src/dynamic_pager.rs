@@ -1,20 +1,151 @@
-use crate::error::MinusError;
-use crate::minus_core::init;
-use crate::Pager;
+use std::thread::{self, JoinHandle};
+
+use crossbeam_channel::Sender;
+use crossterm::tty::IsTty;
+
+use crate::error::{MinusError, SetupError};
+use crate::minus_core::{commands::Command, init};
+use crate::{ExitReason, Pager};
 
 /// Starts a asynchronously running pager
 ///
-/// This means that data and configuration can be fed into the pager while it is running.
+/// The pager runs on a separate thread, so data and configuration can be fed into the pager while
+/// it is running. The returned [`PagerHandle`] can be used to stop the pager and to wait for it to
+/// quit.
 ///
 /// See [examples](../index.html#examples) on how to use this function.
 ///
 /// # Panics
-/// This function will panic if another instance of minus is already running.
+/// This function will panic if the pager is already running. Once the pager has quit, it can be
+/// started again. Remember to set the exit strategy to
+/// [`ExitStrategy::PagerQuit`](crate::ExitStrategy::PagerQuit) as the process is otherwise
+/// exited when the pager quits.
+///
+/// Separate pagers can run at the same time if each of them has its own output and input set
+/// with [`Pager::set_output`](crate::Pager::set_output) and
+/// [`Pager::set_input`](crate::Pager::set_input). Only one pager at a time can use the terminal
+/// of the process though, since raw mode is a setting of the whole terminal and a pager that
+/// quits turns it off for all the others. Starting a pager while another one is using the
+/// terminal panics.
+///
+/// # Example
+/// ```no_run
+/// use std::{thread, time::Duration};
+///
+/// let pager = minus::Pager::new();
+/// let handle = minus::dynamic_paging(pager.clone());
+/// pager.push_str("Closing in 10 seconds\n").unwrap();
+/// thread::sleep(Duration::from_secs(10));
+/// handle.stop().unwrap();
+/// handle.join().unwrap();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "dynamic_output")))]
+#[must_use]
+pub fn dynamic_paging(pager: Pager) -> PagerHandle {
+    let runmode = init::mark_running(&pager, crate::RunMode::Dynamic);
+    let tx = pager.tx.clone();
+    let thread = thread::spawn(move || init::init_core(pager, runmode));
+    PagerHandle { tx, thread }
+}
+
+/// A handle to a pager started with [`dynamic_paging`]
+///
+/// It lets the application stop the pager from its own logic, like after a timeout, instead of
+/// only the user quitting it. Dropping the handle doesn't stop the pager.
+#[cfg_attr(docsrs, doc(cfg(feature = "dynamic_output")))]
+#[derive(Debug)]
+pub struct PagerHandle {
+    tx: Sender<Command>,
+    thread: JoinHandle<Result<ExitReason, MinusError>>,
+}
+
+impl PagerHandle {
+    /// Quit the pager
+    ///
+    /// The pager quits just like when the user quits it, except that the process is never exited
+    /// even if the [`ExitStrategy`](crate::ExitStrategy) is
+    /// [`ProcessQuit`](crate::ExitStrategy::ProcessQuit). This does nothing if the pager has
+    /// already quit. Use [`join`](PagerHandle::join) to wait till the pager has quit.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if
+    /// the data could not be sent to the receiver
+    pub fn stop(&self) -> Result<(), MinusError> {
+        if self.is_running() {
+            self.tx.send(Command::Stop)?;
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the pager hasn't quit yet
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        !self.thread.is_finished()
+    }
+
+    /// Wait for the pager to quit and get the reason why it quit
+    ///
+    /// # Errors
+    /// This function returns the error that the pager quit with.
+    ///
+    /// # Panics
+    /// If the pager panicked, the panic is continued on the calling thread.
+    pub fn join(self) -> Result<ExitReason, MinusError> {
+        // The pager might be waiting for all the senders to be dropped when the output isn't a
+        // terminal
+        drop(self.tx);
+        self.thread
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
+
+/// Page the data that is piped into the standard input
+///
+/// The standard input is read in the background and its data is appended to the `pager` as it
+/// arrives while the keyboard input is taken directly from the terminal (`/dev/tty` on Unix and
+/// `CONIN$` on Windows). This lets applications behave like `less` when data is piped into them.
+///
+/// Unlike [`dynamic_paging`], this blocks until the pager is quit and then returns why it quit.
+/// Other data and configuration can still be sent to the `pager` while it is running.
+///
+/// # Panics
+/// This function will panic if the pager is already running. Once the pager has quit, it can be
+/// started again. Remember to set the exit strategy to
+/// [`ExitStrategy::PagerQuit`](crate::ExitStrategy::PagerQuit) as the process is otherwise
+/// exited when the pager quits.
+///
+/// Separate pagers can run at the same time if each of them has its own output and input set
+/// with [`Pager::set_output`](crate::Pager::set_output) and
+/// [`Pager::set_input`](crate::Pager::set_input). Only one pager at a time can use the terminal
+/// of the process though, since raw mode is a setting of the whole terminal and a pager that
+/// quits turns it off for all the others. Starting a pager while another one is using the
+/// terminal panics.
 ///
 /// # Errors
-/// The function will return with an error if it encounters a error during paging.
+/// The function will return a [`SetupError::InteractiveStdin`] if the standard input is a
+/// terminal instead of a pipe or a file. Otherwise it will return with an error if it
+/// encounters a error during paging.
+///
+/// # Example
+/// ```no_run
+/// minus::page_stdin(minus::Pager::new()).unwrap();
+/// ```
 #[cfg_attr(docsrs, doc(cfg(feature = "dynamic_output")))]
-#[allow(clippy::needless_pass_by_value)]
-pub fn dynamic_paging(pager: Pager) -> Result<(), MinusError> {
-    init::init_core(&pager, crate::RunMode::Dynamic)
+pub fn page_stdin(pager: Pager) -> Result<ExitReason, MinusError> {
+    let stdin = std::io::stdin();
+    if stdin.is_tty() {
+        return Err(SetupError::InteractiveStdin.into());
+    }
+
+    let reader = pager.read_from(stdin);
+    let notifier = pager.clone();
+    // Tell the user if the input stopped because reading it failed. The pager might have
+    // already quit by then so nothing can be done if sending the message fails.
+    std::thread::spawn(move || {
+        if let Ok(Err(MinusError::ReadInput(e))) = reader.join() {
+            let _ = notifier.send_message(format!("Failed to read the standard input: {e}"));
+        }
+    });
+    dynamic_paging(pager).join()
 }
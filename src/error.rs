@@ -38,6 +38,9 @@ pub enum SetupError {
     #[error("The standard output is not a valid terminal")]
     InvalidTerminal,
 
+    #[error("The standard input is a terminal and has no data to page")]
+    InteractiveStdin,
+
     #[error("Failed to switch to alternate screen")]
     AlternateScreen(TermError),
 
@@ -50,6 +53,12 @@ pub enum SetupError {
     #[error("Failed to enable mouse capture")]
     EnableMouseCapture(TermError),
 
+    #[error("Failed to enable bracketed paste")]
+    EnableBracketedPaste(TermError),
+
+    #[error("Failed to enable focus reporting")]
+    EnableFocusChange(TermError),
+
     #[error("Couldn't determine the terminal size")]
     TerminalSize(TermError),
 }
@@ -61,6 +70,12 @@ pub enum CleanupError {
     #[error("Failed to disable mouse capture")]
     DisableMouseCapture(TermError),
 
+    #[error("Failed to disable bracketed paste")]
+    DisableBracketedPaste(TermError),
+
+    #[error("Failed to disable focus reporting")]
+    DisableFocusChange(TermError),
+
     #[error("Failed to show the cursor")]
     ShowCursor(TermError),
 
@@ -96,6 +111,9 @@ pub enum MinusError {
     #[error("Failed to send data to the receiver")]
     Communication(#[from] crossbeam_channel::SendError<Command>),
 
+    #[error("Failed to read the input")]
+    ReadInput(std::io::Error),
+
     #[error("Failed to convert between some primitives")]
     Conversion,
 
@@ -108,6 +126,11 @@ pub enum MinusError {
     #[error(transparent)]
     #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
     JoinError(#[from] tokio::task::JoinError),
+
+    #[cfg(feature = "watch")]
+    #[error("Failed to watch the file")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "watch")))]
+    WatchFile(#[from] notify::Error),
 }
 
 // Just for  convenience helper which is useful in many places
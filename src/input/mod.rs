@@ -188,14 +188,20 @@ use crate::search::SearchMode;
 use crate::{LineNumbers, PagerState};
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
 pub use hashed_event_register::HashedEventRegister;
+use std::{io, sync::mpsc, time::Duration};
 
 /// Events handled by the `minus` pager.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[allow(clippy::module_name_repetitions)]
 #[non_exhaustive]
 pub enum InputEvent {
-    /// `Ctrl+C` or `Q`, exits the application.
+    /// `Q`, exits the application.
     Exit,
+    /// `Ctrl+C`, exits the application just like [`InputEvent::Exit`]
+    ///
+    /// The pager then reports [`ExitReason::Interrupted`](crate::ExitReason::Interrupted) instead
+    /// of [`ExitReason::UserQuit`](crate::ExitReason::UserQuit).
+    Interrupt,
     /// The terminal was resized. Contains the new number of rows.
     UpdateTermArea(usize, usize),
     /// Sent by movement keys like `Up` `Down`, `PageUp`, 'PageDown', 'g', `G` etc.
@@ -208,7 +214,14 @@ pub enum InputEvent {
     Number(char),
     /// Restore the original prompt
     RestorePrompt,
+    /// Show the full text of the prompt or message above it if it has been cut off
+    ///
+    /// The full text stays until the prompt is restored.
+    ShowFullPrompt,
     /// Whether to allow Horizontal scrolling
+    ///
+    /// Turning this on truncates lines at the edge of the terminal instead of wrapping them. The
+    /// line at the top of the screen is kept in place when the text is reformatted.
     HorizontalScroll(bool),
     /// Sets the left mark of Horizontal scrolling
     ///
@@ -249,6 +262,100 @@ pub enum InputEvent {
     /// This is similar to [Pager::follow_output](crate::pager::Pager::follow_output) except that
     /// this is used to control it from the user's side.
     FollowOutput(bool),
+    /// Go back to the position before the last large jump
+    ///
+    /// Large jumps include going to the top/bottom of the text or to a specific line and following
+    /// search matches.
+    JumpBack,
+    /// Go forward to the position from which [`InputEvent::JumpBack`] returned
+    JumpForward,
+    /// Whether to make invisible characters visible
+    ///
+    /// This is similar to [Pager::show_invisibles](crate::pager::Pager::show_invisibles) except
+    /// that this is used to control it from the user's side.
+    ShowInvisibles(bool),
+    /// Whether to capture the mouse
+    ///
+    /// Releasing the mouse lets the terminal select and copy text with it while the mouse wheel
+    /// no longer scrolls the text. This is similar to
+    /// [Pager::set_mouse_capture](crate::pager::Pager::set_mouse_capture) except that this is
+    /// used to control it from the user's side.
+    MouseCapture(bool),
+    /// `|`, prompts for a shell command and pipes the text into it
+    ///
+    /// The terminal is handed over to the command while it runs and the pager resumes once a key
+    /// is pressed afterwards. The text is piped as it was given to the pager, without any
+    /// formatting.
+    Pipe(PipeRange),
+    /// `Ctrl+Z`, suspends the process like any other job of the shell
+    ///
+    /// The terminal is restored to its original state before suspending and the pager is drawn
+    /// again once the process is resumed. This does nothing on platforms other than Unix or if
+    /// the input isn't read from the terminal.
+    Suspend,
+    /// `s`, prompts for the name of a file and saves the text to it
+    ///
+    /// The text is saved as it was given to the pager, without any formatting. If the file
+    /// already exists, the user is asked whether to overwrite it. See
+    /// [Pager::set_save_path_hook](crate::pager::Pager::set_save_path_hook) to check the path
+    /// before anything is written to it.
+    Save,
+    /// `:`, prompts for an ex-style command and runs it
+    ///
+    /// Commands like `:q` to quit, `:123` to go to line 123 and `:set nu` to show line numbers
+    /// are built in. The commands added with
+    /// [Pager::add_command](crate::pager::Pager::add_command) can be run by their names.
+    CommandPrompt,
+    /// Sent by `Up`, `Down` etc. when the cursor line is shown. Contains the index of the new
+    /// cursor line.
+    ///
+    /// See [Pager::set_cursor_line](crate::pager::Pager::set_cursor_line).
+    SelectLine(usize),
+    /// `Enter` in picker mode, quits the pager with the cursor line
+    ///
+    /// See [Pager::set_picker_mode](crate::pager::Pager::set_picker_mode).
+    PickLine,
+    /// Sent by `:n`, `:p` and `:b`. Displays the buffer at this index.
+    ///
+    /// See [Pager::add_buffer](crate::pager::Pager::add_buffer).
+    SwitchBuffer(usize),
+    /// `S`, splits the screen into two panes or goes back to a single one
+    ///
+    /// Both panes show the same text and are scrolled independently. This keeps one part of the
+    /// text, like an error, visible while scrolling through another part. See
+    /// [Pager::set_split_view](crate::pager::Pager::set_split_view).
+    SplitView(bool),
+    /// `Ctrl+W`, moves the focus to the other pane of the split view
+    ///
+    /// The keys for moving around the text only move the focused pane.
+    SwitchPane,
+    /// `z`, folds the region containing the line at this index or opens it if it is folded
+    ///
+    /// The line is the one whose number is typed before `z`, else the cursor line, else the line
+    /// at the top of the screen. A folded region only shows its first line, followed by the number
+    /// of lines hidden. Search matches in the hidden lines are skipped. See
+    /// [Pager::add_folds](crate::pager::Pager::add_folds).
+    ToggleFold(usize),
+    /// `Z`, folds all the outermost regions if true, else opens all the folded regions
+    FoldAll(bool),
+    /// `}`, goes to the nth section after the current line
+    ///
+    /// The current line is the cursor line if it is shown, else the line at the top of the
+    /// screen. See [Pager::add_sections](crate::pager::Pager::add_sections).
+    NextSection(usize),
+    /// `{`, goes to the nth section before the current line
+    PrevSection(usize),
+    /// `o`, shows the list of sections over the text to pick one to go to
+    SectionPicker,
+}
+
+/// Part of the text to be piped into a command. See [`InputEvent::Pipe`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PipeRange {
+    /// The entire text
+    All,
+    /// Only the lines that are at least partially displayed on the screen
+    Screen,
 }
 
 /// Classifies the input and returns the appropriate [`InputEvent`]
@@ -262,28 +369,87 @@ pub trait InputClassifier {
     fn classify_input(&self, ev: Event, ps: &PagerState) -> Option<InputEvent>;
 }
 
+/// A source of terminal events for the pager
+///
+/// By default minus reads the events from the terminal. Applications can supply their own source
+/// with [`Pager::set_input`](crate::Pager::set_input), for example to read the input of a remote
+/// terminal. This is implemented for [`mpsc::Receiver<Event>`] so that events can simply be sent
+/// through a channel.
+pub trait EventSource: Send + 'static {
+    /// Wait at most `timeout` for the next event
+    ///
+    /// Returns `None` if no event arrived in time.
+    ///
+    /// # Errors
+    /// Reading the event may fail. This stops the pager.
+    fn next_event(&mut self, timeout: Duration) -> io::Result<Option<Event>>;
+}
+
+/// Reads the events from the terminal
+pub(crate) struct TerminalEvents;
+
+impl EventSource for TerminalEvents {
+    fn next_event(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        if crossterm::event::poll(timeout)? {
+            crossterm::event::read().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl EventSource for mpsc::Receiver<Event> {
+    fn next_event(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        match self.recv_timeout(timeout) {
+            Ok(ev) => Ok(Some(ev)),
+            Err(mpsc::RecvTimeoutError::Timeout) => Ok(None),
+            // No more events will ever arrive, but wait anyway so that we don't spin
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                std::thread::sleep(timeout);
+                Ok(None)
+            }
+        }
+    }
+}
+
 /// Insert the default set of actions into the [`HashedEventRegister`]
 #[allow(clippy::too_many_lines)]
 pub fn generate_default_bindings<S>(map: &mut HashedEventRegister<S>)
 where
     S: std::hash::BuildHasher,
 {
-    map.add_key_events(&["q", "c-c"], |_, _| InputEvent::Exit);
+    map.add_key_events(&["q"], |_, _| InputEvent::Exit);
+    map.add_key_events(&["c-c"], |_, _| InputEvent::Interrupt);
 
+    // The cursor line always moves by a single line, unless a number is typed first
     map.add_key_events(&["up", "k"], |_, ps| {
-        let position = ps.prefix_num.parse::<usize>().unwrap_or(1);
-        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_sub(position))
+        let prefix = ps.prefix_num.parse::<usize>().ok();
+        ps.selected_line.map_or_else(
+            || {
+                let lines = prefix.unwrap_or(ps.arrow_scroll_lines);
+                InputEvent::UpdateUpperMark(ps.upper_mark.saturating_sub(lines))
+            },
+            |line| InputEvent::SelectLine(line.saturating_sub(prefix.unwrap_or(1))),
+        )
     });
     map.add_key_events(&["down", "j"], |_, ps| {
-        let position = ps.prefix_num.parse::<usize>().unwrap_or(1);
-        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(position))
+        let prefix = ps.prefix_num.parse::<usize>().ok();
+        ps.selected_line.map_or_else(
+            || {
+                let lines = prefix.unwrap_or(ps.arrow_scroll_lines);
+                InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(lines))
+            },
+            |line| InputEvent::SelectLine(line.saturating_add(prefix.unwrap_or(1))),
+        )
     });
     map.add_key_events(&["c-f"], |_, ps| {
         InputEvent::FollowOutput(!ps.follow_output)
     });
     map.add_key_events(&["enter"], |_, ps| {
-        if ps.message.is_some() {
+        if ps.message.is_some() || !ps.full_prompt.is_empty() {
             InputEvent::RestorePrompt
+        } else if ps.picker_mode {
+            InputEvent::PickLine
         } else {
             let position = ps.prefix_num.parse::<usize>().unwrap_or(1);
             InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(position))
@@ -319,15 +485,59 @@ where
             .unwrap_or(&(usize::MAX - 1));
         InputEvent::UpdateUpperMark(row_to_go)
     });
+    map.add_key_events(&["="], |_, _| InputEvent::ShowFullPrompt);
+    map.add_key_events(&["%"], |_, ps| {
+        let percent = ps.prefix_num.parse::<usize>().unwrap_or(0);
+        InputEvent::UpdateUpperMark(ps.row_at_percentage(percent))
+    });
     map.add_key_events(&["pageup"], |_, ps| {
-        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_sub(ps.rows - 1))
+        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_sub(ps.page_rows()))
     });
     map.add_key_events(&["pagedown", "space"], |_, ps| {
-        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(ps.rows - 1))
+        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(ps.page_rows()))
     });
     map.add_key_events(&["c-l"], |_, ps| {
         InputEvent::UpdateLineNumber(!ps.line_numbers)
     });
+    map.add_key_events(&["c-o"], |_, _| InputEvent::JumpBack);
+    map.add_key_events(&["tab"], |_, _| InputEvent::JumpForward);
+    map.add_key_events(&["s-i", "I"], |_, ps| {
+        InputEvent::ShowInvisibles(!ps.screen.show_invisibles)
+    });
+    map.add_key_events(&["s-m", "M"], |_, ps| {
+        InputEvent::MouseCapture(!ps.mouse_capture)
+    });
+    map.add_key_events(&["s"], |_, _| InputEvent::Save);
+    map.add_key_events(&["s-s", "S"], |_, ps| {
+        InputEvent::SplitView(ps.split.is_none())
+    });
+    map.add_key_events(&["c-w"], |_, _| InputEvent::SwitchPane);
+    map.add_key_events(&["z"], |_, ps| {
+        let line = ps.prefix_num.parse::<usize>().map_or_else(
+            |_| {
+                ps.selected_line.unwrap_or_else(|| {
+                    ps.lines_to_row_map
+                        .line_of_row(ps.upper_mark.saturating_add(ps.header_rows()))
+                })
+            },
+            |number| number.saturating_sub(1),
+        );
+        InputEvent::ToggleFold(line)
+    });
+    map.add_key_events(&["s-z", "Z"], |_, ps| {
+        InputEvent::FoldAll(ps.screen.folds.all_open())
+    });
+    map.add_key_events(&["}"], |_, ps| {
+        InputEvent::NextSection(ps.prefix_num.parse::<usize>().unwrap_or(1))
+    });
+    map.add_key_events(&["{"], |_, ps| {
+        InputEvent::PrevSection(ps.prefix_num.parse::<usize>().unwrap_or(1))
+    });
+    map.add_key_events(&["o"], |_, _| InputEvent::SectionPicker);
+    #[cfg(unix)]
+    map.add_key_events(&["c-z"], |_, _| InputEvent::Suspend);
+    map.add_key_events(&["|"], |_, _| InputEvent::Pipe(PipeRange::All));
+    map.add_key_events(&[":"], |_, _| InputEvent::CommandPrompt);
     #[cfg(feature = "search")]
     {
         map.add_key_events(&["/"], |_, _| InputEvent::Search(SearchMode::Forward));
@@ -357,13 +567,13 @@ where
     }
 
     map.add_mouse_events(&["scroll:up"], |_, ps| {
-        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_sub(5))
+        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_sub(ps.wheel_scroll_lines))
     });
     map.add_mouse_events(&["scroll:down"], |_, ps| {
-        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(5))
+        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(ps.wheel_scroll_lines))
     });
 
-    map.add_key_events(&["c-s-h", "c-h"], |_, ps| {
+    map.add_key_events(&["c-s-h", "c-h", "w"], |_, ps| {
         InputEvent::HorizontalScroll(!ps.screen.line_wrapping)
     });
     map.add_key_events(&["h", "left"], |_, ps| {
@@ -417,7 +627,10 @@ impl InputClassifier for DefaultInputClassifier {
                 modifiers: KeyModifiers::NONE,
                 ..
             }) if code == KeyCode::Up || code == KeyCode::Char('k') => {
-                let position = ps.prefix_num.parse::<usize>().unwrap_or(1);
+                let position = ps
+                    .prefix_num
+                    .parse::<usize>()
+                    .unwrap_or(ps.arrow_scroll_lines);
                 Some(InputEvent::UpdateUpperMark(
                     ps.upper_mark.saturating_sub(position),
                 ))
@@ -429,7 +642,10 @@ impl InputClassifier for DefaultInputClassifier {
                 modifiers: KeyModifiers::NONE,
                 ..
             }) if code == KeyCode::Down || code == KeyCode::Char('j') => {
-                let position = ps.prefix_num.parse::<usize>().unwrap_or(1);
+                let position = ps
+                    .prefix_num
+                    .parse::<usize>()
+                    .unwrap_or(ps.arrow_scroll_lines);
                 Some(InputEvent::UpdateUpperMark(
                     ps.upper_mark.saturating_add(position),
                 ))
@@ -492,11 +708,15 @@ impl InputClassifier for DefaultInputClassifier {
             Event::Mouse(MouseEvent {
                 kind: MouseEventKind::ScrollUp,
                 ..
-            }) => Some(InputEvent::UpdateUpperMark(ps.upper_mark.saturating_sub(5))),
+            }) => Some(InputEvent::UpdateUpperMark(
+                ps.upper_mark.saturating_sub(ps.wheel_scroll_lines),
+            )),
             Event::Mouse(MouseEvent {
                 kind: MouseEventKind::ScrollDown,
                 ..
-            }) => Some(InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(5))),
+            }) => Some(InputEvent::UpdateUpperMark(
+                ps.upper_mark.saturating_add(ps.wheel_scroll_lines),
+            )),
             // Go to top.
             Event::Key(KeyEvent {
                 code: KeyCode::Char('g'),
@@ -528,6 +748,9 @@ impl InputClassifier for DefaultInputClassifier {
                     .saturating_sub(1);
                 if position == 0 {
                     position = usize::MAX;
+                } else {
+                    // Keep the scroll-off margin above the row that is jumped to
+                    position = position.saturating_sub(ps.scroll_off_rows());
                 }
                 Some(InputEvent::UpdateUpperMark(position))
             }
@@ -538,14 +761,14 @@ impl InputClassifier for DefaultInputClassifier {
                 modifiers: KeyModifiers::NONE,
                 ..
             }) => Some(InputEvent::UpdateUpperMark(
-                ps.upper_mark.saturating_sub(ps.rows - 1),
+                ps.upper_mark.saturating_sub(ps.page_rows()),
             )),
             Event::Key(KeyEvent {
                 code: c,
                 modifiers: KeyModifiers::NONE,
                 ..
             }) if c == KeyCode::PageDown || c == KeyCode::Char(' ') => Some(
-                InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(ps.rows - 1)),
+                InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(ps.page_rows())),
             ),
 
             // Resize event from the terminal.
@@ -564,12 +787,12 @@ impl InputClassifier for DefaultInputClassifier {
                 code: KeyCode::Char('q'),
                 modifiers: KeyModifiers::NONE,
                 ..
-            })
-            | Event::Key(KeyEvent {
+            }) => Some(InputEvent::Exit),
+            Event::Key(KeyEvent {
                 code: KeyCode::Char('c'),
                 modifiers: KeyModifiers::CONTROL,
                 ..
-            }) => Some(InputEvent::Exit),
+            }) => Some(InputEvent::Interrupt),
 
             // Horizontal scrolling
             Event::Key(KeyEvent {
@@ -579,6 +802,11 @@ impl InputClassifier for DefaultInputClassifier {
             }) if modifiers == KeyModifiers::CONTROL.intersection(KeyModifiers::SHIFT) => {
                 Some(InputEvent::HorizontalScroll(!ps.screen.line_wrapping))
             }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('w'),
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => Some(InputEvent::HorizontalScroll(!ps.screen.line_wrapping)),
 
             Event::Key(KeyEvent {
                 code: KeyCode::Char('h'),
@@ -187,6 +187,73 @@ fn test_kb_nav() {
     }
 }
 
+#[test]
+fn test_percent_jump() {
+    let mut pager = PagerState::new().unwrap();
+    pager.screen.orig_text = (1..=40).map(|n| format!("{n}\n")).collect();
+    pager.screen.line_count = 40;
+    pager.format_lines();
+    let ev = Event::Key(KeyEvent {
+        code: KeyCode::Char('%'),
+        modifiers: KeyModifiers::NONE,
+        kind: crossterm::event::KeyEventKind::Press,
+        state: KeyEventState::NONE,
+    });
+
+    pager.prefix_num = "50".to_string();
+    assert_eq!(
+        Some(InputEvent::UpdateUpperMark(20)),
+        handle_input(ev.clone(), &pager)
+    );
+
+    // Without a number, go to the top
+    pager.prefix_num = String::new();
+    assert_eq!(
+        Some(InputEvent::UpdateUpperMark(0)),
+        handle_input(ev.clone(), &pager)
+    );
+
+    // Anything past 100% goes to the bottom
+    pager.prefix_num = "150".to_string();
+    assert_eq!(
+        Some(InputEvent::UpdateUpperMark(usize::MAX - 1)),
+        handle_input(ev, &pager)
+    );
+}
+
+#[test]
+fn test_page_overlap() {
+    let mut pager = PagerState::new().unwrap();
+    pager.rows = 11;
+    pager.upper_mark = 20;
+    pager.page_overlap = 3;
+    let key = |code| {
+        Event::Key(KeyEvent {
+            code,
+            modifiers: KeyModifiers::NONE,
+            kind: crossterm::event::KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+    };
+
+    // 10 writable rows, 3 of which are shown again
+    assert_eq!(
+        Some(InputEvent::UpdateUpperMark(27)),
+        handle_input(key(KeyCode::Char(' ')), &pager)
+    );
+    assert_eq!(
+        Some(InputEvent::UpdateUpperMark(13)),
+        handle_input(key(KeyCode::PageUp), &pager)
+    );
+
+    // The screen moves by at least one row
+    pager.page_overlap = 50;
+    assert_eq!(
+        Some(InputEvent::UpdateUpperMark(21)),
+        handle_input(key(KeyCode::PageDown), &pager)
+    );
+}
+
 #[test]
 fn test_restore_prompt() {
     let mut pager = PagerState::new().unwrap();
@@ -205,6 +272,33 @@ fn test_restore_prompt() {
             pager.input_classifier.classify_input(ev, &pager)
         );
     }
+    {
+        let ev = Event::Key(KeyEvent {
+            code: KeyCode::Char('='),
+            modifiers: KeyModifiers::NONE,
+            kind: crossterm::event::KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        });
+        assert_eq!(
+            Some(InputEvent::ShowFullPrompt),
+            pager.input_classifier.classify_input(ev, &pager)
+        );
+    }
+    {
+        // Enter also hides the full prompt
+        pager.message = None;
+        pager.full_prompt = vec!["Full prompt".to_string()];
+        let ev = Event::Key(KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            kind: crossterm::event::KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        });
+        assert_eq!(
+            Some(InputEvent::RestorePrompt),
+            pager.input_classifier.classify_input(ev, &pager)
+        );
+    }
 }
 
 #[test]
@@ -236,11 +330,46 @@ fn test_mouse_nav() {
         });
         assert_eq!(
             Some(InputEvent::UpdateUpperMark(pager.upper_mark - 5)),
+            handle_input(ev.clone(), &pager)
+        );
+
+        pager.wheel_scroll_lines = 3;
+        assert_eq!(
+            Some(InputEvent::UpdateUpperMark(pager.upper_mark - 3)),
             handle_input(ev, &pager)
         );
     }
 }
 
+#[test]
+fn test_arrow_scroll_amount() {
+    let mut pager = PagerState::new().unwrap();
+    pager.upper_mark = 12;
+    pager.arrow_scroll_lines = 3;
+    let ev = Event::Key(KeyEvent {
+        code: KeyCode::Down,
+        modifiers: KeyModifiers::NONE,
+        kind: crossterm::event::KeyEventKind::Press,
+        state: KeyEventState::NONE,
+    });
+    assert_eq!(
+        Some(InputEvent::UpdateUpperMark(15)),
+        handle_input(ev.clone(), &pager)
+    );
+
+    // A typed number takes precedence
+    pager.prefix_num = "2".to_string();
+    assert_eq!(
+        Some(InputEvent::UpdateUpperMark(14)),
+        handle_input(ev.clone(), &pager)
+    );
+
+    // The cursor line moves by a single line
+    pager.prefix_num.clear();
+    pager.selected_line = Some(4);
+    assert_eq!(Some(InputEvent::SelectLine(5)), handle_input(ev, &pager));
+}
+
 #[test]
 fn test_saturation() {
     let mut pager = PagerState::new().unwrap();
@@ -313,6 +442,19 @@ fn test_misc_events() {
         );
     }
 
+    {
+        let ev = Event::Key(KeyEvent {
+            code: KeyCode::Char('w'),
+            modifiers: KeyModifiers::NONE,
+            kind: crossterm::event::KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        });
+        assert_eq!(
+            Some(InputEvent::HorizontalScroll(!pager.screen.line_wrapping)),
+            handle_input(ev, &pager)
+        );
+    }
+
     {
         let ev = Event::Key(KeyEvent {
             code: KeyCode::Char('q'),
@@ -330,7 +472,31 @@ fn test_misc_events() {
             kind: crossterm::event::KeyEventKind::Press,
             state: KeyEventState::NONE,
         });
-        assert_eq!(Some(InputEvent::Exit), handle_input(ev, &pager));
+        assert_eq!(Some(InputEvent::Interrupt), handle_input(ev, &pager));
+    }
+
+    {
+        let ev = Event::Key(KeyEvent {
+            code: KeyCode::Char('M'),
+            modifiers: KeyModifiers::SHIFT,
+            kind: crossterm::event::KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        });
+        assert_eq!(
+            Some(InputEvent::MouseCapture(!pager.mouse_capture)),
+            handle_input(ev, &pager)
+        );
+    }
+
+    #[cfg(unix)]
+    {
+        let ev = Event::Key(KeyEvent {
+            code: KeyCode::Char('z'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: crossterm::event::KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        });
+        assert_eq!(Some(InputEvent::Suspend), handle_input(ev, &pager));
     }
 
     {
@@ -35,22 +35,21 @@
 //! use minus::{dynamic_paging, MinusError, Pager};
 //! use std::{
 //!     fmt::Write,
-//!     thread::{spawn, sleep},
+//!     thread::sleep,
 //!     time::Duration
 //! };
 //!
 //! fn main() -> Result<(), MinusError> {
 //!     // Initialize the pager
 //!     let mut pager = Pager::new();
-//!     // Run the pager in a separate thread
-//!     let pager2 = pager.clone();
-//!     let pager_thread = spawn(move || dynamic_paging(pager2));
+//!     // The pager runs in a separate thread
+//!     let pager_handle = dynamic_paging(pager.clone());
 //!
 //!     for i in 0..=100_u32 {
 //!         writeln!(pager, "{}", i);
 //!         sleep(Duration::from_millis(100));
 //!     }
-//!     pager_thread.join().unwrap()?;
+//!     pager_handle.join()?;
 //!     Ok(())
 //! }
 //! ```
@@ -76,10 +75,10 @@
 //!         }
 //!         Result::<_, MinusError>::Ok(())
 //!     };
-//!     // spawn_blocking(dynamic_paging(...)) creates a separate thread managed by the tokio
-//!     // runtime and runs the async_paging inside it
-//!     let pager = pager.clone();
-//!     let (res1, res2) = join!(spawn_blocking(move || dynamic_paging(pager)), increment);
+//!     // Waiting for the pager to quit blocks, so it is done in a separate thread managed by the
+//!     // tokio runtime
+//!     let pager_handle = dynamic_paging(pager.clone());
+//!     let (res1, res2) = join!(spawn_blocking(move || pager_handle.join()), increment);
 //!     // .unwrap() unwraps any error while creating the tokio task
 //!     //  The ? mark unpacks any error that might have occurred while the
 //!     // pager is running
@@ -123,7 +122,7 @@
 //! | Ctrl+C/q            | Quit the pager                                                               |
 //! | \[n\] Arrow Up/k    | Scroll up by n number of line(s). If n is omitted, scroll up by 1 line       |
 //! | \[n\] Arrow Down/j  | Scroll down by n number of line(s). If n is omitted, scroll down by 1 line   |
-//! | Ctrl+h              | Turn off line wrapping and allow horizontal scrolling                        |
+//! | Ctrl+h/w            | Toggle line wrapping. Horizontal scrolling is allowed while it is off        |
 //! | \[n\] Arrow left/h  | Scroll left by n number of line(s). If n is omitted, scroll up by 1 line     |
 //! | \[n\] Arrow right/l | Scroll right by n number of line(s). If n is omitted, scroll down by 1 line  |
 //! | Page Up             | Scroll up by entire page                                                     |
@@ -134,10 +133,25 @@
 //! | Ctrl+D/d            | Scroll down by half a screen                                                 |
 //! | g                   | Go to the very top of the output                                             |
 //! | \[n\] G             | Go to the very bottom of the output. If n is present, goes to that line      |
-//! | Mouse scroll Up     | Scroll up by 5 lines                                                         |
-//! | Mouse scroll Down   | Scroll down by 5 lines                                                       |
+//! | \[n\] %             | Go to n percent of the way through the output. Goes to the top without n     |
+//! | =                   | Show the full prompt or message if it is cut off. Enter hides it again       |
+//! | Mouse scroll Up     | Scroll up by 5 lines, unless changed with [`Pager::set_scroll_amounts`]      |
+//! | Mouse scroll Down   | Scroll down by 5 lines, unless changed with [`Pager::set_scroll_amounts`]    |
 //! | Ctrl+L              | Toggle line numbers if not forced enabled/disabled                           |
 //! | Ctrl+f              | Toggle [follow-mode]                                                         |
+//! | Ctrl+o              | Go back to the position before the last large jump                           |
+//! | Tab                 | Go forward to the position left by Ctrl+o                                    |
+//! | I                   | Toggle showing invisible characters like tabs and trailing spaces            |
+//! | M                   | Toggle capturing the mouse. Lets the terminal select text while it is off    |
+//! | Ctrl+Z              | Suspend the process to the background. Only available on Unix                |
+//! | s                   | Prompt for a file name and save the text to it                               |
+//! | \[n\] z             | Fold the region containing line n, else the cursor line. Opens a fold        |
+//! | Z                   | Fold all the regions, or open all the folds                                  |
+//! | \[n\] }             | Go to the next section, or n sections ahead                                  |
+//! | \[n\] {             | Go to the previous section, or n sections back                               |
+//! | o                   | List the sections to pick one to go to                                       |
+//! | \|                  | Prompt for a shell command and pipe the text into it                         |
+//! | :                   | Prompt for a command like `:q`, `:123` to go to a line or `:set nu`          |
 //! | /                   | Start forward search                                                         |
 //! | ?                   | Start backward search                                                        |
 //! | Esc                 | Cancel search input                                                          |
@@ -149,18 +163,23 @@
 //!
 //! ## Key Bindings Available at Search Prompt
 //!
+//! These are also available at the other prompts, like the one for `:`.
+//!
 //! | Key Bindings      | Description                                         |
 //! |-------------------|-----------------------------------------------------|
 //! | Esc               | Cancel the search                                   |
 //! | Enter             | Confirm the search query                            |
 //! | Backspace         | Remove the character before the cursor              |
 //! | Delete            | Remove the character under the cursor               |
+//! | Ctrl+Backspace    | Remove the word before the cursor. Also Ctrl+W      |
+//! | Ctrl+Delete       | Remove the word after the cursor. Also Alt+D        |
 //! | Arrow Left        | Move cursor towards left                            |
 //! | Arrow right       | Move cursor towards right                           |
 //! | Ctrl+Arrow left   | Move cursor towards left word by word               |
 //! | Ctrl+Arrow right  | Move cursor towards right word by word              |
 //! | Home              | Move cursor at the beginning pf search query        |
 //! | End               | Move cursor at the end pf search query              |
+//! | Paste             | Insert the pasted text. Line breaks become spaces   |
 //!
 //! Currently these cannot be changed by applications but this may be supported in the future.
 //!
@@ -170,33 +189,64 @@
 //! [follow-mode]: struct.Pager.html#method.follow_output
 //! [paging]: https://en.wikipedia.org/wiki/Terminal_pager
 //! [README]: https://github.com/arijit79/minus#motivation
+mod buffers;
+mod color;
+#[cfg(feature = "compression")]
+mod compression;
+mod diff;
 #[cfg(feature = "dynamic_output")]
 mod dynamic_pager;
 pub mod error;
 pub mod input;
 #[path = "core/mod.rs"]
 mod minus_core;
+#[cfg(feature = "mmap")]
+mod mmap;
 mod pager;
 pub mod screen;
 #[cfg(feature = "search")]
 #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
 pub mod search;
+mod sources;
 pub mod state;
 #[cfg(feature = "static_output")]
 mod static_pager;
+mod table;
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
+mod theme;
+#[cfg(feature = "watch")]
+mod watch;
 
 #[cfg(feature = "dynamic_output")]
-pub use dynamic_pager::dynamic_paging;
+pub use dynamic_pager::{dynamic_paging, page_stdin, PagerHandle};
 #[cfg(feature = "static_output")]
 pub use static_pager::page_all;
 
+pub use color::ColorSupport;
 pub use minus_core::RunMode;
 #[cfg(feature = "search")]
 pub use search::SearchMode;
 
+/// The version of `encoding_rs` used by minus, for picking the
+/// [`Encoding`](encoding_rs::Encoding) of the text
+#[cfg(feature = "encoding")]
+#[cfg_attr(docsrs, doc(cfg(feature = "encoding")))]
+pub use encoding_rs;
 pub use error::MinusError;
+#[cfg(feature = "tokio")]
+pub use pager::AsyncWriter;
 pub use pager::Pager;
+pub use screen::LineProvider;
+pub use screen::Sign;
+pub use screen::StyledLine;
 pub use state::PagerState;
+pub use table::{Alignment, Cell};
+pub use theme::Theme;
+#[cfg(feature = "watch")]
+#[cfg_attr(docsrs, doc(cfg(feature = "watch")))]
+pub use watch::FileWatcher;
 
 /// A convenient type for `Vec<Box<dyn FnMut() + Send + Sync + 'static>>`
 pub type ExitCallbacks = Vec<Box<dyn FnMut() + Send + Sync + 'static>>;
@@ -223,6 +273,151 @@ pub enum ExitStrategy {
     PagerQuit,
 }
 
+/// Behaviour that happens when the user presses `Ctrl+C`
+///
+/// This can be set with [`Pager::set_interrupt_strategy`] independently of what `q` does.
+#[derive(Default)]
+#[non_exhaustive]
+pub enum InterruptStrategy {
+    /// Quit the pager just like `q` does, following the [`ExitStrategy`]
+    ///
+    /// **This is the default strategy.**
+    #[default]
+    Exit,
+    /// Quit the pager only, whatever the [`ExitStrategy`] is
+    PagerQuit,
+    /// Kill the entire application immediately, whatever the [`ExitStrategy`] is
+    ProcessQuit,
+    /// Keep the pager running and call the function instead
+    ///
+    /// This lets applications use `Ctrl+C` for something like cancelling the work that produces
+    /// the data while the user keeps reading it.
+    Forward(Box<dyn FnMut() + Send + Sync + 'static>),
+}
+
+impl std::fmt::Debug for InterruptStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exit => write!(f, "Exit"),
+            Self::PagerQuit => write!(f, "PagerQuit"),
+            Self::ProcessQuit => write!(f, "ProcessQuit"),
+            Self::Forward(_) => write!(f, "Forward"),
+        }
+    }
+}
+
+/// Why the pager quit
+///
+/// This is returned once the pager has quit so that applications can tell whether the user
+/// aborted the pager or everything was displayed. If the pager quits because of an error, the
+/// error is returned instead.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum ExitReason {
+    /// The user quit the pager, like by pressing `q`
+    UserQuit,
+    /// The user interrupted the pager by pressing `Ctrl+C`
+    Interrupted,
+    /// The application stopped the pager with [`PagerHandle::stop`]
+    Stopped,
+    /// All the text fit on one screen so it was written to the terminal without starting the
+    /// pager
+    ///
+    /// See [`Pager::set_run_no_overflow`] and [`Pager::set_quit_if_one_screen`].
+    FitsOneScreen,
+    /// The text was written straight to the output without starting the pager since a full
+    /// screen pager cannot work on it
+    ///
+    /// This happens if the output isn't a terminal or when running in a CI or a terminal with
+    /// `TERM=dumb`. See [`Pager::set_passthrough_on_pipe`].
+    Passthrough,
+    /// The user picked the line at this index by pressing `Enter` in picker mode
+    ///
+    /// Lines are counted from 0. If lines were discarded because of
+    /// [`Pager::set_max_lines`], they are counted from the oldest line that is still kept.
+    /// See [`Pager::set_picker_mode`].
+    Picked(usize),
+    /// The user moved down past the end of the data
+    ///
+    /// See [`Pager::set_quit_at_eof`].
+    EndOfData,
+}
+
+/// Where the screen is placed in the text when the pager starts
+///
+/// See [`Pager::set_initial_position`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum Position {
+    /// Show the end of the text, like `less +G`
+    Bottom,
+    /// Show the text starting from the line at this index, counted from 0
+    ///
+    /// If there are fewer lines, the end of the text is shown.
+    Line(usize),
+    /// Show the text starting from the first line that matches the regex, like `less +/pattern`
+    ///
+    /// The matches are highlighted as if the user had searched for the regex, so that the user
+    /// can move through them with `n` and `p`. The screen stays at the top if there are no
+    /// matches.
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    FirstMatch(String),
+}
+
+/// Different modes in which the prompt line can be
+///
+/// Each of these can be given a different style using
+/// [`Pager::set_prompt_style`](crate::Pager::set_prompt_style) so that users can always tell
+/// whether their keystrokes are being used for navigation or typed as text.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum PromptMode {
+    /// The prompt set by the application is being displayed.
+    ///
+    /// By default, it is displayed in dimmed white text over black background.
+    Normal,
+    /// A message sent by the application is being displayed.
+    ///
+    /// By default, it is displayed in bold black text over red background. This is the style of
+    /// [error](MessageLevel::Error) messages, the other levels have their own styles in the
+    /// [`Theme`].
+    Message,
+    /// The user is typing a search query.
+    ///
+    /// By default, it is displayed in black text over yellow background.
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    Search,
+    /// The user is typing text asked for by the pager, like the name of the file to save the
+    /// text to.
+    ///
+    /// By default, it is displayed in black text over cyan background.
+    Input,
+}
+
+/// How important a message on the prompt line is
+///
+/// Messages of each level are displayed in a different style so that users can tell errors apart
+/// from other messages at a glance. See [`Pager::send_message_with`](crate::Pager::send_message_with).
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum MessageLevel {
+    /// Something the user may want to know, like that the text was saved.
+    ///
+    /// By default, it is displayed in black text over green background.
+    Info,
+    /// Something that may need the user's attention.
+    ///
+    /// By default, it is displayed in black text over yellow background.
+    Warning,
+    /// Something went wrong. Messages sent with [`Pager::send_message`](crate::Pager::send_message)
+    /// are errors.
+    ///
+    /// By default, it is displayed in bold black text over red background.
+    Error,
+}
+
 /// Enum indicating whether to display the line numbers or not.
 ///
 /// Note that displaying line numbers may be less performant than not doing it.
@@ -232,6 +427,7 @@ pub enum ExitStrategy {
 /// This implements [`Not`](std::ops::Not) to allow turning on/off line numbers
 /// when they where not locked in by the binary displaying the text.
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
 pub enum LineNumbers {
     /// Enable line numbers permanently, cannot be turned off by user.
     AlwaysOn,
@@ -243,6 +439,18 @@ pub enum LineNumbers {
     Disabled,
     /// Disable line numbers permanently, cannot be turned on by user.
     AlwaysOff,
+    /// Display the distance of each line from the line at the top of the screen.
+    ///
+    /// This is useful along with the numeric prefixes of keys like `j` and `k` to quickly
+    /// jump to a line on the screen. Like [`LineNumbers::AlwaysOn`], this cannot be turned
+    /// off by user.
+    ///
+    /// Note that the all the text needs to be reformatted whenever the line at the top of the
+    /// screen changes, hence this is less performant than displaying normal line numbers.
+    Relative,
+    /// Same as [`LineNumbers::Relative`] except that the line at the top of the screen shows its
+    /// actual line number.
+    Hybrid,
 }
 
 impl LineNumbers {
@@ -257,10 +465,86 @@ impl LineNumbers {
     }
 
     const fn is_on(self) -> bool {
-        matches!(self, Self::Enabled | Self::AlwaysOn)
+        matches!(
+            self,
+            Self::Enabled | Self::AlwaysOn | Self::Relative | Self::Hybrid
+        )
+    }
+
+    /// Returns `true` if the displayed numbers depend on the line at the top of the screen
+    const fn is_relative(self) -> bool {
+        matches!(self, Self::Relative | Self::Hybrid)
+    }
+
+    /// Returns the number to be displayed for the line at index `idx` when the line at index
+    /// `anchor` is at the top of the screen and the first line is numbered `start`
+    const fn number_for(self, idx: usize, anchor: usize, start: usize) -> usize {
+        match self {
+            Self::Relative => idx.abs_diff(anchor),
+            Self::Hybrid if idx != anchor => idx.abs_diff(anchor),
+            _ => idx + start,
+        }
     }
 }
 
+/// What to display in the line number gutter for the rows of a line that has been wrapped
+///
+/// Line numbers always refer to the lines of the text and not the rows they are wrapped into.
+/// Only the first row of a line shows its line number while the rest of the rows show the
+/// configured continuation.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum LineNumberContinuation {
+    /// Leave the gutter blank.
+    ///
+    /// **This is the default.**
+    Blank,
+    /// Display a `↪` marker in the gutter.
+    Arrow,
+    /// Repeat the line number of the line in dimmed text.
+    RepeatDimmed,
+}
+
+/// How to display the control characters present in the text
+///
+/// Passing control characters to the terminal as is can corrupt the display, for example when
+/// paging binary data. The other options display them as visible escapes instead: ASCII control
+/// characters are displayed in caret notation like `^X` and other control characters like
+/// `<U+009B>`. Newlines and tabs are never escaped.
+///
+/// Unless all control characters are escaped, the backspace sequences used by `man` pages for
+/// bold (`c\bc`) and underlined (`_\bc`) text are displayed as bold and underlined text.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum ControlChars {
+    /// Pass control characters to the terminal as is.
+    ///
+    /// **This is the default.**
+    Raw,
+    /// Escape all control characters except the ANSI escape sequences that set colors and text
+    /// styles.
+    ///
+    /// This is similar to `less -R`.
+    EscapeExceptColors,
+    /// Escape all control characters.
+    Escape,
+}
+
+/// A rectangular area of the terminal in which the pager is drawn
+///
+/// See [`Pager::set_viewport`] for more info.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Viewport {
+    /// Column of the terminal where the left edge of the viewport is
+    pub column: u16,
+    /// Row of the terminal where the top edge of the viewport is
+    pub row: u16,
+    /// Number of columns in the viewport
+    pub width: u16,
+    /// Number of rows in the viewport, including the prompt row
+    pub height: u16,
+}
+
 impl std::ops::Not for LineNumbers {
     type Output = Self;
 
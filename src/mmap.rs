@@ -0,0 +1,218 @@
+//! Provides paging of memory mapped files
+//!
+//! The file is never read into memory as a whole. Instead its pages are loaded by the OS as minus
+//! accesses them through the [`LineProvider`] implemented by [`MmapLines`].
+
+use std::{borrow::Cow, fs::File, io, ops::Range, path::Path};
+
+#[cfg(feature = "encoding")]
+use encoding_rs::Encoding;
+use memmap2::Mmap;
+use once_cell::sync::OnceCell;
+
+use crate::screen::LineProvider;
+
+/// A memory mapped file which serves its lines on demand
+pub struct MmapLines {
+    /// The mapped contents of the file. This is [`None`] for empty files as they can't be mapped.
+    map: Option<Mmap>,
+    /// Byte offsets at which each line starts
+    ///
+    /// This is only built when the lines are first accessed as this requires going through the
+    /// whole file once.
+    index: OnceCell<Vec<usize>>,
+    /// Encoding the lines are decoded from. Lines are converted lossily from UTF-8 if this is
+    /// [`None`].
+    #[cfg(feature = "encoding")]
+    encoding: Option<&'static Encoding>,
+}
+
+/// Number of bytes from the start of the file that are looked at to detect its encoding
+#[cfg(feature = "encoding")]
+const DETECTION_SAMPLE: usize = 64 * 1024;
+
+impl MmapLines {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let map = if file.metadata()?.len() == 0 {
+            None
+        } else {
+            // SAFETY: The mapping is only ever read from, but it is only sound as long as no
+            // other process changes the file while it is mapped. If the file gets truncated, the
+            // OS raises SIGBUS as soon as a page past its new end is accessed, which kills the
+            // process. This is documented on `Pager::from_file`, which points to reading the
+            // file instead when it may change.
+            Some(unsafe { Mmap::map(&file)? })
+        };
+        Ok(Self {
+            map,
+            index: OnceCell::new(),
+            #[cfg(feature = "encoding")]
+            encoding: None,
+        })
+    }
+
+    /// Decode the lines from `encoding` instead of UTF-8
+    ///
+    /// Lines are split at `\n` bytes, so `encoding` must be
+    /// [ASCII compatible](Encoding::is_ascii_compatible).
+    #[cfg(feature = "encoding")]
+    pub fn with_encoding(mut self, encoding: &'static Encoding) -> Self {
+        debug_assert!(encoding.is_ascii_compatible());
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Guess the encoding of the file
+    ///
+    /// A byte order mark decides the encoding if there is one. Otherwise it is guessed from the
+    /// start of the file.
+    #[cfg(feature = "encoding")]
+    pub fn detect_encoding(&self) -> &'static Encoding {
+        let bytes = self.bytes();
+        if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+            return encoding;
+        }
+        let sample = &bytes[..bytes.len().min(DETECTION_SAMPLE)];
+        let mut detector = chardetng::EncodingDetector::new();
+        detector.feed(sample, sample.len() == bytes.len());
+        detector.guess(None, true)
+    }
+
+    /// Decode the whole file from `encoding` at once
+    #[cfg(feature = "encoding")]
+    pub fn decode_all(&self, encoding: &'static Encoding) -> String {
+        encoding
+            .decode_with_bom_removal(self.bytes())
+            .0
+            .into_owned()
+    }
+
+    /// Decode a single line, where `first` tells whether it is the first line of the file
+    #[cfg_attr(
+        not(feature = "encoding"),
+        allow(unused_variables, clippy::unused_self)
+    )]
+    fn decode<'a>(&self, line: &'a [u8], first: bool) -> Cow<'a, str> {
+        #[cfg(feature = "encoding")]
+        if let Some(encoding) = self.encoding {
+            // Only the start of the file can have a byte order mark
+            return if first {
+                encoding.decode_with_bom_removal(line).0
+            } else {
+                encoding.decode_without_bom_handling(line).0
+            };
+        }
+        String::from_utf8_lossy(line)
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self.map.as_deref().unwrap_or_default()
+    }
+
+    fn index(&self) -> &[usize] {
+        self.index.get_or_init(|| {
+            let bytes = self.bytes();
+            let mut index = Vec::new();
+            if !bytes.is_empty() {
+                index.push(0);
+            }
+            // A terminating newline at the end of the file does not start a new line
+            index.extend(
+                memchr::memchr_iter(b'\n', bytes)
+                    .map(|pos| pos + 1)
+                    .filter(|&start| start < bytes.len()),
+            );
+            index
+        })
+    }
+}
+
+impl LineProvider for MmapLines {
+    fn len(&self) -> usize {
+        self.index().len()
+    }
+
+    fn lines(&self, range: Range<usize>) -> Vec<String> {
+        let bytes = self.bytes();
+        let index = self.index();
+        let end = range.end.min(index.len());
+        let start = range.start.min(end);
+
+        index[start..end]
+            .iter()
+            .enumerate()
+            .map(|(i, &line_start)| {
+                let line_end = index.get(start + i + 1).map_or(bytes.len(), |&next| next);
+                let line = &bytes[line_start..line_end];
+                let line = line.strip_suffix(b"\n").unwrap_or(line);
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                self.decode(line, start + i == 0).into_owned()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MmapLines;
+    use crate::LineProvider;
+    use std::io::Write;
+
+    fn mapped(contents: &[u8]) -> MmapLines {
+        let path = std::env::temp_dir().join(format!(
+            "minus-mmap-test-{}-{}",
+            std::process::id(),
+            contents.len()
+        ));
+        std::fs::File::create(&path)
+            .unwrap()
+            .write_all(contents)
+            .unwrap();
+        let lines = MmapLines::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        lines
+    }
+
+    #[test]
+    fn index_lines() {
+        let lines = mapped(b"first\r\nsecond\n\nfourth\n");
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines.lines(0..2), vec!["first", "second"]);
+        assert_eq!(lines.lines(2..10), vec!["", "fourth"]);
+    }
+
+    #[test]
+    fn unterminated_last_line() {
+        let lines = mapped(b"a\nbc");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines.lines(1..2), vec!["bc"]);
+    }
+
+    #[test]
+    #[cfg(feature = "encoding")]
+    fn decode_legacy_encoding() {
+        // "café\nnaïve" in Latin-1
+        let lines = mapped(b"caf\xE9\nna\xEFve\n");
+        let encoding = lines.detect_encoding();
+        assert_eq!(encoding, encoding_rs::WINDOWS_1252);
+        let lines = lines.with_encoding(encoding);
+        assert_eq!(lines.lines(0..2), vec!["café", "naïve"]);
+    }
+
+    #[test]
+    #[cfg(feature = "encoding")]
+    fn detect_bom() {
+        let lines = mapped(b"\xFF\xFEh\x00i\x00\n\x00");
+        let encoding = lines.detect_encoding();
+        assert_eq!(encoding, encoding_rs::UTF_16LE);
+        assert_eq!(lines.decode_all(encoding), "hi\n");
+    }
+
+    #[test]
+    fn empty_file() {
+        let lines = mapped(b"");
+        assert!(lines.is_empty());
+        assert!(lines.lines(0..5).is_empty());
+    }
+}
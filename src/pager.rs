@@ -1,8 +1,27 @@
 //! Proivdes the [Pager] type
 
-use crate::{error::MinusError, input, minus_core::commands::Command, ExitStrategy, LineNumbers};
-use crossbeam_channel::{Receiver, Sender};
-use std::fmt;
+use crate::{
+    diff::Diff,
+    error::MinusError,
+    input::{self, InputEvent},
+    minus_core::{commands::Command, utils::take_complete_text},
+    screen::{LineProvider, Sign, StyledLine},
+    Cell, ColorSupport, ControlChars, ExitReason, ExitStrategy, InterruptStrategy,
+    LineNumberContinuation, LineNumbers, MessageLevel, PagerState, PromptMode, RunMode, Theme,
+    Viewport,
+};
+use crossbeam_channel::{Receiver, SendError, Sender};
+use crossterm::{event::KeyEvent, style::ContentStyle};
+use parking_lot::Mutex;
+use std::{
+    fmt,
+    io::{self, Read},
+    ops::{Bound, Range, RangeBounds},
+    path::PathBuf,
+    sync::Arc,
+    thread::JoinHandle,
+    time::Duration,
+};
 
 #[cfg(feature = "search")]
 use crate::search::SearchOpts;
@@ -34,10 +53,27 @@ use crate::search::SearchOpts;
 /// writeln!(pager, "Hello {WHO}").unwrap();
 /// // which is also equivalent to writing this
 /// pager.push_str(format!("Hello {WHO}\n")).unwrap();
-#[derive(Clone)]
+/// ```
+///
+/// It also implements [std::io::Write] so that bytes, for example the output of a subprocess,
+/// can be copied into it directly. See [`Pager::set_lossy_utf8`] for how bytes which aren't valid
+/// UTF-8 are handled.
 pub struct Pager {
     pub(crate) tx: Sender<Command>,
     pub(crate) rx: Receiver<Command>,
+    /// Name of the buffer that the commands of this pager go to, if it was made by
+    /// [`add_buffer`](Pager::add_buffer)
+    buffer: Option<String>,
+    /// Whether the pager is running and in which mode
+    pub(crate) running: Arc<Mutex<RunMode>>,
+    /// Bytes written through [`io::Write`] or [`push_bytes`](Pager::push_bytes) which form an
+    /// incomplete UTF-8 character. This is shared by all the clones of the pager and stays locked
+    /// until the completed text is sent, so that the text is appended in the order it was written.
+    utf8_pending: Arc<Mutex<Vec<u8>>>,
+    lossy_utf8: bool,
+    /// Shared by all the clones of the pager and the pagers of its buffers, so that minus can
+    /// tell whether any of them are left to send more commands
+    clones: Arc<()>,
 }
 
 impl Pager {
@@ -45,19 +81,2565 @@ impl Pager {
     ///
     /// # Example
     /// ```
-    /// let pager = minus::Pager::new();
+    /// let pager = minus::Pager::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        Self {
+            tx,
+            rx,
+            running: Arc::new(Mutex::new(RunMode::Uninitialized)),
+            buffer: None,
+            utf8_pending: Arc::new(Mutex::new(Vec::new())),
+            lossy_utf8: true,
+            clones: Arc::new(()),
+        }
+    }
+
+    /// Send `command` to the pager, or to the buffer of this pager if it has one
+    fn send(&self, command: Command) -> Result<(), SendError<Command>> {
+        match &self.buffer {
+            Some(name) => self
+                .tx
+                .send(Command::InBuffer(name.clone(), Box::new(command))),
+            None => self.tx.send(command),
+        }
+    }
+
+    /// Returns true if any other clone of this pager or pager of one of its buffers is left
+    ///
+    /// Commands can still be sent to the pager as long as there are some.
+    #[cfg(feature = "dynamic_output")]
+    pub(crate) fn has_other_clones(&self) -> bool {
+        Arc::strong_count(&self.clones) > 1
+    }
+
+    /// Get a writer which implements [`tokio::io::AsyncWrite`] for appending data to the pager
+    ///
+    /// This makes it possible to use [`tokio::io::copy`] for copying data from an async reader,
+    /// like the output of a child process, into the pager. Bytes are handled just
+    /// like they are by the [`io::Write`] implementation of `Pager`.
+    ///
+    /// # Example
+    /// ```
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let pager = minus::Pager::new();
+    /// let mut writer = pager.async_writer();
+    /// let mut reader: &[u8] = b"Hello\nWorld\n";
+    /// tokio::io::copy(&mut reader, &mut writer).await.unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    #[must_use]
+    pub fn async_writer(&self) -> AsyncWriter {
+        AsyncWriter(self.clone())
+    }
+
+    /// Set how bytes written through [`io::Write`] which aren't valid UTF-8 are handled
+    ///
+    /// If `lossy` is `true`, which is the default, they are replaced with
+    /// `U+FFFD REPLACEMENT CHARACTER`. Otherwise the write fails with an
+    /// [`ErrorKind::InvalidData`](io::ErrorKind::InvalidData) error and none of the bytes of that
+    /// write are appended.
+    ///
+    /// This only applies to this `Pager` and not to any of its clones.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    ///
+    /// let mut pager = minus::Pager::new();
+    /// pager.set_lossy_utf8(false);
+    /// assert!(pager.write_all(b"invalid \xFF").is_err());
+    /// ```
+    pub const fn set_lossy_utf8(&mut self, lossy: bool) {
+        self.lossy_utf8 = lossy;
+    }
+
+    /// Initialize a new pager which pages the file at `path`
+    ///
+    /// The file is memory mapped instead of being read into memory, so this is suitable for paging
+    /// files that are gigabytes in size. Only the lines that are displayed or searched through
+    /// are read from it. The lines are served through a [`LineProvider`], so the same limitations
+    /// as with [`set_line_provider`](Pager::set_line_provider) apply.
+    ///
+    /// The line index of the file is built when the pager first accesses its lines, which
+    /// requires going through the whole file once. Lines that aren't valid UTF-8 are converted
+    /// lossily.
+    ///
+    /// With the `compression` feature, files compressed with gzip or zstd are recognized by their
+    /// first bytes and decompressed on the fly. The decompressed text is appended in the
+    /// background like with [`read_from`](Pager::read_from) and [`finish_data`](Pager::finish_data)
+    /// is called once all of it has been read. If decompressing fails midway, the pager shows an
+    /// error message.
+    ///
+    /// The file must not be changed by other processes while it is being paged. If it gets
+    /// truncated, accessing the lines past its new end makes the OS raise `SIGBUS`, which
+    /// terminates the whole program. Files that may change while they are paged, like logs that
+    /// are rotated, should be read with [`read_from`](Pager::read_from) instead, which copies
+    /// their text into memory.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::ReadInput)`](MinusError::ReadInput) if the
+    /// file could not be opened or mapped
+    ///
+    /// # Example
+    /// ```no_run
+    /// let pager = minus::Pager::from_file("/usr/share/dict/words").unwrap();
+    ///
+    /// // A file that may be truncated while it is paged is read instead
+    /// let pager = minus::Pager::new();
+    /// pager.read_from(std::fs::File::open("/var/log/syslog").unwrap());
+    /// ```
+    #[cfg(feature = "mmap")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, MinusError> {
+        #[cfg(feature = "compression")]
+        if let Some(reader) =
+            crate::compression::open_compressed(path.as_ref()).map_err(MinusError::ReadInput)?
+        {
+            let pager = Self::new();
+            pager.read_to_end(reader);
+            return Ok(pager);
+        }
+        let lines = crate::mmap::MmapLines::open(path.as_ref()).map_err(MinusError::ReadInput)?;
+        let pager = Self::new();
+        pager.set_line_provider(lines)?;
+        Ok(pager)
+    }
+
+    /// Initialize a new pager which pages the file at `path` decoded from `encoding`
+    ///
+    /// This is like [`from_file`](Pager::from_file) but for files that aren't encoded in UTF-8,
+    /// like files in Latin-1 or Shift-JIS. If `encoding` is [`None`], it is detected from the byte
+    /// order mark of the file or, failing that, guessed from the start of the file.
+    ///
+    /// Files in encodings that aren't compatible with ASCII, like UTF-16, can't be split into
+    /// lines without decoding them. Such files are read into memory as a whole.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::ReadInput)`](MinusError::ReadInput) if the
+    /// file could not be opened or mapped
+    ///
+    /// # Example
+    /// ```no_run
+    /// use minus::encoding_rs::SHIFT_JIS;
+    ///
+    /// let pager = minus::Pager::from_file_with_encoding("notes.txt", Some(SHIFT_JIS)).unwrap();
+    /// // Let minus figure out the encoding
+    /// let pager = minus::Pager::from_file_with_encoding("legacy.txt", None).unwrap();
+    /// ```
+    #[cfg(feature = "encoding")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "encoding")))]
+    pub fn from_file_with_encoding(
+        path: impl AsRef<std::path::Path>,
+        encoding: Option<&'static encoding_rs::Encoding>,
+    ) -> Result<Self, MinusError> {
+        let lines = crate::mmap::MmapLines::open(path.as_ref()).map_err(MinusError::ReadInput)?;
+        let encoding = encoding.unwrap_or_else(|| lines.detect_encoding());
+        let pager = Self::new();
+        if encoding.is_ascii_compatible() {
+            pager.set_line_provider(lines.with_encoding(encoding))?;
+        } else {
+            pager.set_text(lines.decode_all(encoding))?;
+        }
+        Ok(pager)
+    }
+
+    /// Set the output text to this `t`
+    ///
+    /// Note that unlike [`Pager::push_str`], this replaces the original text.
+    /// If you want to append text, use the [`Pager::push_str`] function or the
+    /// [`write!`]/[`writeln!`] macros
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// pager.set_text("This is a line").expect("Failed to send data to the pager");
+    /// ```
+    pub fn set_text(&self, s: impl Into<String>) -> Result<(), MinusError> {
+        self.utf8_pending.lock().clear();
+        Ok(self.send(Command::SetData(s.into()))?)
+    }
+
+    /// Remove all the text from the pager
+    ///
+    /// This is the same as calling [`set_text`](Pager::set_text) with an empty string. It is
+    /// useful to replace the output of a command with the output of its next run.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// pager.push_str("Old output\n").unwrap();
+    /// pager.clear().expect("Failed to send data to the pager");
+    /// pager.push_str("New output\n").unwrap();
+    /// ```
+    pub fn clear(&self) -> Result<(), MinusError> {
+        self.set_text("")
+    }
+
+    /// Keep only the first `lines` lines of the text and remove the rest
+    ///
+    /// Nothing happens if the text doesn't have more lines than that. Highlights and signs of the
+    /// removed lines are removed too. Text served by a [line provider](Pager::set_line_provider)
+    /// is never truncated.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// pager.set_text("Header\nOld output\n").unwrap();
+    /// // Replace everything below the header
+    /// pager.truncate(1).expect("Failed to send data to the pager");
+    /// pager.push_str("New output\n").unwrap();
+    /// ```
+    pub fn truncate(&self, lines: usize) -> Result<(), MinusError> {
+        Ok(self.send(Command::Truncate(lines))?)
+    }
+
+    /// Replace the text of the line at index `line`
+    ///
+    /// Only the rows of this line are formatted and drawn again, which makes this much cheaper
+    /// than sending the whole text again with [`set_text`](Pager::set_text) when a single line
+    /// changes, like the status line of a task. If `text` contains line breaks, it replaces the
+    /// line with several lines. Nothing happens if there is no line at `line`.
+    ///
+    /// Like all the line editing functions, this has no effect on text served by a
+    /// [line provider](Pager::set_line_provider), laid out as a [table](Pager::push_row) or made
+    /// up of the lines of [input sources](Pager::push_source_str).
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// pager.set_text("build: running\ntest: waiting\n").unwrap();
+    /// pager.replace_line(0, "build: done").expect("Failed to send data to the pager");
+    /// ```
+    pub fn replace_line(&self, line: usize, text: impl Into<String>) -> Result<(), MinusError> {
+        Ok(self.send(Command::EditLines(
+            line..line.saturating_add(1),
+            text.into(),
+        ))?)
+    }
+
+    /// Insert `lines` before the line at index `line`
+    ///
+    /// The lines are added at the end of the text if `line` is past the last line.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// pager.set_text("first\nlast\n").unwrap();
+    /// pager
+    ///     .insert_lines(1, ["second", "third"])
+    ///     .expect("Failed to send data to the pager");
+    /// ```
+    pub fn insert_lines<S: AsRef<str>>(
+        &self,
+        line: usize,
+        lines: impl IntoIterator<Item = S>,
+    ) -> Result<(), MinusError> {
+        Ok(self.send(Command::EditLines(line..line, join_lines(lines)))?)
+    }
+
+    /// Remove the lines whose indices are in `lines`
+    ///
+    /// The lines after them move up. Highlights and signs of the removed lines are removed too.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// pager.set_text("keep\nremove\nremove\nkeep\n").unwrap();
+    /// pager.delete_lines(1..3).expect("Failed to send data to the pager");
+    /// ```
+    pub fn delete_lines(&self, lines: impl RangeBounds<usize>) -> Result<(), MinusError> {
+        Ok(self.send(Command::EditLines(line_range(&lines), String::new()))?)
+    }
+
+    /// Add a row of cells to a table displayed by the pager
+    ///
+    /// minus lays out the rows into columns, each as wide as its widest cell, and places the text
+    /// of each cell according to its [`Alignment`](crate::Alignment). Columns are separated by
+    /// two spaces. When line wrapping is turned on, the widest columns are shrunk so that the rows
+    /// fit on the screen and their cells are truncated with `…`. The table is laid out again
+    /// whenever the terminal is resized, so applications don't have to format tables that then
+    /// wrap badly. When scrolling horizontally, the cells are never truncated.
+    ///
+    /// The table replaces any text of the pager. [`set_text`](Pager::set_text) removes the table
+    /// and text added with [`push_str`](Pager::push_str) is discarded when the table is laid out
+    /// again. Use [`set_header_lines`](Pager::set_header_lines) to keep a row of column names on
+    /// the screen.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{Alignment, Cell, Pager};
+    ///
+    /// let pager = Pager::new();
+    /// pager.push_row(&[Cell::new("PID").align(Alignment::Right), Cell::new("COMMAND")]).unwrap();
+    /// pager.push_row(&[Cell::new("1").align(Alignment::Right), Cell::new("/sbin/init")]).unwrap();
+    /// pager.set_header_lines(1).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn push_row(&self, cells: &[Cell]) -> Result<(), MinusError> {
+        Ok(self.send(Command::PushRow(cells.to_vec()))?)
+    }
+
+    /// Display the differences between the texts `old` and `new` side by side
+    ///
+    /// The lines of `old` are shown on the left and the lines of `new` on the right, each with its
+    /// line number. Lines that both texts have are shown next to each other, so both sides always
+    /// scroll together. Removed lines leave the right side empty and added lines leave the left
+    /// side empty. A removed line followed by an added line is shown as changed and the words
+    /// that differ between them are emphasized. The styles are part of the [`Theme`].
+    ///
+    /// When line wrapping is turned on, both sides take half of the screen and their lines are
+    /// truncated with `…`. The diff is laid out again whenever the terminal is resized. The texts
+    /// should not contain ANSI escape sequences as the widths of the lines would be off.
+    ///
+    /// The lines are compared right away, on the thread calling this function. Like a
+    /// [table](Pager::push_row), the diff replaces any text of the pager until
+    /// [`set_text`](Pager::set_text) is called.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// pager
+    ///     .set_diff("let x = 1;\nprint(x);\n", "let x = 2;\nprint(x);\n")
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_diff(&self, old: &str, new: &str) -> Result<(), MinusError> {
+        self.utf8_pending.lock().clear();
+        Ok(self.send(Command::SetDiff(Diff::new(old, new)))?)
+    }
+
+    /// Display a diff in the unified format side by side
+    ///
+    /// This reads the output of tools like `diff -u` or `git diff` and shows the old and the new
+    /// lines of each hunk next to each other, just like [`set_diff`](Pager::set_diff) does. The
+    /// lines outside of the hunks, like the names of the files and the headers of the hunks, are
+    /// shown across both sides.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// let diff = "--- a/main.rs\n+++ b/main.rs\n@@ -1,2 +1,2 @@\n-let x = 1;\n+let x = 2;\n print(x);\n";
+    /// pager.set_unified_diff(diff).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_unified_diff(&self, diff: &str) -> Result<(), MinusError> {
+        self.utf8_pending.lock().clear();
+        Ok(self.send(Command::SetDiff(Diff::from_unified(diff)))?)
+    }
+
+    /// Appends text to the pager output.
+    ///
+    /// You can also use [`write!`]/[`writeln!`] macros to append data to the pager.
+    /// The implementation basically calls this function internally. One difference
+    /// between using the macros and this function is that this does not require `Pager`
+    /// to be declared mutable while in order to use the macros, you need to declare
+    /// the `Pager` as mutable.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use std::fmt::Write;
+    ///
+    /// let mut pager = minus::Pager::new();
+    /// pager.push_str("This is some text").expect("Failed to send data to the pager");
+    /// // This is same as above
+    /// write!(pager, "This is some text").expect("Failed to send data to the pager");
+    /// ```
+    pub fn push_str(&self, s: impl Into<String>) -> Result<(), MinusError> {
+        Ok(self.send(Command::AppendData(s.into()))?)
+    }
+
+    /// Appends a line with styles applied to parts of its text
+    ///
+    /// minus writes the ANSI escape sequences for the styles of the [`StyledLine`] so that
+    /// applications don't have to embed them in the text. A newline is added after the line.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use crossterm::style::{ContentStyle, Stylize};
+    /// use minus::{Pager, StyledLine};
+    ///
+    /// let pager = Pager::new();
+    /// pager
+    ///     .push_line(&StyledLine::new("warning: unused variable").style(0..7, ContentStyle::new().yellow()))
+    ///     .expect("Failed to send data to the pager");
+    /// ```
+    pub fn push_line(&self, line: &StyledLine) -> Result<(), MinusError> {
+        Ok(self.send(Command::AppendData(format!("{line}\n")))?)
+    }
+
+    /// Apply many changes to the pager and display them at once
+    ///
+    /// Nothing is drawn while `f` runs, so setting the text, appending to it and changing the
+    /// prompt in `f` doesn't make the screen flicker through every intermediate state. The screen
+    /// is drawn again once `f` returns, even if it returns an error or panics. Batches can be
+    /// nested, in which case the screen is drawn after the outermost one finishes.
+    ///
+    /// Keep the work in `f` short as the screen doesn't respond to scrolling either until it is
+    /// done.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver. Otherwise it returns what `f` returns.
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager
+    ///     .batch(|pager| {
+    ///         pager.set_text("")?;
+    ///         for i in 0..100 {
+    ///             pager.push_str(format!("{i}\n"))?;
+    ///         }
+    ///         pager.set_prompt("Done")
+    ///     })
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn batch<T>(
+        &self,
+        f: impl FnOnce(&Self) -> Result<T, MinusError>,
+    ) -> Result<T, MinusError> {
+        self.send(Command::BeginUpdate)?;
+        let _guard = BatchGuard(self);
+        f(self)
+    }
+
+    /// Append bytes which should be UTF-8 text to the pager output
+    ///
+    /// Byte streams from sockets and files can be fed to the pager as they arrive. A UTF-8
+    /// character split between two calls is held back until the rest of it arrives, even if the
+    /// calls are made on different clones of the pager. Bytes which aren't valid UTF-8 are
+    /// replaced with `U+FFFD REPLACEMENT CHARACTER`, as is an incomplete character which is still
+    /// held back when [`finish_data`](Pager::finish_data) is called.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// let text = "Grüße\n".as_bytes();
+    /// // The ü is split between the two calls
+    /// pager.push_bytes(&text[..3]).expect("Failed to send data to the pager");
+    /// pager.push_bytes(&text[3..]).expect("Failed to send data to the pager");
+    /// ```
+    #[allow(clippy::significant_drop_tightening)]
+    pub fn push_bytes(&self, bytes: &[u8]) -> Result<(), MinusError> {
+        let mut pending = self.utf8_pending.lock();
+        pending.extend_from_slice(bytes);
+        self.push_complete_text(&mut pending)
+    }
+
+    /// Append the text in `pending` up to an incomplete character at its end
+    fn push_complete_text(&self, pending: &mut Vec<u8>) -> Result<(), MinusError> {
+        let text = take_complete_text(pending);
+        if !text.is_empty() {
+            self.send(Command::AppendData(text))?;
+        }
+        Ok(())
+    }
+
+    /// Append the bytes of an incomplete character which are still held back
+    ///
+    /// They are replaced with `U+FFFD REPLACEMENT CHARACTER` as nothing will complete them anymore.
+    #[allow(clippy::significant_drop_tightening)]
+    fn push_incomplete_char(&self) -> Result<(), MinusError> {
+        let mut pending = self.utf8_pending.lock();
+        if !pending.is_empty() {
+            let text = String::from_utf8_lossy(&pending).into_owned();
+            pending.clear();
+            self.send(Command::AppendData(text))?;
+        }
+        Ok(())
+    }
+
+    /// Append each item of `lines` as a line to the pager output
+    ///
+    /// This saves joining the lines into one string first. The lines are all sent to the pager at
+    /// once, so they are also displayed at once. Line breaks inside the items split them into
+    /// several lines.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// let entries = vec!["first entry", "second entry"];
+    /// pager.push_lines(&entries).expect("Failed to send data to the pager");
+    /// pager
+    ///     .push_lines((1..=3).map(|i| format!("line {i}")))
+    ///     .expect("Failed to send data to the pager");
+    /// ```
+    pub fn push_lines<S: AsRef<str>>(
+        &self,
+        lines: impl IntoIterator<Item = S>,
+    ) -> Result<(), MinusError> {
+        Ok(self.send(Command::AppendData(join_lines(lines)))?)
+    }
+
+    /// Append everything that can be read from `reader` to the pager output
+    ///
+    /// This spawns a thread which reads from `reader` until it reaches the end of its input and
+    /// appends the text as soon as it arrives. Lines and UTF-8 characters that are split across
+    /// reads are joined back together and bytes which aren't valid UTF-8 are replaced with
+    /// `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// This is useful for paging the output of a subprocess or the standard input. The returned
+    /// handle can be joined to know when all the input has been read.
+    ///
+    /// # Errors
+    /// The thread returns a [`Err(MinusError::ReadInput)`](MinusError::ReadInput) if reading from
+    /// `reader` failed or a [`Err(MinusError::Communication)`](MinusError::Communication) if the
+    /// data could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::process::{Command, Stdio};
+    ///
+    /// let pager = minus::Pager::new();
+    /// let child = Command::new("ls")
+    ///     .arg("-l")
+    ///     .stdout(Stdio::piped())
+    ///     .spawn()
+    ///     .unwrap();
+    /// pager.read_from(child.stdout.unwrap());
+    /// ```
+    pub fn read_from(
+        &self,
+        reader: impl Read + Send + 'static,
+    ) -> JoinHandle<Result<(), MinusError>> {
+        let pager = self.clone();
+        std::thread::spawn(move || read_text(reader, |text| pager.push_str(text)))
+    }
+
+    /// Add a buffer called `name` and get a pager which sends its text to it
+    ///
+    /// A pager can hold several texts, each in its own buffer, like the files passed to `less`.
+    /// Only one buffer is displayed at a time. The user can switch between them by typing `:n` or
+    /// `:p` for the next or previous buffer and `:b` followed by the name or number of a buffer at
+    /// the `:` prompt. The application can display a buffer with
+    /// [`show_buffer`](Pager::show_buffer). The `%buffer` placeholder of the
+    /// [prompt format](Pager::set_prompt_format) shows the name of the displayed buffer.
+    ///
+    /// The returned pager works like any other pager except that its text goes to the new buffer,
+    /// whether it is displayed or not. The text given to the pager before any buffers were added
+    /// is in the first buffer, which has an empty name unless it is named with
+    /// [`set_buffer_name`](Pager::set_buffer_name). Options that aren't about the text, like the
+    /// prompt or line numbers, are shared by all buffers. If there already is a buffer called
+    /// `name`, the returned pager sends its text to that buffer.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// pager.set_buffer_name("build").unwrap();
+    /// pager.push_str("Compiling minus\n").unwrap();
+    ///
+    /// let tests = pager.add_buffer("test").unwrap();
+    /// tests.push_str("test result: ok\n").unwrap();
+    /// ```
+    pub fn add_buffer(&self, name: impl Into<String>) -> Result<Self, MinusError> {
+        let name = name.into();
+        self.send(Command::AddBuffer(name.clone()))?;
+        Ok(Self {
+            tx: self.tx.clone(),
+            rx: self.rx.clone(),
+            running: self.running.clone(),
+            buffer: Some(name),
+            utf8_pending: Arc::new(Mutex::new(Vec::new())),
+            lossy_utf8: self.lossy_utf8,
+            clones: self.clones.clone(),
+        })
+    }
+
+    /// Display the buffer called `name`
+    ///
+    /// See [`add_buffer`](Pager::add_buffer) for more info about buffers.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// let errors = pager.add_buffer("errors").unwrap();
+    /// errors.push_str("error: expected `;`\n").unwrap();
+    /// pager.show_buffer("errors").unwrap();
+    /// ```
+    pub fn show_buffer(&self, name: impl Into<String>) -> crate::Result {
+        Ok(self.send(Command::ShowBuffer(name.into()))?)
+    }
+
+    /// Set the name of the buffer that this pager sends its text to
+    ///
+    /// Nothing happens if another buffer already has this name. See
+    /// [`add_buffer`](Pager::add_buffer) for more info about buffers.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// pager.set_buffer_name("main.rs").unwrap();
+    /// ```
+    pub fn set_buffer_name(&self, name: impl Into<String>) -> crate::Result {
+        Ok(self.send(Command::SetBufferName(name.into()))?)
+    }
+
+    /// Register an input source called `name` whose name is drawn with `style`
+    ///
+    /// Sources don't need to be registered before text is pushed from them, but registering all
+    /// of them upfront aligns the names of all sources. See
+    /// [`push_source_str`](Pager::push_source_str) for more info about sources.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use crossterm::style::{Color, ContentStyle, Stylize};
+    ///
+    /// let pager = minus::Pager::new();
+    /// pager.add_source("web", ContentStyle::new().with(Color::Cyan)).unwrap();
+    /// pager.add_source("database", ContentStyle::new().with(Color::Yellow)).unwrap();
+    /// ```
+    pub fn add_source(&self, name: impl Into<String>, style: ContentStyle) -> crate::Result {
+        Ok(self.send(Command::AddSource(name.into(), style))?)
+    }
+
+    /// Append text which comes from the input source called `name`
+    ///
+    /// This allows merging the output of several streams into one pager like
+    /// `docker compose logs` does. Every line is prefixed with the name of its source, drawn in
+    /// the style it was [registered](Pager::add_source) with. Text is only appended once a line
+    /// is complete, so lines of different sources which arrive at the same time never get mixed
+    /// up.
+    ///
+    /// The lines of a source can be hidden and shown again with
+    /// [`show_source`](Pager::show_source). The text is then rebuilt from the lines of the
+    /// sources, so text that was appended to the pager in other ways is lost. Lines that were
+    /// discarded because of [`set_max_lines`](Pager::set_max_lines) or removed with
+    /// [`truncate`](Pager::truncate) don't come back. The lines of sources can't be edited with
+    /// functions like [`replace_line`](Pager::replace_line).
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// pager.push_source_str("web", "GET /index.html\n").unwrap();
+    /// pager.push_source_str("database", "Connection accep").unwrap();
+    /// // The line of the database is only appended here
+    /// pager.push_source_str("database", "ted\n").unwrap();
+    /// ```
+    pub fn push_source_str(
+        &self,
+        name: impl Into<String>,
+        text: impl Into<String>,
+    ) -> crate::Result {
+        Ok(self.send(Command::AppendSourceData(name.into(), text.into()))?)
+    }
+
+    /// Append everything that can be read from `reader` as text of the input source called `name`
+    ///
+    /// This is like [`read_from`](Pager::read_from), except that the text is appended with
+    /// [`push_source_str`](Pager::push_source_str). An unterminated last line is appended once
+    /// `reader` reaches the end of its input.
+    ///
+    /// # Errors
+    /// The thread returns a [`Err(MinusError::ReadInput)`](MinusError::ReadInput) if reading from
+    /// `reader` failed or a [`Err(MinusError::Communication)`](MinusError::Communication) if the
+    /// data could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::process::{Command, Stdio};
+    ///
+    /// let pager = minus::Pager::new();
+    /// for service in ["web", "database"] {
+    ///     let child = Command::new("journalctl")
+    ///         .args(["--follow", "--unit", service])
+    ///         .stdout(Stdio::piped())
+    ///         .spawn()
+    ///         .unwrap();
+    ///     pager.read_from_source(service, child.stdout.unwrap());
+    /// }
+    /// ```
+    pub fn read_from_source(
+        &self,
+        name: impl Into<String>,
+        reader: impl Read + Send + 'static,
+    ) -> JoinHandle<Result<(), MinusError>> {
+        let pager = self.clone();
+        let name = name.into();
+        std::thread::spawn(move || {
+            let mut terminated = true;
+            read_text(reader, |text| {
+                terminated = text.ends_with('\n');
+                pager.push_source_str(name.clone(), text)
+            })?;
+            if !terminated {
+                pager.push_source_str(name, "\n")?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Show or hide the lines of the input source called `name`
+    ///
+    /// See [`push_source_str`](Pager::push_source_str) for more info about sources.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// pager.push_source_str("web", "GET /index.html\n").unwrap();
+    /// pager.push_source_str("database", "Connection accepted\n").unwrap();
+    /// // Only show the lines of the web server
+    /// pager.show_source("database", false).unwrap();
+    /// ```
+    pub fn show_source(&self, name: impl Into<String>, show: bool) -> crate::Result {
+        Ok(self.send(Command::ShowSource(name.into(), show))?)
+    }
+
+    /// Page the file at `path` and keep following it as it changes, like `tail -F`
+    ///
+    /// The current contents of the file are appended to the output right away. After that,
+    /// whatever gets written to the file is appended too. If the file gets truncated, the output
+    /// is replaced with its new contents. If the file is replaced by another one, like when logs
+    /// are rotated, the rest of the old file is read and the new file is followed from its start.
+    ///
+    /// The file is followed until the returned [`FileWatcher`](crate::FileWatcher) is dropped.
+    /// Combine this with [`follow_output`](Pager::follow_output) to keep the newest lines on the
+    /// screen. Errors while reading the file are shown as messages in the prompt.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::ReadInput)`](MinusError::ReadInput) if the
+    /// file could not be read or a [`Err(MinusError::WatchFile)`](MinusError::WatchFile) if
+    /// watching it failed
+    ///
+    /// # Example
+    /// ```no_run
+    /// let pager = minus::Pager::new();
+    /// let _watcher = pager.watch_file("/var/log/syslog").unwrap();
+    /// pager.follow_output(true).unwrap();
+    /// ```
+    #[cfg(feature = "watch")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "watch")))]
+    pub fn watch_file(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<crate::FileWatcher, MinusError> {
+        crate::FileWatcher::new(self.clone(), path.as_ref())
+    }
+
+    /// Append everything from `reader` in the background and finish the data after it
+    ///
+    /// An error while reading is shown as a message instead.
+    #[cfg(feature = "compression")]
+    fn read_to_end(&self, reader: impl Read + Send + 'static) {
+        let reader = self.read_from(reader);
+        let pager = self.clone();
+        // The pager might have already quit by then so nothing can be done if sending fails
+        std::thread::spawn(move || match reader.join() {
+            Ok(Ok(())) => {
+                let _ = pager.finish_data();
+            }
+            Ok(Err(MinusError::ReadInput(e))) => {
+                let _ = pager.send_message(format!("Failed to decompress the file: {e}"));
+            }
+            _ => {}
+        });
+    }
+
+    /// Set line number configuration for the pager
+    ///
+    /// See [`LineNumbers`] for available options
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{Pager, LineNumbers};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_line_numbers(LineNumbers::Enabled).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_line_numbers(&self, l: LineNumbers) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetLineNumbers(l))?)
+    }
+
+    /// Set a custom format for the line numbers
+    ///
+    /// The given function receives the number to be displayed and returns the entire text to be
+    /// placed before the first row of the line. This lets applications control the separator,
+    /// padding, alignment and color of the line numbers. Any padding must be added by the function
+    /// itself and the text it returns should be of the same width for all the numbers.
+    ///
+    /// By default, line numbers are displayed in bold, right aligned and followed by a `.`
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{Pager, LineNumbers};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_line_numbers(LineNumbers::Enabled).expect("Failed to communicate with the pager");
+    /// pager
+    ///     .set_line_number_format(|n| format!("{n:>5} │ "))
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_line_number_format(
+        &self,
+        formatter: impl Fn(usize) -> String + Send + Sync + 'static,
+    ) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetLineNumberFormat(Box::new(formatter)))?)
+    }
+
+    /// Set a function that applies styles to each line of the text, like syntax highlighting
+    ///
+    /// The function receives the text of a line and its index, counted from 0, and returns the
+    /// line as a [`StyledLine`]. Its text should be the same as the text that was given, only with
+    /// styles applied. The function is called whenever a line is formatted, so the text doesn't
+    /// have to be highlighted all at once before it is given to minus. Lines served by a
+    /// [`LineProvider`](crate::LineProvider) are only formatted when they are displayed.
+    ///
+    /// The text given to the function has its tabs expanded and, depending on
+    /// [`set_control_chars`](Pager::set_control_chars), its control characters escaped.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use crossterm::style::{ContentStyle, Stylize};
+    /// use minus::{Pager, StyledLine};
+    ///
+    /// let pager = Pager::new();
+    /// // Show comments in a dimmed color
+    /// pager
+    ///     .set_line_formatter(|line, _| {
+    ///         let styled = StyledLine::new(line);
+    ///         match line.find("//") {
+    ///             Some(start) => styled.style(start..line.len(), ContentStyle::new().dim()),
+    ///             None => styled,
+    ///         }
+    ///     })
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_line_formatter(
+        &self,
+        formatter: impl Fn(&str, usize) -> StyledLine + Send + Sync + 'static,
+    ) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetLineFormatter(Box::new(formatter)))?)
+    }
+
+    /// Set what to display in the line number gutter for the wrapped rows of a line
+    ///
+    /// See [`LineNumberContinuation`] for available options
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{Pager, LineNumberContinuation};
+    ///
+    /// let pager = Pager::new();
+    /// pager
+    ///     .set_line_number_continuation(LineNumberContinuation::Arrow)
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_line_number_continuation(
+        &self,
+        continuation: LineNumberContinuation,
+    ) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetLineNumberContinuation(continuation))?)
+    }
+
+    /// Set the line number of the first line
+    ///
+    /// This is useful when paging a part of a larger file, like the output of `tail` or a hunk
+    /// of a diff, to show the real line numbers of the file instead of starting from 1.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{Pager, LineNumbers};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_line_numbers(LineNumbers::Enabled).expect("Failed to communicate with the pager");
+    /// pager.set_line_number_start(120).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_line_number_start(&self, start: usize) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetLineNumberStart(start))?)
+    }
+
+    /// Set the number of columns between each tab stop
+    ///
+    /// Tab characters in the text are replaced by spaces up to the next tab stop. The default
+    /// tab width is 8. If this is set to 0, tabs are removed from the displayed text.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_tab_width(4).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_tab_width(&self, width: usize) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetTabWidth(width))?)
+    }
+
+    /// Set how to display the control characters present in the text
+    ///
+    /// See [`ControlChars`] for available options
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{ControlChars, Pager};
+    ///
+    /// let pager = Pager::new();
+    /// pager
+    ///     .set_control_chars(ControlChars::EscapeExceptColors)
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_control_chars(&self, control_chars: ControlChars) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetControlChars(control_chars))?)
+    }
+
+    /// Remove the ANSI escape sequences from the text
+    ///
+    /// This displays the text without its colors and styles, for users who want plain output or
+    /// whose terminals don't display colors properly. The escape sequences are removed before the
+    /// text is wrapped so that the rows are as wide as the text that is displayed.
+    ///
+    /// This is turned on by default if the `NO_COLOR` environment variable is set to a non-empty
+    /// value. See <https://no-color.org> for more info.
+    ///
+    /// Only the escape sequences present in the text are removed. The highlights of a search and
+    /// the styles of the [line formatter](Pager::set_line_formatter) are still displayed.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_strip_ansi(true).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_strip_ansi(&self, strip: bool) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetStripAnsi(strip))?)
+    }
+
+    /// Turn the `\r\n` line endings of the text into `\n`
+    ///
+    /// Text coming from Windows programs or HTTP bodies often ends its lines with `\r\n`. The
+    /// `\r` would otherwise be left at the end of each line, where it shows up as `^M` when
+    /// [control characters](Pager::set_control_chars) are escaped and ends up in the saved text.
+    /// A `\r` that is not followed by `\n` is kept.
+    ///
+    /// This is turned on by default. Turn it off to keep the text exactly as it was given. It
+    /// only applies to the text that is set or appended after it is changed.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_normalize_line_endings(false).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_normalize_line_endings(&self, normalize: bool) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetNormalizeLineEndings(normalize))?)
+    }
+
+    /// Set the colors that the terminal can display
+    ///
+    /// The colors of the styles used by minus that the terminal can't display are replaced by the
+    /// closest colors that it can. This applies to the styles of the prompt, line highlights, signs,
+    /// the [line formatter](Pager::set_line_formatter) and color rules. The text itself is
+    /// displayed as is.
+    ///
+    /// By default, the color support is guessed from the `TERM`, `COLORTERM` and `NO_COLOR`
+    /// environment variables. See [`ColorSupport`] for available options.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{ColorSupport, Pager};
+    ///
+    /// let pager = Pager::new();
+    /// pager
+    ///     .set_color_support(ColorSupport::Ansi256)
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_color_support(&self, support: ColorSupport) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetColorSupport(support))?)
+    }
+
+    /// Set the markers placed around rows created by wrapping a line
+    ///
+    /// The `prefix` is placed before each row that a line has been wrapped onto and the `suffix`
+    /// is placed after each row of a line that continues onto the next row. This lets users
+    /// distinguish wrapped rows from actual lines, especially when line numbers are turned off.
+    /// Both are empty by default. They take no effect when line wrapping is turned off.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_wrap_indicator("↪ ", "").expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_wrap_indicator(
+        &self,
+        prefix: impl Into<String>,
+        suffix: impl Into<String>,
+    ) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetWrapIndicator(prefix.into(), suffix.into()))?)
+    }
+
+    /// Whether to make invisible characters visible
+    ///
+    /// When turned on, tabs are marked with `→`, non-breaking spaces are displayed as `␣` and
+    /// spaces at the end of lines are displayed as `·`. This is helpful for applications paging
+    /// diffs or configuration files where invisible characters matter. Users can also toggle this
+    /// by pressing `I`.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.show_invisibles(true).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn show_invisibles(&self, show: bool) -> Result<(), MinusError> {
+        Ok(self.send(Command::ShowInvisibles(show))?)
+    }
+
+    /// Set the text displayed at the bottom prompt
+    ///
+    /// # Panics
+    /// This function panics if the given text contains newline characters.
+    /// This is because, the pager reserves only one line for showing the prompt
+    /// and a newline will cause it to span multiple lines, breaking the display
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_prompt("my prompt").expect("Failed to send data to the pager");
+    /// ```
+    pub fn set_prompt(&self, text: impl Into<String>) -> Result<(), MinusError> {
+        let text: String = text.into();
+        assert!(!text.contains('\n'), "Prompt cannot contain newlines");
+        Ok(self.send(Command::SetPrompt(text))?)
+    }
+
+    /// Set the lines of the status area, which is shown above the prompt
+    ///
+    /// The status area takes up one row for each line, so applications can show more than what
+    /// fits on the prompt, like the current file, the active filters or hints for the keys. Lines
+    /// that are too long for the screen are cut off. At least one row is always left for the text.
+    /// Pass no lines to remove the status area.
+    ///
+    /// The lines are drawn with the `status` style of the [`Theme`].
+    ///
+    /// # Panics
+    /// This function panics if any of the lines contains newline characters.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager
+    ///     .set_status(["server.log", "Filter: level >= WARN"])
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_status<S: Into<String>>(
+        &self,
+        lines: impl IntoIterator<Item = S>,
+    ) -> Result<(), MinusError> {
+        let lines: Vec<String> = lines.into_iter().map(Into::into).collect();
+        assert!(
+            lines.iter().all(|line| !line.contains('\n')),
+            "Status lines cannot contain newlines"
+        );
+        Ok(self.send(Command::SetStatus(lines))?)
+    }
+
+    /// Set a template for the text displayed at the bottom prompt
+    ///
+    /// The template replaces the [prompt](Pager::set_prompt) text. The following placeholders in it
+    /// are replaced by their values whenever the prompt is drawn:
+    ///
+    /// | Placeholder | Value                                                      |
+    /// |-------------|------------------------------------------------------------|
+    /// | `%prompt`   | The text set with [`set_prompt`](Pager::set_prompt)        |
+    /// | `%line`     | Line number of the first line on the screen                |
+    /// | `%bottom`   | Line number of the last line on the screen                 |
+    /// | `%total`    | Total number of lines                                      |
+    /// | `%percent`  | How far through the text the bottom of the screen is, in % |
+    /// | `%search`   | Index of the current search match and number of matches    |
+    /// | `%buffer`   | Name of the displayed [buffer](Pager::add_buffer)          |
+    /// | `%%`        | A literal `%`                                              |
+    ///
+    /// Any other text, including a `%` that doesn't start a placeholder, is displayed as is. When a
+    /// template is set, the search match indicator is only shown where `%search` is placed.
+    /// Messages sent with [`send_message`](Pager::send_message) are still displayed in place of
+    /// the prompt.
+    ///
+    /// # Panics
+    /// This function panics if the given template contains newline characters.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager
+    ///     .set_prompt_format("%prompt  %line/%total  %percent%  %search")
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_prompt_format(&self, format: impl Into<String>) -> Result<(), MinusError> {
+        let format: String = format.into();
+        assert!(
+            !format.contains('\n'),
+            "Prompt format cannot contain newlines"
+        );
+        Ok(self.send(Command::SetPromptFormat(format))?)
+    }
+
+    /// Show how far through the text the screen is at the right end of the prompt
+    ///
+    /// The percentage is how far through the text the bottom of the screen is, like in `less`. It
+    /// is turned off by default. Use the `%percent` placeholder of
+    /// [`set_prompt_format`](Pager::set_prompt_format) to place it elsewhere in the prompt.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.show_percentage(true).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn show_percentage(&self, show: bool) -> Result<(), MinusError> {
+        Ok(self.send(Command::ShowPercentage(show))?)
+    }
+
+    /// Show how far the application has got in fetching or generating the data
+    ///
+    /// The `progress` goes from 0.0 when nothing has been done to 1.0 when all of the data is
+    /// there. It is shown as a small progress bar followed by the percentage at the right end of
+    /// the prompt, so that the user knows that more data is coming while reading what is already
+    /// there. Pass `None` to remove it once the application is done.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_progress(Some(0.4)).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_progress(&self, progress: Option<f32>) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetProgress(progress))?)
+    }
+
+    /// Draw a marker on the rows of the screen past the end of the text
+    ///
+    /// Like the `~` drawn by `less` and `vim`, this lets users tell apart the rows that are empty
+    /// because there is no more text from blank lines in the text. The `filler` is drawn at the
+    /// start of each of those rows with the [`filler`](crate::Theme::filler) style of the theme.
+    /// Pass `None` to leave the rows empty, which is the default.
+    ///
+    /// # Panics
+    /// This function panics if the given filler contains newline characters.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_filler(Some("~")).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_filler(&self, filler: Option<impl Into<String>>) -> Result<(), MinusError> {
+        let filler = filler.map(Into::into);
+        assert!(
+            !filler
+                .as_ref()
+                .map_or(false, |filler| filler.contains('\n')),
+            "The filler cannot contain newlines"
+        );
+        Ok(self.send(Command::SetFiller(filler))?)
+    }
+
+    /// Show a placeholder in place of the empty screen until the first data arrives
+    ///
+    /// Without it, the screen stays blank while the application is still fetching or generating
+    /// the data, which can look like the pager is stuck. The `text` is shown on the first row and
+    /// is replaced by the data as soon as some of it is appended. When `spinner` is `true`, a
+    /// small spinner is animated before the text. Nothing is shown once
+    /// [`finish_data`](Pager::finish_data) has been called, even if no data was sent. Pass `None`
+    /// to turn the placeholder off, which is the default.
+    ///
+    /// This only has an effect in dynamic mode.
+    ///
+    /// # Panics
+    /// This function panics if the given text contains newline characters.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager
+    ///     .set_waiting_placeholder(Some("Fetching the logs..."), true)
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_waiting_placeholder(
+        &self,
+        text: Option<impl Into<String>>,
+        spinner: bool,
+    ) -> Result<(), MinusError> {
+        let text = text.map(Into::into);
+        assert!(
+            !text.as_ref().map_or(false, |text| text.contains('\n')),
+            "The waiting placeholder cannot contain newlines"
+        );
+        Ok(self.send(Command::SetWaitingPlaceholder(text, spinner))?)
+    }
+
+    /// Show a scrollbar in the last column of the screen
+    ///
+    /// The thumb of the scrollbar shows where the screen is in the text and how much of the text
+    /// it covers. The rows with search matches are marked on the scrollbar so that the matches
+    /// across the whole text can be seen at once. The scrollbar is only drawn when the text
+    /// doesn't fit on the screen. Its styles are part of the [`Theme`].
+    ///
+    /// It is turned off by default.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.show_scrollbar(true).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn show_scrollbar(&self, show: bool) -> Result<(), MinusError> {
+        Ok(self.send(Command::ShowScrollbar(show))?)
+    }
+
+    /// Split the screen into two panes showing different parts of the text
+    ///
+    /// Both panes start out at the current position and are scrolled independently. The keys for
+    /// moving around only move the focused pane, which is the bottom one at first. This keeps one
+    /// part of the text, like an error, visible at the top while the user scrolls through another
+    /// part below it. The user can split the screen with `S` and move the focus with `Ctrl+W`.
+    ///
+    /// The header isn't shown while the screen is split. The screen can't be split if the lines
+    /// are served by a [`LineProvider`](crate::LineProvider).
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_split_view(true).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_split_view(&self, split: bool) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetSplitView(split))?)
+    }
+
+    /// Set the style of the prompt line when it is in the given [`PromptMode`]
+    ///
+    /// The style applies to the entire prompt line apart from the indicators displayed at its
+    /// right end.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use crossterm::style::{Color, ContentStyle, Stylize};
+    /// use minus::{Pager, PromptMode};
+    ///
+    /// let pager = Pager::new();
+    /// pager
+    ///     .set_prompt_style(PromptMode::Normal, ContentStyle::new().with(Color::White).on(Color::DarkGrey))
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_prompt_style(
+        &self,
+        mode: PromptMode,
+        style: ContentStyle,
+    ) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetPromptStyle(mode, style))?)
+    }
+
+    /// Set the [`Theme`] of the pager
+    ///
+    /// The theme has the styles of everything that minus draws apart from the text, like the
+    /// prompt line, messages, line numbers, search matches and the selected line. This replaces
+    /// any style set with [`set_prompt_style`](Pager::set_prompt_style) before.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use crossterm::style::{Color, ContentStyle, Stylize};
+    /// use minus::{Pager, Theme};
+    ///
+    /// let mut theme = Theme::default();
+    /// theme.message = ContentStyle::new().with(Color::White).on(Color::DarkMagenta);
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_theme(theme).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_theme(&self, theme: Theme) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetTheme(Box::new(theme)))?)
+    }
+
+    /// Send a message to be displayed the prompt area
+    ///
+    /// The text message is temporary and will get cleared whenever the use
+    /// rdoes a action on the terminal like pressing a key or scrolling using the mouse.
+    /// It is displayed as an [error](MessageLevel::Error) message.
+    ///
+    /// # Panics
+    /// This function panics if the given text contains newline characters.
+    /// This is because, the pager reserves only one line for showing the prompt
+    /// and a newline will cause it to span multiple lines, breaking the display
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.send_message("An error occurred").expect("Failed to send data to the pager");
+    /// ```
+    pub fn send_message(&self, text: impl Into<String>) -> Result<(), MinusError> {
+        self.send_message_with(MessageLevel::Error, text, None)
+    }
+
+    /// Send a message of the given [`MessageLevel`] to be displayed in the prompt area
+    ///
+    /// Each level is displayed in its own style of the [`Theme`]. If a `timeout` is given, the
+    /// message is cleared by itself once it has been shown for that long. Otherwise it stays until
+    /// the user clears it, like the messages sent with [`send_message`](Pager::send_message).
+    ///
+    /// # Panics
+    /// This function panics if the given text contains newline characters.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{MessageLevel, Pager};
+    /// use std::time::Duration;
+    ///
+    /// let pager = Pager::new();
+    /// pager
+    ///     .send_message_with(MessageLevel::Info, "Reloaded", Some(Duration::from_secs(3)))
+    ///     .expect("Failed to send data to the pager");
+    /// ```
+    pub fn send_message_with(
+        &self,
+        level: MessageLevel,
+        text: impl Into<String>,
+        timeout: Option<Duration>,
+    ) -> Result<(), MinusError> {
+        let text: String = text.into();
+        assert!(!text.contains('\n'), "Message cannot contain newlines");
+        Ok(self.send(Command::SendMessage(level, text, timeout))?)
+    }
+
+    /// Send an input event to the pager as if the user caused it
+    ///
+    /// This lets applications drive the pager without a keyboard, for example to scroll it,
+    /// open the search prompt or quit it for automation or demos. The event is handled exactly
+    /// like the same event from the [input classifier](crate::input). Events sent before the pager
+    /// starts are handled right after it starts.
+    ///
+    /// [`InputEvent::Number`] has no effect as count prefixes are resolved while classifying the
+    /// input.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{input::InputEvent, Pager};
+    ///
+    /// let pager = Pager::new();
+    /// // Scroll to the 10th line
+    /// pager
+    ///     .send_input(InputEvent::UpdateUpperMark(10))
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn send_input(&self, input: InputEvent) -> Result<(), MinusError> {
+        Ok(self.send(Command::UserInput(input))?)
+    }
+
+    /// Set the default exit strategy.
+    ///
+    /// This controls how the pager will behave when the user presses `q` or `Ctrl+C`.
+    /// See [`ExitStrategy`] for available options. What `Ctrl+C` does can be changed separately
+    /// with [`Pager::set_interrupt_strategy`].
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// ```
+    /// use minus::{Pager, ExitStrategy};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_exit_strategy(ExitStrategy::ProcessQuit).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_exit_strategy(&self, es: ExitStrategy) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetExitStrategy(es))?)
+    }
+
+    /// Set what happens when the user presses `Ctrl+C`
+    ///
+    /// By default, `Ctrl+C` quits the pager just like `q` and follows the [`ExitStrategy`]. See
+    /// [`InterruptStrategy`] for available options.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{InterruptStrategy, Pager};
+    /// use std::sync::{
+    ///     atomic::{AtomicBool, Ordering},
+    ///     Arc,
+    /// };
+    ///
+    /// let pager = Pager::new();
+    /// // Cancel producing the data but keep the pager open
+    /// let cancelled = Arc::new(AtomicBool::new(false));
+    /// let cancelled2 = cancelled.clone();
+    /// pager
+    ///     .set_interrupt_strategy(InterruptStrategy::Forward(Box::new(move || {
+    ///         cancelled2.store(true, Ordering::SeqCst);
+    ///     })))
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_interrupt_strategy(&self, is: InterruptStrategy) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetInterruptStrategy(is))?)
+    }
+
+    /// Set whether to display pager if there's less data than
+    /// available screen height
+    ///
+    /// When this is set to false, the pager will simply print all the lines
+    /// to the main screen and immediately quit if the number of lines to
+    /// display is less than the available columns in the terminal.
+    /// Setting this to true will cause a full pager to start and display the data
+    /// even if there is less number of lines to display than available rows.
+    ///
+    /// This is only available in static output mode as the size of the data is
+    /// known beforehand.
+    /// In async output the pager can receive more data anytime
+    ///
+    /// By default this is set to false
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_run_no_overflow(true).expect("Failed to communicate with the pager");
+    /// ```
+    #[cfg(feature = "static_output")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "static_output")))]
+    pub fn set_run_no_overflow(&self, val: bool) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetRunNoOverflow(val))?)
+    }
+
+    /// Whether to write the data straight to the standard output if it is not a terminal
+    ///
+    /// When the standard output is piped or redirected to a file, a pager cannot be displayed. If
+    /// this is set to `true`, minus writes the text straight to the standard output instead
+    /// without touching the terminal. In dynamic mode, the data that is sent afterwards is also
+    /// written as it arrives until this `Pager` and all its clones are dropped.
+    ///
+    /// If this is set to `false`, minus will fail with a
+    /// [`SetupError::InvalidTerminal`](crate::error::SetupError::InvalidTerminal) in such cases.
+    ///
+    /// By default this is set to `true`
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_passthrough_on_pipe(false).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_passthrough_on_pipe(&self, val: bool) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetPassthroughOnPipe(val))?)
+    }
+
+    /// Whether to page in the terminal's alternate screen
+    ///
+    /// By default minus switches to the alternate screen while it is running, so the terminal's
+    /// contents are restored when it quits. If this is set to `false`, minus pages in the normal
+    /// screen instead and the last displayed view stays in the terminal's scrollback after
+    /// quitting, just like `less -X`.
+    ///
+    /// This must be set before the pager starts. Changing it while the pager is running has no
+    /// effect.
+    ///
+    /// By default this is set to `true`
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_alternate_screen(false).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_alternate_screen(&self, val: bool) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetAlternateScreen(val))?)
+    }
+
+    /// Whether to capture the mouse while the pager is running
+    ///
+    /// The mouse is captured by default so that scrolling the mouse wheel scrolls the text. This
+    /// also stops the terminal from selecting text with the mouse in most terminals. Set this
+    /// to `false` so that users can select and copy text like they normally would, at the cost of
+    /// scrolling with the mouse wheel.
+    ///
+    /// This can also be changed while the pager is running, for example to let users copy some
+    /// text and capture the mouse again afterwards. Users can toggle it themselves with `M`.
+    ///
+    /// By default this is set to `true`
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_mouse_capture(false).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_mouse_capture(&self, val: bool) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetMouseCapture(val))?)
+    }
+
+    /// Set how many lines the arrow keys and the mouse wheel scroll by
+    ///
+    /// `arrow` is the number of lines scrolled by `Up`, `Down`, `j` and `k`, unless a number is
+    /// typed before them. `wheel` is the number of lines scrolled by each tick of the mouse
+    /// wheel. The cursor line set with [`set_cursor_line`](Pager::set_cursor_line) still moves by
+    /// a single line.
+    ///
+    /// By default the arrow keys scroll by 1 line and the mouse wheel by 5 lines.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_scroll_amounts(2, 3).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_scroll_amounts(&self, arrow: usize, wheel: usize) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetScrollAmounts(arrow, wheel))?)
+    }
+
+    /// Draw the pager only inside the given area of the terminal
+    ///
+    /// By default the pager takes up the whole terminal. When a [`Viewport`] is set, the text and
+    /// the prompt are drawn only inside it and the rest of the terminal is left untouched. This
+    /// lets TUI applications embed minus as a scrollable pane next to their own UI. Usually
+    /// [`set_alternate_screen(false)`](Pager::set_alternate_screen) should be set too so that the
+    /// application's UI stays visible.
+    ///
+    /// The viewport keeps its size when the terminal is resized. Passing [`None`] makes the
+    /// pager take up the whole terminal again. Incremental search is not available inside a
+    /// viewport.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{Pager, Viewport};
+    ///
+    /// let pager = Pager::new();
+    /// // Use the right half of an 80x24 terminal
+    /// pager
+    ///     .set_viewport(Some(Viewport {
+    ///         column: 40,
+    ///         row: 0,
+    ///         width: 40,
+    ///         height: 24,
+    ///     }))
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_viewport(&self, viewport: Option<Viewport>) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetViewport(viewport))?)
+    }
+
+    /// Draw the pager to the given `output` instead of the standard output
+    ///
+    /// This is useful when the terminal is not the process's own, like a pty driven by the
+    /// application, an SSH channel or a remote session. `cols` and `rows` are the size of the
+    /// terminal behind the `output`. Later changes in its size should be sent as
+    /// [`Event::Resize`](crossterm::event::Event::Resize) through the source given to
+    /// [`set_input`](Pager::set_input).
+    ///
+    /// The output can only be set before the pager starts. Since the process can't tell what
+    /// happens on the other end of the output, it is best used with
+    /// [`ExitStrategy::PagerQuit`](crate::ExitStrategy::PagerQuit).
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager
+    ///     .set_output(std::io::sink(), 80, 24)
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_output(
+        &self,
+        output: impl io::Write + Send + 'static,
+        cols: u16,
+        rows: u16,
+    ) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetOutput(Mutex::new(Box::new(output)), cols, rows))?)
+    }
+
+    /// Read the terminal events from the given `source` instead of the terminal
+    ///
+    /// The pager reads every key press, mouse event and resize from the `source`. A
+    /// [`std::sync::mpsc::Receiver`] of [`Event`](crossterm::event::Event)s can be used
+    /// directly, or [`EventSource`](input::EventSource) can be implemented for a custom type.
+    ///
+    /// When a source is set, minus does not put the terminal into raw mode. That is left to the
+    /// application, which owns the terminal the events come from. The source can only be set
+    /// before the pager starts.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    /// use crossterm::event::Event;
+    ///
+    /// let (events, source) = std::sync::mpsc::channel::<Event>();
+    /// let pager = Pager::new();
+    /// pager.set_input(source).expect("Failed to communicate with the pager");
+    /// # drop(events);
+    /// ```
+    pub fn set_input(&self, source: impl input::EventSource) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetInput(Mutex::new(Box::new(source))))?)
+    }
+
+    /// Whether to allow scrolling horizontally
+    ///
+    /// Setting this to `true` implicitly disables line wrapping
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.horizontal_scroll(true).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn horizontal_scroll(&self, value: bool) -> Result<(), MinusError> {
+        Ok(self.send(Command::LineWrapping(!value))?)
+    }
+
+    /// Keep the first `cols` columns of the text in place when scrolling horizontally
+    ///
+    /// The frozen columns are always shown after the line numbers while the rest of each row
+    /// scrolls to the right, so identifiers at the start of the rows of a wide table stay
+    /// visible. This takes no effect when line wrapping is turned on. By default no columns
+    /// are frozen.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.horizontal_scroll(true).unwrap();
+    /// pager.set_frozen_columns(8).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_frozen_columns(&self, cols: usize) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetFrozenColumns(cols))?)
+    }
+
+    /// Set a custom input classifer type.
+    ///
+    /// An input classifier type is a type that implements the [InputClassifier]
+    /// trait. It only has one required function, [InputClassifier::classify_input]
+    /// which matches user input events and maps them to a [InputEvent]s.
+    /// When the pager encounters a user input, it calls the input classifier with
+    /// the event and [PagerState] as parameters.
+    ///
+    /// Previously, whenever any application wanted to change the default key/mouse bindings
+    /// they neededd to create a new type, implement the [InputClassifier] type by copying and
+    /// pasting the default minus's implementation of it available in the [DefaultInputClassifier]
+    /// and change the parts they wanted to change. This is not only unergonomic but also
+    /// extreemely prone to bugs. Hence a newer and much simpler method was developed.
+    /// This method is still allowed to avoid breaking backwards compatiblity but will be dropped
+    /// in the next major release.
+    ///
+    /// With the newer method, minus already provides a type called [HashedEventRegister]
+    /// which implementing the [InputClassifier] and is based on a
+    /// [HashMap] storing all the key/mouse bindings and its associated callback function.
+    /// This allows easy addition/updation/deletion of the default bindings with simple functions
+    /// like [HashedEventRegister::add_key_events] and [HashedEventRegister::add_mouse_events]
+    ///
+    /// See the [input] module for information about implementing it.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// [HashedEventRegister::add_key_events]: input::HashedEventRegister::add_key_events
+    /// [HashedEventRegister::add_mouse_events]: input::HashedEventRegister::add_mouse_events
+    /// [HashMap]: std::collections::HashMap
+    /// [PagerState]: crate::state::PagerState
+    /// [InputEvent]: input::InputEvent
+    /// [InputClassifier]: input::InputClassifier
+    /// [InputClassifier::classify_input]: input::InputClassifier
+    /// [HashedEventRegister]: input::HashedEventRegister
+    /// [DefaultInputClassifier]: input::DefaultInputClassifier
+    pub fn set_input_classifier(
+        &self,
+        handler: Box<dyn input::InputClassifier + Send + Sync>,
+    ) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetInputClassifier(handler))?)
+    }
+
+    /// Temporarily replace the current input classifier with `handler`
+    ///
+    /// The currently active input classifier is kept on a stack and gets restored when
+    /// [`Pager::pop_input_classifier`] is called. This makes it easy to implement modal key
+    /// bindings, for example entering a custom mode where keys have a different meaning and
+    /// returning to the normal bindings afterwards.
+    ///
+    /// The swap happens on the pager's event loop under the same lock that is used while
+    /// classifying user input, hence no key press is ever classified by a half-swapped
+    /// classifier. Any pending numeric prefix typed by the user is also cleared.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{input::{HashedEventRegister, InputEvent}, Pager};
+    ///
+    /// let pager = Pager::new();
+    /// let mut modal_bindings = HashedEventRegister::default();
+    /// modal_bindings.add_key_events(&["esc"], |_, _| InputEvent::Ignore);
+    ///
+    /// pager.push_input_classifier(Box::new(modal_bindings)).unwrap();
+    /// // ... and later return to the previous bindings
+    /// pager.pop_input_classifier().unwrap();
+    /// ```
+    pub fn push_input_classifier(
+        &self,
+        handler: Box<dyn input::InputClassifier + Send + Sync>,
+    ) -> Result<(), MinusError> {
+        Ok(self.send(Command::PushInputClassifier(handler))?)
+    }
+
+    /// Restore the input classifier that was active before the last call to
+    /// [`Pager::push_input_classifier`]
+    ///
+    /// If no input classifier has been pushed, this does nothing.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    pub fn pop_input_classifier(&self) -> Result<(), MinusError> {
+        Ok(self.send(Command::PopInputClassifier)?)
+    }
+
+    /// Adds a function that will be called when the user quits the pager
+    ///
+    /// Multiple functions can be stored for calling when the user quits. These functions
+    /// run sequentially in the order they were added
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// fn hello() {
+    ///     println!("Hello");
+    /// }
+    ///
+    /// let pager = Pager::new();
+    /// pager.add_exit_callback(Box::new(hello)).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn add_exit_callback(
+        &self,
+        cb: Box<dyn FnMut() + Send + Sync + 'static>,
+    ) -> Result<(), MinusError> {
+        Ok(self.send(Command::AddExitCallback(cb))?)
+    }
+
+    /// Add a command that the user can run by pressing `key`
+    ///
+    /// `key` is described just like in
+    /// [`HashedEventRegister::add_key_events`](input::HashedEventRegister::add_key_events), for
+    /// example `r` or `c-r` for `Ctrl+R`. It takes precedence over the actions that the input
+    /// classifier binds to the same key. The command can also be run by typing its `name` at the
+    /// `:` prompt. Adding a command with the name of an existing one replaces it.
+    ///
+    /// The `callback` runs on the thread that handles the pager's events and is given the
+    /// current state of the pager. It can use a clone of the [`Pager`] to push new content or
+    /// change the configuration, which is handled right after it returns.
+    ///
+    /// # Panics
+    /// This function will panic if `key` isn't a valid description of a key.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// let pager2 = pager.clone();
+    /// pager
+    ///     .add_command("r", "refresh", move |ps| {
+    ///         let _ = pager2.set_text(format!("Refreshed at line {}", ps.upper_mark));
+    ///     })
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn add_command(
+        &self,
+        key: &str,
+        name: impl Into<String>,
+        callback: impl FnMut(&PagerState) + Send + Sync + 'static,
+    ) -> Result<(), MinusError> {
+        let key = input::definitions::keydefs::parse_key_event(key);
+        Ok(self.send(Command::AddCustom(name.into(), key, Box::new(callback)))?)
+    }
+
+    /// Set a function that is called with the keys that aren't bound to any action
+    ///
+    /// A key isn't bound to any action if the input classifier doesn't map it to any
+    /// [`InputEvent`] or maps it to [`InputEvent::Ignore`].
+    ///
+    /// This lets applications add their own actions, like opening the selected item or refreshing
+    /// the data, without writing a whole [`InputClassifier`](input::InputClassifier). The function
+    /// is called on the thread that reads the user's input, so it should return quickly. It can
+    /// use a clone of the [`Pager`] to change what is displayed.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use crossterm::event::KeyCode;
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// let pager2 = pager.clone();
+    /// pager
+    ///     .set_unhandled_key_callback(move |key| {
+    ///         if key.code == KeyCode::Char('r') {
+    ///             let _ = pager2.set_text("Refreshed data");
+    ///         }
+    ///     })
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_unhandled_key_callback(
+        &self,
+        cb: impl FnMut(KeyEvent) + Send + Sync + 'static,
+    ) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetUnhandledKeyCallback(Box::new(cb)))?)
+    }
+
+    /// Set a function that decides whether the user may quit the pager
+    ///
+    /// When the user tries to quit the pager, like by pressing `q` or `Ctrl+C`, this function is
+    /// called with the [`ExitReason`] before anything is cleaned up. If it returns an error
+    /// message, the pager keeps running and the message is displayed at the prompt. This isn't
+    /// called when the application stops the pager with
+    /// [`PagerHandle::stop`](crate::PagerHandle::stop).
+    ///
+    /// By default, the user can always quit.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// // Ask the user to confirm the first time they try to quit
+    /// let mut confirmed = false;
+    /// pager
+    ///     .set_exit_veto(move |_| {
+    ///         if confirmed {
+    ///             return Ok(());
+    ///         }
+    ///         confirmed = true;
+    ///         Err(String::from("Unsaved annotations, press q again to quit"))
+    ///     })
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_exit_veto(
+        &self,
+        veto: impl FnMut(ExitReason) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetExitVeto(Box::new(veto)))?)
+    }
+
+    /// Set a function that checks the path to which the user asked to save the text
+    ///
+    /// When the user presses `s` and enters the name of a file, the path is passed to this
+    /// function before anything is written. It can return a different path to save the text to,
+    /// for example to resolve it against some directory, or an error message to refuse saving.
+    /// The message is then displayed at the prompt.
+    ///
+    /// By default, the text is saved to the path exactly as it was entered.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager
+    ///     .set_save_path_hook(|path| {
+    ///         if path.exists() {
+    ///             Err(format!("{} already exists", path.display()))
+    ///         } else {
+    ///             Ok(path)
+    ///         }
+    ///     })
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_save_path_hook(
+        &self,
+        hook: impl Fn(PathBuf) -> Result<PathBuf, String> + Send + Sync + 'static,
+    ) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetSavePathHook(Box::new(hook)))?)
+    }
+
+    /// Override the condition for running incremental search
+    ///
+    /// See [Incremental Search](../search/index.html#incremental-search) to know more on how this
+    /// works
+    ///
+    /// # Errors
+    /// This function will returns a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be send to the receiver end.
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn set_incremental_search_condition(
+        &self,
+        cb: Box<dyn Fn(&SearchOpts) -> bool + Send + Sync + 'static>,
+    ) -> crate::Result {
+        self.send(Command::IncrementalSearchCondition(cb))?;
+        Ok(())
+    }
+
+    /// Control whether to show the prompt
+    ///
+    /// Many applications don't want the prompt to be displayed at all. This function can be used to completely turn
+    /// off the prompt. Passing `false` to this will stops the prompt from displaying and instead a blank line will
+    /// be displayed.
+    ///
+    /// Note that This merely stop the prompt from being shown. Your application can still update the
+    /// prompt and send messages to the user but it won't be shown until the prompt isn't re-enabled.
+    /// The prompt section will also be used when user opens the search prompt to type a search query.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.show_prompt(false).unwrap();
+    /// ```
+    pub fn show_prompt(&self, show: bool) -> crate::Result {
+        self.send(Command::ShowPrompt(show))?;
+        Ok(())
+    }
+
+    /// Configure how appended text is batched before it is displayed
+    ///
+    /// When text is appended in many small pieces, like when it comes from a network stream,
+    /// formatting and drawing each piece separately becomes slow. Hence minus merges the appends
+    /// that are waiting to be handled and displays them at once. With a non-zero `flush_interval`,
+    /// minus also waits up to that long for more text to arrive before displaying it. At most
+    /// `max_batch` appends are merged together.
+    ///
+    /// By default, `flush_interval` is zero and `max_batch` is 256. This only has an effect
+    /// in dynamic paging.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    /// use std::time::Duration;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_append_batching(Duration::from_millis(16), 1024).unwrap();
+    /// ```
+    pub fn set_append_batching(&self, flush_interval: Duration, max_batch: usize) -> crate::Result {
+        self.send(Command::SetAppendBatching(flush_interval, max_batch))?;
+        Ok(())
+    }
+
+    /// Check the size of the terminal periodically
+    ///
+    /// Some environments, like certain multiplexers, CI emulators and Windows consoles, do not
+    /// reliably report when the terminal is resized. With `Some(interval)`, minus also compares the
+    /// terminal's size with the size of the layout every `interval` and redraws the screen for the
+    /// new size if they differ. `None` turns this off, which is the default.
+    ///
+    /// This only applies when minus draws to the terminal of the process. It has no effect
+    /// with [`set_output`](Pager::set_output) or [`set_viewport`](Pager::set_viewport).
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    /// use std::time::Duration;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_resize_poll_interval(Some(Duration::from_millis(500))).unwrap();
+    /// ```
+    pub fn set_resize_poll_interval(&self, interval: Option<Duration>) -> crate::Result {
+        Ok(self.send(Command::SetResizePollInterval(interval))?)
+    }
+
+    /// Serve the lines to be displayed from a [`LineProvider`]
+    ///
+    /// Instead of storing all the text up front, minus fetches only the lines that it is about
+    /// to display or search through from the `provider`. This replaces any text that was set
+    /// before. While a provider is set, text appended with [`push_str`](Pager::push_str) is
+    /// ignored and setting text with [`set_text`](Pager::set_text) removes the provider.
+    ///
+    /// Each line is displayed on exactly one row, so line wrapping is turned off and longer lines
+    /// can be viewed by horizontal scrolling. Incremental search is not available but matches can
+    /// still be navigated to once a search has been made.
+    ///
+    /// Lines that are already in memory can be shared with the pager without copying them by
+    /// passing them in an [`Arc`], like `Arc<[Arc<str>]>` or `Arc<Vec<String>>`. Static data
+    /// like `&'static [&'static str]` can be passed directly.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{LineProvider, Pager};
+    /// use std::{ops::Range, sync::Arc};
+    ///
+    /// struct Numbers;
+    ///
+    /// impl LineProvider for Numbers {
+    ///     fn len(&self) -> usize {
+    ///         1_000_000
+    ///     }
+    ///
+    ///     fn lines(&self, range: Range<usize>) -> Vec<String> {
+    ///         range.map(|n| n.to_string()).collect()
+    ///     }
+    /// }
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_line_provider(Numbers).unwrap();
+    ///
+    /// // The application keeps using the same lines
+    /// let shared: Arc<[Arc<str>]> = vec![Arc::from("first"), Arc::from("second")].into();
+    /// pager.set_line_provider(Arc::clone(&shared)).unwrap();
+    /// ```
+    pub fn set_line_provider(&self, provider: impl LineProvider) -> crate::Result {
+        self.send(Command::SetLineProvider(Box::new(provider)))?;
+        Ok(())
+    }
+
+    /// Set the maximum number of lines to keep
+    ///
+    /// When more lines are added, the oldest lines are discarded so that only the most recent
+    /// `max_lines` lines are kept, similar to `journalctl -f`. This keeps the memory used by
+    /// long-running applications that feed the pager forever in check. The retained lines keep
+    /// their line numbers. Setting this to 0 removes the limit, which is the default.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_max_lines(10_000).unwrap();
+    /// ```
+    pub fn set_max_lines(&self, max_lines: usize) -> crate::Result {
+        self.send(Command::SetMaxLines(max_lines))?;
+        Ok(())
+    }
+
+    /// Set the maximum number of times per second that appended text is drawn
+    ///
+    /// When text is appended faster than this, it is collected and drawn all at once on the next
+    /// refresh. This keeps thousands of appends per second from turning into thousands of
+    /// redraws. Setting this to 0 removes the limit. By default, this is 60.
+    ///
+    /// This only has an effect in dynamic paging. Also see
+    /// [set_append_batching](Pager::set_append_batching).
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_max_refresh_rate(30).unwrap();
+    /// ```
+    pub fn set_max_refresh_rate(&self, rate: u32) -> crate::Result {
+        self.send(Command::SetMaxRefreshRate(rate))?;
+        Ok(())
+    }
+
+    /// Whether to draw each frame as a synchronized update
+    ///
+    /// With synchronized output, the terminal holds back displaying the screen while minus is
+    /// drawing it and then displays the finished frame all at once. This eliminates tearing
+    /// during fast streaming updates. It uses the synchronized update mode (DEC mode 2026)
+    /// which is ignored by terminals that don't support it.
+    ///
+    /// By default, this is turned on unless the terminal is known to not handle it well.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_synchronized_output(false).unwrap();
+    /// ```
+    pub fn set_synchronized_output(&self, sync: bool) -> crate::Result {
+        self.send(Command::SetSynchronizedOutput(sync))?;
+        Ok(())
+    }
+
+    /// Tell the pager that all the data has been sent
+    ///
+    /// Some features need to know when no more data will arrive. For example with
+    /// [`set_quit_if_one_screen`](Pager::set_quit_if_one_screen), the pager quits if all the data
+    /// fits on one screen once this has been called. Once the end of the data is on the screen,
+    /// the prompt shows `(END)` like in `less`. If the output is being
+    /// [followed](Pager::follow_output), the screen moves to the end of the data a last time and
+    /// follow mode is turned off.
+    ///
+    /// All the data is known from the start in static mode, so this is not needed there.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.push_str("All the data").unwrap();
+    /// pager.finish_data().unwrap();
+    /// ```
+    pub fn finish_data(&self) -> crate::Result {
+        self.push_incomplete_char()?;
+        self.send(Command::DataFinished)?;
+        Ok(())
+    }
+
+    /// Quit without entering the pager if all the data fits on one screen
+    ///
+    /// This is the equivalent of `less -F` for dynamic mode. When this is set to `true`, minus
+    /// waits for either the data to overflow the screen or for [`finish_data`](Pager::finish_data)
+    /// to be called before deciding whether to start the pager. If the data is finished and it
+    /// fits on one screen, it is written to the main screen and minus quits without entering the
+    /// alternate screen. Note that nothing is displayed until then.
+    ///
+    /// In static mode this behaviour is controlled by
+    /// [`set_run_no_overflow`](Pager::set_run_no_overflow) instead as the data is always finished.
+    ///
+    /// By default this is set to `false`
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_quit_if_one_screen(true).unwrap();
+    /// ```
+    #[cfg(feature = "dynamic_output")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dynamic_output")))]
+    pub fn set_quit_if_one_screen(&self, val: bool) -> crate::Result {
+        self.send(Command::SetQuitIfOneScreen(val))?;
+        Ok(())
+    }
+
+    /// Quit once the user moves down past the end of the data
+    ///
+    /// This is the equivalent of `less -E`. When this is set to `true` and the end of the data
+    /// is already on the screen, pressing a key that scrolls down, like `Space` or `Down`, quits
+    /// the pager with [`ExitReason::EndOfData`](crate::ExitReason::EndOfData). In dynamic mode,
+    /// this only happens after [`finish_data`](Pager::finish_data) has been called as more data
+    /// might arrive otherwise.
+    ///
+    /// By default this is set to `false`
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_quit_at_eof(true).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_quit_at_eof(&self, val: bool) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetQuitAtEof(val))?)
+    }
+
+    /// Keep `rows` rows of the previous screen visible when moving by a page
+    ///
+    /// When paging down with `Space` or `PageDown`, the last rows of the previous screen are
+    /// shown again at the top of the new one, and likewise the first rows when paging up. This
+    /// gives readers some context so that they don't lose their place. The screen still moves by
+    /// at least one row if the overlap is as large as the screen.
+    ///
+    /// By default this is set to 0
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_page_overlap(2).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_page_overlap(&self, rows: usize) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetPageOverlap(rows))?)
+    }
+
+    /// Keep `rows` rows of context visible around the rows that are jumped to
+    ///
+    /// When moving to a search match, a line number with `G` or the [cursor line](Pager::set_cursor_line),
+    /// the screen is placed so that at least this many rows are shown above it, and below it for the
+    /// cursor line. This way the match doesn't land on the very first row of the screen, like
+    /// with the `scrolloff` option of `vim`. The margin is made smaller on screens that are too
+    /// small for it.
+    ///
+    /// By default this is set to 0
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_scroll_off(3).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_scroll_off(&self, rows: usize) -> Result<(), MinusError> {
+        Ok(self.send(Command::SetScrollOff(rows))?)
+    }
+
+    /// Set where the screen is placed in the text when the pager starts
+    ///
+    /// This lets tools like log viewers open the pager already at the end of the text or at the
+    /// first error, like `less +G` or `less +/pattern`. The position is applied once, using the
+    /// text that has been sent by the time the pager starts. In dynamic mode, use
+    /// [`follow_output`](Pager::follow_output) to keep showing the end of the text as it arrives.
+    ///
+    /// By default, the pager starts at the top of the text.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::SearchExpError)`](MinusError::SearchExpError)
+    /// if the pattern of [`Position::FirstMatch`](crate::Position::FirstMatch) is not a valid
+    /// regex or a [`Err(MinusError::Communication)`](MinusError::Communication) if the data could
+    /// not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{Pager, Position};
+    ///
+    /// let pager = Pager::new();
+    /// pager
+    ///     .set_initial_position(Position::Bottom)
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_initial_position(&self, position: crate::Position) -> crate::Result {
+        #[cfg(feature = "search")]
+        if let crate::Position::FirstMatch(pattern) = &position {
+            regex::Regex::new(pattern)?;
+        }
+        Ok(self.send(Command::SetInitialPosition(position))?)
+    }
+
+    /// Configures follow output
+    ///
+    /// When set to true, minus ensures that the user's screen always follows the end part of the
+    /// output. By default it is turned off.
+    ///
+    /// This is similar to [InputEvent::FollowOutput](crate::input::InputEvent::FollowOutput) except that
+    /// this is used to control it from the application's side.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.follow_output(true).unwrap();
     /// ```
-    #[must_use]
-    pub fn new() -> Self {
-        let (tx, rx) = crossbeam_channel::unbounded();
-        Self { tx, rx }
+    pub fn follow_output(&self, follow_output: bool) -> crate::Result {
+        self.send(Command::FollowOutput(follow_output))?;
+        Ok(())
     }
 
-    /// Set the output text to this `t`
+    /// Pause drawing and following the output while the terminal doesn't have the focus
     ///
-    /// Note that unlike [`Pager::push_str`], this replaces the original text.
-    /// If you want to append text, use the [`Pager::push_str`] function or the
-    /// [`write!`]/[`writeln!`] macros
+    /// This reduces the work done by pagers that keep receiving data, like log viewers, while
+    /// nobody is looking at them. If `repaints` is `true`, nothing is drawn while the terminal is
+    /// unfocused. If `follow_output` is `true`, the screen stays where it is instead of
+    /// [following the output](Pager::follow_output). Both resume with a full redraw once the
+    /// terminal regains the focus, catching up with the output that arrived in the meantime.
+    ///
+    /// This only has an effect on terminals that report when they gain or lose the focus. Others
+    /// are always treated as focused.
+    ///
+    /// By default both are set to `false`
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
@@ -65,20 +2647,29 @@ impl Pager {
     ///
     /// # Example
     /// ```
-    /// let pager = minus::Pager::new();
-    /// pager.set_text("This is a line").expect("Failed to send data to the pager");
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.follow_output(true).expect("Failed to communicate with the pager");
+    /// pager.set_unfocused_pause(true, true).expect("Failed to communicate with the pager");
     /// ```
-    pub fn set_text(&self, s: impl Into<String>) -> Result<(), MinusError> {
-        Ok(self.tx.send(Command::SetData(s.into()))?)
+    pub fn set_unfocused_pause(&self, repaints: bool, follow_output: bool) -> crate::Result {
+        Ok(self.send(Command::SetUnfocusedPause(repaints, follow_output))?)
     }
 
-    /// Appends text to the pager output.
+    /// Show a cursor line
     ///
-    /// You can also use [`write!`]/[`writeln!`] macros to append data to the pager.
-    /// The implementation basically calls this function internally. One difference
-    /// between using the macros and this function is that this does not require `Pager`
-    /// to be declared mutable while in order to use the macros, you need to declare
-    /// the `Pager` as mutable.
+    /// The cursor line is highlighted by a cursor bar, starting with the first line of the text.
+    /// `Up`/`k` and `Down`/`j` move the bar instead of scrolling the text, which only scrolls to
+    /// keep the bar on the screen. When the text is scrolled in other ways, like with `PageDown`,
+    /// the bar moves along to stay on the screen. By default it is turned off.
+    ///
+    /// The index of the cursor line is available as
+    /// [`PagerState::selected_line`](crate::PagerState::selected_line), for example to commands
+    /// added with [`add_command`](Pager::add_command) which act on the line that the user is
+    /// looking at.
+    ///
+    /// Turning this off also turns off [picker mode](Pager::set_picker_mode).
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
@@ -86,20 +2677,24 @@ impl Pager {
     ///
     /// # Example
     /// ```
-    /// use std::fmt::Write;
+    /// use minus::Pager;
     ///
-    /// let mut pager = minus::Pager::new();
-    /// pager.push_str("This is some text").expect("Failed to send data to the pager");
-    /// // This is same as above
-    /// write!(pager, "This is some text").expect("Failed to send data to the pager");
+    /// let pager = Pager::new();
+    /// pager.set_cursor_line(true).unwrap();
     /// ```
-    pub fn push_str(&self, s: impl Into<String>) -> Result<(), MinusError> {
-        Ok(self.tx.send(Command::AppendData(s.into()))?)
+    pub fn set_cursor_line(&self, val: bool) -> crate::Result {
+        Ok(self.send(Command::SetCursorLine(val))?)
     }
 
-    /// Set line number configuration for the pager
+    /// Let the user pick a line of the text
     ///
-    /// See [`LineNumbers`] for available options
+    /// In picker mode, the [cursor line](Pager::set_cursor_line) is shown and `Enter` quits the
+    /// pager with [`ExitReason::Picked`](crate::ExitReason::Picked) holding the index of the
+    /// cursor line. The application can then look up the line in its own data. This turns minus
+    /// into a simple chooser for command line applications. By default it is turned off.
+    ///
+    /// The line is picked even if [`ExitStrategy::ProcessQuit`](crate::ExitStrategy::ProcessQuit)
+    /// is set, since the application has to receive it.
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
@@ -107,48 +2702,51 @@ impl Pager {
     ///
     /// # Example
     /// ```
-    /// use minus::{Pager, LineNumbers};
+    /// use minus::Pager;
     ///
     /// let pager = Pager::new();
-    /// pager.set_line_numbers(LineNumbers::Enabled).expect("Failed to communicate with the pager");
+    /// pager.set_text("red\ngreen\nblue\n").unwrap();
+    /// pager.set_picker_mode(true).unwrap();
     /// ```
-    pub fn set_line_numbers(&self, l: LineNumbers) -> Result<(), MinusError> {
-        Ok(self.tx.send(Command::SetLineNumbers(l))?)
+    pub fn set_picker_mode(&self, val: bool) -> crate::Result {
+        Ok(self.send(Command::SetPickerMode(val))?)
     }
 
-    /// Set the text displayed at the bottom prompt
+    /// Highlight the given range of lines with `style`
     ///
-    /// # Panics
-    /// This function panics if the given text contains newline characters.
-    /// This is because, the pager reserves only one line for showing the prompt
-    /// and a newline will cause it to span multiple lines, breaking the display
+    /// Lines are counted from 0. This can mark regions of the text like the output of a failing
+    /// test or a hunk of a diff, usually with a background color. The highlight covers all the
+    /// rows of the lines up to the edge of the screen and stays on the lines as they are wrapped
+    /// and scrolled. If ranges overlap, the one highlighted last is drawn on top. The
+    /// [cursor line](Pager::set_cursor_line) is drawn above all of them.
+    ///
+    /// If lines are discarded because of [`set_max_lines`](Pager::set_max_lines), the ranges
+    /// move along with the remaining lines.
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
     /// could not be sent to the receiver
     ///
-    /// Example
+    /// # Example
     /// ```
+    /// use crossterm::style::{Color, ContentStyle, Stylize};
     /// use minus::Pager;
     ///
     /// let pager = Pager::new();
-    /// pager.set_prompt("my prompt").expect("Failed to send data to the pager");
+    /// pager.set_text("test a ... ok\ntest b ... FAILED\n  assertion failed\n").unwrap();
+    /// pager
+    ///     .highlight_lines(1..=2, ContentStyle::new().on(Color::DarkRed))
+    ///     .expect("Failed to communicate with the pager");
     /// ```
-    pub fn set_prompt(&self, text: impl Into<String>) -> Result<(), MinusError> {
-        let text: String = text.into();
-        assert!(!text.contains('\n'), "Prompt cannot contain newlines");
-        Ok(self.tx.send(Command::SetPrompt(text))?)
+    pub fn highlight_lines(
+        &self,
+        lines: impl RangeBounds<usize>,
+        style: ContentStyle,
+    ) -> crate::Result {
+        Ok(self.send(Command::HighlightLines(line_range(&lines), style))?)
     }
 
-    /// Send a message to be displayed the prompt area
-    ///
-    /// The text message is temporary and will get cleared whenever the use
-    /// rdoes a action on the terminal like pressing a key or scrolling using the mouse.
-    ///
-    /// # Panics
-    /// This function panics if the given text contains newline characters.
-    /// This is because, the pager reserves only one line for showing the prompt
-    /// and a newline will cause it to span multiple lines, breaking the display
+    /// Remove all the highlights added with [`highlight_lines`](Pager::highlight_lines)
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
@@ -159,130 +2757,125 @@ impl Pager {
     /// use minus::Pager;
     ///
     /// let pager = Pager::new();
-    /// pager.send_message("An error occurred").expect("Failed to send data to the pager");
+    /// pager.clear_highlights().expect("Failed to communicate with the pager");
     /// ```
-    pub fn send_message(&self, text: impl Into<String>) -> Result<(), MinusError> {
-        let text: String = text.into();
-        assert!(!text.contains('\n'), "Message cannot contain newlines");
-        Ok(self.tx.send(Command::SendMessage(text))?)
+    pub fn clear_highlights(&self) -> crate::Result {
+        Ok(self.send(Command::ClearHighlights)?)
     }
 
-    /// Set the default exit strategy.
+    /// Keep the first `lines` lines of the text at the top of the screen
     ///
-    /// This controls how the pager will behave when the user presses `q` or `Ctrl+C`.
-    /// See [`ExitStrategy`] for available options
+    /// The header stays in place while the rest of the text scrolls beneath it, like the column
+    /// names of a table from `ps` or an SQL query. Scrolling by a page, jumping to search matches
+    /// and the [cursor line](Pager::set_cursor_line) skip the rows covered by the header. At least
+    /// one row of the screen is always left for the rest of the text. Setting this to 0, the
+    /// default, removes the header.
+    ///
+    /// The header is not shown when the lines are served by a
+    /// [`LineProvider`](crate::LineProvider). If lines are discarded because of
+    /// [`set_max_lines`](Pager::set_max_lines), the first lines that remain become the header.
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
     /// could not be sent to the receiver
     ///
+    /// # Example
     /// ```
-    /// use minus::{Pager, ExitStrategy};
+    /// use minus::Pager;
     ///
     /// let pager = Pager::new();
-    /// pager.set_exit_strategy(ExitStrategy::ProcessQuit).expect("Failed to communicate with the pager");
+    /// pager.set_text("  PID CMD\n    1 init\n  412 sshd\n").unwrap();
+    /// pager.set_header_lines(1).expect("Failed to communicate with the pager");
     /// ```
-    pub fn set_exit_strategy(&self, es: ExitStrategy) -> Result<(), MinusError> {
-        Ok(self.tx.send(Command::SetExitStrategy(es))?)
+    pub fn set_header_lines(&self, lines: usize) -> crate::Result {
+        Ok(self.send(Command::SetHeaderLines(lines))?)
     }
 
-    /// Set whether to display pager if there's less data than
-    /// available screen height
-    ///
-    /// When this is set to false, the pager will simply print all the lines
-    /// to the main screen and immediately quit if the number of lines to
-    /// display is less than the available columns in the terminal.
-    /// Setting this to true will cause a full pager to start and display the data
-    /// even if there is less number of lines to display than available rows.
+    /// Place markers in the sign column before the given lines
     ///
-    /// This is only available in static output mode as the size of the data is
-    /// known beforehand.
-    /// In async output the pager can receive more data anytime
+    /// Each item pairs the index of a line, counted from 0, with the [`Sign`] to show before it,
+    /// replacing any sign already on that line. Signs can mark lines like the added and removed
+    /// lines of a diff or the lines with lint warnings. The sign column is placed before the line
+    /// numbers and is only shown once a sign is added. It is as wide as the widest sign so the
+    /// text stays aligned. Rows created by wrapping a line are left blank in the sign column.
     ///
-    /// By default this is set to false
+    /// Adding signs formats the text again, so it is best to add many signs in a single call.
+    /// If lines are discarded because of [`set_max_lines`](Pager::set_max_lines), the signs move
+    /// along with the remaining lines.
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
     /// could not be sent to the receiver
     ///
+    /// # Example
     /// ```
-    /// use minus::Pager;
+    /// use crossterm::style::{Color, ContentStyle, Stylize};
+    /// use minus::{Pager, Sign};
     ///
     /// let pager = Pager::new();
-    /// pager.set_run_no_overflow(true).expect("Failed to communicate with the pager");
+    /// pager.set_text(" context\n-removed\n+added\n").unwrap();
+    /// pager
+    ///     .set_signs([
+    ///         (1, Sign::new("-", ContentStyle::new().red())),
+    ///         (2, Sign::new("+", ContentStyle::new().with(Color::Green))),
+    ///     ])
+    ///     .expect("Failed to communicate with the pager");
     /// ```
-    #[cfg(feature = "static_output")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "static_output")))]
-    pub fn set_run_no_overflow(&self, val: bool) -> Result<(), MinusError> {
-        Ok(self.tx.send(Command::SetRunNoOverflow(val))?)
+    pub fn set_signs(&self, signs: impl IntoIterator<Item = (usize, Sign)>) -> crate::Result {
+        Ok(self.send(Command::SetSigns(signs.into_iter().collect()))?)
     }
 
-    /// Whether to allow scrolling horizontally
+    /// Remove all the signs added with [`set_signs`](Pager::set_signs)
     ///
-    /// Setting this to `true` implicitly disables line wrapping
+    /// The sign column is hidden until signs are added again.
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
     /// could not be sent to the receiver
     ///
+    /// # Example
     /// ```
     /// use minus::Pager;
     ///
     /// let pager = Pager::new();
-    /// pager.horizontal_scroll(true).expect("Failed to communicate with the pager");
+    /// pager.clear_signs().expect("Failed to communicate with the pager");
     /// ```
-    pub fn horizontal_scroll(&self, value: bool) -> Result<(), MinusError> {
-        Ok(self.tx.send(Command::LineWrapping(!value))?)
+    pub fn clear_signs(&self) -> crate::Result {
+        Ok(self.send(Command::ClearSigns)?)
     }
 
-    /// Set a custom input classifer type.
-    ///
-    /// An input classifier type is a type that implements the [InputClassifier]
-    /// trait. It only has one required function, [InputClassifier::classify_input]
-    /// which matches user input events and maps them to a [InputEvent]s.
-    /// When the pager encounters a user input, it calls the input classifier with
-    /// the event and [PagerState] as parameters.
-    ///
-    /// Previously, whenever any application wanted to change the default key/mouse bindings
-    /// they neededd to create a new type, implement the [InputClassifier] type by copying and
-    /// pasting the default minus's implementation of it available in the [DefaultInputClassifier]
-    /// and change the parts they wanted to change. This is not only unergonomic but also
-    /// extreemely prone to bugs. Hence a newer and much simpler method was developed.
-    /// This method is still allowed to avoid breaking backwards compatiblity but will be dropped
-    /// in the next major release.
+    /// Mark regions of the text which the user can fold
     ///
-    /// With the newer method, minus already provides a type called [HashedEventRegister]
-    /// which implementing the [InputClassifier] and is based on a
-    /// [HashMap] storing all the key/mouse bindings and its associated callback function.
-    /// This allows easy addition/updation/deletion of the default bindings with simple functions
-    /// like [HashedEventRegister::add_key_events] and [HashedEventRegister::add_mouse_events]
+    /// Each region is a range of line indices, counted from 0, like the body of a function or a
+    /// section of a document. Pressing `z` on a line folds the smallest region containing it so
+    /// that only the first line of the region is shown, followed by the number of lines hidden.
+    /// Pressing `z` on that line again opens the fold. `Z` folds all the outermost regions or
+    /// opens all the folds. Regions can be nested and regions with a single line are ignored.
     ///
-    /// See the [input] module for information about implementing it.
+    /// The lines hidden in a fold are skipped by search. Folding has no effect on the text of a
+    /// [line provider](Pager::set_line_provider).
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
     /// could not be sent to the receiver
     ///
-    /// [HashedEventRegister::add_key_events]: input::HashedEventRegister::add_key_events
-    /// [HashedEventRegister::add_mouse_events]: input::HashedEventRegister::add_mouse_events
-    /// [HashMap]: std::collections::HashMap
-    /// [PagerState]: crate::state::PagerState
-    /// [InputEvent]: input::InputEvent
-    /// [InputClassifier]: input::InputClassifier
-    /// [InputClassifier::classify_input]: input::InputClassifier
-    /// [HashedEventRegister]: input::HashedEventRegister
-    /// [DefaultInputClassifier]: input::DefaultInputClassifier
-    pub fn set_input_classifier(
-        &self,
-        handler: Box<dyn input::InputClassifier + Send + Sync>,
-    ) -> Result<(), MinusError> {
-        Ok(self.tx.send(Command::SetInputClassifier(handler))?)
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_text("[section]\nkey = value\nother = value\n").unwrap();
+    /// pager.add_folds([0..3]).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn add_folds(&self, regions: impl IntoIterator<Item = Range<usize>>) -> crate::Result {
+        Ok(self.send(Command::AddFolds(regions.into_iter().collect()))?)
     }
 
-    /// Adds a function that will be called when the user quits the pager
+    /// Let the user fold the lines followed by more indented lines
     ///
-    /// Multiple functions can be stored for calling when the user quits. These functions
-    /// run sequentially in the order they were added
+    /// When this is on, `z` can also fold a region made of a line and the lines after it which
+    /// are indented more than it, like the body of a block of code. Blank lines inside the region
+    /// belong to it. This is off by default. See [`add_folds`](Pager::add_folds) for the keys.
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
@@ -292,86 +2885,137 @@ impl Pager {
     /// ```
     /// use minus::Pager;
     ///
-    /// fn hello() {
-    ///     println!("Hello");
-    /// }
+    /// let pager = Pager::new();
+    /// pager.set_fold_by_indent(true).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_fold_by_indent(&self, val: bool) -> crate::Result {
+        Ok(self.send(Command::SetFoldByIndent(val))?)
+    }
+
+    /// Remove all the regions added with [`add_folds`](Pager::add_folds) and open all the folds
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
     ///
     /// let pager = Pager::new();
-    /// pager.add_exit_callback(Box::new(hello)).expect("Failed to communicate with the pager");
+    /// pager.clear_folds().expect("Failed to communicate with the pager");
     /// ```
-    pub fn add_exit_callback(
-        &self,
-        cb: Box<dyn FnMut() + Send + Sync + 'static>,
-    ) -> Result<(), MinusError> {
-        Ok(self.tx.send(Command::AddExitCallback(cb))?)
+    pub fn clear_folds(&self) -> crate::Result {
+        Ok(self.send(Command::ClearFolds)?)
     }
 
-    /// Override the condition for running incremental search
+    /// Mark lines as the starts of sections, like the headings of a document
     ///
-    /// See [Incremental Search](../search/index.html#incremental-search) to know more on how this
-    /// works
+    /// Each item pairs the index of a line, counted from 0, with the label of its section,
+    /// replacing any label already given to that line. The user can go to the next section with
+    /// `}` and to the previous one with `{`, or type a number before them to skip several
+    /// sections. `o` lists the labels of all the sections over the text, in the order of their
+    /// lines, to pick one to go to. When the cursor line is shown, it is moved to the section.
+    ///
+    /// If lines are discarded because of [`set_max_lines`](Pager::set_max_lines), the sections
+    /// move along with the remaining lines.
     ///
     /// # Errors
-    /// This function will returns a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
-    /// could not be send to the receiver end.
-    #[cfg(feature = "search")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
-    pub fn set_incremental_search_condition(
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_text("# Install\n...\n# Usage\n...\n").unwrap();
+    /// pager
+    ///     .add_sections([(0, "Install"), (2, "Usage")])
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    pub fn add_sections(
         &self,
-        cb: Box<dyn Fn(&SearchOpts) -> bool + Send + Sync + 'static>,
+        sections: impl IntoIterator<Item = (usize, impl Into<String>)>,
     ) -> crate::Result {
-        self.tx.send(Command::IncrementalSearchCondition(cb))?;
-        Ok(())
+        Ok(self.send(Command::AddSections(
+            sections
+                .into_iter()
+                .map(|(line, label)| (line, label.into()))
+                .collect(),
+        ))?)
     }
 
-    /// Control whether to show the prompt
-    ///
-    /// Many applications don't want the prompt to be displayed at all. This function can be used to completely turn
-    /// off the prompt. Passing `false` to this will stops the prompt from displaying and instead a blank line will
-    /// be displayed.
-    ///
-    /// Note that This merely stop the prompt from being shown. Your application can still update the
-    /// prompt and send messages to the user but it won't be shown until the prompt isn't re-enabled.
-    /// The prompt section will also be used when user opens the search prompt to type a search query.
+    /// Remove all the sections added with [`add_sections`](Pager::add_sections)
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
-    /// could not be sent to the mus's receiving end
+    /// could not be sent to the receiver
     ///
     /// # Example
     /// ```
     /// use minus::Pager;
     ///
     /// let pager = Pager::new();
-    /// pager.show_prompt(false).unwrap();
+    /// pager.clear_sections().expect("Failed to communicate with the pager");
     /// ```
-    pub fn show_prompt(&self, show: bool) -> crate::Result {
-        self.tx.send(Command::ShowPrompt(show))?;
-        Ok(())
+    pub fn clear_sections(&self) -> crate::Result {
+        Ok(self.send(Command::ClearSections)?)
     }
 
-    /// Configures follow output
+    /// Color every match of the regex `pattern` with `style`
     ///
-    /// When set to true, minus ensures that the user's screen always follows the end part of the
-    /// output. By default it is turned off.
+    /// This is useful to make parts of the text stand out, like the timestamps, IP addresses or log
+    /// levels in a log file. Unlike the highlights of a search, the rules stay in effect for all the
+    /// text until they are removed with [`clear_color_rules`](Pager::clear_color_rules).
     ///
-    /// This is similar to [InputEvent::FollowOutput](crate::input::InputEvent::FollowOutput) except that
-    /// this is used to control it from the application's side.
+    /// The rules are applied in the order they were added, on top of the styles of the
+    /// [line formatter](Pager::set_line_formatter). Where the matches of several rules overlap, the
+    /// colors of the rule added last win.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::SearchExpError)`](MinusError::SearchExpError)
+    /// if `pattern` is not a valid regex or a
+    /// [`Err(MinusError::Communication)`](MinusError::Communication) if the data could not be sent
+    /// to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use crossterm::style::{Color, ContentStyle, Stylize};
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager
+    ///     .add_color_rule(r"\bERROR\b", ContentStyle::new().red().bold())
+    ///     .expect("Failed to add the rule");
+    /// pager
+    ///     .add_color_rule(r"\d{2}:\d{2}:\d{2}", ContentStyle::new().with(Color::Blue))
+    ///     .expect("Failed to add the rule");
+    /// ```
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn add_color_rule(&self, pattern: &str, style: ContentStyle) -> crate::Result {
+        let regex = regex::Regex::new(pattern)?;
+        Ok(self.send(Command::AddColorRule(regex, style))?)
+    }
+
+    /// Remove all the rules added with [`add_color_rule`](Pager::add_color_rule)
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
-    /// could not be sent to the mus's receiving end
+    /// could not be sent to the receiver
     ///
     /// # Example
     /// ```
     /// use minus::Pager;
     ///
     /// let pager = Pager::new();
-    /// pager.follow_output(true).unwrap();
+    /// pager.clear_color_rules().expect("Failed to communicate with the pager");
     /// ```
-    pub fn follow_output(&self, follow_output: bool) -> crate::Result {
-        self.tx.send(Command::FollowOutput(follow_output))?;
-        Ok(())
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn clear_color_rules(&self) -> crate::Result {
+        Ok(self.send(Command::ClearColorRules)?)
     }
 }
 
@@ -381,8 +3025,153 @@ impl Default for Pager {
     }
 }
 
+/// Join `lines` into a block of text where each of them is terminated by a line break
+fn join_lines<S: AsRef<str>>(lines: impl IntoIterator<Item = S>) -> String {
+    let mut text = String::new();
+    for line in lines {
+        text.push_str(line.as_ref());
+        text.push('\n');
+    }
+    text
+}
+
+/// Read `reader` until the end of its input and pass the text to `push` as soon as it arrives
+///
+/// UTF-8 characters that are split across reads are joined back together and bytes which aren't
+/// valid UTF-8 are replaced with `U+FFFD REPLACEMENT CHARACTER`.
+fn read_text(
+    mut reader: impl Read,
+    mut push: impl FnMut(String) -> Result<(), MinusError>,
+) -> Result<(), MinusError> {
+    let mut buf = vec![0; 8192];
+    let mut pending = Vec::new();
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(MinusError::ReadInput(e)),
+        };
+        pending.extend_from_slice(&buf[..n]);
+        let text = take_complete_text(&mut pending);
+        if !text.is_empty() {
+            push(text)?;
+        }
+    }
+    if !pending.is_empty() {
+        push(String::from_utf8_lossy(&pending).into_owned())?;
+    }
+    Ok(())
+}
+
+/// Convert `lines` into a range of line indices
+fn line_range(lines: &impl RangeBounds<usize>) -> Range<usize> {
+    let start = match lines.start_bound() {
+        Bound::Included(start) => *start,
+        Bound::Excluded(start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match lines.end_bound() {
+        Bound::Included(end) => end.saturating_add(1),
+        Bound::Excluded(end) => *end,
+        Bound::Unbounded => usize::MAX,
+    };
+    start..end
+}
+
+/// Closes the batch opened by [`Pager::batch`] when it is dropped
+struct BatchGuard<'a>(&'a Pager);
+
+impl Drop for BatchGuard<'_> {
+    fn drop(&mut self) {
+        // The pager has already quit if this fails so there is nothing left to draw
+        let _ = self.0.send(Command::EndUpdate);
+    }
+}
+
+impl Clone for Pager {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            rx: self.rx.clone(),
+            running: self.running.clone(),
+            buffer: self.buffer.clone(),
+            utf8_pending: self.utf8_pending.clone(),
+            lossy_utf8: self.lossy_utf8,
+            clones: self.clones.clone(),
+        }
+    }
+}
+
 impl fmt::Write for Pager {
     fn write_str(&mut self, s: &str) -> fmt::Result {
         self.push_str(s).map_err(|_| fmt::Error)
     }
 }
+
+/// Appends the written bytes to the pager output
+///
+/// A UTF-8 character which is incomplete at the end of a write is held back until the rest of
+/// it is written, just like with [`Pager::push_bytes`]. Flushing the writer appends what is held
+/// back as `U+FFFD REPLACEMENT CHARACTER`.
+impl io::Write for Pager {
+    #[allow(clippy::significant_drop_tightening)]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut pending = self.utf8_pending.lock();
+        let prev_len = pending.len();
+        pending.extend_from_slice(buf);
+        if !self.lossy_utf8 {
+            if let Err(e) = std::str::from_utf8(&pending) {
+                if e.error_len().is_some() {
+                    pending.truncate(prev_len);
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+                }
+            }
+        }
+        self.push_complete_text(&mut pending)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.push_incomplete_char()
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
+}
+
+/// A [`tokio::io::AsyncWrite`] adapter for appending data to a [`Pager`]
+///
+/// This is returned by [`Pager::async_writer`]. Sending data to the pager never blocks, so all
+/// the writes complete immediately.
+///
+/// A UTF-8 character which is still incomplete when the writer is shut down is appended as
+/// `U+FFFD REPLACEMENT CHARACTER`. Flushing the writer doesn't do that, as
+/// [`tokio::io::copy`] flushes whenever the reader has no data ready.
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub struct AsyncWriter(Pager);
+
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncWrite for AsyncWriter {
+    fn poll_write(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<io::Result<usize>> {
+        std::task::Poll::Ready(io::Write::write(&mut self.get_mut().0, buf))
+    }
+
+    fn poll_flush(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        std::task::Poll::Ready(io::Write::flush(&mut self.get_mut().0))
+    }
+}
@@ -0,0 +1,297 @@
+//! Regions of the text which can be folded to hide all but their first line
+use super::{strip_ansi, Text};
+use std::{collections::BTreeMap, ops::Range};
+
+/// Foldable regions of the text and the ones that are currently folded
+///
+/// A region is a range of line indices. When it is folded, its first line stays on the screen
+/// and the rest of its lines are hidden. Folded regions never overlap: folding a region opens the
+/// folds inside it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Folds {
+    /// Regions marked by the application
+    regions: Vec<Range<usize>>,
+    /// Whether a line followed by more indented lines starts a region too
+    by_indent: bool,
+    /// Folded regions, mapping the index of the first line of each to the end of the region
+    closed: BTreeMap<usize, usize>,
+}
+
+impl Folds {
+    /// Make an empty set of regions
+    pub const fn new() -> Self {
+        Self {
+            regions: Vec::new(),
+            by_indent: false,
+            closed: BTreeMap::new(),
+        }
+    }
+
+    /// Add regions which can be folded
+    ///
+    /// Regions with fewer than two lines have nothing to hide and are ignored.
+    pub fn add_regions(&mut self, regions: impl IntoIterator<Item = Range<usize>>) {
+        self.regions
+            .extend(regions.into_iter().filter(|region| region.len() > 1));
+    }
+
+    /// Set whether lines followed by more indented lines can be folded
+    pub const fn set_by_indent(&mut self, by_indent: bool) {
+        self.by_indent = by_indent;
+    }
+
+    /// Remove all the regions marked by the application and open all the folds
+    pub fn clear(&mut self) {
+        self.regions.clear();
+        self.closed.clear();
+    }
+
+    /// Open all the folds
+    pub fn open_all(&mut self) {
+        self.closed.clear();
+    }
+
+    /// Check whether no region is folded
+    pub fn all_open(&self) -> bool {
+        self.closed.is_empty()
+    }
+
+    /// Get the number of lines hidden by the fold starting at `line`, if it is folded
+    pub fn hidden_after(&self, line: usize) -> Option<usize> {
+        self.closed.get(&line).map(|end| end - line - 1)
+    }
+
+    /// Get the folded region that hides `line`
+    fn hiding(&self, line: usize) -> Option<Range<usize>> {
+        self.closed
+            .range(..line)
+            .next_back()
+            .filter(|(_, end)| **end > line)
+            .map(|(start, end)| *start..*end)
+    }
+
+    /// Check whether `line` is hidden inside a folded region
+    pub fn is_hidden(&self, line: usize) -> bool {
+        self.hiding(line).is_some()
+    }
+
+    /// Get the closest line to `line` that is not hidden
+    ///
+    /// A hidden line is moved to the line after its fold if `forward` is true and that line
+    /// exists, otherwise to the first line of the fold.
+    pub fn skip_hidden(&self, line: usize, forward: bool, line_count: usize) -> usize {
+        match self.hiding(line) {
+            Some(fold) if forward && fold.end < line_count => fold.end,
+            Some(fold) => fold.start,
+            None => line,
+        }
+    }
+
+    /// Fold `region`, opening the folds inside it
+    fn close(&mut self, region: Range<usize>) {
+        let inner = self
+            .closed
+            .range(region.clone())
+            .map(|(start, _)| *start)
+            .collect::<Vec<_>>();
+        for start in inner {
+            self.closed.remove(&start);
+        }
+        self.closed.insert(region.start, region.end);
+    }
+
+    /// Open the fold starting at `line`, or else fold the innermost region containing it
+    ///
+    /// `text` is the text whose indentation is looked at and the regions are cut short at
+    /// `line_count` lines. Returns false if there is no region to fold.
+    pub fn toggle(&mut self, line: usize, text: &Text, line_count: usize) -> bool {
+        if self.closed.remove(&line).is_some() {
+            return true;
+        }
+        let mut innermost = self
+            .regions
+            .iter()
+            .filter(|region| region.contains(&line))
+            .min_by_key(|region| region.len())
+            .cloned();
+        if self.by_indent {
+            let lines = text.lines().collect::<Vec<_>>();
+            if let Some(region) = indent_region_containing(&lines, line) {
+                if innermost.as_ref().map_or(true, |r| region.len() < r.len()) {
+                    innermost = Some(region);
+                }
+            }
+        }
+        match innermost.map(|region| region.start..region.end.min(line_count)) {
+            Some(region) if region.len() > 1 && !self.is_hidden(region.start) => {
+                self.close(region);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Fold all the outermost regions
+    pub fn close_all(&mut self, text: &Text, line_count: usize) {
+        let mut regions = self.regions.clone();
+        if self.by_indent {
+            let lines = text.lines().collect::<Vec<_>>();
+            let mut line = 0;
+            while line < lines.len() {
+                match indent_region(&lines, line) {
+                    Some(region) => {
+                        line = region.end;
+                        regions.push(region);
+                    }
+                    None => line += 1,
+                }
+            }
+        }
+        // Outer regions come before the regions they contain
+        regions.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+        for region in regions {
+            let region = region.start..region.end.min(line_count);
+            if region.len() > 1
+                && !self.closed.contains_key(&region.start)
+                && !self.is_hidden(region.start)
+            {
+                self.close(region);
+            }
+        }
+    }
+
+    /// Move the regions when the lines in `old` are replaced by `added` lines
+    ///
+    /// Regions left with fewer than two lines are removed.
+    pub fn replace_lines(&mut self, old: Range<usize>, added: usize) {
+        let moved = |line: usize| {
+            if line <= old.start {
+                line
+            } else if line >= old.end {
+                line - old.end + old.start + added
+            } else {
+                line.min(old.start + added)
+            }
+        };
+        self.regions.retain_mut(|region| {
+            *region = moved(region.start)..moved(region.end);
+            region.len() > 1
+        });
+        self.closed = std::mem::take(&mut self.closed)
+            .into_iter()
+            .map(|(start, end)| (moved(start), moved(end)))
+            .filter(|(start, end)| end - start > 1)
+            .collect();
+    }
+
+    /// Remove the first `count` lines, along with the regions starting in them
+    pub fn remove_first_lines(&mut self, count: usize) {
+        self.regions.retain(|region| region.start >= count);
+        for region in &mut self.regions {
+            *region = region.start - count..region.end - count;
+        }
+        self.closed = self
+            .closed
+            .split_off(&count)
+            .into_iter()
+            .map(|(start, end)| (start - count, end - count))
+            .collect();
+    }
+
+    /// Cut the regions short at `count` lines
+    pub fn truncate(&mut self, count: usize) {
+        self.replace_lines(count..usize::MAX, 0);
+    }
+}
+
+/// Get the width of the indentation of `line`, or `None` if it is blank
+fn indent(line: &str) -> Option<usize> {
+    let line = strip_ansi(line);
+    let trimmed = line.trim_start();
+    (!trimmed.is_empty()).then(|| line.len() - trimmed.len())
+}
+
+/// Get the region formed by the line at `start` and the more indented lines following it
+///
+/// Blank lines inside the region belong to it while the ones at its end don't.
+fn indent_region(lines: &[&str], start: usize) -> Option<Range<usize>> {
+    let start_indent = indent(lines.get(start)?)?;
+    let mut end = start + 1;
+    for (idx, line) in lines.iter().enumerate().skip(start + 1) {
+        match indent(line) {
+            Some(line_indent) if line_indent <= start_indent => break,
+            Some(_) => end = idx + 1,
+            None => {}
+        }
+    }
+    (end - start > 1).then_some(start..end)
+}
+
+/// Get the smallest indentation region that contains `line`
+fn indent_region_containing(lines: &[&str], line: usize) -> Option<Range<usize>> {
+    if let Some(region) = indent_region(lines, line) {
+        return Some(region);
+    }
+    // Only a less indented line can start a region containing this one
+    let mut max_indent = lines.get(line).and_then(|text| indent(text));
+    for start in (0..line).rev() {
+        let Some(start_indent) = indent(lines[start]) else {
+            continue;
+        };
+        if max_indent.map_or(true, |max| start_indent < max) {
+            match indent_region(lines, start) {
+                Some(region) if region.contains(&line) => return Some(region),
+                _ => max_indent = Some(start_indent),
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Folds, Text};
+
+    const TEXT: &str = "fn main() {\n    if x {\n        a();\n\n        b();\n    }\n\n}\n";
+
+    #[test]
+    fn fold_by_indent() {
+        let text = Text::from(TEXT);
+        let mut folds = Folds::default();
+        folds.set_by_indent(true);
+        // The line inside the `if` folds the `if` and not the whole function
+        assert!(folds.toggle(2, &text, 8));
+        assert_eq!(folds.hidden_after(1), Some(3));
+        assert!(folds.is_hidden(4));
+        assert!(!folds.is_hidden(5));
+        // Folding the function opens the fold inside it
+        assert!(folds.toggle(0, &text, 8));
+        assert_eq!(folds.hidden_after(0), Some(5));
+        assert_eq!(folds.hidden_after(1), None);
+        assert_eq!(folds.skip_hidden(3, true, 8), 6);
+        assert_eq!(folds.skip_hidden(3, false, 8), 0);
+        // The last line isn't indented and has nothing to fold
+        assert!(!folds.toggle(7, &text, 8));
+        assert!(folds.toggle(0, &text, 8));
+        assert!(folds.all_open());
+    }
+
+    #[test]
+    fn fold_regions() {
+        let text = Text::from(TEXT);
+        let mut folds = Folds::default();
+        folds.add_regions([0..1, 2..6, 3..5]);
+        assert!(folds.toggle(4, &text, 8));
+        assert_eq!(folds.hidden_after(3), Some(1));
+        folds.close_all(&text, 8);
+        assert_eq!(folds.hidden_after(2), Some(3));
+        assert_eq!(folds.hidden_after(3), None);
+
+        folds.remove_first_lines(1);
+        assert_eq!(folds.hidden_after(1), Some(3));
+        folds.truncate(3);
+        assert_eq!(folds.hidden_after(1), Some(1));
+        folds.truncate(2);
+        assert!(folds.all_open());
+    }
+}
@@ -3,12 +3,18 @@
 //! This module is still a work is progress and is subject to change.
 use crate::{
     minus_core::{self, utils::LinesRowMap},
-    LineNumbers,
+    ColorSupport, ControlChars, LineNumberContinuation, LineNumbers, Theme,
 };
+use crossterm::style::{Attribute, ContentStyle};
 #[cfg(feature = "search")]
 use regex::Regex;
 
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::BTreeMap, fmt::Write, io, ops::Range, sync::Arc};
+
+mod folds;
+mod text;
+pub(crate) use folds::Folds;
+pub(crate) use text::Text;
 
 #[cfg(feature = "search")]
 use {crate::search, std::collections::BTreeSet};
@@ -22,6 +28,266 @@ pub type Line<'a> = &'a str;
 pub type TextBlock<'a> = &'a str;
 pub type OwnedTextBlock = String;
 
+/// A function that formats the line number shown in the gutter
+///
+/// It receives the number to be displayed and must return the entire gutter text including any
+/// padding, separator and styling.
+pub type LineNumberFormatter = Box<dyn Fn(usize) -> String + Send + Sync + 'static>;
+
+/// A function that applies styles to a line of the text, like syntax highlighting
+///
+/// It receives the text of the line and its index, counted from 0.
+pub type LineFormatter = Box<dyn Fn(&str, usize) -> StyledLine + Send + Sync + 'static>;
+
+/// A marker displayed in the sign column before a line
+///
+/// See [`Pager::set_signs`](crate::Pager::set_signs) for more info.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sign {
+    /// Text of the marker, usually a single character like `+` or `!`
+    pub text: String,
+    /// Style applied to the text
+    pub style: ContentStyle,
+}
+
+impl Sign {
+    /// Make a sign displaying `text` with the given `style`
+    pub fn new(text: impl Into<String>, style: ContentStyle) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+}
+
+/// A line of text with styles applied to parts of it
+///
+/// Instead of embedding ANSI escape sequences in the text, applications can attach styles to
+/// ranges of the text and minus writes the escape sequences for them. Styles are kept intact when
+/// the line is wrapped onto several rows. See [`Pager::push_line`](crate::Pager::push_line).
+///
+/// # Example
+/// ```
+/// use crossterm::style::{ContentStyle, Stylize};
+/// use minus::StyledLine;
+///
+/// let line = StyledLine::new("error: file not found")
+///     .style(0..5, ContentStyle::new().bold().red())
+///     .style(7..11, ContentStyle::new().underlined());
+/// assert_eq!(line.text(), "error: file not found");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StyledLine {
+    text: String,
+    spans: Vec<(Range<usize>, ContentStyle)>,
+}
+
+impl StyledLine {
+    /// Make a line displaying `text` without any styles
+    ///
+    /// Newlines in `text` are displayed as spaces.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            spans: Vec::new(),
+        }
+    }
+
+    /// Apply `style` to the bytes of the text in `range`
+    ///
+    /// The ends of the range are moved back to the start of the characters that they fall in and
+    /// any part of it past the end of the text is ignored. Where ranges overlap, the colors of the
+    /// one applied last are used and the attributes of all of them are combined.
+    #[must_use]
+    pub fn style(mut self, range: Range<usize>, style: ContentStyle) -> Self {
+        self.spans.push((range, style));
+        self
+    }
+
+    /// Get the text of the line without any styles
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl std::fmt::Display for StyledLine {
+    /// Write the text with ANSI escape sequences for the styles
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_styled(f, ColorSupport::TrueColor)
+    }
+}
+
+impl StyledLine {
+    /// Write the text with ANSI escape sequences for the styles, using only the colors allowed by
+    /// `color_support`
+    fn write_styled(
+        &self,
+        f: &mut impl std::fmt::Write,
+        color_support: ColorSupport,
+    ) -> std::fmt::Result {
+        let text = self.text.replace('\n', " ");
+        let floor = |idx: usize| {
+            let mut idx = idx.min(text.len());
+            while !text.is_char_boundary(idx) {
+                idx -= 1;
+            }
+            idx
+        };
+        let spans: Vec<_> = self
+            .spans
+            .iter()
+            .map(|(range, style)| (floor(range.start)..floor(range.end), style))
+            .collect();
+        // The text is split into parts wherever a range starts or ends, each with a single style
+        let mut bounds: Vec<usize> = spans
+            .iter()
+            .flat_map(|(range, _)| [range.start, range.end])
+            .chain([0, text.len()])
+            .collect();
+        bounds.sort_unstable();
+        bounds.dedup();
+        for part in bounds.windows(2) {
+            let mut style = ContentStyle::new();
+            for (_, span) in spans.iter().filter(|(range, _)| range.contains(&part[0])) {
+                style.foreground_color = span.foreground_color.or(style.foreground_color);
+                style.background_color = span.background_color.or(style.background_color);
+                style.underline_color = span.underline_color.or(style.underline_color);
+                style.attributes.extend(span.attributes);
+            }
+            let part = &text[part[0]..part[1]];
+            let style = color_support.adapt(style);
+            if style == ContentStyle::new() {
+                f.write_str(part)?;
+            } else {
+                write!(f, "{}", style.apply(part))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A source of lines that are fetched only when they are needed
+///
+/// Instead of giving all the text to minus up front, applications can implement this trait to
+/// serve lines lazily, for example from a database, a file index or a generator. minus only asks
+/// for the lines that it is about to display or search through.
+///
+/// Each line is displayed on exactly one row of the terminal. Lines that are longer than the
+/// terminal's width can be viewed by horizontal scrolling. Lines must not contain a newline; any
+/// text after a newline is not displayed.
+///
+/// See [`Pager::set_line_provider`](crate::Pager::set_line_provider)
+///
+/// # Example
+/// ```
+/// use minus::LineProvider;
+/// use std::ops::Range;
+///
+/// // Serves the squares of the first million numbers
+/// struct Squares;
+///
+/// impl LineProvider for Squares {
+///     fn len(&self) -> usize {
+///         1_000_000
+///     }
+///
+///     fn lines(&self, range: Range<usize>) -> Vec<String> {
+///         range.map(|n| format!("{n}^2 = {}", n * n)).collect()
+///     }
+/// }
+/// ```
+pub trait LineProvider: Send + Sync + 'static {
+    /// Get the total number of lines
+    ///
+    /// This can change over time as the number of lines grows or shrinks.
+    fn len(&self) -> usize;
+
+    /// Whether there are no lines at all
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the lines whose indices lie in `range`
+    ///
+    /// The range never goes beyond [`len`](LineProvider::len). If fewer lines are returned than
+    /// requested, the remaining ones are treated as missing.
+    fn lines(&self, range: Range<usize>) -> Vec<String>;
+}
+
+/// Lines stored in memory are served without being copied as a whole
+///
+/// Only the lines that minus is about to display or search through are copied. Wrapping the lines
+/// in an [`Arc`] shares them between the application and the pager without duplicating them, e.g.
+/// `Arc<[Arc<str>]>` or `Arc<Vec<String>>`.
+impl<L> LineProvider for [L]
+where
+    L: AsRef<str> + Send + Sync + 'static,
+{
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn lines(&self, range: Range<usize>) -> Vec<String> {
+        let end = range.end.min(self.len());
+        self[range.start.min(end)..end]
+            .iter()
+            .map(|line| line.as_ref().to_string())
+            .collect()
+    }
+}
+
+impl<L> LineProvider for Vec<L>
+where
+    L: AsRef<str> + Send + Sync + 'static,
+{
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn lines(&self, range: Range<usize>) -> Vec<String> {
+        LineProvider::lines(self.as_slice(), range)
+    }
+}
+
+impl<P: LineProvider + ?Sized> LineProvider for Arc<P> {
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    fn lines(&self, range: Range<usize>) -> Vec<String> {
+        (**self).lines(range)
+    }
+}
+
+impl<P: LineProvider + ?Sized> LineProvider for &'static P {
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    fn lines(&self, range: Range<usize>) -> Vec<String> {
+        (**self).lines(range)
+    }
+}
+
+/// Write all the lines of `provider` to `out`, fetching a chunk of them at a time
+pub(crate) fn write_provider_lines(
+    out: &mut impl io::Write,
+    provider: &dyn LineProvider,
+) -> io::Result<()> {
+    const CHUNK_SIZE: usize = 1024;
+    let len = provider.len();
+    let mut start = 0;
+    while start < len {
+        let end = start.saturating_add(CHUNK_SIZE).min(len);
+        for line in provider.lines(start..end) {
+            writeln!(out, "{line}")?;
+        }
+        start = end;
+    }
+    Ok(())
+}
+
 // ||||||||||||||||||||||||||||||||||||||||||||||
 //  SCREEN TYPE AND ITS REKATED FUNCTIONS
 // ||||||||||||||||||||||||||||||||||||||||||||||
@@ -34,7 +300,7 @@ pub type OwnedTextBlock = String;
 /// Most of the functions of this type are cheap as minus does a lot of caching of the analysis
 /// behind the scenes
 pub struct Screen {
-    pub(crate) orig_text: OwnedTextBlock,
+    pub(crate) orig_text: Text,
     pub(crate) formatted_lines: Rows,
     pub(crate) line_count: usize,
     pub(crate) max_line_length: usize,
@@ -46,6 +312,55 @@ pub struct Screen {
     ///
     /// Its negation gives the state of whether horizontal scrolling is allowed.
     pub(crate) line_wrapping: bool,
+    /// Index of the line at the top of the screen when the text was last formatted
+    ///
+    /// This is used to calculate the displayed numbers for [`LineNumbers::Relative`] and
+    /// [`LineNumbers::Hybrid`].
+    pub(crate) line_number_anchor: usize,
+    /// Custom formatter for the line number gutter
+    ///
+    /// If this is `None`, line numbers are displayed right aligned and followed by a `.`
+    pub(crate) line_number_formatter: Option<LineNumberFormatter>,
+    /// Custom formatter applying styles to each line
+    pub(crate) line_formatter: Option<LineFormatter>,
+    /// Styles applied to the matches of each regex in every line
+    #[cfg(feature = "search")]
+    pub(crate) color_rules: Vec<(Regex, ContentStyle)>,
+    /// What to display in the gutter for the wrapped rows of a line
+    pub(crate) line_number_continuation: LineNumberContinuation,
+    /// Line number of the first line
+    pub(crate) line_number_start: usize,
+    /// Number of lines discarded from the start of the text to keep it within
+    /// [`max_lines`](crate::state::PagerState::max_lines)
+    pub(crate) discarded_lines: usize,
+    /// Number of columns between each tab stop
+    pub(crate) tab_width: usize,
+    /// How to display control characters
+    pub(crate) control_chars: ControlChars,
+    /// Whether to remove the ANSI escape sequences from the text
+    pub(crate) strip_ansi: bool,
+    /// Colors that the terminal can display
+    pub(crate) color_support: ColorSupport,
+    /// Styles of the prompt, line numbers, search matches and selection
+    pub(crate) theme: Theme,
+    /// Whether to make tabs, non-breaking spaces and trailing spaces visible
+    pub(crate) show_invisibles: bool,
+    /// Text placed before each row of a line that has been wrapped onto it
+    pub(crate) wrap_prefix: String,
+    /// Text placed after each row of a line that continues onto the next row
+    pub(crate) wrap_suffix: String,
+    /// Markers displayed in the sign column, keyed by the index of their line
+    pub(crate) signs: BTreeMap<usize, Sign>,
+    /// Regions of the text which can be folded and the ones that are folded
+    pub(crate) folds: Folds,
+    /// Number of columns at the start of the text that stay in place when scrolling horizontally
+    pub(crate) frozen_columns: usize,
+    /// Source of the lines if they are fetched on demand instead of being stored in
+    /// [`orig_text`](Screen::orig_text)
+    pub(crate) line_provider: Option<Box<dyn LineProvider>>,
+    /// Index of the line fetched from the [`line_provider`](Screen::line_provider) which is the
+    /// first row in [`formatted_lines`](Screen::formatted_lines)
+    pub(crate) provider_window_start: usize,
 }
 
 impl Screen {
@@ -53,7 +368,9 @@ impl Screen {
     /// terminal
     #[must_use]
     pub fn formatted_lines_count(&self) -> usize {
-        self.formatted_lines.len()
+        self.line_provider
+            .as_ref()
+            .map_or(self.formatted_lines.len(), |provider| provider.len())
     }
     /// Get the number of [`Lines`](std::str::Lines) in the text.
     #[must_use]
@@ -61,22 +378,270 @@ impl Screen {
         self.line_count
     }
     /// Returns all the [Rows] within the bounds
+    ///
+    /// If the lines are served by a [`LineProvider`], only the rows that have been fetched with
+    /// [`fetch_provider_window`](Screen::fetch_provider_window) are available.
     pub(crate) fn get_formatted_lines_with_bounds(&self, start: usize, end: usize) -> &[Row] {
-        if start >= self.formatted_lines_count() || start > end {
+        let start = start.saturating_sub(self.provider_window_start);
+        let end = end.saturating_sub(self.provider_window_start);
+        if start >= self.formatted_lines.len() || start > end {
             &[]
-        } else if end >= self.formatted_lines_count() {
+        } else if end >= self.formatted_lines.len() {
             &self.formatted_lines[start..]
         } else {
             &self.formatted_lines[start..end]
         }
     }
 
+    /// Write the original text, before any formatting, to `out`
+    ///
+    /// If the lines are served by a [`LineProvider`], all of its lines are written.
+    pub(crate) fn write_text(&self, out: &mut impl io::Write) -> io::Result<()> {
+        match self.line_provider.as_deref() {
+            Some(provider) => write_provider_lines(out, provider),
+            None => self.orig_text.write_to(out),
+        }
+    }
+
+    /// Write the original text of the lines in `range` to `out`
+    pub(crate) fn write_lines(
+        &self,
+        out: &mut impl io::Write,
+        range: Range<usize>,
+    ) -> io::Result<()> {
+        match self.line_provider.as_deref() {
+            Some(provider) => {
+                let range = range.start.min(provider.len())..range.end.min(provider.len());
+                for line in provider.lines(range) {
+                    writeln!(out, "{line}")?;
+                }
+            }
+            None => self.orig_text.write_lines(out, range)?,
+        }
+        Ok(())
+    }
+
     /// Get the length of the longest [Line] in the text.
     #[must_use]
     pub const fn get_max_line_length(&self) -> usize {
         self.max_line_length
     }
 
+    /// Get the line number of the first line in the text
+    ///
+    /// The lines that were discarded from the start of the text still count, so that the remaining
+    /// lines keep their numbers.
+    pub(crate) const fn first_line_number(&self) -> usize {
+        self.line_number_start + self.discarded_lines
+    }
+
+    /// Get the line number of the last line in the text
+    pub(crate) const fn last_line_number(&self) -> usize {
+        (self.line_count + self.first_line_number()).saturating_sub(1)
+    }
+
+    /// Get the number of columns taken by the gutter, made of the sign column and line numbers
+    ///
+    /// This is 0 if line numbers are turned off and there are no signs.
+    pub(crate) fn gutter_width(&self, line_numbers: LineNumbers) -> usize {
+        if !line_numbers.is_on() {
+            return self.sign_column_width();
+        }
+        let gutter = line_number_gutter(
+            self.last_line_number(),
+            minus_core::utils::digits(self.last_line_number()),
+            self.line_number_formatter.as_ref(),
+            self.theme.line_numbers,
+        );
+        self.sign_column_width() + textwrap::core::display_width(&gutter)
+    }
+
+    /// Get the number of columns at the start of each row that stay in place when scrolling
+    /// horizontally
+    ///
+    /// These are the gutter and the [frozen columns](Screen::frozen_columns) of the text.
+    pub(crate) fn fixed_width(&self, line_numbers: LineNumbers) -> usize {
+        self.gutter_width(line_numbers) + self.frozen_columns
+    }
+
+    /// Get the number of columns taken by the sign column, including the space after it
+    ///
+    /// This is 0 if there are no signs.
+    pub(crate) fn sign_column_width(&self) -> usize {
+        self.signs
+            .values()
+            .map(|sign| textwrap::core::display_width(&sign.text) + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Make sure that the `count` lines starting from `start` have been fetched from the
+    /// [`LineProvider`] and formatted
+    ///
+    /// A page of lines before and after them is fetched as well so that scrolling a little doesn't
+    /// require fetching again. This does nothing if no provider is set.
+    pub(crate) fn fetch_provider_window(
+        &mut self,
+        start: usize,
+        count: usize,
+        line_numbers: LineNumbers,
+        cols: usize,
+        #[cfg(feature = "search")] search_term: &Option<Regex>,
+    ) {
+        let Some(provider) = self.line_provider.as_ref() else {
+            return;
+        };
+        let len = provider.len();
+        let end = start.saturating_add(count).min(len);
+        let window_end = self.provider_window_start + self.formatted_lines.len();
+        if self.line_count == len && start >= self.provider_window_start && end <= window_end {
+            return;
+        }
+
+        let fetch_start = start.min(len).saturating_sub(count);
+        let fetch_end = end.saturating_add(count).min(len);
+        let lines = provider.lines(fetch_start..fetch_end);
+
+        self.line_count = len;
+        let line_number_digits = minus_core::utils::digits(self.last_line_number());
+        let sign_column_width = self.sign_column_width();
+        #[cfg(feature = "search")]
+        let mut search_idx = BTreeSet::new();
+
+        self.formatted_lines.clear();
+        for (offset, line) in lines.iter().enumerate() {
+            let line = line.split('\n').next().unwrap_or_default();
+            let (rows, line_length) = self.format_line(
+                line,
+                fetch_start + offset,
+                line_number_digits,
+                sign_column_width,
+                line_numbers,
+                cols,
+                false,
+                #[cfg(feature = "search")]
+                0,
+                #[cfg(feature = "search")]
+                &mut search_idx,
+                #[cfg(feature = "search")]
+                search_term,
+            );
+            self.max_line_length = self.max_line_length.max(line_length);
+            self.formatted_lines.extend(rows);
+        }
+        self.provider_window_start = fetch_start;
+    }
+
+    /// Format the lines in `lines` on their own, without touching the rest of the text
+    ///
+    /// Returns the rows of the lines along with the index of the first row of each line, counting
+    /// from `first_row`. The rows with search matches are added to `search_idx`.
+    #[allow(clippy::ref_option)]
+    pub(crate) fn format_line_range(
+        &mut self,
+        lines: Range<usize>,
+        first_row: usize,
+        line_numbers: LineNumbers,
+        cols: usize,
+        #[cfg(feature = "search")] search_idx: &mut BTreeSet<usize>,
+        #[cfg(feature = "search")] search_term: &Option<Regex>,
+    ) -> (Rows, Vec<usize>) {
+        let line_number_digits = minus_core::utils::digits(self.last_line_number());
+        let sign_column_width = self.sign_column_width();
+        let mut rows = Rows::new();
+        let mut first_rows = Vec::with_capacity(lines.len());
+        let mut max_line_length = self.max_line_length;
+
+        for (idx, line) in lines.clone().zip(self.orig_text.lines_from(lines.start)) {
+            first_rows.push(first_row + rows.len());
+            let (line_rows, line_length) = self.format_line(
+                line,
+                idx,
+                line_number_digits,
+                sign_column_width,
+                line_numbers,
+                cols,
+                self.line_wrapping,
+                #[cfg(feature = "search")]
+                (first_row + rows.len()),
+                #[cfg(feature = "search")]
+                search_idx,
+                #[cfg(feature = "search")]
+                search_term,
+            );
+            max_line_length = max_line_length.max(line_length);
+            rows.extend(line_rows);
+        }
+        self.max_line_length = max_line_length;
+        (rows, first_rows)
+    }
+
+    /// Format a single line of the text which is at index `idx`
+    ///
+    /// Returns the rows of the line and the length of the line before it is wrapped.
+    #[allow(clippy::too_many_arguments, clippy::ref_option)]
+    fn format_line(
+        &self,
+        line: &str,
+        idx: usize,
+        line_number_digits: usize,
+        sign_column_width: usize,
+        line_numbers: LineNumbers,
+        cols: usize,
+        line_wrapping: bool,
+        #[cfg(feature = "search")] formatted_idx: usize,
+        #[cfg(feature = "search")] search_idx: &mut BTreeSet<usize>,
+        #[cfg(feature = "search")] search_term: &Option<Regex>,
+    ) -> (Rows, usize) {
+        let line = if self.strip_ansi {
+            strip_ansi(line)
+        } else {
+            Cow::Borrowed(line)
+        };
+        let line = render_overstrike(&line, self.control_chars);
+        let line = escape_control_chars(&line, self.control_chars);
+        let line = expand_tabs(&line, self.tab_width, self.show_invisibles);
+        let line = if self.show_invisibles {
+            mark_invisibles(&line)
+        } else {
+            Cow::Borrowed(&*line)
+        };
+        let line = style_line(
+            &line,
+            idx,
+            self.line_formatter.as_ref(),
+            #[cfg(feature = "search")]
+            &self.color_rules,
+            self.color_support,
+        );
+
+        let rows = formatted_line(
+            &line,
+            line_number_digits,
+            idx,
+            line_numbers,
+            self.line_number_anchor,
+            self.line_number_formatter.as_ref(),
+            self.line_number_continuation,
+            self.first_line_number(),
+            self.signs.get(&idx),
+            sign_column_width,
+            self.color_support,
+            &self.theme,
+            cols,
+            line_wrapping,
+            &self.wrap_prefix,
+            &self.wrap_suffix,
+            #[cfg(feature = "search")]
+            formatted_idx,
+            #[cfg(feature = "search")]
+            search_idx,
+            #[cfg(feature = "search")]
+            search_term,
+        );
+        (rows, line.len())
+    }
+
     /// Insert the text into the []
     pub(crate) fn push_screen_buf(
         &mut self,
@@ -90,9 +655,13 @@ impl Screen {
         //
         // Appropriately in that case we set the last lne of self.screen.orig_text as attachment
         // text for the FormatOpts.
-        let clean_append = self.orig_text.ends_with('\n') || self.orig_text.is_empty();
+        let clean_append = self.orig_text.ends_with("\n") || self.orig_text.is_empty();
         // We check if number of digits in current line count change during this text push.
         let old_lc = self.line_count();
+        // Number of rows before the rows of the unterminated last line are removed. The formatter
+        // itself accounts for those rows being formatted again along with the incoming text
+        let formatted_lines_count = self.formatted_lines.len();
+        let sign_column_width = self.sign_column_width();
 
         // Conditionally appends to [`self.formatted_lines`] or changes the last unterminated rows of
         // [`self.formatted_lines`]
@@ -106,21 +675,38 @@ impl Screen {
             let attachment = if clean_append {
                 None
             } else {
-                self.orig_text.lines().last()
+                self.orig_text.last_line()
             };
 
-            let formatted_lines_count = self.formatted_lines.len();
-
             let append_opts = FormatOpts {
                 buffer: &mut self.formatted_lines,
                 text,
                 attachment,
                 line_numbers,
                 formatted_lines_count,
+                lines_after: 0,
                 lines_count: old_lc,
                 prev_unterminated: self.unterminated,
                 cols: cols.into(),
                 line_wrapping: self.line_wrapping,
+                line_number_anchor: self.line_number_anchor,
+                line_number_formatter: self.line_number_formatter.as_ref(),
+                line_formatter: self.line_formatter.as_ref(),
+                #[cfg(feature = "search")]
+                color_rules: &self.color_rules,
+                line_number_continuation: self.line_number_continuation,
+                line_number_start: self.line_number_start + self.discarded_lines,
+                tab_width: self.tab_width,
+                control_chars: self.control_chars,
+                strip_ansi: self.strip_ansi,
+                color_support: self.color_support,
+                theme: self.theme,
+                show_invisibles: self.show_invisibles,
+                wrap_prefix: &self.wrap_prefix,
+                wrap_suffix: &self.wrap_suffix,
+                signs: &self.signs,
+                folds: &self.folds,
+                sign_column_width,
                 #[cfg(feature = "search")]
                 search_term,
             };
@@ -148,11 +734,32 @@ impl Default for Screen {
     fn default() -> Self {
         Self {
             line_wrapping: true,
-            orig_text: String::with_capacity(100 * 1024),
+            orig_text: Text::new(),
             formatted_lines: Vec::with_capacity(500 * 1024),
             line_count: 0,
             max_line_length: 0,
             unterminated: 0,
+            line_number_anchor: 0,
+            line_number_formatter: None,
+            line_formatter: None,
+            #[cfg(feature = "search")]
+            color_rules: Vec::new(),
+            line_number_continuation: LineNumberContinuation::Blank,
+            line_number_start: 1,
+            discarded_lines: 0,
+            tab_width: 8,
+            control_chars: ControlChars::Raw,
+            strip_ansi: false,
+            color_support: ColorSupport::TrueColor,
+            theme: Theme::default(),
+            show_invisibles: false,
+            wrap_prefix: String::new(),
+            wrap_suffix: String::new(),
+            signs: BTreeMap::new(),
+            folds: Folds::new(),
+            frozen_columns: 0,
+            line_provider: None,
+            provider_window_start: 0,
         }
     }
 }
@@ -252,6 +859,9 @@ where
     /// This is equal to the number of lines in [`PagerState::formatted_lines`](crate::state::PagerState::lines). This is used to
     /// calculate the search index of the rows of the line.
     pub formatted_lines_count: usize,
+    /// Number of lines that come after `text` when the text is formatted in several blocks. The
+    /// line numbers of all the blocks are padded to the same width.
+    pub lines_after: usize,
     /// Actual number of columns available for displaying
     pub cols: usize,
     /// Number of lines that are previously unterminated. It is only relevant when there is `attachment` text otherwise
@@ -263,6 +873,41 @@ where
 
     /// Value of [PagerState::line_wrapping]
     pub line_wrapping: bool,
+    /// Value of [`Screen::line_number_anchor`]
+    pub line_number_anchor: usize,
+    /// Value of [`Screen::line_number_formatter`]
+    pub line_number_formatter: Option<&'a LineNumberFormatter>,
+    /// Value of [`Screen::line_formatter`]
+    pub line_formatter: Option<&'a LineFormatter>,
+    /// Value of [`Screen::color_rules`]
+    #[cfg(feature = "search")]
+    pub color_rules: &'a [(Regex, ContentStyle)],
+    /// Value of [`Screen::line_number_continuation`]
+    pub line_number_continuation: LineNumberContinuation,
+    /// Value of [`Screen::first_line_number`]
+    pub line_number_start: usize,
+    /// Value of [`Screen::tab_width`]
+    pub tab_width: usize,
+    /// Value of [`Screen::control_chars`]
+    pub control_chars: ControlChars,
+    /// Value of [`Screen::strip_ansi`]
+    pub strip_ansi: bool,
+    /// Value of [`Screen::color_support`]
+    pub color_support: ColorSupport,
+    /// Value of [`Screen::theme`]
+    pub theme: Theme,
+    /// Value of [`Screen::show_invisibles`]
+    pub show_invisibles: bool,
+    /// Value of [`Screen::wrap_prefix`]
+    pub wrap_prefix: &'a str,
+    /// Value of [`Screen::wrap_suffix`]
+    pub wrap_suffix: &'a str,
+    /// Value of [`Screen::signs`]
+    pub signs: &'a BTreeMap<usize, Sign>,
+    /// Value of [`Screen::folds`]
+    pub folds: &'a Folds,
+    /// Value of [`Screen::sign_column_width`]
+    pub sign_column_width: usize,
 }
 
 /// Contains the formatted rows along with some basic information about the text formatted
@@ -335,8 +980,10 @@ where
     // * After all the formatting is done, we return the format results.
 
     // Compute the text to be format and set clean_append
-    let to_format;
-    if let Some(attached_text) = opts.attachment {
+    //
+    // The text is only copied when an attachment has to be joined with it. Otherwise we format it
+    // in place as it can be the entire text when everything is being reformatted
+    let to_format = if let Some(attached_text) = opts.attachment {
         // Tweak certain parameters if we are joining the last line of already present text with the first line of
         // incoming text.
         //
@@ -354,10 +1001,26 @@ where
         s.push_str(attached_text);
         s.push_str(opts.text);
 
-        to_format = s;
+        Cow::Owned(s)
     } else {
-        to_format = opts.text.to_string();
-    }
+        Cow::Borrowed(opts.text)
+    };
+
+    // Escape control characters and expand the tabs so that the width of each line can be
+    // calculated properly
+    let to_format = if opts.strip_ansi {
+        strip_ansi(&to_format)
+    } else {
+        Cow::Borrowed(&*to_format)
+    };
+    let to_format = render_overstrike(&to_format, opts.control_chars);
+    let to_format = escape_control_chars(&to_format, opts.control_chars);
+    let to_format = expand_tabs(&to_format, opts.tab_width, opts.show_invisibles);
+    let to_format = if opts.show_invisibles {
+        mark_invisibles(&to_format)
+    } else {
+        Cow::Borrowed(&*to_format)
+    };
 
     let lines = to_format
         .lines()
@@ -377,7 +1040,10 @@ where
         clean_append: opts.attachment.is_none(),
     };
 
-    let line_number_digits = minus_core::utils::digits(opts.lines_count + to_format_size);
+    let line_number_digits = minus_core::utils::digits(
+        (opts.lines_count + to_format_size + opts.lines_after + opts.line_number_start)
+            .saturating_sub(1),
+    );
 
     // Return if we have nothing to format
     if lines.is_empty() {
@@ -386,6 +1052,9 @@ where
 
     // Number of rows that have been formatted so far
     // Whenever a line is formatted, this will be incremented to te number of rows that the formatted line has occupied
+    //
+    // It starts from the number of rows already present, so the search indices found for the rows are already
+    // positions in [`PagerState::formatted_lines`] and need no adjustment
     let mut formatted_row_count = opts.formatted_lines_count;
 
     {
@@ -393,6 +1062,20 @@ where
         let cols = opts.cols;
         let lines_count = opts.lines_count;
         let line_wrapping = opts.line_wrapping;
+        let line_number_anchor = opts.line_number_anchor;
+        let line_number_formatter = opts.line_number_formatter;
+        let line_formatter = opts.line_formatter;
+        #[cfg(feature = "search")]
+        let color_rules = opts.color_rules;
+        let line_number_continuation = opts.line_number_continuation;
+        let line_number_start = opts.line_number_start;
+        let wrap_prefix = opts.wrap_prefix;
+        let wrap_suffix = opts.wrap_suffix;
+        let signs = opts.signs;
+        let folds = opts.folds;
+        let sign_column_width = opts.sign_column_width;
+        let color_support = opts.color_support;
+        let theme = &opts.theme;
         #[cfg(feature = "search")]
         let search_term = opts.search_term;
 
@@ -401,13 +1084,37 @@ where
                 .iter()
                 .take(lines.len().saturating_sub(1))
                 .flat_map(|(idx, line)| {
-                    let fmt_line = formatted_line(
+                    // The lines hidden in a fold take no rows but still get mapped to the row
+                    // where the next line starts
+                    if folds.is_hidden(lines_count + idx) {
+                        fr.lines_to_row_map.insert(formatted_row_count, true);
+                        return Vec::new();
+                    }
+                    let line = style_line(
                         line,
+                        lines_count + idx,
+                        line_formatter,
+                        #[cfg(feature = "search")]
+                        color_rules,
+                        color_support,
+                    );
+                    let mut fmt_line = formatted_line(
+                        &line,
                         line_number_digits,
                         lines_count + idx,
                         line_numbers,
+                        line_number_anchor,
+                        line_number_formatter,
+                        line_number_continuation,
+                        line_number_start,
+                        signs.get(&(lines_count + idx)),
+                        sign_column_width,
+                        color_support,
+                        theme,
                         cols,
                         line_wrapping,
+                        wrap_prefix,
+                        wrap_suffix,
                         #[cfg(feature = "search")]
                         formatted_row_count,
                         #[cfg(feature = "search")]
@@ -415,9 +1122,13 @@ where
                         #[cfg(feature = "search")]
                         search_term,
                     );
+                    if let Some(hidden) = folds.hidden_after(lines_count + idx) {
+                        let marker_cols = if line_wrapping { cols } else { usize::MAX };
+                        add_fold_marker(&mut fmt_line, hidden, theme, color_support, marker_cols);
+                    }
                     fr.lines_to_row_map.insert(formatted_row_count, true);
                     formatted_row_count += fmt_line.len();
-                    if lines.len() > fr.max_line_length {
+                    if line.len() > fr.max_line_length {
                         fr.max_line_length = line.len();
                     }
 
@@ -426,52 +1137,62 @@ where
         opts.buffer.extend_buffer(rest_lines);
     };
 
-    let mut last_line = formatted_line(
-        lines.last().unwrap().1,
-        line_number_digits,
-        opts.lines_count + to_format_size - 1,
-        opts.line_numbers,
-        opts.cols,
-        opts.line_wrapping,
-        #[cfg(feature = "search")]
-        formatted_row_count,
-        #[cfg(feature = "search")]
-        &mut fr.append_search_idx,
-        #[cfg(feature = "search")]
-        opts.search_term,
-    );
+    let last_idx = opts.lines_count + to_format_size - 1;
+    let mut last_line = if opts.folds.is_hidden(last_idx) {
+        Vec::new()
+    } else {
+        let last_line_text = style_line(
+            lines.last().unwrap().1,
+            last_idx,
+            opts.line_formatter,
+            #[cfg(feature = "search")]
+            opts.color_rules,
+            opts.color_support,
+        );
+        let mut last_line = formatted_line(
+            &last_line_text,
+            line_number_digits,
+            last_idx,
+            opts.line_numbers,
+            opts.line_number_anchor,
+            opts.line_number_formatter,
+            opts.line_number_continuation,
+            opts.line_number_start,
+            opts.signs.get(&last_idx),
+            opts.sign_column_width,
+            opts.color_support,
+            &opts.theme,
+            opts.cols,
+            opts.line_wrapping,
+            opts.wrap_prefix,
+            opts.wrap_suffix,
+            #[cfg(feature = "search")]
+            formatted_row_count,
+            #[cfg(feature = "search")]
+            &mut fr.append_search_idx,
+            #[cfg(feature = "search")]
+            opts.search_term,
+        );
+        if let Some(hidden) = opts.folds.hidden_after(last_idx) {
+            add_fold_marker(
+                &mut last_line,
+                hidden,
+                &opts.theme,
+                opts.color_support,
+                if opts.line_wrapping {
+                    opts.cols
+                } else {
+                    usize::MAX
+                },
+            );
+        }
+        if last_line_text.len() > fr.max_line_length {
+            fr.max_line_length = last_line_text.len();
+        }
+        last_line
+    };
     fr.lines_to_row_map.insert(formatted_row_count, true);
     formatted_row_count += last_line.len();
-    if lines.last().unwrap().1.len() > fr.max_line_length {
-        fr.max_line_length = lines.last().unwrap().1.len();
-    }
-
-    #[cfg(feature = "search")]
-    {
-        // NOTE: VERY IMPORTANT BLOCK TO GET PROPER SEARCH INDEX
-        // Here is the current scenario: suppose you have text block like this (markers are present to denote where a
-        // new line begins).
-        //
-        // * This is line one row one
-        //   This is line one row two
-        //   This is line one row three
-        // * This is line two row one
-        //   This is line two row two
-        //   This is line two row three
-        //   This is line two row four
-        //
-        // and suppose a match is found at line 1 row 2 and line 2 row 4. So the index generated will be [1, 6].
-        // Let's say this text block is going to be appended to [PagerState::formatted_lines] from index 23.
-        // Now if directly append this generated index to [`PagerState::search_idx`], it will probably be wrong
-        // as these numbers are *relative to current text block*. The actual search index should have been 24, 30.
-        //
-        // To fix this we basically add the number of items in [`PagerState::formatted_lines`].
-        fr.append_search_idx = fr
-            .append_search_idx
-            .iter()
-            .map(|i| opts.formatted_lines_count + i)
-            .collect();
-    }
 
     // Calculate number of rows which are part of last line and are left unterminated  due to absence of \n
     fr.num_unterminated = if opts.text.ends_with('\n') {
@@ -486,6 +1207,373 @@ where
     fr
 }
 
+/// Add the marker showing how many lines are hidden by a fold after the rows of its first line
+///
+/// The marker goes on a row of its own if it doesn't fit within `cols` after the last row.
+fn add_fold_marker(
+    rows: &mut Rows,
+    hidden: usize,
+    theme: &Theme,
+    color_support: ColorSupport,
+    cols: usize,
+) {
+    let marker = format!("⋯ {hidden} more line{}", if hidden == 1 { "" } else { "s" });
+    let styled = color_support.adapt(theme.fold).apply(&marker).to_string();
+    match rows.last_mut() {
+        Some(row)
+            if textwrap::core::display_width(row) + 1 + textwrap::core::display_width(&marker)
+                <= cols =>
+        {
+            row.push(' ');
+            row.push_str(&styled);
+        }
+        _ => rows.push(styled),
+    }
+}
+
+/// Renders the backspace sequences that `nroff` uses for bold and underlined text
+///
+/// Like `less`, `c\bc` is made bold and `_\bc` is underlined. Any other character followed by a
+/// backspace is replaced by the character after it. The text is left as is if all control
+/// characters are shown with [`ControlChars::Escape`].
+pub(crate) fn render_overstrike(text: &str, control_chars: ControlChars) -> Cow<'_, str> {
+    if control_chars == ControlChars::Escape || !text.contains('\x08') {
+        return Cow::Borrowed(text);
+    }
+    let mut rendered = String::with_capacity(text.len() + 32);
+    let (mut bold, mut underline) = (false, false);
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let mut shown = c;
+        let (mut is_bold, mut is_underlined) = (false, false);
+        // A character can be struck over several times, like `_\bc\bc` for bold underlined text
+        while chars.peek() == Some(&'\x08') {
+            let mut rest = chars.clone();
+            rest.next();
+            match rest.next() {
+                Some(next) if next != '\n' => {
+                    if next == shown {
+                        is_bold = true;
+                    } else if shown == '_' {
+                        is_underlined = true;
+                        shown = next;
+                    } else if next == '_' {
+                        is_underlined = true;
+                    } else {
+                        shown = next;
+                    }
+                    chars = rest;
+                }
+                _ => break,
+            }
+        }
+        // Styles are ended before each newline so that every line can be drawn on its own
+        if is_bold != bold {
+            rendered.push_str(if is_bold { "\x1b[1m" } else { "\x1b[22m" });
+            bold = is_bold;
+        }
+        if is_underlined != underline {
+            rendered.push_str(if is_underlined { "\x1b[4m" } else { "\x1b[24m" });
+            underline = is_underlined;
+        }
+        rendered.push(shown);
+    }
+    if bold {
+        rendered.push_str("\x1b[22m");
+    }
+    if underline {
+        rendered.push_str("\x1b[24m");
+    }
+    Cow::Owned(rendered)
+}
+
+/// Replaces the control characters in `text` with visible escapes according to `control_chars`
+///
+/// See [`ControlChars`] for more info.
+pub(crate) fn escape_control_chars(text: &str, control_chars: ControlChars) -> Cow<'_, str> {
+    let needs_escape = |c: char| c.is_control() && c != '\n' && c != '\t';
+    if control_chars == ControlChars::Raw || !text.contains(needs_escape) {
+        return Cow::Borrowed(text);
+    }
+    let mut escaped = String::with_capacity(text.len() + 32);
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '\x1b' && control_chars == ControlChars::EscapeExceptColors {
+            if let Some(len) = sgr_sequence_len(rest) {
+                escaped.push_str(&rest[..len]);
+                rest = &rest[len..];
+                continue;
+            }
+        }
+        match c {
+            c if !needs_escape(c) => escaped.push(c),
+            '\x7f' => escaped.push_str("^?"),
+            c if c < ' ' => {
+                escaped.push('^');
+                escaped.push(char::from_u32(u32::from(c) + 0x40).unwrap_or('?'));
+            }
+            c => write!(escaped, "<U+{:04X}>", u32::from(c)).unwrap(),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    Cow::Owned(escaped)
+}
+
+/// Turns the `\r\n` line endings of `text` into `\n`
+pub(crate) fn normalize_line_endings(text: &str) -> Cow<'_, str> {
+    if text.contains("\r\n") {
+        Cow::Owned(text.replace("\r\n", "\n"))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Removes all the ANSI escape sequences from `text`
+///
+/// Besides the sequences for setting colors and text styles, this removes the sequences for
+/// moving the cursor, setting the title of the terminal, hyperlinks and so on. A sequence that is
+/// not terminated before the end of its line is removed up to the end of the line.
+pub(crate) fn strip_ansi(text: &str) -> Cow<'_, str> {
+    if !text.contains(['\x1b', '\u{9b}']) {
+        return Cow::Borrowed(text);
+    }
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{9b}' => skip_until(&mut chars, |c| ('@'..='~').contains(&c)),
+            '\x1b' => match chars.peek() {
+                // Control sequences like colors and cursor movements
+                Some('[') => {
+                    chars.next();
+                    skip_until(&mut chars, |c| ('@'..='~').contains(&c));
+                }
+                // Operating system commands like titles and hyperlinks, ended by BEL or ESC \
+                Some(']' | 'P' | 'X' | '^' | '_') => {
+                    chars.next();
+                    skip_until(&mut chars, |c| c == '\x07' || c == '\x1b');
+                    if chars.peek() == Some(&'\\') {
+                        chars.next();
+                    }
+                }
+                // Sequences like `ESC ( B` that have intermediate characters before the last one
+                Some(' '..='/') => skip_until(&mut chars, |c| !(' '..='/').contains(&c)),
+                Some('\n') | None => {}
+                Some(_) => {
+                    chars.next();
+                }
+            },
+            c => stripped.push(c),
+        }
+    }
+    Cow::Owned(stripped)
+}
+
+/// Skips the characters of an escape sequence up to and including the first one matching `is_end`
+fn skip_until(chars: &mut std::iter::Peekable<std::str::Chars<'_>>, is_end: impl Fn(char) -> bool) {
+    while let Some(c) = chars.next_if(|c| *c != '\n') {
+        if is_end(c) {
+            break;
+        }
+    }
+}
+
+/// Returns the length of the ANSI escape sequence for setting colors and text styles at the start
+/// of `text`, if there is one
+fn sgr_sequence_len(text: &str) -> Option<usize> {
+    let params = text.strip_prefix("\x1b[")?;
+    let end = params.find(|c: char| !(c.is_ascii_digit() || c == ';' || c == ':'))?;
+    (params.as_bytes()[end] == b'm').then_some(end + 3)
+}
+
+/// Replaces each tab character in `text` with spaces up to the next tab stop
+///
+/// Tab stops are placed after every `tab_width` columns of each line. If `tab_width` is 0, tabs
+/// are simply removed. If `mark_tabs` is `true`, the first column of each tab is replaced by `→`.
+pub(crate) fn expand_tabs(text: &str, tab_width: usize, mark_tabs: bool) -> Cow<'_, str> {
+    if !text.contains('\t') {
+        return Cow::Borrowed(text);
+    }
+    let mut expanded = String::with_capacity(text.len() + tab_width * 8);
+
+    for (line_idx, line) in text.split('\n').enumerate() {
+        if line_idx > 0 {
+            expanded.push('\n');
+        }
+        // Number of columns taken by the line so far
+        let mut width = 0;
+        for (segment_idx, segment) in line.split('\t').enumerate() {
+            if segment_idx > 0 && tab_width > 0 {
+                let spaces = tab_width - width % tab_width;
+                if mark_tabs {
+                    expanded.push('→');
+                    expanded.push_str(&" ".repeat(spaces - 1));
+                } else {
+                    expanded.push_str(&" ".repeat(spaces));
+                }
+                width += spaces;
+            }
+            expanded.push_str(segment);
+            width += textwrap::core::display_width(segment);
+        }
+    }
+    Cow::Owned(expanded)
+}
+
+/// Makes the non-breaking spaces and trailing spaces of each line in `text` visible
+///
+/// Non-breaking spaces are replaced by `␣` and trailing spaces by `·`.
+pub(crate) fn mark_invisibles(text: &str) -> Cow<'_, str> {
+    if !text.contains('\u{a0}') && !text.contains(" \n") && !text.ends_with(' ') {
+        return Cow::Borrowed(text);
+    }
+    let mut marked = String::with_capacity(text.len() + 32);
+
+    for (line_idx, line) in text.split('\n').enumerate() {
+        if line_idx > 0 {
+            marked.push('\n');
+        }
+        let content = line.trim_end_matches(' ');
+        marked.push_str(&content.replace('\u{a0}', "␣"));
+        marked.push_str(&"·".repeat(line.len() - content.len()));
+    }
+    Cow::Owned(marked)
+}
+
+/// Makes the gutter text displayed before the first row of a line
+///
+/// - `number`: The number to be displayed
+/// - `len_line_number`: is the number of digits that number of lines in [`PagerState::lines`] occupy.
+/// - `formatter`: The custom formatter set by the application, if any
+/// - `style`: The style of the line numbers from the [`Theme`]
+///
+/// [`PagerState::lines`]: crate::state::PagerState::lines
+pub(crate) fn line_number_gutter(
+    number: usize,
+    len_line_number: usize,
+    formatter: Option<&LineNumberFormatter>,
+    style: ContentStyle,
+) -> String {
+    let gutter = unstyled_line_number_gutter(number, len_line_number, formatter);
+    // Custom formatters do their own styling
+    if formatter.is_some() {
+        gutter
+    } else {
+        styled_gutter(gutter, style)
+    }
+}
+
+/// Makes the gutter text displayed before the rows of a line except the first one
+///
+/// `gutter_width` is the width of the gutter of the first row of the line. See
+/// [`line_number_gutter`] for the rest of the arguments.
+pub(crate) fn continuation_gutter(
+    continuation: LineNumberContinuation,
+    number: usize,
+    len_line_number: usize,
+    formatter: Option<&LineNumberFormatter>,
+    style: ContentStyle,
+    gutter_width: usize,
+) -> String {
+    match continuation {
+        LineNumberContinuation::Blank => " ".repeat(gutter_width),
+        LineNumberContinuation::Arrow => {
+            format!("{: >width$} ", '↪', width = gutter_width.saturating_sub(1))
+        }
+        // Keep the colors of the line numbers but dim them instead of any other attribute
+        LineNumberContinuation::RepeatDimmed => styled_gutter(
+            unstyled_line_number_gutter(number, len_line_number, formatter),
+            ContentStyle {
+                attributes: Attribute::Dim.into(),
+                ..style
+            },
+        ),
+    }
+}
+
+/// Makes the text of the sign column for a line with the given `sign`
+///
+/// The text is padded to `sign_column_width` columns, which includes the space after the sign.
+fn sign_gutter(
+    sign: Option<&Sign>,
+    sign_column_width: usize,
+    color_support: ColorSupport,
+) -> String {
+    let Some(sign) = sign else {
+        return " ".repeat(sign_column_width);
+    };
+    let padding = sign_column_width.saturating_sub(textwrap::core::display_width(&sign.text));
+    let style = color_support.adapt(sign.style);
+    format!("{}{:padding$}", style.apply(&sign.text), "")
+}
+
+/// Makes the gutter text without any styling applied by minus
+fn unstyled_line_number_gutter(
+    number: usize,
+    len_line_number: usize,
+    formatter: Option<&LineNumberFormatter>,
+) -> String {
+    formatter.map_or_else(
+        || {
+            // Padding is equal to LineNumbers::EXTRA_PADDING + len_line_number + 1 (for '.')
+            format!(
+                "{: >len$} ",
+                number.to_string() + ".",
+                len = len_line_number + LineNumbers::EXTRA_PADDING + 1,
+            )
+        },
+        |formatter| formatter(number),
+    )
+}
+
+/// Applies `attribute` to the `gutter`
+///
+/// If minus is run under test, ascii codes for styling are not inserted because they add extra
+/// difficulty while writing tests
+fn styled_gutter(gutter: String, style: ContentStyle) -> String {
+    if cfg!(test) {
+        gutter
+    } else {
+        style.apply(gutter).to_string()
+    }
+}
+
+/// Applies the styles set by the application to the line at index `idx`
+///
+/// The styles of the line `formatter` are applied first and then the styles of the `color_rules`
+/// on top of them.
+fn style_line<'a>(
+    line: &'a str,
+    idx: usize,
+    formatter: Option<&LineFormatter>,
+    #[cfg(feature = "search")] color_rules: &[(Regex, ContentStyle)],
+    color_support: ColorSupport,
+) -> Cow<'a, str> {
+    #[cfg(feature = "search")]
+    let has_rules = !color_rules.is_empty();
+    #[cfg(not(feature = "search"))]
+    let has_rules = false;
+    if formatter.is_none() && !has_rules {
+        return Cow::Borrowed(line);
+    }
+    #[cfg_attr(not(feature = "search"), allow(unused_mut))]
+    let mut styled =
+        formatter.map_or_else(|| StyledLine::new(line), |formatter| formatter(line, idx));
+    #[cfg(feature = "search")]
+    for (regex, style) in color_rules {
+        let ranges: Vec<_> = regex.find_iter(styled.text()).map(|m| m.range()).collect();
+        for range in ranges {
+            styled = styled.style(range, *style);
+        }
+    }
+    let mut text = String::new();
+    styled.write_styled(&mut text, color_support).unwrap();
+    Cow::Owned(text)
+}
+
 /// Formats the given `line`
 ///
 /// - `line`: The line to format
@@ -495,6 +1583,15 @@ where
 ///     number of lines in [`PagerState::lines`] is 500. This is used for calculating the padding
 ///     of each displayed line.
 /// - `idx`: is the position index where the line is placed in [`PagerState::lines`].
+/// - `line_number_anchor`: is the index of the line at the top of the screen. This is used only
+///   for relative line numbers.
+/// - `line_number_formatter`: is the custom formatter for the line number gutter, if any.
+/// - `line_number_continuation`: tells what to display in the gutter of the wrapped rows.
+/// - `line_number_start`: is the line number of the first line in [`PagerState::lines`].
+/// - `sign`: is the marker displayed in the sign column for this line, if any.
+/// - `sign_column_width`: is the number of columns taken by the sign column. It is 0 if there
+///   is no sign column.
+/// - `theme`: has the styles of the line numbers and search matches.
 /// - `formatted_idx`: is the position index where the line will be placed in the resulting
 ///    [`PagerState::formatted_lines`](crate::state::PagerState::formatted_lines)
 /// - `cols`: Number of columns in the terminal
@@ -502,14 +1599,25 @@ where
 ///
 /// [`PagerState::lines`]: crate::state::PagerState::lines
 #[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_lines)]
 #[allow(clippy::uninlined_format_args)]
 pub(crate) fn formatted_line<'a>(
     line: Line<'a>,
     len_line_number: usize,
     idx: usize,
     line_numbers: LineNumbers,
+    line_number_anchor: usize,
+    line_number_formatter: Option<&LineNumberFormatter>,
+    line_number_continuation: LineNumberContinuation,
+    line_number_start: usize,
+    sign: Option<&Sign>,
+    sign_column_width: usize,
+    color_support: ColorSupport,
+    theme: &Theme,
     cols: usize,
     line_wrapping: bool,
+    wrap_prefix: &str,
+    wrap_suffix: &str,
     #[cfg(feature = "search")] formatted_idx: usize,
     #[cfg(feature = "search")] search_idx: &mut BTreeSet<usize>,
     #[cfg(feature = "search")] search_term: &Option<regex::Regex>,
@@ -519,31 +1627,66 @@ pub(crate) fn formatted_line<'a>(
         "Newlines found in appending line {:?}",
         line
     );
-    let line_numbers = matches!(line_numbers, LineNumbers::Enabled | LineNumbers::AlwaysOn);
 
     // NOTE: Only relevant when line numbers are active
-    // Padding is the space that the actual line text will be shifted to accommodate for
-    // line numbers. This is equal to:-
-    // LineNumbers::EXTRA_PADDING + len_line_number + 1 (for '.') + 1 (for 1 space)
+    // The gutter is the text placed before the actual line text to accommodate for line numbers.
+    // Line number is added only to the first row of a line. Subsequent rows get a continuation
+    // gutter of the same width. This makes a better UI overall
     //
-    // We reduce this from the number of available columns as this space cannot be used for
+    // We reduce its width from the number of available columns as this space cannot be used for
     // actual line display when wrapping the lines
-    let padding = len_line_number + LineNumbers::EXTRA_PADDING + 1;
+    let number = line_numbers.number_for(idx, line_number_anchor, line_number_start);
+    let line_number_style = color_support.adapt(theme.line_numbers);
+    let number_gutter = line_numbers.is_on().then(|| {
+        line_number_gutter(
+            number,
+            len_line_number,
+            line_number_formatter,
+            line_number_style,
+        )
+    });
+    let number_gutter_width = number_gutter
+        .as_deref()
+        .map_or(0, textwrap::core::display_width);
+    // The sign column is placed before the line numbers
+    let gutter = if sign_column_width == 0 {
+        number_gutter
+    } else {
+        Some(
+            sign_gutter(sign, sign_column_width, color_support)
+                + number_gutter.as_deref().unwrap_or(""),
+        )
+    };
+    let gutter_width = sign_column_width + number_gutter_width;
 
-    let cols_avail = if line_numbers {
-        cols.saturating_sub(padding + 2)
+    let cols_avail = if gutter.is_some() {
+        cols.saturating_sub(gutter_width + 1)
     } else {
         cols
     };
 
     // Wrap the line and return an iterator over all the rows
     let mut enumerated_rows = if line_wrapping {
-        textwrap::wrap(line, cols_avail)
+        wrap_line(line, cols_avail, wrap_prefix, wrap_suffix)
     } else {
         vec![Cow::from(line)]
     }
     .into_iter()
     .enumerate();
+    let last_wrap_idx = enumerated_rows.len().saturating_sub(1);
+
+    // Surround the row with the wrap indicators depending on whether the line has been wrapped
+    // onto it or continues after it
+    let wrap_indicators = |wrap_idx: usize| {
+        (
+            if wrap_idx > 0 { wrap_prefix } else { "" },
+            if wrap_idx < last_wrap_idx {
+                wrap_suffix
+            } else {
+                ""
+            },
+        )
+    };
 
     // highlight the lines with matching search terms
     // If a match is found, add this line's index to PagerState::search_idx
@@ -552,7 +1695,12 @@ pub(crate) fn formatted_line<'a>(
     let mut handle_search = |row: &mut Cow<'a, str>, wrap_idx: usize| {
         #[cfg(feature = "search")]
         if let Some(st) = search_term.as_ref() {
-            let (highlighted_row, is_match) = search::highlight_line_matches(row, st, false);
+            let (highlighted_row, is_match) = search::highlight_line_matches(
+                row,
+                st,
+                color_support.adapt(theme.search_match),
+                false,
+            );
             if is_match {
                 *row.to_mut() = highlighted_row;
                 search_idx.insert(formatted_idx + wrap_idx);
@@ -560,53 +1708,44 @@ pub(crate) fn formatted_line<'a>(
         }
     };
 
-    if line_numbers {
+    if let Some(gutter) = gutter {
         let mut formatted_rows = Vec::with_capacity(256);
 
-        // Formatter for only when line numbers are active
-        // * If minus is run under test, ascii codes for making the numbers bol is not inserted because they add
-        // extra difficulty while writing tests
-        // * Line number is added only to the first row of a line. This makes a better UI overall
-        let formatter = |row: Cow<'_, str>, is_first_row: bool, idx: usize| {
-            format!(
-                "{bold}{number: >len$}{reset} {row}",
-                bold = if cfg!(not(test)) && is_first_row {
-                    crossterm::style::Attribute::Bold.to_string()
-                } else {
-                    String::new()
-                },
-                number = if is_first_row {
-                    (idx + 1).to_string() + "."
-                } else {
-                    String::new()
-                },
-                len = padding,
-                reset = if cfg!(not(test)) && is_first_row {
-                    crossterm::style::Attribute::Reset.to_string()
-                } else {
-                    String::new()
-                },
-                row = row
-            )
-        };
-
         // First format the first row separate from other rows, then the subsequent rows and finally join them
         // This is because only the first row contains the line number and not the subsequent rows
         let first_row = {
             #[cfg_attr(not(feature = "search"), allow(unused_mut))]
             let mut row = enumerated_rows.next().unwrap().1;
             handle_search(&mut row, 0);
-            formatter(row, true, idx)
+            let (_, suffix) = wrap_indicators(0);
+            format!("{gutter}{row}{suffix}")
         };
         formatted_rows.push(first_row);
 
-        #[cfg_attr(not(feature = "search"), allow(unused_mut))]
-        #[cfg_attr(not(feature = "search"), allow(unused_variables))]
-        let rows_left = enumerated_rows.map(|(wrap_idx, mut row)| {
-            handle_search(&mut row, wrap_idx);
-            formatter(row, false, 0)
-        });
-        formatted_rows.extend(rows_left);
+        // Only make the continuation gutter if the line has actually been wrapped
+        if enumerated_rows.len() > 0 {
+            let number_continuation = if line_numbers.is_on() {
+                continuation_gutter(
+                    line_number_continuation,
+                    number,
+                    len_line_number,
+                    line_number_formatter,
+                    line_number_style,
+                    number_gutter_width,
+                )
+            } else {
+                String::new()
+            };
+            let continuation = " ".repeat(sign_column_width) + &number_continuation;
+            #[cfg_attr(not(feature = "search"), allow(unused_mut))]
+            #[cfg_attr(not(feature = "search"), allow(unused_variables))]
+            let rows_left = enumerated_rows.map(|(wrap_idx, mut row)| {
+                handle_search(&mut row, wrap_idx);
+                let (prefix, suffix) = wrap_indicators(wrap_idx);
+                format!("{continuation}{prefix}{row}{suffix}")
+            });
+            formatted_rows.extend(rows_left);
+        }
 
         formatted_rows
     } else {
@@ -615,34 +1754,150 @@ pub(crate) fn formatted_line<'a>(
         enumerated_rows
             .map(|(wrap_idx, mut row)| {
                 handle_search(&mut row, wrap_idx);
-                row.to_string()
+                let (prefix, suffix) = wrap_indicators(wrap_idx);
+                format!("{prefix}{row}{suffix}")
             })
             .collect::<Vec<String>>()
     }
 }
 
+/// Wraps `line` into rows that fit within `cols` columns
+///
+/// Room is left for the `wrap_prefix` placed before each row except the first and for the
+/// `wrap_suffix` placed after each row except the last. The returned rows do not contain them.
+fn wrap_line<'a>(
+    line: &'a str,
+    cols: usize,
+    wrap_prefix: &str,
+    wrap_suffix: &str,
+) -> Vec<Cow<'a, str>> {
+    let mut rows = if wrap_prefix.is_empty() && wrap_suffix.is_empty() {
+        textwrap::wrap(line, cols)
+    } else {
+        let indent = " ".repeat(textwrap::core::display_width(wrap_prefix));
+        let options =
+            textwrap::Options::new(cols.saturating_sub(textwrap::core::display_width(wrap_suffix)))
+                .subsequent_indent(&indent);
+
+        textwrap::wrap(line, options)
+            .into_iter()
+            .enumerate()
+            .map(|(wrap_idx, row)| {
+                if wrap_idx == 0 {
+                    Cow::Owned(row.into_owned())
+                } else {
+                    Cow::Owned(row[indent.len()..].to_owned())
+                }
+            })
+            .collect()
+    };
+    if line.contains('\x1b') {
+        carry_styles(&mut rows);
+    }
+    rows
+}
+
+/// Make each row of a wrapped line start with the styles that are active where it starts
+///
+/// Rows that are followed by another row of the line end by resetting the styles. This way each
+/// row can be drawn on its own and the styles don't spill into the gutter or the wrap indicators.
+fn carry_styles(rows: &mut [Cow<'_, str>]) {
+    let last_idx = rows.len().saturating_sub(1);
+    // The escape sequences for the styles set since the last reset
+    let mut active = String::new();
+    for (wrap_idx, row) in rows.iter_mut().enumerate() {
+        let carried = active.clone();
+        let mut rest: &str = row;
+        while let Some(start) = rest.find('\x1b') {
+            rest = &rest[start..];
+            let len = sgr_sequence_len(rest).unwrap_or(1);
+            match &rest[..len] {
+                "\x1b[0m" | "\x1b[m" => active.clear(),
+                seq if len > 1 => active.push_str(seq),
+                _ => {}
+            }
+            rest = &rest[len..];
+        }
+        let end = if wrap_idx < last_idx && !active.is_empty() {
+            "\x1b[0m"
+        } else {
+            ""
+        };
+        if !carried.is_empty() || !end.is_empty() {
+            *row = Cow::Owned(format!("{carried}{row}{end}"));
+        }
+    }
+}
+
+/// Formats the entire text of the `screen` from scratch
+///
+/// The formatting configuration like line wrapping and the line number formatter are taken from the
+/// `screen` itself. The text is formatted one chunk at a time so that it is never copied as a
+/// whole.
 pub(crate) fn make_format_lines(
-    text: &String,
+    screen: &Screen,
     line_numbers: LineNumbers,
     cols: usize,
-    line_wrapping: bool,
     #[cfg(feature = "search")] search_term: &Option<regex::Regex>,
 ) -> (Rows, FormatResult) {
     let mut buffer = Vec::with_capacity(256);
-    let format_opts = FormatOpts {
-        buffer: &mut buffer,
-        text,
-        attachment: None,
-        line_numbers,
-        formatted_lines_count: 0,
-        lines_count: 0,
-        prev_unterminated: 0,
-        cols,
+    let mut fr = FormatResult {
+        lines_formatted: 0,
+        rows_formatted: 0,
+        num_unterminated: 0,
         #[cfg(feature = "search")]
-        search_term,
-        line_wrapping,
+        append_search_idx: BTreeSet::new(),
+        lines_to_row_map: LinesRowMap::new(),
+        max_line_length: 0,
+        clean_append: true,
     };
-    let fr = format_text_block(format_opts);
+    let mut lines_after = screen.orig_text.line_count();
+    let sign_column_width = screen.sign_column_width();
+
+    for (chunk, chunk_lines) in screen.orig_text.chunks() {
+        lines_after -= chunk_lines;
+        let format_opts = FormatOpts {
+            buffer: &mut buffer,
+            text: chunk,
+            attachment: None,
+            line_numbers,
+            formatted_lines_count: fr.rows_formatted,
+            lines_after,
+            lines_count: fr.lines_formatted,
+            prev_unterminated: 0,
+            cols,
+            #[cfg(feature = "search")]
+            search_term,
+            line_wrapping: screen.line_wrapping,
+            line_number_anchor: screen.line_number_anchor,
+            line_number_formatter: screen.line_number_formatter.as_ref(),
+            line_formatter: screen.line_formatter.as_ref(),
+            #[cfg(feature = "search")]
+            color_rules: &screen.color_rules,
+            line_number_continuation: screen.line_number_continuation,
+            line_number_start: screen.first_line_number(),
+            tab_width: screen.tab_width,
+            control_chars: screen.control_chars,
+            strip_ansi: screen.strip_ansi,
+            color_support: screen.color_support,
+            theme: screen.theme,
+            show_invisibles: screen.show_invisibles,
+            wrap_prefix: &screen.wrap_prefix,
+            wrap_suffix: &screen.wrap_suffix,
+            signs: &screen.signs,
+            folds: &screen.folds,
+            sign_column_width,
+        };
+        let mut chunk_fr = format_text_block(format_opts);
+        fr.lines_formatted += chunk_fr.lines_formatted;
+        fr.rows_formatted += chunk_fr.rows_formatted;
+        fr.num_unterminated = chunk_fr.num_unterminated;
+        #[cfg(feature = "search")]
+        fr.append_search_idx.append(&mut chunk_fr.append_search_idx);
+        fr.lines_to_row_map
+            .append(&mut chunk_fr.lines_to_row_map, true);
+        fr.max_line_length = fr.max_line_length.max(chunk_fr.max_line_length);
+    }
     (buffer, fr)
 }
 
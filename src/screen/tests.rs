@@ -1,7 +1,11 @@
 mod unterminated {
-    use crate::screen::{format_text_block, FormatOpts, Rows};
+    use crate::screen::{format_text_block, Folds, FormatOpts, Rows, Sign};
+    use std::collections::BTreeMap;
 
-    const fn get_append_opts_template(text: &str) -> FormatOpts<Rows> {
+    static EMPTY_SIGNS: BTreeMap<usize, Sign> = BTreeMap::new();
+    static NO_FOLDS: Folds = Folds::new();
+
+    fn get_append_opts_template(text: &str) -> FormatOpts<Rows> {
         FormatOpts {
             buffer: Vec::new(),
             text,
@@ -10,10 +14,29 @@ mod unterminated {
             search_term: &None,
             lines_count: 0,
             formatted_lines_count: 0,
+            lines_after: 0,
             cols: 80,
             line_numbers: crate::LineNumbers::Disabled,
             prev_unterminated: 0,
             line_wrapping: true,
+            line_number_anchor: 0,
+            line_number_formatter: None,
+            line_formatter: None,
+            #[cfg(feature = "search")]
+            color_rules: &[],
+            line_number_continuation: crate::LineNumberContinuation::Blank,
+            line_number_start: 1,
+            tab_width: 8,
+            control_chars: crate::ControlChars::Raw,
+            strip_ansi: false,
+            color_support: crate::ColorSupport::TrueColor,
+            theme: crate::Theme::default(),
+            show_invisibles: false,
+            wrap_prefix: "",
+            wrap_suffix: "",
+            signs: &EMPTY_SIGNS,
+            folds: &NO_FOLDS,
+            sign_column_width: 0,
         }
     }
 
@@ -190,3 +213,197 @@ third line\n",
         assert_eq!(3, append_style.num_unterminated);
     }
 }
+
+mod tab_expansion {
+    use crate::screen::expand_tabs;
+
+    #[test]
+    fn no_tabs() {
+        assert_eq!(
+            expand_tabs("A line\nAnother line", 8, false),
+            "A line\nAnother line"
+        );
+    }
+
+    #[test]
+    fn align_to_tab_stops() {
+        assert_eq!(expand_tabs("\tA", 8, false), "        A");
+        assert_eq!(expand_tabs("ABC\tD\tE", 4, false), "ABC D   E");
+        assert_eq!(expand_tabs("ABCD\tE", 4, false), "ABCD    E");
+        // Tab stops restart on each line
+        assert_eq!(expand_tabs("AB\tC\nA\tB", 4, false), "AB  C\nA   B");
+    }
+
+    #[test]
+    fn escape_sequences_take_no_columns() {
+        assert_eq!(
+            expand_tabs("\x1b[1mA\x1b[0m\tB", 4, false),
+            "\x1b[1mA\x1b[0m   B"
+        );
+    }
+
+    #[test]
+    fn zero_width() {
+        assert_eq!(expand_tabs("A\tB", 0, false), "AB");
+    }
+
+    #[test]
+    fn marked_tabs() {
+        assert_eq!(expand_tabs("AB\tC\t", 4, true), "AB→ C→  ");
+    }
+}
+
+mod invisibles {
+    use crate::screen::mark_invisibles;
+
+    #[test]
+    fn nothing_to_mark() {
+        assert_eq!(
+            mark_invisibles("A line\nAnother line"),
+            "A line\nAnother line"
+        );
+    }
+
+    #[test]
+    fn trailing_spaces() {
+        assert_eq!(
+            mark_invisibles("A line  \nAnother line "),
+            "A line··\nAnother line·"
+        );
+    }
+
+    #[test]
+    fn non_breaking_spaces() {
+        assert_eq!(mark_invisibles("A\u{a0}line"), "A␣line");
+    }
+}
+
+mod control_chars {
+    use crate::{screen::escape_control_chars, ControlChars};
+
+    #[test]
+    fn raw() {
+        assert_eq!(
+            escape_control_chars("A\x01B\x1b[1mC\u{9b}", ControlChars::Raw),
+            "A\x01B\x1b[1mC\u{9b}"
+        );
+    }
+
+    #[test]
+    fn escape() {
+        assert_eq!(
+            escape_control_chars("A\x01B\x7fC\u{9b}D\x1b[1mE", ControlChars::Escape),
+            "A^AB^?C<U+009B>D^[[1mE"
+        );
+        // Newlines and tabs are left as is
+        assert_eq!(
+            escape_control_chars("A\tB\nC", ControlChars::Escape),
+            "A\tB\nC"
+        );
+    }
+
+    #[test]
+    fn escape_except_colors() {
+        assert_eq!(
+            escape_control_chars(
+                "\x1b[1;31mA\x1b[0m\x1b[2JB\x1b",
+                ControlChars::EscapeExceptColors
+            ),
+            "\x1b[1;31mA\x1b[0m^[[2JB^["
+        );
+    }
+}
+
+mod styles {
+    use crate::screen::{wrap_line, StyledLine};
+    use crossterm::style::{Color, ContentStyle, Stylize};
+
+    #[test]
+    fn styled_line() {
+        let line = StyledLine::new("red bold plain")
+            .style(0..8, ContentStyle::new().with(Color::Red))
+            .style(4..8, ContentStyle::new().bold());
+        assert_eq!(
+            line.to_string(),
+            "\x1b[38;5;9mred \x1b[39m\x1b[38;5;9m\x1b[1mbold\x1b[0m plain"
+        );
+        // Ranges are clamped to the text and to the starts of characters
+        let line = StyledLine::new("añb").style(2..10, ContentStyle::new().bold());
+        assert_eq!(line.to_string(), "a\x1b[1mñb\x1b[0m");
+    }
+
+    #[test]
+    fn carry_styles_across_rows() {
+        let rows = wrap_line("\x1b[1mbold text\x1b[0m and more", 10, "", "");
+        assert_eq!(rows, ["\x1b[1mbold text\x1b[0m", "and more"]);
+        let rows = wrap_line("plain \x1b[31mred text\x1b[0m", 10, "", "");
+        assert_eq!(rows, ["plain \x1b[31mred\x1b[0m", "\x1b[31mtext\x1b[0m"]);
+    }
+}
+
+mod strip_ansi {
+    use crate::screen::strip_ansi;
+
+    #[test]
+    fn colors() {
+        assert_eq!(
+            strip_ansi("\x1b[1;31mError:\x1b[0m file \u{9b}4mnot\u{9b}24m found"),
+            "Error: file not found"
+        );
+    }
+
+    #[test]
+    fn other_sequences() {
+        // Title, hyperlink, cursor movement and character set selection
+        assert_eq!(
+            strip_ansi("\x1b]0;title\x07\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\\x1b[2K\x1b(Btext\x1b="),
+            "linktext"
+        );
+    }
+
+    #[test]
+    fn unterminated_sequence() {
+        // Only the rest of the line is removed
+        assert_eq!(strip_ansi("A\x1b[31\nB\x1b]0;title\nC\x1b"), "A\nB\nC");
+    }
+
+    #[test]
+    fn plain_text() {
+        assert!(matches!(
+            strip_ansi("plain text"),
+            std::borrow::Cow::Borrowed(_)
+        ));
+    }
+}
+
+mod overstrike {
+    use crate::{screen::render_overstrike, ControlChars};
+
+    #[test]
+    fn bold_and_underline() {
+        assert_eq!(
+            render_overstrike("N\x08NA\x08AM\x08ME\x08E ls", ControlChars::Raw),
+            "\x1b[1mNAME\x1b[22m ls"
+        );
+        assert_eq!(
+            render_overstrike("_\x08f_\x08i_\x08l_\x08e\n", ControlChars::Raw),
+            "\x1b[4mfile\x1b[24m\n"
+        );
+        // Both at once and other characters that are struck over
+        assert_eq!(
+            render_overstrike("_\x08a\x08a b\x08c", ControlChars::EscapeExceptColors),
+            "\x1b[1m\x1b[4ma\x1b[22m\x1b[24m c"
+        );
+    }
+
+    #[test]
+    fn left_as_is() {
+        assert_eq!(
+            render_overstrike("plain text", ControlChars::Raw),
+            "plain text"
+        );
+        // A backspace with nothing after it on the line is kept
+        assert_eq!(render_overstrike("a\x08\nb", ControlChars::Raw), "a\x08\nb");
+        assert_eq!(render_overstrike("a\x08a", ControlChars::Escape), "a\x08a");
+    }
+}
@@ -0,0 +1,388 @@
+//! Storage for the original text, kept in chunks of whole lines
+use std::{io, iter::FromIterator, ops::Range};
+
+/// Number of bytes after which no more lines are added to a chunk
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The original text given to the pager, stored in chunks of whole lines
+///
+/// Every chunk except the last one ends with a newline so no line is ever split between two
+/// chunks, and no chunk is empty. Appending text, discarding lines from either end or finding a
+/// line only touches the chunks involved instead of copying or scanning all of the text, which
+/// keeps very large texts fast to page through.
+#[derive(Debug, Default)]
+pub struct Text {
+    chunks: Vec<String>,
+    /// Number of newlines in each chunk
+    newlines: Vec<usize>,
+}
+
+impl Text {
+    /// Make an empty text
+    pub const fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            newlines: Vec::new(),
+        }
+    }
+
+    /// Check whether there is no text at all
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Get the length of the text in bytes
+    pub fn len(&self) -> usize {
+        self.chunks.iter().map(String::len).sum()
+    }
+
+    /// Get the number of [`Lines`](std::str::Lines) in the text
+    pub fn line_count(&self) -> usize {
+        let unterminated = matches!(self.chunks.last(), Some(chunk) if !chunk.ends_with('\n'));
+        self.newlines.iter().sum::<usize>() + usize::from(unterminated)
+    }
+
+    /// Check whether the text ends with `suffix`, which may span several chunks
+    pub fn ends_with(&self, suffix: &str) -> bool {
+        let mut suffix = suffix.as_bytes();
+        for chunk in self.chunks.iter().rev().map(String::as_bytes) {
+            if chunk.len() >= suffix.len() {
+                return chunk.ends_with(suffix);
+            }
+            if !suffix.ends_with(chunk) {
+                return false;
+            }
+            suffix = &suffix[..suffix.len() - chunk.len()];
+        }
+        suffix.is_empty()
+    }
+
+    /// Append `text` at the end
+    ///
+    /// Once the last chunk is full and its last line has been terminated, the text goes into new
+    /// chunks.
+    pub fn push_str(&mut self, text: &str) {
+        let mut rest = text;
+        while !rest.is_empty() {
+            let has_room = matches!(
+                self.chunks.last(),
+                Some(chunk) if chunk.len() < CHUNK_SIZE || !chunk.ends_with('\n')
+            );
+            if !has_room {
+                self.chunks.push(String::new());
+                self.newlines.push(0);
+            }
+            let chunk = self.chunks.last_mut().unwrap();
+            // Fill the chunk up to its size and then up to the end of the line where that ends
+            let room = CHUNK_SIZE.saturating_sub(chunk.len()).min(rest.len());
+            let end = rest.as_bytes()[room..]
+                .iter()
+                .position(|b| *b == b'\n')
+                .map_or(rest.len(), |idx| room + idx + 1);
+            let (part, tail) = rest.split_at(end);
+            chunk.push_str(part);
+            *self.newlines.last_mut().unwrap() += newline_count(part);
+            rest = tail;
+        }
+    }
+
+    /// Remove the last character and return it
+    pub fn pop(&mut self) -> Option<char> {
+        let chunk = self.chunks.last_mut()?;
+        let c = chunk.pop();
+        if c == Some('\n') {
+            *self.newlines.last_mut().unwrap() -= 1;
+        }
+        if chunk.is_empty() {
+            self.chunks.pop();
+            self.newlines.pop();
+        }
+        c
+    }
+
+    /// Remove all the text
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.newlines.clear();
+    }
+
+    /// Get the chunks of the text along with the number of lines in each of them
+    pub fn chunks(&self) -> impl Iterator<Item = (&str, usize)> {
+        let last = self.chunks.len().saturating_sub(1);
+        self.chunks
+            .iter()
+            .zip(&self.newlines)
+            .enumerate()
+            .map(move |(idx, (chunk, newlines))| {
+                (
+                    chunk.as_str(),
+                    newlines + usize::from(idx == last && !chunk.ends_with('\n')),
+                )
+            })
+    }
+
+    /// Get all the [`Lines`](std::str::Lines) of the text
+    pub fn lines(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.chunks.iter().flat_map(|chunk| chunk.lines())
+    }
+
+    /// Get the [`Lines`](std::str::Lines) of the text starting from the one at index `start`
+    ///
+    /// The chunks before the line are skipped without looking at their text.
+    pub fn lines_from(&self, start: usize) -> impl Iterator<Item = &str> {
+        self.chunks_from(start).flat_map(str::lines)
+    }
+
+    /// Get the last line of the text
+    pub fn last_line(&self) -> Option<&str> {
+        self.lines().next_back()
+    }
+
+    /// Write the lines in `lines`, with their newlines, to `out`
+    pub fn write_lines(&self, out: &mut impl io::Write, lines: Range<usize>) -> io::Result<()> {
+        for line in self
+            .chunks_from(lines.start)
+            .flat_map(|chunk| chunk.split_inclusive('\n'))
+            .take(lines.len())
+        {
+            out.write_all(line.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Write all the text to `out`
+    pub fn write_to(&self, out: &mut impl io::Write) -> io::Result<()> {
+        for chunk in &self.chunks {
+            out.write_all(chunk.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Remove the first `count` lines
+    ///
+    /// Returns false and leaves the text untouched if there are not that many terminated lines.
+    pub fn remove_first_lines(&mut self, count: usize) -> bool {
+        let Some((chunk, offset)) = self.line_start(count) else {
+            return false;
+        };
+        self.chunks.drain(..chunk);
+        self.newlines.drain(..chunk);
+        if let Some(first) = self.chunks.first_mut() {
+            self.newlines[0] -= newline_count(&first[..offset]);
+            first.drain(..offset);
+            if first.is_empty() {
+                self.chunks.remove(0);
+                self.newlines.remove(0);
+            }
+        }
+        true
+    }
+
+    /// Remove all the lines after the first `count` ones
+    pub fn truncate_lines(&mut self, count: usize) {
+        let Some((chunk, offset)) = self.line_start(count) else {
+            return;
+        };
+        self.chunks.truncate(chunk + 1);
+        self.newlines.truncate(chunk + 1);
+        if let Some(last) = self.chunks.get_mut(chunk) {
+            self.newlines[chunk] -= newline_count(&last[offset..]);
+            last.truncate(offset);
+            if last.is_empty() {
+                self.chunks.pop();
+                self.newlines.pop();
+            }
+        }
+    }
+
+    /// Replace the lines in `lines` with `text`
+    ///
+    /// `text` must be empty or end with a newline. The lines must lie within the terminated lines
+    /// of the text, or else end at its end. Only the chunks containing the lines are split up
+    /// again.
+    pub fn replace_lines(&mut self, lines: Range<usize>, text: &str) {
+        let len = self.chunks.len();
+        let (start_chunk, start) = self.line_start(lines.start).unwrap_or((len, 0));
+        let (end_chunk, end) = self.line_start(lines.end).unwrap_or((len, 0));
+        // Take the chunks from the one with the first line up to the one where the last line ends
+        let end_chunk = if end > 0 { end_chunk + 1 } else { end_chunk };
+        let mut replaced = Self::new();
+        if let Some(first) = self.chunks.get(start_chunk) {
+            replaced.push_str(&first[..start]);
+        }
+        replaced.push_str(text);
+        if end > 0 {
+            replaced.push_str(&self.chunks[end_chunk - 1][end..]);
+        }
+        self.chunks.splice(start_chunk..end_chunk, replaced.chunks);
+        self.newlines
+            .splice(start_chunk..end_chunk, replaced.newlines);
+    }
+
+    /// Get the text from the start of the line at index `line` split at the ends of the chunks
+    fn chunks_from(&self, line: usize) -> impl Iterator<Item = &str> {
+        let (chunk, offset) = self.line_start(line).unwrap_or((self.chunks.len(), 0));
+        let first = self.chunks.get(chunk).map_or("", |text| &text[offset..]);
+        let rest = self.chunks.get(chunk + 1..).unwrap_or_default();
+        std::iter::once(first).chain(rest.iter().map(String::as_str))
+    }
+
+    /// Get the index of the chunk and the position in it where the line at index `line` starts
+    ///
+    /// The line right after the last newline starts at the end of the text. Returns `None` if the
+    /// text has fewer newlines than `line`.
+    fn line_start(&self, line: usize) -> Option<(usize, usize)> {
+        let mut before = 0;
+        for (idx, (chunk, lines)) in self.chunks().enumerate() {
+            if line < before + lines {
+                let offset = (line - before)
+                    .checked_sub(1)
+                    .and_then(|prev| chunk.match_indices('\n').nth(prev))
+                    .map_or(0, |(idx, _)| idx + 1);
+                return Some((idx, offset));
+            }
+            before += self.newlines[idx];
+        }
+        (line == before).then_some((self.chunks.len(), 0))
+    }
+}
+
+fn newline_count(text: &str) -> usize {
+    text.bytes().filter(|b| *b == b'\n').count()
+}
+
+impl From<&str> for Text {
+    fn from(text: &str) -> Self {
+        let mut new = Self::new();
+        new.push_str(text);
+        new
+    }
+}
+
+impl From<String> for Text {
+    fn from(text: String) -> Self {
+        // Text which fits in a single chunk is kept without being copied
+        if text.is_empty() || text.len() > CHUNK_SIZE {
+            return Self::from(text.as_str());
+        }
+        Self {
+            newlines: vec![newline_count(&text)],
+            chunks: vec![text],
+        }
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for Text {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let mut text = Self::new();
+        for part in iter {
+            text.push_str(part.as_ref());
+        }
+        text
+    }
+}
+
+impl PartialEq<str> for Text {
+    fn eq(&self, other: &str) -> bool {
+        let mut rest = other;
+        for chunk in &self.chunks {
+            match rest.strip_prefix(chunk.as_str()) {
+                Some(tail) => rest = tail,
+                None => return false,
+            }
+        }
+        rest.is_empty()
+    }
+}
+
+impl PartialEq<&str> for Text {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<String> for Text {
+    fn eq(&self, other: &String) -> bool {
+        *self == *other.as_str()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Text, CHUNK_SIZE};
+
+    fn numbered_lines(count: usize) -> String {
+        (0..count).map(|n| format!("line {n}\n")).collect()
+    }
+
+    #[test]
+    fn chunks_hold_whole_lines() {
+        let lines = numbered_lines(30_000);
+        let mut text = Text::new();
+        text.push_str(&lines[..100]);
+        text.push_str(&lines[100..]);
+        text.push_str("unterminated");
+        assert!(text.chunks.len() > 2);
+        for (chunk, _) in text.chunks().take(text.chunks.len() - 1) {
+            assert!(chunk.ends_with('\n'));
+            assert!(chunk.len() < CHUNK_SIZE + 16);
+        }
+        assert_eq!(text, lines.clone() + "unterminated");
+        assert_eq!(text.line_count(), 30_001);
+        assert_eq!(text.chunks().map(|(_, lines)| lines).sum::<usize>(), 30_001);
+        assert_eq!(text.last_line(), Some("unterminated"));
+        assert_eq!(text.lines_from(20_000).next(), Some("line 20000"));
+        assert_eq!(
+            text.lines_from(30_000).collect::<Vec<_>>(),
+            ["unterminated"]
+        );
+        assert!(text.ends_with("line 29999\nunterminated"));
+
+        let mut out = Vec::new();
+        text.write_lines(&mut out, 29_999..30_001).unwrap();
+        assert_eq!(out, b"line 29999\nunterminated");
+    }
+
+    #[test]
+    fn remove_lines() {
+        let lines = numbered_lines(30_000);
+        let mut text = Text::from(lines.as_str());
+        assert!(!text.remove_first_lines(30_001));
+        assert!(text.remove_first_lines(20_000));
+        assert_eq!(text, &lines[lines.find("line 20000\n").unwrap()..]);
+        assert_eq!(text.line_count(), 10_000);
+
+        text.truncate_lines(5);
+        assert_eq!(
+            text,
+            "line 20000\nline 20001\nline 20002\nline 20003\nline 20004\n"
+        );
+        text.truncate_lines(0);
+        assert!(text.is_empty());
+    }
+
+    #[test]
+    fn replace_lines() {
+        let lines = numbered_lines(30_000);
+        let mut text = Text::from(lines.as_str());
+        text.replace_lines(10_000..20_000, "a\nb\n");
+        let expected = format!(
+            "{}a\nb\n{}",
+            &lines[..lines.find("line 10000\n").unwrap()],
+            &lines[lines.find("line 20000\n").unwrap()..]
+        );
+        assert_eq!(text, expected);
+        assert_eq!(text.line_count(), 20_002);
+        assert_eq!(text.lines().nth(10_001), Some("b"));
+
+        let mut text = Text::from("a\nb\nc");
+        text.replace_lines(1..1, "x\n");
+        assert_eq!(text, "a\nx\nb\nc");
+        text.replace_lines(2..4, "");
+        assert_eq!(text, "a\nx\n");
+        text.replace_lines(2..2, "y\n");
+        assert_eq!(text, "a\nx\ny\n");
+        text.replace_lines(0..3, "");
+        assert!(text.is_empty());
+    }
+}
@@ -51,14 +51,18 @@
 //! ```
 
 #![allow(unused_imports)]
-use crate::minus_core::utils::{display, term};
-use crate::screen::Screen;
+use crate::minus_core::utils::{
+    display,
+    input_field::{self, FieldEvent, InputField},
+    term,
+};
+use crate::screen::{LineProvider, Screen};
 use crate::{error::MinusError, input::HashedEventRegister, screen};
-use crate::{LineNumbers, PagerState};
+use crate::{theme, LineNumbers, PagerState, Viewport};
 use crossterm::{
     cursor::{self, MoveTo},
     event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
-    style::Attribute,
+    style::{Attribute, ContentStyle},
     terminal::{Clear, ClearType},
 };
 use once_cell::sync::Lazy;
@@ -67,21 +71,17 @@ use std::collections::BTreeSet;
 use std::{
     convert::{TryFrom, TryInto},
     io::Write,
+    mem,
     time::Duration,
 };
 
 use std::collections::hash_map::RandomState;
 
-static INVERT: Lazy<String> = Lazy::new(|| Attribute::Reverse.to_string());
-static NORMAL: Lazy<String> = Lazy::new(|| Attribute::NoReverse.to_string());
 static ANSI_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new("[\\u001b\\u009b]\\[[()#;?]*(?:[0-9]{1,4}(?:;[0-9]{0,4})*)?[0-9A-ORZcf-nqry=><]")
         .unwrap()
 });
 
-static WORD: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r#"([\w_]+)|([-?~@#!$%^&*()-+={}\[\]:;\\|'/?<>.,"]+)|\W"#).unwrap());
-
 #[derive(Clone, Copy, Debug, Eq)]
 #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
 #[allow(clippy::module_name_repetitions)]
@@ -139,6 +139,8 @@ pub struct SearchOpts<'a> {
     pub incremental_search_options: Option<IncrementalSearchOpts<'a>>,
     incremental_search_cache: Option<IncrementalSearchCache>,
     compiled_regex: Option<Regex>,
+    prompt_style: ContentStyle,
+    viewport: Option<Viewport>,
 }
 
 /// Options to control incremental search
@@ -154,15 +156,25 @@ pub struct IncrementalSearchOpts<'a> {
     pub screen: &'a Screen,
     /// Value of [PagerState::upper_mark] before starting of search prompt
     pub initial_left_mark: usize,
+    /// Number of columns that the text is formatted to, leaving out the scrollbar
+    pub text_cols: usize,
+    /// Number of rows that the text can take up, leaving out the prompt and the status area
+    pub writable_rows: usize,
+    /// Lines of the status area to draw back below the text, as they are displayed
+    pub status: Vec<String>,
 }
 
 impl<'a> From<&'a PagerState> for IncrementalSearchOpts<'a> {
     fn from(ps: &'a PagerState) -> Self {
         Self {
-            line_numbers: ps.line_numbers,
+            line_numbers: ps.displayed_line_numbers(),
             initial_upper_mark: ps.upper_mark,
             screen: &ps.screen,
             initial_left_mark: ps.left_mark,
+            text_cols: ps.text_cols(),
+            // The search prompt is drawn over the last row in the minimal layout
+            writable_rows: ps.writable_rows().min(ps.rows.saturating_sub(1)),
+            status: ps.displayed_status().collect(),
         }
     }
 }
@@ -178,7 +190,11 @@ impl<'a> From<&'a PagerState> for SearchOpts<'a> {
             unreachable!();
         };
 
-        let incremental_search_options = IncrementalSearchOpts::from(ps);
+        // Only the lines around the screen are available when they are served by a line provider
+        // and redrawing the whole screen would clear everything around a viewport
+        let incremental_search_options = (ps.screen.line_provider.is_none()
+            && ps.viewport.is_none())
+        .then(|| IncrementalSearchOpts::from(ps));
 
         Self {
             ev: None,
@@ -189,14 +205,23 @@ impl<'a> From<&'a PagerState> for SearchOpts<'a> {
             search_char,
             rows: ps.rows.try_into().unwrap(),
             cols: ps.cols.try_into().unwrap(),
-            incremental_search_options: Some(incremental_search_options),
+            incremental_search_options,
             incremental_search_cache: None,
             compiled_regex: None,
             search_mode: ps.search_state.search_mode,
+            prompt_style: ps.screen.color_support.adapt(ps.screen.theme.search_prompt),
+            viewport: ps.viewport,
         }
     }
 }
 
+impl SearchOpts<'_> {
+    /// Move the cursor to the given `column` of the prompt row
+    fn move_cursor(&self, out: &mut impl Write, column: u16, flush: bool) -> crate::Result {
+        input_field::move_to_prompt(out, self.rows, self.viewport, column, flush)
+    }
+}
+
 /// Status of the search prompt
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum InputStatus {
@@ -254,6 +279,12 @@ pub(crate) struct IncrementalSearchCache {
     pub(crate) upper_mark: usize,
 }
 
+/// Draw back the status area that was cleared along with the text during incremental search
+fn draw_status(out: &mut impl Write, iso: &IncrementalSearchOpts<'_>) -> crate::Result {
+    let status_row = iso.writable_rows.try_into().unwrap_or(u16::MAX);
+    display::write_status(out, &iso.status, 0, status_row)
+}
+
 /// Runs the incremental search
 ///
 /// It will return if `Ok(SomeIncrementalSearchCache)` if there was a successful run of incremental
@@ -283,26 +314,25 @@ where
     // This is an important bit when running incremental search.It reset the terminal screen to
     // display the lines from the same location and in the same way as before the search even
     // started. Basically print it exactly how it looked before pressing `/` or `?`,
-    let reset_screen = |out: &mut O, so: &SearchOpts<'_>| -> crate::Result {
+    let reset_screen = |out: &mut O| -> crate::Result {
         display::write_text_checked(
             out,
             &iso.screen.formatted_lines,
             iso.initial_upper_mark,
-            so.rows.into(),
-            so.cols.into(),
+            iso.writable_rows,
+            iso.text_cols,
             iso.screen.line_wrapping,
             iso.initial_left_mark,
-            iso.line_numbers,
-            iso.screen.line_count(),
+            iso.screen.fixed_width(iso.line_numbers),
         )?;
-        Ok(())
+        draw_status(out, iso)
     };
 
     // If the query prior to the current one had a successful incremental search run and now the
     // current query isn't a valid regex or the incremental search condition has returned false
     // then
     if so.incremental_search_cache.is_some() && !should_proceed {
-        reset_screen(out, so)?;
+        reset_screen(out)?;
         return Ok(None);
     }
 
@@ -319,10 +349,9 @@ where
     //
     // PERF: Check if this can be futhur optimized
     let (buffer, format_result) = screen::make_format_lines(
-        &iso.screen.orig_text,
+        iso.screen,
         iso.line_numbers,
-        so.cols.into(),
-        iso.screen.line_wrapping,
+        iso.text_cols,
         &so.compiled_regex,
     );
     let position_of_next_match =
@@ -336,15 +365,15 @@ where
             out,
             &buffer,
             upper_mark,
-            so.rows.into(),
-            so.cols.into(),
+            iso.writable_rows,
+            iso.text_cols,
             iso.screen.line_wrapping,
             iso.initial_left_mark,
-            iso.line_numbers,
-            iso.screen.line_count(),
+            iso.screen.fixed_width(iso.line_numbers),
         )?;
+        draw_status(out, iso)?;
     } else {
-        reset_screen(out, so)?;
+        reset_screen(out)?;
         return Ok(None);
     }
     // Return the results obtained by running incremental search so that they can be stored as a
@@ -360,7 +389,6 @@ where
 /// Respond to keyboard events
 ///
 /// This souuld be called exactly once for each event by [fetch_input]
-#[allow(clippy::too_many_lines)]
 fn handle_key_press<O, F>(
     out: &mut O,
     so: &mut SearchOpts<'_>,
@@ -370,186 +398,67 @@ where
     O: Write,
     F: Fn(&SearchOpts<'_>) -> bool,
 {
-    // Bounds between which our cursor can move
-    const FIRST_AVAILABLE_COLUMN: u16 = 1;
-    let last_available_column: u16 = so.string.len().saturating_add(1).try_into().unwrap();
-
     // If no event is present, abort
-    if so.ev.is_none() {
+    let Some(ev) = so.ev.as_ref() else {
         return Ok(());
-    }
-
-    let populate_word_index = |so: &mut SearchOpts<'_>| {
-        so.word_index = WORD
-            .find_iter(&so.string)
-            .map(|c| c.start().saturating_add(1).try_into().unwrap())
-            .collect::<Vec<u16>>();
     };
 
-    let refresh_display = |out: &mut O, so: &mut SearchOpts<'_>| -> Result<(), MinusError> {
-        // Cache the compiled regex if the regex is valid
-        so.compiled_regex = Regex::new(&so.string).ok();
-
-        // Run incremental search and update the upper mark if incremental search had a successful
-        // run otherwise set it to the initial upper mark
-        so.incremental_search_cache =
-            run_incremental_search(out, so, incremental_search_condition)?;
-
-        // Update prompt
-        term::move_cursor(out, 0, so.rows, false)?;
-        write!(
-            out,
-            "\r{}{}{}",
-            Clear(ClearType::CurrentLine),
-            so.search_char,
-            so.string,
-        )?;
-        Ok(())
+    let mut field = InputField {
+        string: mem::take(&mut so.string),
+        cursor_position: so.cursor_position,
+        word_index: mem::take(&mut so.word_index),
     };
+    let field_event = field.handle_event(ev);
+    so.string = field.string;
+    so.cursor_position = field.cursor_position;
+    so.word_index = field.word_index;
 
-    match so.ev.as_ref().unwrap() {
-        Event::Key(KeyEvent { kind, .. }) if *kind != KeyEventKind::Press => (),
+    match field_event {
         // If Esc is pressed, cancel the search and also make sure that the search query is
-        // ")cleared
-        Event::Key(KeyEvent {
-            code: KeyCode::Esc,
-            modifiers: KeyModifiers::NONE,
-            ..
-        }) => {
+        // cleared
+        FieldEvent::Cancelled => {
             so.string.clear();
             so.input_status = InputStatus::Cancelled;
         }
-        Event::Key(KeyEvent {
-            code: KeyCode::Backspace,
-            modifiers: KeyModifiers::NONE,
-            ..
-        }) => {
-            // On backspace, remove the last character just before the cursor from the so.string
-            // But if we are at very first character, do nothing.
-            if so.cursor_position == FIRST_AVAILABLE_COLUMN {
-                return Ok(());
-            }
-            so.cursor_position = so.cursor_position.saturating_sub(1);
-            so.string
-                .remove(so.cursor_position.saturating_sub(1).into());
-            populate_word_index(so);
-            // Update the line
-            refresh_display(out, so)?;
-            term::move_cursor(out, so.cursor_position, so.rows, false)?;
-            out.flush()?;
-        }
-        Event::Key(KeyEvent {
-            code: KeyCode::Delete,
-            modifiers: KeyModifiers::NONE,
-            ..
-        }) => {
-            // On delete, remove the character under the cursor from the so.string
-            // But if we are at the column right after the last character, do nothing.
-            if so.cursor_position >= last_available_column {
-                return Ok(());
-            }
-            so.cursor_position = so.cursor_position.saturating_sub(1);
-            so.string
-                .remove(<u16 as Into<usize>>::into(so.cursor_position));
-            populate_word_index(so);
-            so.cursor_position = so.cursor_position.saturating_add(1);
-            // Update the line
-            refresh_display(out, so)?;
-            term::move_cursor(out, so.cursor_position, so.rows, false)?;
+        FieldEvent::Confirmed => so.input_status = InputStatus::Confirmed,
+        FieldEvent::Changed => {
+            // Cache the compiled regex if the regex is valid
+            so.compiled_regex = Regex::new(&so.string).ok();
+
+            // Run incremental search and update the upper mark if incremental search had a
+            // successful run otherwise set it to the initial upper mark
+            so.incremental_search_cache =
+                run_incremental_search(out, so, incremental_search_condition)?;
+
+            // Update the prompt
+            write_search_prompt(out, so)?;
+            so.move_cursor(out, so.cursor_position, false)?;
             out.flush()?;
         }
-        Event::Key(KeyEvent {
-            code: KeyCode::Enter,
-            modifiers: KeyModifiers::NONE,
-            ..
-        }) => {
-            so.input_status = InputStatus::Confirmed;
-        }
-        Event::Key(KeyEvent {
-            code: KeyCode::Left,
-            modifiers: KeyModifiers::NONE,
-            ..
-        }) => {
-            if so.cursor_position == FIRST_AVAILABLE_COLUMN {
-                return Ok(());
-            }
-            so.cursor_position = so.cursor_position.saturating_sub(1);
-            term::move_cursor(out, so.cursor_position, so.rows, true)?;
-        }
-        Event::Key(KeyEvent {
-            code: KeyCode::Left,
-            modifiers: KeyModifiers::CONTROL,
-            ..
-        }) => {
-            // Find the column number where a word starts which is exactly before the current
-            // cursor position
-            // If we can't find any such column, jump to the very first available column
-            so.cursor_position = *so
-                .word_index
-                .iter()
-                .rfind(|c| c < &&so.cursor_position)
-                .unwrap_or(&FIRST_AVAILABLE_COLUMN);
-            term::move_cursor(out, so.cursor_position, so.rows, true)?;
-        }
-        Event::Key(KeyEvent {
-            code: KeyCode::Right,
-            modifiers: KeyModifiers::NONE,
-            ..
-        }) => {
-            if so.cursor_position >= last_available_column {
-                return Ok(());
-            }
-            so.cursor_position = so.cursor_position.saturating_add(1);
-            term::move_cursor(out, so.cursor_position, so.rows, true)?;
-        }
-        Event::Key(KeyEvent {
-            code: KeyCode::Right,
-            modifiers: KeyModifiers::CONTROL,
-            ..
-        }) => {
-            // Find the column number where a word starts which is exactly after the current
-            // cursor position
-            // If we can't find any such column, jump to the very last available column
-            so.cursor_position = *so
-                .word_index
-                .iter()
-                .find(|c| c > &&so.cursor_position)
-                .unwrap_or(&last_available_column);
-            term::move_cursor(out, so.cursor_position, so.rows, true)?;
-        }
-        Event::Key(KeyEvent {
-            code: KeyCode::Home,
-            modifiers: KeyModifiers::NONE,
-            ..
-        }) => {
-            so.cursor_position = 1;
-            term::move_cursor(out, 1, so.rows, true)?;
-        }
-        Event::Key(KeyEvent {
-            code: KeyCode::End,
-            modifiers: KeyModifiers::NONE,
-            ..
-        }) => {
-            so.cursor_position = so.string.len().saturating_add(1).try_into().unwrap();
-            term::move_cursor(out, so.cursor_position, so.rows, true)?;
-        }
+        FieldEvent::Moved => so.move_cursor(out, so.cursor_position, true)?,
+        FieldEvent::Ignored => {}
+    }
+    Ok(())
+}
 
-        Event::Key(event) => {
-            // For any character key, without a modifier, insert it into so.string before
-            // current cursor position and update the line
-            if let KeyCode::Char(c) = event.code {
-                so.string
-                    .insert(so.cursor_position.saturating_sub(1).into(), c);
-
-                populate_word_index(so);
-                refresh_display(out, so)?;
-                so.cursor_position = so.cursor_position.saturating_add(1);
-                term::move_cursor(out, so.cursor_position, so.rows, false)?;
-                out.flush()?;
-            }
-        }
-        _ => return Ok(()),
+/// Writes the search character followed by the query typed so far at the prompt site
+///
+/// The query is padded to take up the entire row so that the prompt style covers all of it. Callers
+/// must place the cursor at the correct position afterwards.
+fn write_search_prompt(out: &mut impl Write, so: &SearchOpts<'_>) -> Result<(), MinusError> {
+    let query = format!("{}{}", so.search_char, so.string);
+    so.move_cursor(out, 0, false)?;
+    // The padded query already covers the whole row of a viewport, the rest of the terminal row
+    // must be left alone
+    if so.viewport.is_none() {
+        write!(out, "\r{}", Clear(ClearType::CurrentLine))?;
     }
+    write!(
+        out,
+        "{}",
+        so.prompt_style
+            .apply(format!("{query:<width$}", width = usize::from(so.cols))),
+    )?;
     Ok(())
 }
 
@@ -565,34 +474,26 @@ pub(crate) fn fetch_input(
     out: &mut impl std::io::Write,
     ps: &PagerState,
 ) -> Result<FetchInputResult, MinusError> {
-    // Set the search character to show at column 0
-    let search_char = if ps.search_state.search_mode == SearchMode::Forward {
-        '/'
-    } else {
-        '?'
-    };
+    let mut search_opts = SearchOpts::from(ps);
 
     // Initial setup
-    // - Place the cursor at the beginning of prompt line
     // - Clear the prompt
-    // - Write the search character and
+    // - Write the search character
+    // - Place the cursor right after the search character and
     // - Show the cursor
-    term::move_cursor(out, 0, ps.rows.try_into().unwrap(), false)?;
-    write!(
-        out,
-        "{}{}{}",
-        Clear(ClearType::CurrentLine),
-        search_char,
-        cursor::Show
-    )?;
+    write_search_prompt(out, &search_opts)?;
+    search_opts.move_cursor(out, search_opts.cursor_position, false)?;
+    write!(out, "{}", cursor::Show)?;
     out.flush()?;
 
-    let mut search_opts = SearchOpts::from(ps);
-
     // Fetch events from the terminal and handle them
     loop {
-        if event::poll(Duration::from_millis(100)).map_err(|e| MinusError::HandleEvent(e.into()))? {
-            let ev = event::read().map_err(|e| MinusError::HandleEvent(e.into()))?;
+        let ev = ps
+            .event_source
+            .lock()
+            .next_event(Duration::from_millis(100))
+            .map_err(|e| MinusError::HandleEvent(e.into()))?;
+        if let Some(ev) = ev {
             search_opts.ev = Some(ev);
             handle_key_press(
                 out,
@@ -606,8 +507,12 @@ pub(crate) fn fetch_input(
         }
     }
     // Teardown: almost opposite of setup
-    term::move_cursor(out, 0, ps.rows.try_into().unwrap(), false)?;
-    write!(out, "{}{}", Clear(ClearType::CurrentLine), cursor::Hide)?;
+    // The prompt is redrawn afterwards, which also covers the search prompt inside a viewport
+    search_opts.move_cursor(out, 0, false)?;
+    if search_opts.viewport.is_none() {
+        write!(out, "{}", Clear(ClearType::CurrentLine))?;
+    }
+    write!(out, "{}", cursor::Hide)?;
     out.flush()?;
 
     let fetch_input_result = match search_opts.input_status {
@@ -624,13 +529,14 @@ pub(crate) fn fetch_input(
     Ok(fetch_input_result)
 }
 
-/// Highlights the search match
+/// Highlights the search match with `style`
 ///
 /// The first return value returns the line that has all the search matches highlighted
 /// The second tells whether a search match was actually found
 pub(crate) fn highlight_line_matches(
     line: &str,
     query: &regex::Regex,
+    style: ContentStyle,
     accurate: bool,
 ) -> (String, bool) {
     // Remove all ansi escapes so we can look through it as if it had none
@@ -640,6 +546,7 @@ pub(crate) fn highlight_line_matches(
     if !query.is_match(&stripped_str) {
         return (line.to_string(), false);
     }
+    let (invert, normal) = theme::style_escapes(style);
 
     // sum_width is used to calculate the total width of the ansi escapes
     // up to the point in the original string where it is being used
@@ -671,7 +578,7 @@ pub(crate) fn highlight_line_matches(
     // by inverting their background/foreground colors
     let mut inverted = query
         .replace_all(&stripped_str, |caps: &regex::Captures| {
-            format!("{}{}{}", *INVERT, &caps[0], *NORMAL)
+            format!("{invert}{}{normal}", &caps[0])
         })
         .to_string();
 
@@ -694,12 +601,12 @@ pub(crate) fn highlight_line_matches(
         let mut pos = if !accurate && match_count % 2 == 1 {
             // INFO: Its safe to unwrap here
             matches.get(match_count).unwrap()
-                + NORMAL.len()
+                + normal.len()
                 + inserted_escs_len
-                + (num_invert * INVERT.len())
-                + (num_normal * NORMAL.len())
+                + (num_invert * invert.len())
+                + (num_normal * normal.len())
         } else {
-            esc.0 + inserted_escs_len + (num_invert * INVERT.len()) + (num_normal * NORMAL.len())
+            esc.0 + inserted_escs_len + (num_invert * invert.len()) + (num_normal * normal.len())
         };
 
         if match_count % 2 == 1 {
@@ -716,6 +623,74 @@ pub(crate) fn highlight_line_matches(
     (inverted, true)
 }
 
+/// Draw the search matches on `row` with `current_style` instead of `style`
+///
+/// The matches have been highlighted with `style` by [`highlight_line_matches`] when the row was
+/// formatted. This swaps its escape sequences for those of `current_style` so that the current
+/// match can be told apart without formatting the row again.
+pub(crate) fn restyle_matches(
+    row: &str,
+    style: ContentStyle,
+    current_style: ContentStyle,
+) -> String {
+    let (start, end) = theme::style_escapes(style);
+    // Without any escape sequences, the matches can't be found
+    if start.is_empty() || end.is_empty() {
+        return row.to_string();
+    }
+    let (current_start, current_end) = theme::style_escapes(current_style);
+    row.replace(&start, &current_start)
+        .replace(&end, &current_end)
+}
+
+/// Find the `n`th line served by `provider` that matches `regex`
+///
+/// Lines are searched forward or backward starting from the line after or before `from`. They are
+/// fetched in chunks so that only the lines up to the match need to be fetched. Returns the index
+/// of the matching line, or None if there are fewer than `n` matches in that direction.
+pub(crate) fn find_provider_match(
+    provider: &dyn LineProvider,
+    regex: &Regex,
+    from: usize,
+    forward: bool,
+    n: usize,
+) -> Option<usize> {
+    const CHUNK_SIZE: usize = 1024;
+    let len = provider.len();
+    let mut matches_left = n.max(1);
+
+    if forward {
+        let mut start = from.saturating_add(1);
+        while start < len {
+            let end = start.saturating_add(CHUNK_SIZE).min(len);
+            for (offset, line) in provider.lines(start..end).iter().enumerate() {
+                if regex.is_match(line) {
+                    matches_left -= 1;
+                    if matches_left == 0 {
+                        return Some(start + offset);
+                    }
+                }
+            }
+            start = end;
+        }
+    } else {
+        let mut end = from.min(len);
+        while end > 0 {
+            let start = end.saturating_sub(CHUNK_SIZE);
+            for (offset, line) in provider.lines(start..end).iter().enumerate().rev() {
+                if regex.is_match(line) {
+                    matches_left -= 1;
+                    if matches_left == 0 {
+                        return Some(start + offset);
+                    }
+                }
+            }
+            end = start;
+        }
+    }
+    None
+}
+
 /// Return a index of an element from `search_idx` that will contain a search match and
 /// will be after the `upper_mark`
 ///
@@ -784,6 +759,7 @@ mod tests {
         use crossterm::{
             cursor::MoveTo,
             event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers},
+            style::ContentStyle,
             terminal::{Clear, ClearType},
         };
         use std::{convert::TryInto, io::Write};
@@ -808,6 +784,8 @@ mod tests {
                 incremental_search_cache: None,
                 compiled_regex: None,
                 search_mode: sm,
+                prompt_style: ContentStyle::new(),
+                viewport: None,
             }
         }
 
@@ -1016,9 +994,11 @@ mod tests {
                 cursor_position = cursor_position.saturating_add(1);
                 write!(
                     result_out,
-                    "{move_to_prompt}\r{clear_line}/{string}{move_to_position}",
+                    "{move_to_prompt}\r{clear_line}{query:<width$}{move_to_position}",
                     move_to_prompt = MoveTo(0, search_opts.rows),
                     clear_line = Clear(ClearType::CurrentLine),
+                    query = format!("/{string}"),
+                    width = usize::from(search_opts.cols),
                     move_to_position = MoveTo(cursor_position, search_opts.rows),
                 )
                 .unwrap();
@@ -1051,9 +1031,11 @@ mod tests {
                 cursor_position = cursor_position.saturating_add(1);
                 write!(
                     result_out,
-                    "{move_to_prompt}\r{clear_line}?{string}{move_to_position}",
+                    "{move_to_prompt}\r{clear_line}{query:<width$}{move_to_position}",
                     move_to_prompt = MoveTo(0, search_opts.rows),
                     clear_line = Clear(ClearType::CurrentLine),
+                    query = format!("?{string}"),
+                    width = usize::from(search_opts.cols),
                     move_to_position = MoveTo(cursor_position, search_opts.rows),
                 )
                 .unwrap();
@@ -1081,15 +1063,54 @@ mod tests {
     mod highlighting {
         use std::collections::BTreeSet;
 
-        use crate::search::{highlight_line_matches, next_nth_match, INVERT, NORMAL};
+        use crate::search::next_nth_match;
         use crate::PagerState;
-        use crossterm::style::Attribute;
+        use crossterm::style::{Attribute, ContentStyle, Stylize};
+        use once_cell::sync::Lazy;
         use regex::Regex;
 
+        static INVERT: Lazy<String> = Lazy::new(|| Attribute::Reverse.to_string());
+        static NORMAL: Lazy<String> = Lazy::new(|| Attribute::NoReverse.to_string());
+
+        fn highlight_line_matches(
+            line: &str,
+            query: &regex::Regex,
+            accurate: bool,
+        ) -> (String, bool) {
+            crate::search::highlight_line_matches(
+                line,
+                query,
+                ContentStyle::new().reverse(),
+                accurate,
+            )
+        }
+
         // generic escape code
         const ESC: &str = "\x1b[34m";
         const NONE: &str = "\x1b[0m";
 
+        #[test]
+        fn restyle_current_matches() {
+            let row = format!(
+                "a {}test{} and {}test{}",
+                *INVERT, *NORMAL, *INVERT, *NORMAL
+            );
+            let current = ContentStyle::new().underlined();
+            assert_eq!(
+                crate::search::restyle_matches(&row, ContentStyle::new().reverse(), current),
+                format!(
+                    "a {u}test{nu} and {u}test{nu}",
+                    u = Attribute::Underlined,
+                    nu = Attribute::NoUnderline
+                )
+            );
+            // Nothing can be restyled if the matches have no style
+            assert_eq!(
+                crate::search::restyle_matches("a test", ContentStyle::new(), current),
+                "a test"
+            );
+        }
+
         mod consistent {
             use super::*;
 
@@ -1299,4 +1320,58 @@ eros.",
             }
         }
     }
+
+    mod provider {
+        use crate::{search::find_provider_match, LineProvider};
+        use regex::Regex;
+        use std::ops::Range;
+
+        struct EveryThousandth;
+
+        impl LineProvider for EveryThousandth {
+            fn len(&self) -> usize {
+                5000
+            }
+
+            fn lines(&self, range: Range<usize>) -> Vec<String> {
+                range
+                    .map(|n| if n % 1000 == 0 { "match" } else { "line" }.to_string())
+                    .collect()
+            }
+        }
+
+        #[test]
+        fn forward() {
+            let regex = Regex::new("match").unwrap();
+            assert_eq!(
+                find_provider_match(&EveryThousandth, &regex, 0, true, 1),
+                Some(1000)
+            );
+            assert_eq!(
+                find_provider_match(&EveryThousandth, &regex, 500, true, 2),
+                Some(2000)
+            );
+            assert_eq!(
+                find_provider_match(&EveryThousandth, &regex, 4000, true, 1),
+                None
+            );
+        }
+
+        #[test]
+        fn backward() {
+            let regex = Regex::new("match").unwrap();
+            assert_eq!(
+                find_provider_match(&EveryThousandth, &regex, 2500, false, 1),
+                Some(2000)
+            );
+            assert_eq!(
+                find_provider_match(&EveryThousandth, &regex, 2500, false, 3),
+                Some(0)
+            );
+            assert_eq!(
+                find_provider_match(&EveryThousandth, &regex, 0, false, 1),
+                None
+            );
+        }
+    }
 }
@@ -0,0 +1,170 @@
+//! Provides merging the lines of several input sources into one text
+//!
+//! See [`Pager::push_source_str`](crate::Pager::push_source_str) for more info.
+
+use crossterm::style::ContentStyle;
+
+/// The input sources of the pager and the lines they have sent
+#[derive(Default)]
+pub struct Sources {
+    sources: Vec<Source>,
+    /// Every complete line received from a source, with its prefix, and the index of its source
+    lines: Vec<(usize, String)>,
+}
+
+struct Source {
+    name: String,
+    style: ContentStyle,
+    shown: bool,
+    /// Text received after the last newline of this source
+    partial: String,
+}
+
+impl Sources {
+    /// Get the index of the source called `name`, adding it if it is new
+    fn index(&mut self, name: &str) -> usize {
+        self.sources
+            .iter()
+            .position(|source| source.name == name)
+            .unwrap_or_else(|| {
+                self.sources.push(Source {
+                    name: name.to_string(),
+                    style: ContentStyle::new(),
+                    shown: true,
+                    partial: String::new(),
+                });
+                self.sources.len() - 1
+            })
+    }
+
+    pub fn set_style(&mut self, name: &str, style: ContentStyle) {
+        let index = self.index(name);
+        self.sources[index].style = style;
+    }
+
+    /// Add `text` sent by the source called `name`
+    ///
+    /// Lines are only taken once they are complete, so that lines of different sources don't get
+    /// mixed up. Returns the prefixed lines which have to be appended to the output, which is
+    /// empty if the source is hidden. If `max_lines` isn't 0, only the last `max_lines` lines of
+    /// each hidden source are kept.
+    pub fn push(&mut self, name: &str, text: &str, max_lines: usize) -> String {
+        let index = self.index(name);
+        // Names are aligned to the longest one known so far like `docker compose logs` does
+        let width = self
+            .sources
+            .iter()
+            .map(|s| s.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let source = &mut self.sources[index];
+        source.partial.push_str(text);
+        let Some(end) = source.partial.rfind('\n') else {
+            return String::new();
+        };
+        let complete: String = source.partial.drain(..=end).collect();
+        let prefix = format!(
+            "{} | ",
+            source.style.apply(format!("{:width$}", source.name))
+        );
+
+        let mut appended = String::new();
+        for line in complete.lines() {
+            let line = format!("{prefix}{line}");
+            if source.shown {
+                appended.push_str(&line);
+                appended.push('\n');
+            }
+            self.lines.push((index, line));
+        }
+        // The lines of shown sources are discarded along with the text. Trimming the hidden ones
+        // only once there are many of them keeps appending cheap.
+        if max_lines != 0 && self.lines.len() > 2 * max_lines * self.sources.len() {
+            self.trim_hidden(max_lines);
+        }
+        appended
+    }
+
+    /// Keep only the last `max_lines` lines of each hidden source
+    fn trim_hidden(&mut self, max_lines: usize) {
+        let mut counts = vec![0; self.sources.len()];
+        let mut keep: Vec<bool> = self
+            .lines
+            .iter()
+            .rev()
+            .map(|(index, _)| {
+                counts[*index] += 1;
+                self.sources[*index].shown || counts[*index] <= max_lines
+            })
+            .collect();
+        self.lines.retain(|_| keep.pop().unwrap_or(true));
+    }
+
+    /// Index in the lines right after the `count`th line of a shown source
+    ///
+    /// This is the number of lines that make up the first `count` lines of the text, or all of
+    /// them if the text doesn't have that many lines.
+    fn end_of_shown(&self, count: usize) -> usize {
+        if count == 0 {
+            return 0;
+        }
+        let mut shown = 0;
+        self.lines
+            .iter()
+            .position(|(index, _)| {
+                shown += usize::from(self.sources[*index].shown);
+                shown == count
+            })
+            .map_or(self.lines.len(), |end| end + 1)
+    }
+
+    /// Show or hide the lines of the source called `name`
+    ///
+    /// Returns whether this changed which sources are shown.
+    pub fn set_shown(&mut self, name: &str, shown: bool) -> bool {
+        let index = self.index(name);
+        let changed = self.sources[index].shown != shown;
+        self.sources[index].shown = shown;
+        changed
+    }
+
+    /// The text made up of the lines of all the shown sources
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for (_, line) in self
+            .lines
+            .iter()
+            .filter(|(index, _)| self.sources[*index].shown)
+        {
+            text.push_str(line);
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Forget the lines received so far, for example because the text was replaced
+    pub fn clear_lines(&mut self) {
+        self.lines.clear();
+    }
+
+    /// Returns `true` if no lines have been received since the text was last replaced
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Forget the lines which make up the first `count` lines of the text, as they have been
+    /// discarded from it
+    ///
+    /// Lines of hidden sources received before them are forgotten too, so that they don't come
+    /// back when the source is shown again.
+    pub fn remove_first_lines(&mut self, count: usize) {
+        let end = self.end_of_shown(count);
+        self.lines.drain(..end);
+    }
+
+    /// Forget the lines received after the first `count` lines of the text, as it was truncated
+    pub fn truncate(&mut self, count: usize) {
+        let end = self.end_of_shown(count);
+        self.lines.truncate(end);
+    }
+}
@@ -1,35 +1,63 @@
 //! Contains types that hold run-time information of the pager.
 
 #[cfg(feature = "search")]
-use crate::search::{SearchMode, SearchOpts};
+use crate::search::{self, SearchMode, SearchOpts};
 
 use crate::{
+    buffers::Buffers,
+    color::{self, ColorSupport},
+    diff::{Diff, DiffStyles},
     error::{MinusError, TermError},
-    input::{self, HashedEventRegister},
+    input::{self, EventSource, HashedEventRegister, TerminalEvents},
     minus_core::{
         self,
-        utils::{display::AppendStyle, LinesRowMap},
+        utils::{
+            display::{AppendStyle, Frame},
+            term::TermOptions,
+            LinesRowMap,
+        },
         CommandQueue,
     },
     screen::{self, Screen},
-    ExitStrategy, LineNumbers,
+    sources::Sources,
+    table::Table,
+    ExitReason, ExitStrategy, InterruptStrategy, LineNumbers, MessageLevel, Position, Viewport,
 };
-use crossterm::{terminal, tty::IsTty};
-#[cfg(feature = "search")]
+use crossterm::{event::KeyEvent, style::ContentStyle, terminal, tty::IsTty};
 use parking_lot::Condvar;
 use parking_lot::Mutex;
 #[cfg(feature = "search")]
 use std::collections::BTreeSet;
 use std::{
-    collections::hash_map::RandomState,
+    borrow::Cow,
+    collections::{hash_map::RandomState, BTreeMap},
     convert::TryInto,
     io::stdout,
-    io::Stdout,
+    io::Write,
+    ops::Range,
+    path::PathBuf,
     sync::{atomic::AtomicBool, Arc},
+    time::{Duration, Instant},
 };
 
 use crate::minus_core::{commands::Command, ev_handler::handle_event};
 use crossbeam_channel::Receiver;
+#[cfg(feature = "dynamic_output")]
+use crossbeam_channel::RecvTimeoutError;
+
+/// Frames of the spinner shown while waiting for the data
+const SPINNER_FRAMES: [&str; 4] = ["|", "/", "-", "\\"];
+/// How long each frame of the spinner is shown for
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+/// How often to check whether the pager can still receive data while waiting for it to overflow
+#[cfg(feature = "dynamic_output")]
+const SENDERS_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+/// Terminals with at most this many rows only display the text, without the prompt
+const MINIMAL_LAYOUT_ROWS: usize = 2;
+/// Terminals with fewer columns than this are too small to display the text
+const MIN_COLS: usize = 4;
+/// Text shown in place of the text of the pager if the terminal is too small
+const TOO_SMALL_TEXT: &str = "Terminal too small";
 
 #[cfg(feature = "search")]
 #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
@@ -78,6 +106,95 @@ impl Default for SearchState {
     }
 }
 
+/// Positions of the pager before any large jump was made
+///
+/// Whenever the user jumps far away from the current screen, like going to the top/bottom of the
+/// text or following a search match, the old [`upper_mark`](PagerState::upper_mark) is recorded
+/// here. This allows the user to go back and forth between those positions similar to the jump
+/// list in Vim.
+#[derive(Debug, Default)]
+pub(crate) struct JumpList {
+    /// Positions that can be visited by going back
+    back: Vec<usize>,
+    /// Positions that can be visited by going forward, after going back
+    forward: Vec<usize>,
+}
+
+impl JumpList {
+    /// Maximum number of positions to remember
+    const MAX_ENTRIES: usize = 100;
+
+    /// Record `pos` as a position that the user can return to
+    ///
+    /// Recording a new position discards all positions that could have been reached by going
+    /// forward.
+    pub(crate) fn record(&mut self, pos: usize) {
+        if self.back.last() != Some(&pos) {
+            self.back.push(pos);
+        }
+        if self.back.len() > Self::MAX_ENTRIES {
+            self.back.remove(0);
+        }
+        self.forward.clear();
+    }
+
+    /// Return the previously recorded position, remembering `current` so that the user can
+    /// go forward to it again
+    pub(crate) fn back(&mut self, current: usize) -> Option<usize> {
+        let pos = self.back.pop()?;
+        self.forward.push(current);
+        Some(pos)
+    }
+
+    /// Move all positions up by `rows` as those many rows have been removed from the start
+    pub(crate) fn shift_up(&mut self, rows: usize) {
+        for pos in self.back.iter_mut().chain(self.forward.iter_mut()) {
+            *pos = pos.saturating_sub(rows);
+        }
+    }
+
+    /// Return the position the user came back from, remembering `current` so that the user can
+    /// go back to it again
+    pub(crate) fn forward(&mut self, current: usize) -> Option<usize> {
+        let pos = self.forward.pop()?;
+        self.back.push(current);
+        Some(pos)
+    }
+}
+
+/// The second pane of the screen while it is split
+///
+/// The screen shows two parts of the text on top of each other. Both panes are scrolled
+/// independently but only the focused one follows the movement keys. Its position is kept in
+/// [`upper_mark`](PagerState::upper_mark) like when the screen isn't split, so that everything
+/// else keeps working on the focused pane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SplitView {
+    /// Upper mark of the pane which isn't focused
+    pub(crate) other_mark: usize,
+    /// Whether the top pane is the focused one
+    pub(crate) top_focused: bool,
+}
+
+/// A function that checks, and possibly changes, the path to which the text is about to be saved
+pub(crate) type SavePathHook = Box<dyn Fn(PathBuf) -> Result<PathBuf, String> + Send + Sync>;
+
+/// A function that runs a command added by the application
+pub(crate) type CustomCommandCallback = Box<dyn FnMut(&PagerState) + Send + Sync>;
+
+/// A command added by the application with [`Pager::add_command`](crate::Pager::add_command)
+pub(crate) struct CustomCommand {
+    pub(crate) name: String,
+    pub(crate) key: KeyEvent,
+    pub(crate) callback: CustomCommandCallback,
+}
+
+/// A function that is called with the keys that aren't bound to any action
+pub(crate) type UnhandledKeyCallback = Box<dyn FnMut(KeyEvent) + Send + Sync>;
+
+/// A function that decides whether the user may quit the pager
+pub(crate) type ExitVeto = Box<dyn FnMut(ExitReason) -> Result<(), String> + Send + Sync>;
+
 /// Holds all information and configuration about the pager during
 /// its run time.
 ///
@@ -88,6 +205,7 @@ impl Default for SearchState {
 /// Various fields are made public so that their values can be accessed while implementing the
 /// trait.
 #[allow(clippy::module_name_repetitions)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct PagerState {
     /// Configuration for line numbers. See [`LineNumbers`]
     pub line_numbers: LineNumbers,
@@ -95,6 +213,10 @@ pub struct PagerState {
     /// The first element contains the actual message, while the second element tells
     /// whether the message has changed since the last display.
     pub message: Option<String>,
+    /// How important the [`message`](PagerState::message) is
+    pub message_level: MessageLevel,
+    /// When the message should be cleared, if it is only shown for some time
+    pub(crate) message_expiry: Option<Instant>,
     /// The upper bound of scrolling.
     ///
     /// This is useful for keeping track of the range of lines which are currently being displayed on
@@ -109,6 +231,10 @@ pub struct PagerState {
     ///
     /// When this is `> 0`, this amount of text will be truncated from the left side
     pub left_mark: usize,
+    /// Index of the cursor line, or `None` if the cursor line isn't shown
+    ///
+    /// See [`Pager::set_cursor_line`](crate::pager::Pager::set_cursor_line) for more info.
+    pub selected_line: Option<usize>,
     /// Direction of search
     ///
     /// See [`SearchMode`] for available options
@@ -126,21 +252,102 @@ pub struct PagerState {
     /// It keeps track of all the numbers that have been entered by the user
     /// until any of `j`, `k`, `G`, `Up` or `Down` is pressed
     pub prefix_num: String,
-    /// Describes whether minus is running and in which mode
-    pub running: &'static Mutex<crate::RunMode>,
+    /// Describes whether this pager is running and in which mode
+    ///
+    /// This is shared by the [`Pager`](crate::Pager) and all of its clones.
+    pub running: Arc<Mutex<crate::RunMode>>,
     #[cfg(feature = "search")]
     #[cfg_attr(docsrs, cfg(feature = "search"))]
     pub search_state: SearchState,
     pub screen: Screen,
     /// The prompt displayed at the bottom wrapped to available terminal width
     pub(crate) prompt: String,
+    /// Template of the prompt line with placeholders for the position in the text
+    ///
+    /// See [set_prompt_format](crate::pager::Pager::set_prompt_format) for more info.
+    pub(crate) prompt_format: Option<String>,
+    /// Whether to show how far through the text the screen is at the right end of the prompt
+    ///
+    /// See [show_percentage](crate::pager::Pager::show_percentage) for more info.
+    pub(crate) show_percentage: bool,
+    /// Whether to draw a scrollbar in the last column of the screen
+    ///
+    /// See [show_scrollbar](crate::pager::Pager::show_scrollbar) for more info.
+    pub(crate) show_scrollbar: bool,
+    /// How much of the data the application has fetched or generated, from 0 to 1
+    ///
+    /// See [set_progress](crate::pager::Pager::set_progress) for more info.
+    pub(crate) progress: Option<f32>,
+    /// Number of rows of the previous screen that are still shown after moving by a page
+    ///
+    /// See [set_page_overlap](crate::pager::Pager::set_page_overlap) for more info.
+    pub(crate) page_overlap: usize,
+    /// Number of rows kept visible above and below a row that is jumped to
+    ///
+    /// See [set_scroll_off](crate::pager::Pager::set_scroll_off) for more info.
+    pub(crate) scroll_off: usize,
+    /// Where to place the screen in the text when the pager starts
+    ///
+    /// See [set_initial_position](crate::pager::Pager::set_initial_position) for more info.
+    pub(crate) initial_position: Option<Position>,
+    /// Marker drawn on the rows past the end of the text
+    ///
+    /// See [set_filler](crate::pager::Pager::set_filler) for more info.
+    pub(crate) filler: Option<String>,
+    /// Text shown in place of the empty screen until the first data arrives
+    ///
+    /// See [set_waiting_placeholder](crate::pager::Pager::set_waiting_placeholder) for more info.
+    pub(crate) waiting_text: Option<String>,
+    /// Whether a spinner is shown before the [`waiting_text`](PagerState::waiting_text)
+    pub(crate) waiting_spinner: bool,
+    /// Index of the frame of the spinner that is currently shown
+    pub(crate) spinner_frame: usize,
+    /// When the spinner last moved to its current frame
+    pub(crate) spinner_moved: Instant,
+    /// How often to check whether the size of the terminal has changed, if at all
+    ///
+    /// See [set_resize_poll_interval](crate::pager::Pager::set_resize_poll_interval) for more info.
+    pub(crate) resize_poll_interval: Option<Duration>,
+    /// When the size of the terminal was last checked
+    pub(crate) size_checked: Instant,
+    /// Lines of the status area drawn above the prompt
+    ///
+    /// See [set_status](crate::pager::Pager::set_status) for more info.
+    pub(crate) status_lines: Vec<String>,
+    /// Rows of the full text of the prompt or message, shown above the prompt when it is cut off
+    ///
+    /// This is empty unless the user has asked to see the full text with `=`.
+    pub(crate) full_prompt: Vec<String>,
     /// The input classifier to be called when a input is detected
     pub(crate) input_classifier: Box<dyn input::InputClassifier + Sync + Send>,
+    /// Input classifiers that have been temporarily replaced by a newer one
+    ///
+    /// See [`Pager::push_input_classifier`](crate::Pager::push_input_classifier)
+    pub(crate) input_classifier_stack: Vec<Box<dyn input::InputClassifier + Sync + Send>>,
     /// Functions to run when the pager quits
     pub(crate) exit_callbacks: Vec<Box<dyn FnMut() + Send + Sync + 'static>>,
+    /// Commands added by the application
+    ///
+    /// See [add_command](crate::pager::Pager::add_command) for more info.
+    pub(crate) custom_commands: Vec<CustomCommand>,
+    /// Called with the keys that aren't bound to any action
+    ///
+    /// See [set_unhandled_key_callback](crate::pager::Pager::set_unhandled_key_callback) for more
+    /// info.
+    pub(crate) unhandled_key_callback: Option<UnhandledKeyCallback>,
+    /// Decides whether the user may quit the pager
+    ///
+    /// See [set_exit_veto](crate::pager::Pager::set_exit_veto) for more info.
+    pub(crate) exit_veto: Option<ExitVeto>,
+    /// Why the pager quit, once it has quit
+    pub(crate) exit_reason: Option<ExitReason>,
     /// The behaviour to do when user quits the program using `q` or `Ctrl+C`
     /// See [`ExitStrategy`] for available options
     pub(crate) exit_strategy: ExitStrategy,
+    /// What to do when the user presses `Ctrl+C`
+    ///
+    /// See [set_interrupt_strategy](crate::pager::Pager::set_interrupt_strategy) for more info.
+    pub(crate) interrupt_strategy: InterruptStrategy,
     /// The prompt that should be displayed to the user, formatted with the
     /// current search index and number of matches (if the search feature is enabled),
     /// and the current numbers inputted to scroll
@@ -150,13 +357,127 @@ pub struct PagerState {
     /// Do we want to page if there is no overflow
     #[cfg(feature = "static_output")]
     pub(crate) run_no_overflow: bool,
+    /// Whether to write the data straight to the stdout if it is not a terminal.
+    /// See [set_passthrough_on_pipe](crate::pager::Pager::set_passthrough_on_pipe) for more info.
+    pub(crate) passthrough_on_pipe: bool,
+    /// Whether to page in the alternate screen.
+    /// See [set_alternate_screen](crate::pager::Pager::set_alternate_screen) for more info.
+    pub(crate) alternate_screen: bool,
+    /// Whether to capture the mouse
+    ///
+    /// See [set_mouse_capture](crate::pager::Pager::set_mouse_capture) for more info.
+    pub(crate) mouse_capture: bool,
+    /// Whether the terminal has the focus
+    ///
+    /// This is only known if the terminal reports when it gains or loses the focus. Otherwise it
+    /// is always assumed to be focused.
+    pub(crate) focused: bool,
+    /// Whether to stop drawing while the terminal doesn't have the focus
+    ///
+    /// See [set_unfocused_pause](crate::pager::Pager::set_unfocused_pause) for more info.
+    pub(crate) pause_repaints_unfocused: bool,
+    /// Whether to stop following the output while the terminal doesn't have the focus
+    ///
+    /// See [set_unfocused_pause](crate::pager::Pager::set_unfocused_pause) for more info.
+    pub(crate) pause_follow_unfocused: bool,
+    /// Number of [batches](crate::pager::Pager::batch) that are currently open
+    ///
+    /// Nothing is drawn while it is non-zero.
+    pub(crate) open_batches: usize,
+    /// Number of lines scrolled by each tick of the mouse wheel
+    ///
+    /// See [set_scroll_amounts](crate::pager::Pager::set_scroll_amounts) for more info.
+    pub(crate) wheel_scroll_lines: usize,
+    /// Number of lines scrolled by each press of an arrow key
+    ///
+    /// See [set_scroll_amounts](crate::pager::Pager::set_scroll_amounts) for more info.
+    pub(crate) arrow_scroll_lines: usize,
+    /// Area of the terminal in which the pager is drawn, if it is not the whole terminal.
+    /// See [set_viewport](crate::pager::Pager::set_viewport) for more info.
+    pub(crate) viewport: Option<Viewport>,
+    /// Output set by the application which is used instead of the stdout.
+    /// See [set_output](crate::pager::Pager::set_output) for more info.
+    pub(crate) output: Option<Box<dyn Write + Send>>,
+    /// Size of the terminal behind the output set by the application, if there is one
+    pub(crate) output_size: Option<(u16, u16)>,
+    /// Source of the terminal events
+    pub(crate) event_source: Arc<Mutex<Box<dyn EventSource>>>,
+    /// Whether the events are read from the terminal, rather than a source set by the application
+    pub(crate) terminal_input: bool,
+    /// User input and requests to stop the pager which were sent before the pager started
+    ///
+    /// See [send_input](crate::pager::Pager::send_input) for more info.
+    pub(crate) pending_commands: Vec<Command>,
+    /// Whether to write the text to the main screen and quit if it fits on one screen once the
+    /// data has been finished.
+    /// See [set_quit_if_one_screen](crate::pager::Pager::set_quit_if_one_screen) for more info.
+    #[cfg(feature = "dynamic_output")]
+    pub(crate) quit_if_one_screen: bool,
+    /// Whether to quit when the user moves down past the end of the data
+    ///
+    /// See [set_quit_at_eof](crate::pager::Pager::set_quit_at_eof) for more info.
+    pub(crate) quit_at_eof: bool,
+    /// Whether the application has told that no more data will be sent.
+    /// See [finish_data](crate::pager::Pager::finish_data) for more info.
+    pub(crate) data_finished: bool,
     pub(crate) lines_to_row_map: LinesRowMap,
     /// Value for follow mode.
     /// See [follow_output](crate::pager::Pager::follow_output) for more info on follow mode.
     pub(crate) follow_output: bool,
+    /// Positions before large jumps. See [`JumpList`]
+    pub(crate) jump_list: JumpList,
+    /// The other pane if the screen is split. See [`SplitView`]
+    pub(crate) split: Option<SplitView>,
+    /// Ranges of lines highlighted by the application, in the order they were added.
+    /// See [highlight_lines](crate::pager::Pager::highlight_lines) for more info.
+    pub(crate) line_highlights: Vec<(Range<usize>, ContentStyle)>,
+    /// Labels of the lines that start a section, keyed by the index of their line.
+    /// See [add_sections](crate::pager::Pager::add_sections) for more info.
+    pub(crate) sections: BTreeMap<usize, String>,
+    /// Number of lines at the start of the text that stay at the top of the screen.
+    /// See [set_header_lines](crate::pager::Pager::set_header_lines) for more info.
+    pub(crate) header_lines: usize,
+    /// Rows of cells that make up the text.
+    /// See [push_row](crate::pager::Pager::push_row) for more info.
+    pub(crate) table: Table,
+    /// Differences between two texts that make up the text.
+    /// See [set_diff](crate::pager::Pager::set_diff) for more info.
+    pub(crate) diff: Diff,
+    /// Whether pressing `Enter` picks the cursor line.
+    /// See [set_picker_mode](crate::pager::Pager::set_picker_mode) for more info.
+    pub(crate) picker_mode: bool,
+    /// Time to wait for more appended text before formatting and drawing it.
+    /// See [set_append_batching](crate::pager::Pager::set_append_batching) for more info.
+    pub(crate) append_flush_interval: Duration,
+    /// Maximum number of appends that are merged together before formatting and drawing them
+    pub(crate) append_max_batch: usize,
+    /// Input sources whose lines are merged into the text.
+    /// See [push_source_str](crate::pager::Pager::push_source_str) for more info.
+    pub(crate) sources: Sources,
+    /// The buffers of the pager. See [add_buffer](crate::pager::Pager::add_buffer) for more info.
+    pub(crate) buffers: Buffers,
+    /// Whether to turn `\r\n` line endings of the incoming text into `\n`.
+    /// See [set_normalize_line_endings](crate::pager::Pager::set_normalize_line_endings) for more
+    /// info.
+    pub(crate) normalize_line_endings: bool,
+    /// Maximum number of lines to keep. 0 means no limit.
+    /// See [set_max_lines](crate::pager::Pager::set_max_lines) for more info.
+    pub(crate) max_lines: usize,
+    /// Maximum number of times per second that appended text is drawn. 0 means no limit.
+    /// See [set_max_refresh_rate](crate::pager::Pager::set_max_refresh_rate) for more info.
+    pub(crate) max_refresh_rate: u32,
+    /// Rows that are currently displayed on the terminal, if they are known
+    pub(crate) displayed_frame: Option<Frame>,
+    /// Whether to draw each frame as a synchronized update.
+    /// See [set_synchronized_output](crate::pager::Pager::set_synchronized_output) for more info.
+    pub(crate) synchronized_output: bool,
+    /// Function that checks the path entered by the user before saving the text to it.
+    /// See [set_save_path_hook](crate::pager::Pager::set_save_path_hook) for more info.
+    pub(crate) save_path_hook: Option<SavePathHook>,
 }
 
 impl PagerState {
+    #[allow(clippy::too_many_lines)]
     pub(crate) fn new() -> Result<Self, TermError> {
         let (rows, cols);
 
@@ -189,17 +510,60 @@ impl PagerState {
             line_numbers: LineNumbers::Disabled,
             upper_mark: 0,
             prompt,
-            running: &minus_core::RUNMODE,
+            prompt_format: None,
+            show_percentage: false,
+            show_scrollbar: false,
+            progress: None,
+            page_overlap: 0,
+            scroll_off: 0,
+            initial_position: None,
+            filler: None,
+            waiting_text: None,
+            waiting_spinner: false,
+            spinner_frame: 0,
+            spinner_moved: Instant::now(),
+            resize_poll_interval: None,
+            size_checked: Instant::now(),
+            status_lines: Vec::new(),
+            full_prompt: Vec::new(),
+            running: Arc::new(Mutex::new(crate::RunMode::Uninitialized)),
             left_mark: 0,
             exit_strategy: ExitStrategy::ProcessQuit,
+            interrupt_strategy: InterruptStrategy::default(),
             input_classifier: Box::<HashedEventRegister<RandomState>>::default(),
+            input_classifier_stack: Vec::new(),
             exit_callbacks: Vec::with_capacity(5),
+            custom_commands: Vec::new(),
+            unhandled_key_callback: None,
+            exit_veto: None,
+            exit_reason: None,
             message: None,
+            message_level: MessageLevel::Error,
+            message_expiry: None,
             screen: Screen::default(),
             displayed_prompt: String::new(),
             show_prompt: true,
             #[cfg(feature = "static_output")]
             run_no_overflow: false,
+            passthrough_on_pipe: true,
+            alternate_screen: true,
+            mouse_capture: true,
+            focused: true,
+            pause_repaints_unfocused: false,
+            pause_follow_unfocused: false,
+            open_batches: 0,
+            wheel_scroll_lines: 5,
+            arrow_scroll_lines: 1,
+            viewport: None,
+            output: None,
+            output_size: None,
+            event_source: Arc::new(Mutex::new(Box::new(TerminalEvents))),
+            terminal_input: true,
+            pending_commands: Vec::new(),
+            #[cfg(feature = "dynamic_output")]
+            quit_if_one_screen: false,
+            quit_at_eof: false,
+            data_finished: false,
             #[cfg(feature = "search")]
             search_mode: SearchMode::default(),
             #[cfg(feature = "search")]
@@ -210,6 +574,25 @@ impl PagerState {
             prefix_num: String::new(),
             lines_to_row_map: LinesRowMap::new(),
             follow_output: false,
+            jump_list: JumpList::default(),
+            split: None,
+            selected_line: None,
+            picker_mode: false,
+            line_highlights: Vec::new(),
+            sections: BTreeMap::new(),
+            header_lines: 0,
+            table: Table::default(),
+            diff: Diff::default(),
+            append_flush_interval: Duration::ZERO,
+            append_max_batch: 256,
+            sources: Sources::default(),
+            buffers: Buffers::default(),
+            normalize_line_endings: true,
+            max_lines: 0,
+            max_refresh_rate: 60,
+            displayed_frame: None,
+            synchronized_output: minus_core::utils::term::supports_synchronized_output(),
+            save_path_hook: None,
         };
 
         state.format_prompt();
@@ -229,30 +612,641 @@ impl PagerState {
     /// to process the events
     pub(crate) fn generate_initial_state(
         rx: &Receiver<Command>,
-        mut out: &mut Stdout,
+        mut out: &mut impl Write,
     ) -> Result<Self, MinusError> {
         let mut ps = Self::new()?;
-        let mut command_queue = CommandQueue::new_zero();
-        rx.try_iter().try_for_each(|ev| -> Result<(), MinusError> {
-            handle_event(
-                ev,
-                &mut out,
-                &mut ps,
-                &mut command_queue,
-                &Arc::new(AtomicBool::new(false)),
-                #[cfg(feature = "search")]
-                &Arc::new((Mutex::new(true), Condvar::new())),
-            )
-        })?;
+        // Applications can still change these before the pager starts
+        ps.screen.strip_ansi = color::no_color();
+        ps.screen.color_support = ColorSupport::detect();
+        rx.try_iter()
+            .try_for_each(|ev| ps.handle_before_start(ev, &mut out))?;
         Ok(ps)
     }
 
+    /// Handle `ev` before minus has started running
+    ///
+    /// User input and stopping the pager can only be handled once the pager is running, so they
+    /// are kept in [`PagerState::pending_commands`] until then.
+    fn handle_before_start(&mut self, ev: Command, out: &mut impl Write) -> Result<(), MinusError> {
+        if matches!(ev, Command::UserInput(_) | Command::Stop) {
+            self.pending_commands.push(ev);
+            return Ok(());
+        }
+        handle_event(
+            ev,
+            out,
+            self,
+            &mut CommandQueue::new_zero(),
+            &Arc::new(AtomicBool::new(false)),
+            &Arc::new((Mutex::new(true), Condvar::new())),
+        )
+    }
+
+    /// Get how the terminal should be set up for the pager
+    pub(crate) const fn term_options(&self) -> TermOptions {
+        TermOptions {
+            alternate_screen: self.alternate_screen,
+            raw_mode: self.terminal_input,
+            mouse_capture: self.mouse_capture,
+            output_rows: match self.output_size {
+                Some((_, rows)) => Some(rows),
+                None => None,
+            },
+        }
+    }
+
+    /// Get the indices of the lines that are at least partially displayed on the screen
+    pub(crate) fn visible_lines(&self) -> Range<usize> {
+        let text_rows = self.text_rows();
+        // Lines served by a line provider take exactly one row each
+        if self.screen.line_provider.is_some() {
+            let end = self
+                .upper_mark
+                .saturating_add(text_rows)
+                .min(self.screen.line_count);
+            return self.upper_mark.min(end)..end;
+        }
+        let row_count = self.screen.formatted_lines_count();
+        if row_count == 0 || text_rows == 0 {
+            return 0..0;
+        }
+        let last_row = self
+            .upper_mark
+            .saturating_add(text_rows - 1)
+            .min(row_count - 1);
+        self.lines_to_row_map.line_of_row(self.upper_mark)
+            ..self.lines_to_row_map.line_of_row(last_row) + 1
+    }
+
+    /// Get the index of the cursor line, or else of the first line below the header
+    pub(crate) fn current_line(&self) -> usize {
+        self.selected_line.unwrap_or_else(|| {
+            let row = self.upper_mark.saturating_add(self.header_rows());
+            if self.screen.line_provider.is_some() {
+                row
+            } else {
+                self.lines_to_row_map.line_of_row(row)
+            }
+        })
+    }
+
+    /// Get the line of the `count`th section after the current line, or before it if `forward`
+    /// is false
+    ///
+    /// If there are fewer sections in that direction, the last one of them is returned.
+    pub(crate) fn section_line(&self, forward: bool, count: usize) -> Option<usize> {
+        let current = self.current_line();
+        let nth = count.saturating_sub(1);
+        if forward {
+            let mut after = self
+                .sections
+                .range(current.saturating_add(1)..)
+                .map(|s| *s.0);
+            after.clone().nth(nth).or_else(|| after.next_back())
+        } else {
+            let mut before = self.sections.range(..current).rev().map(|s| *s.0);
+            before.clone().nth(nth).or_else(|| before.next_back())
+        }
+    }
+
+    /// Keep handling the events from `rx` until the data has been finished or it doesn't fit on
+    /// one screen anymore
+    ///
+    /// The data is also taken to be finished once `has_senders` returns false, as minus itself
+    /// holds a sender of `rx` which keeps it from ever being disconnected.
+    ///
+    /// This must be called before minus has started running as nothing gets drawn.
+    #[cfg(feature = "dynamic_output")]
+    pub(crate) fn wait_for_overflow(
+        &mut self,
+        rx: &Receiver<Command>,
+        out: &mut impl Write,
+        has_senders: impl Fn() -> bool,
+    ) -> Result<(), MinusError> {
+        while !self.data_finished && self.fits_one_screen() {
+            let ev = match rx.recv_timeout(SENDERS_CHECK_INTERVAL) {
+                Ok(ev) => ev,
+                Err(RecvTimeoutError::Timeout) if has_senders() => continue,
+                Err(_) => {
+                    self.data_finished = true;
+                    break;
+                }
+            };
+            let stop = matches!(ev, Command::Stop);
+            self.handle_before_start(ev, out)?;
+            // Start right away so that the pager can be stopped
+            if stop {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if all the text can be written on the main screen without scrolling
+    pub(crate) fn fits_one_screen(&self) -> bool {
+        self.screen.formatted_lines_count() <= self.rows
+    }
+
+    /// Returns true if no more data will arrive and all of it can be written on the main screen
+    /// without scrolling
+    ///
+    /// This decides whether minus quits without starting the pager, both with
+    /// [`set_quit_if_one_screen`](crate::Pager::set_quit_if_one_screen) and in static mode.
+    pub(crate) fn all_data_fits_one_screen(&self) -> bool {
+        self.data_finished && self.fits_one_screen()
+    }
+
+    /// Get the rows taken up by the line at index `line`
+    pub(crate) fn rows_of_line(&self, line: usize) -> Range<usize> {
+        // Each line served by a line provider takes exactly one row
+        if self.screen.line_provider.is_some() {
+            return line..line.saturating_add(1);
+        }
+        let row_count = self.screen.formatted_lines_count();
+        let start = self
+            .lines_to_row_map
+            .get(line)
+            .copied()
+            .unwrap_or(row_count);
+        let end = self
+            .lines_to_row_map
+            .get(line.saturating_add(1))
+            .copied()
+            .unwrap_or(row_count);
+        start..end
+    }
+
+    /// Returns true if nothing is drawn because the terminal doesn't have the focus or a
+    /// [batch](crate::pager::Pager::batch) of updates is open
+    ///
+    /// The screen is drawn again completely once the terminal regains the focus and all the
+    /// batches are closed.
+    pub(crate) const fn repaints_paused(&self) -> bool {
+        (!self.focused && self.pause_repaints_unfocused) || self.open_batches > 0
+    }
+
+    /// Returns true if the screen shouldn't follow the output because the terminal doesn't have
+    /// the focus
+    pub(crate) const fn following_paused(&self) -> bool {
+        !self.focused && self.pause_follow_unfocused
+    }
+
+    /// Get the number of rows that moving by a page scrolls by
+    ///
+    /// The rows covered by the header aren't counted in a page and the
+    /// [overlap](PagerState::page_overlap) with the previous screen is left out. A page is always
+    /// at least one row so that paging never gets stuck.
+    pub(crate) fn page_rows(&self) -> usize {
+        self.text_rows()
+            .saturating_sub(self.header_rows())
+            .saturating_sub(self.page_overlap)
+            .max(1)
+    }
+
+    /// Get the number of rows kept visible above and below a row that is jumped to
+    ///
+    /// The margin is reduced on small screens so that the row can still be shown below the header
+    /// with the full margin on either side.
+    pub(crate) fn scroll_off_rows(&self) -> usize {
+        let body_rows = self.text_rows().saturating_sub(self.header_rows());
+        self.scroll_off.min(body_rows.saturating_sub(1) / 2)
+    }
+
+    /// Get the upper mark that shows `row` right below the header and the scroll-off margin
+    pub(crate) fn upper_mark_for_jump(&self, row: usize) -> usize {
+        row.saturating_sub(self.header_rows() + self.scroll_off_rows())
+    }
+
+    /// Get the row after which the next search match is looked for
+    ///
+    /// This is the first row below the header. With a scroll-off margin, the current match isn't
+    /// on that row after jumping to it, so the search starts from the current match instead if it
+    /// is within the margin.
+    #[cfg(feature = "search")]
+    pub(crate) fn next_match_search_start(&self) -> usize {
+        let body_start = self.upper_mark.saturating_add(self.header_rows());
+        let margin_end = body_start.saturating_add(self.scroll_off_rows());
+        match self.current_match_row() {
+            Some(row) if (body_start..=margin_end).contains(&row) => row,
+            _ => body_start,
+        }
+    }
+
+    /// Move the screen to the [initial position](PagerState::initial_position), if one is set
+    ///
+    /// This must be called once the text has been formatted, right before the screen is first
+    /// drawn.
+    pub(crate) fn go_to_initial_position(&mut self) {
+        let Some(position) = self.initial_position.take() else {
+            return;
+        };
+        // Lines served by a line provider take exactly one row each
+        let row_count = if self.screen.line_provider.is_some() {
+            self.total_lines()
+        } else {
+            self.screen.formatted_lines_count()
+        };
+        let last_upper_mark = row_count.saturating_sub(self.writable_rows());
+        match position {
+            Position::Bottom => self.upper_mark = last_upper_mark,
+            Position::Line(line) => {
+                self.upper_mark = self.rows_of_line(line).start.min(last_upper_mark);
+            }
+            #[cfg(feature = "search")]
+            Position::FirstMatch(pattern) => {
+                let Ok(regex) = regex::Regex::new(&pattern) else {
+                    return;
+                };
+                self.search_state.search_term = Some(regex.clone());
+                let first_match = if let Some(provider) = self.screen.line_provider.as_deref() {
+                    // The search starts after the line it is given
+                    let first_line = provider.lines(0..provider.len().min(1));
+                    if first_line
+                        .first()
+                        .map_or(false, |line| regex.is_match(line))
+                    {
+                        Some(0)
+                    } else {
+                        search::find_provider_match(provider, &regex, 0, true, 1)
+                    }
+                } else {
+                    // Formatting the lines finds the rows with matches
+                    self.format_lines();
+                    self.search_state.search_idx.first().copied()
+                };
+                if let Some(row) = first_match {
+                    // Show the match below the header
+                    self.upper_mark = self.upper_mark_for_jump(row).min(last_upper_mark);
+                }
+            }
+        }
+    }
+
+    /// Returns true if some rows are drawn differently from their text, like the cursor line, the
+    /// header, the scrollbar, the filler or the split view
+    pub(crate) fn has_row_overlays(&self) -> bool {
+        #[cfg(feature = "search")]
+        if self.current_match_row().is_some()
+            && self.screen.theme.current_search_match != self.screen.theme.search_match
+        {
+            return true;
+        }
+        self.selected_line.is_some()
+            || !self.line_highlights.is_empty()
+            || self.header_lines > 0
+            || self.show_scrollbar
+            || self.filler.is_some()
+            || self.split.is_some()
+            || self.is_waiting_for_data()
+            || self.is_too_small()
+    }
+
+    /// Returns true if the placeholder is shown in place of the text because no data has arrived
+    /// yet
+    ///
+    /// This is only ever the case in dynamic mode, as all the data is known from the start in
+    /// static mode.
+    pub(crate) fn is_waiting_for_data(&self) -> bool {
+        #[cfg(feature = "dynamic_output")]
+        {
+            self.waiting_text.is_some()
+                && !self.data_finished
+                && self.total_lines() == 0
+                && self.screen.formatted_lines_count() == 0
+                && *self.running.lock() == crate::RunMode::Dynamic
+        }
+        #[cfg(not(feature = "dynamic_output"))]
+        false
+    }
+
+    /// Get the row that is shown in place of the text while waiting for the data
+    pub(crate) fn waiting_row(&self) -> String {
+        let text = self.waiting_text.as_deref().unwrap_or_default();
+        let row = if self.waiting_spinner {
+            let frame = SPINNER_FRAMES[self.spinner_frame % SPINNER_FRAMES.len()];
+            if text.is_empty() {
+                frame.to_string()
+            } else {
+                format!("{frame} {text}")
+            }
+        } else {
+            text.to_string()
+        };
+        minus_core::utils::truncate_with_ellipsis(&row, self.text_cols()).into_owned()
+    }
+
+    /// Get when the spinner should move to its next frame, if it is shown
+    pub(crate) fn next_spinner_move(&self) -> Option<Instant> {
+        (self.waiting_spinner && self.is_waiting_for_data())
+            .then(|| self.spinner_moved + SPINNER_INTERVAL)
+    }
+
+    /// Get when the size of the terminal should be checked next, if it is polled
+    ///
+    /// The size is only polled if the pager fills the terminal of the process. The size of the
+    /// output set by the application and of a viewport is given by the application.
+    pub(crate) fn next_size_check(&self) -> Option<Instant> {
+        self.resize_poll_interval
+            .filter(|_| self.output_size.is_none() && self.viewport.is_none())
+            .map(|interval| self.size_checked + interval)
+    }
+
+    /// Returns true if all the data has arrived and the end of it is on the screen
+    ///
+    /// The prompt shows `(END)` in this state, unless a message is displayed. The data is
+    /// known to be complete once [`Pager::finish_data`](crate::Pager::finish_data) has been
+    /// called.
+    #[must_use]
+    pub fn eof_reached(&self) -> bool {
+        self.data_finished && self.visible_lines().end >= self.total_lines()
+    }
+
+    /// Get the number of rows at the top of the screen taken by the header
+    ///
+    /// At least one row is always left for the rest of the text. The header is not shown when the
+    /// lines are served by a line provider or the screen is split.
+    pub(crate) fn header_rows(&self) -> usize {
+        if self.header_lines == 0
+            || self.screen.line_provider.is_some()
+            || self.split_rows().is_some()
+        {
+            return 0;
+        }
+        self.rows_of_line(self.header_lines - 1)
+            .end
+            .min(self.writable_rows().saturating_sub(1))
+    }
+
+    /// Get the style of the given `row` if it is highlighted
+    pub(crate) fn row_highlight(&self, row: usize) -> Option<ContentStyle> {
+        if let Some(line) = self.selected_line {
+            if self.rows_of_line(line).contains(&row) {
+                return Some(self.screen.color_support.adapt(self.screen.theme.selection));
+            }
+        }
+        self.line_highlights
+            .iter()
+            .rev()
+            .find(|(lines, _)| {
+                lines.start < lines.end
+                    && self.rows_of_line(lines.start).start <= row
+                    && row < self.rows_of_line(lines.end - 1).end
+            })
+            .map(|(_, style)| self.screen.color_support.adapt(*style))
+    }
+
+    /// Get the row of the search match that was last moved to
+    ///
+    /// Returns None if no search is active or there are no matches.
+    #[cfg(feature = "search")]
+    pub(crate) fn current_match_row(&self) -> Option<usize> {
+        self.search_state.search_term.as_ref()?;
+        self.search_state
+            .search_idx
+            .iter()
+            .nth(self.search_state.search_mark)
+            .copied()
+    }
+
+    /// Move the cursor line back onto the screen if scrolling took it off
+    ///
+    /// Returns true if the cursor line was moved.
+    pub(crate) fn cursor_line_into_view(&mut self) -> bool {
+        let Some(line) = self.selected_line else {
+            return false;
+        };
+        let text_rows = self.text_rows();
+        let rows = self.rows_of_line(line);
+        // The rows below the header are the first ones that aren't covered by it
+        let first_row = self.upper_mark.saturating_add(self.header_rows());
+        let row = if rows.end <= first_row {
+            first_row
+        } else if rows.start >= self.upper_mark.saturating_add(text_rows) {
+            self.upper_mark.saturating_add(text_rows.saturating_sub(1))
+        } else {
+            return false;
+        };
+        let new_line = if self.screen.line_provider.is_some() {
+            row
+        } else {
+            self.lines_to_row_map.line_of_row(row)
+        };
+        self.selected_line = Some(new_line.min(self.last_line()));
+        true
+    }
+
+    /// Get the index of the last line of the text
+    pub(crate) fn last_line(&self) -> usize {
+        let last_row = self.screen.formatted_lines_count().saturating_sub(1);
+        if self.screen.line_provider.is_some() {
+            last_row
+        } else {
+            self.lines_to_row_map.line_of_row(last_row)
+        }
+    }
+
+    /// Get the number of columns of a line's text that are contained in the given `row`
+    ///
+    /// The gutter and the wrap indicators are not counted. One extra column is counted for the
+    /// whitespace which gets removed when a line is wrapped after the row.
+    fn row_text_width(&self, row: usize, first_row_of_line: usize) -> usize {
+        let decoration_width = self.screen.gutter_width(self.displayed_line_numbers())
+            + textwrap::core::display_width(&self.screen.wrap_suffix)
+            + if row > first_row_of_line {
+                textwrap::core::display_width(&self.screen.wrap_prefix)
+            } else {
+                0
+            };
+        self.screen.formatted_lines.get(row).map_or(0, |text| {
+            textwrap::core::display_width(text).saturating_sub(decoration_width) + 1
+        })
+    }
+
+    /// Get the number of rows of the status area that fit on the screen
+    ///
+    /// The status area also holds the full text of the prompt while it is shown. At least one row
+    /// is always left for the text.
+    pub(crate) fn status_rows(&self) -> usize {
+        if self.minimal_layout() {
+            return 0;
+        }
+        (self.status_lines.len() + self.full_prompt.len()).min(self.rows.saturating_sub(2))
+    }
+
+    /// Get the number of rows that the text can take up
+    ///
+    /// This leaves out the prompt and the status area at the bottom of the screen.
+    pub(crate) fn writable_rows(&self) -> usize {
+        if self.minimal_layout() {
+            return self.rows;
+        }
+        self.rows.saturating_sub(1 + self.status_rows())
+    }
+
+    /// Get the number of rows of the top and the bottom pane while the screen is split
+    ///
+    /// A row between the panes separates them. Returns `None` if the screen isn't split, if there
+    /// aren't enough rows for both panes or if the lines are served by a line provider. Only the
+    /// focused pane is shown then.
+    pub(crate) fn split_rows(&self) -> Option<(usize, usize)> {
+        let writable_rows = self.writable_rows();
+        if self.split.is_none() || self.screen.line_provider.is_some() || writable_rows < 3 {
+            return None;
+        }
+        let top = (writable_rows - 1) / 2;
+        Some((top, writable_rows - 1 - top))
+    }
+
+    /// Get the number of rows of the focused pane
+    ///
+    /// These are all the [writable rows](PagerState::writable_rows) unless the screen is split.
+    pub(crate) fn text_rows(&self) -> usize {
+        match (self.split, self.split_rows()) {
+            (Some(split), Some((top, _))) if split.top_focused => top,
+            (Some(_), Some((_, bottom))) => bottom,
+            _ => self.writable_rows(),
+        }
+    }
+
+    /// Split the screen into two panes or go back to a single one
+    ///
+    /// Both panes start out at the current position with the focus on the bottom one. Going back
+    /// to a single pane keeps the position of the focused pane.
+    pub(crate) fn set_split(&mut self, split: bool) {
+        if split != self.split.is_some() {
+            self.split = split.then_some(SplitView {
+                other_mark: self.upper_mark,
+                top_focused: false,
+            });
+        }
+    }
+
+    /// Move the focus to the other pane of the split view
+    pub(crate) fn switch_pane(&mut self) {
+        if let Some(split) = self.split.as_mut() {
+            std::mem::swap(&mut split.other_mark, &mut self.upper_mark);
+            split.top_focused = !split.top_focused;
+        }
+    }
+
+    /// Returns true if the terminal has so few rows or columns that only the text is displayed
+    ///
+    /// The prompt and the status area are left out to leave all the rows to the text. The prompts
+    /// for the user's input are still drawn over the last row while they are open.
+    pub(crate) const fn minimal_layout(&self) -> bool {
+        self.rows <= MINIMAL_LAYOUT_ROWS || self.is_too_small()
+    }
+
+    /// Returns true if the terminal is too small to display the text
+    ///
+    /// A placeholder saying so is displayed instead. See [`PagerState::too_small_row`].
+    pub(crate) const fn is_too_small(&self) -> bool {
+        self.rows == 0 || self.cols < MIN_COLS
+    }
+
+    /// Get the row that is shown in place of the text if the terminal is too small
+    pub(crate) fn too_small_row(&self) -> String {
+        minus_core::utils::truncate_with_ellipsis(TOO_SMALL_TEXT, self.cols).into_owned()
+    }
+
+    /// Get the line numbers as they are displayed
+    ///
+    /// Line numbers are left out if their gutter would take up more than half of the columns of
+    /// the text, so that there is still room for the text on narrow terminals.
+    pub(crate) fn displayed_line_numbers(&self) -> LineNumbers {
+        if self.line_numbers.is_on()
+            && self.screen.gutter_width(self.line_numbers) * 2 > self.text_cols()
+        {
+            LineNumbers::Disabled
+        } else {
+            self.line_numbers
+        }
+    }
+
+    /// Get the number of columns that the text can take up
+    ///
+    /// This leaves out the last column if it is taken by the scrollbar.
+    pub(crate) const fn text_cols(&self) -> usize {
+        if self.show_scrollbar {
+            self.cols.saturating_sub(1)
+        } else {
+            self.cols
+        }
+    }
+
+    /// Get the number of columns that the rows of the table have to fit into
+    ///
+    /// Returns `None` if the rows don't have to fit, because they can be scrolled horizontally.
+    pub(crate) fn table_width(&self) -> Option<usize> {
+        self.screen.line_wrapping.then(|| {
+            self.text_cols()
+                .saturating_sub(self.screen.gutter_width(self.displayed_line_numbers()))
+        })
+    }
+
+    /// Get the minimum time between two draws of appended text, if there is a limit
+    #[cfg(feature = "dynamic_output")]
+    pub(crate) fn refresh_interval(&self) -> Option<Duration> {
+        (self.max_refresh_rate > 0).then(|| Duration::from_secs(1) / self.max_refresh_rate)
+    }
+
+    /// Make sure that the lines to be displayed from `upper_mark` have been fetched from the
+    /// [`LineProvider`](crate::LineProvider), if one is set
+    pub(crate) fn fetch_provider_lines(&mut self, upper_mark: usize) {
+        self.screen.fetch_provider_window(
+            upper_mark,
+            self.writable_rows(),
+            self.displayed_line_numbers(),
+            self.text_cols(),
+            #[cfg(feature = "search")]
+            &self.search_state.search_term,
+        );
+    }
+
     pub(crate) fn format_lines(&mut self) {
+        // Lines served by a line provider are formatted when they are fetched. Just discard the
+        // ones that have already been fetched so that they are formatted again.
+        if self.screen.line_provider.is_some() {
+            self.screen.formatted_lines.clear();
+            self.screen.provider_window_start = 0;
+            self.screen.line_number_anchor = self.upper_mark;
+            self.format_prompt();
+            return;
+        }
+        // The columns of the table are sized to the space left on the screen
+        if !self.table.is_empty() {
+            self.screen.orig_text = self.table.layout(self.table_width()).into();
+        }
+        // Both sides of the diff share the space on the screen
+        if !self.diff.is_empty() {
+            let color_support = self.screen.color_support;
+            let theme = &self.screen.theme;
+            let styles = DiffStyles {
+                removed: color_support.adapt(theme.diff_removed),
+                added: color_support.adapt(theme.diff_added),
+                header: color_support.adapt(theme.diff_header),
+            };
+            self.screen.orig_text = self
+                .diff
+                .layout(self.table_width(), &styles, self.screen.tab_width)
+                .into();
+        }
+        // Remember the line at the top of the screen and how much of its text has been scrolled
+        // past, so that the same part of the line can be put back at the top once the text has
+        // been reflowed. The number of rows scrolled past can't be used for this as it changes
+        // whenever the line is wrapped differently
+        let top_line = self.lines_to_row_map.line_of_row(self.upper_mark);
+        let top_line_text_offset = self.lines_to_row_map.get(top_line).map(|first_row| {
+            (*first_row..self.upper_mark)
+                .map(|row| self.row_text_width(row, *first_row))
+                .sum::<usize>()
+        });
+
+        self.screen.line_number_anchor = top_line;
         let (buffer, format_result) = screen::make_format_lines(
-            &self.screen.orig_text,
-            self.line_numbers,
-            self.cols,
-            self.screen.line_wrapping,
+            &self.screen,
+            self.displayed_line_numbers(),
+            self.text_cols(),
             #[cfg(feature = "search")]
             &self.search_state.search_term,
         );
@@ -266,15 +1260,106 @@ impl PagerState {
         self.screen.max_line_length = format_result.max_line_length;
 
         self.screen.unterminated = format_result.num_unterminated;
+
+        if let (Some(offset), Some(&first_row)) =
+            (top_line_text_offset, self.lines_to_row_map.get(top_line))
+        {
+            let next_line_row = self
+                .lines_to_row_map
+                .get(top_line + 1)
+                .copied()
+                .unwrap_or_else(|| self.screen.formatted_lines_count());
+            // Find the row of the line which contains the text that was at the top before
+            let mut row = first_row;
+            let mut row_offset = 0;
+            while row + 1 < next_line_row {
+                row_offset += self.row_text_width(row, first_row);
+                if row_offset > offset {
+                    break;
+                }
+                row += 1;
+            }
+            self.upper_mark = row;
+        }
         self.format_prompt();
     }
 
+    /// Get the lines of the status area as they should be displayed
+    ///
+    /// Each line is cut off at the edge of the screen and padded to its full width. The rows of
+    /// the full prompt come last, right above the prompt. If not all the lines fit, the first
+    /// ones are left out.
+    pub(crate) fn displayed_status(&self) -> impl Iterator<Item = String> + '_ {
+        let status_style = self.screen.theme.status;
+        let prompt_style = self.prompt_style();
+        let lines = self.status_lines.len() + self.full_prompt.len();
+        self.status_lines
+            .iter()
+            .map(move |line| (line, status_style))
+            .chain(
+                self.full_prompt
+                    .iter()
+                    .map(move |line| (line, prompt_style)),
+            )
+            .skip(lines - self.status_rows())
+            .map(move |(line, style)| {
+                let line = minus_core::utils::truncate_with_ellipsis(line, self.cols);
+                let padding = self
+                    .cols
+                    .saturating_sub(textwrap::core::display_width(&line));
+                self.screen
+                    .color_support
+                    .adapt(style)
+                    .apply(format!("{line}{:padding$}", ""))
+                    .to_string()
+            })
+    }
+
+    /// Get the text that the prompt line shows, which is the message if there is one
+    fn prompt_text(&self) -> Cow<'_, str> {
+        match (&self.message, &self.prompt_format) {
+            (Some(message), _) => Cow::Borrowed(message),
+            (None, _) if self.eof_reached() => Cow::Borrowed("(END)"),
+            (None, Some(format)) => Cow::Owned(self.expand_prompt_format(format)),
+            (None, None) => Cow::Borrowed(&self.prompt),
+        }
+    }
+
+    /// Get the style of the prompt line for the text that it shows
+    const fn prompt_style(&self) -> ContentStyle {
+        if self.message.is_none() {
+            return self.screen.theme.prompt;
+        }
+        match self.message_level {
+            MessageLevel::Info => self.screen.theme.info_message,
+            MessageLevel::Warning => self.screen.theme.warning_message,
+            MessageLevel::Error => self.screen.theme.message,
+        }
+    }
+
+    /// Show the full text of the prompt or message above the prompt, if it doesn't fit on the
+    /// prompt line
+    ///
+    /// Returns true if the full text is shown.
+    pub(crate) fn show_full_prompt(&mut self) -> bool {
+        let text = self.prompt_text();
+        let full_prompt = if textwrap::core::display_width(&text) > self.cols {
+            textwrap::wrap(&text, self.cols.max(1))
+                .into_iter()
+                .map(Cow::into_owned)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        self.full_prompt = full_prompt;
+        !self.full_prompt.is_empty()
+    }
+
     /// Reformat the inputted prompt to how it should be displayed
+    #[allow(clippy::too_many_lines)]
     pub(crate) fn format_prompt(&mut self) {
-        const PROMPT_SPEC: &str = "\x1b[2;40;37m";
         const SEARCH_SPEC: &str = "\x1b[30;44m";
         const INPUT_SPEC: &str = "\x1b[30;43m";
-        const MSG_SPEC: &str = "\x1b[30;1;41m";
         const RESET: &str = "\x1b[0m";
         const FOLLOW_MODE_SPEC: &str = "\x1b[1m";
 
@@ -285,8 +1370,9 @@ impl PagerState {
         // Get the string that will contain the search index/match indicator
         #[cfg(feature = "search")]
         let mut search_str = String::new();
+        // The search index is only shown where the prompt format puts it if there is one
         #[cfg(feature = "search")]
-        if !self.search_state.search_idx.is_empty() {
+        if !self.search_state.search_idx.is_empty() && self.prompt_format.is_none() {
             search_str.push(' ');
             search_str.push_str(&(self.search_state.search_mark + 1).to_string());
             search_str.push('/');
@@ -303,35 +1389,98 @@ impl PagerState {
         }
 
         // And lastly, the string that contains the prompt or msg
-        let prompt_str = self.message.as_ref().unwrap_or(&self.prompt);
+        let prompt_str = self.prompt_text();
+
+        let follow_mode_str: &str = if self.follow_output { "[F]" } else { "" };
+
+        // How far through the text the screen is, placed at the right end of the prompt
+        let mut percent_str = if self.show_percentage {
+            format!(" {}% ", self.scroll_percentage())
+        } else {
+            String::new()
+        };
+
+        // The progress of the application in fetching the data, as a bar followed by the percentage
+        let mut progress_str = self.progress.map_or_else(String::new, |progress| {
+            const BAR_WIDTH: u8 = 10;
+            // NaN is treated as no progress
+            let progress = if progress.is_nan() {
+                0.0
+            } else {
+                progress.clamp(0.0, 1.0)
+            };
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let filled = (progress * f32::from(BAR_WIDTH)).round() as usize;
+            format!(
+                " [{}{}] {:>3.0}% ",
+                "#".repeat(filled),
+                "-".repeat(usize::from(BAR_WIDTH) - filled),
+                progress * 100.0
+            )
+        });
+
+        // Indicators that don't fit on narrow terminals are left out, the least important first
+        let mut space_left = self.cols;
+        let mut fits = |indicator: &str| {
+            let fits = indicator.len() <= space_left;
+            if fits {
+                space_left -= indicator.len();
+            }
+            fits
+        };
+        if !fits(&prefix_str) {
+            prefix_str.clear();
+        }
+        #[cfg(feature = "search")]
+        if !fits(&search_str) {
+            search_str.clear();
+        }
+        let follow_mode_str = if fits(follow_mode_str) {
+            follow_mode_str
+        } else {
+            ""
+        };
+        if !fits(&percent_str) {
+            percent_str.clear();
+        }
+        if !fits(&progress_str) {
+            progress_str.clear();
+        }
 
         #[cfg(feature = "search")]
         let search_len = search_str.len();
         #[cfg(not(feature = "search"))]
         let search_len = 0;
 
-        let follow_mode_str: &str = if self.follow_output { "[F]" } else { "" };
-
         // Calculate how much extra padding in the middle we need between
         // the prompt/message and the indicators on the right
         let prefix_len = prefix_str.len();
+        let indicators_len = progress_str.len()
+            + percent_str.len()
+            + search_len
+            + prefix_len
+            + follow_mode_str.len();
+        // Prompts that are too long are cut off. The user can see the full text with `=`
+        let dsp_prompt = minus_core::utils::truncate_with_ellipsis(
+            &prompt_str,
+            self.cols.saturating_sub(indicators_len),
+        );
         let extra_space = self
             .cols
-            .saturating_sub(search_len + prefix_len + follow_mode_str.len() + prompt_str.len());
-        let dsp_prompt: &str = if extra_space == 0 {
-            &prompt_str[..self.cols - search_len - prefix_len - follow_mode_str.len()]
-        } else {
-            prompt_str
-        };
+            .saturating_sub(indicators_len + textwrap::core::display_width(&dsp_prompt));
 
         // push the prompt/msg
-        if self.message.is_some() {
-            format_string.push_str(MSG_SPEC);
-        } else {
-            format_string.push_str(PROMPT_SPEC);
-        }
-        format_string.push_str(dsp_prompt);
-        format_string.push_str(&" ".repeat(extra_space));
+        format_string.push_str(
+            &self
+                .screen
+                .color_support
+                .adapt(self.prompt_style())
+                .apply(format!(
+                    "{dsp_prompt}{}{progress_str}{percent_str}",
+                    " ".repeat(extra_space)
+                ))
+                .to_string(),
+        );
 
         // add the prefix_num if it exists
         if prefix_len > 0 {
@@ -357,39 +1506,460 @@ impl PagerState {
         self.displayed_prompt = format_string;
     }
 
-    /// Runs the exit callbacks
-    pub(crate) fn exit(&mut self) {
+    /// Get the number of lines in the text
+    pub(crate) fn total_lines(&self) -> usize {
+        self.screen
+            .line_provider
+            .as_ref()
+            .map_or_else(|| self.screen.line_count(), |provider| provider.len())
+    }
+
+    /// Get how far through the text the bottom of the screen is, in percent
+    pub(crate) fn scroll_percentage(&self) -> usize {
+        let total_lines = self.total_lines();
+        (self.visible_lines().end.min(total_lines) * 100)
+            .checked_div(total_lines)
+            .unwrap_or(100)
+    }
+
+    /// Get the row at which the line `percent` percent of the way through the text starts
+    pub(crate) fn row_at_percentage(&self, percent: usize) -> usize {
+        let line = self.total_lines().saturating_mul(percent.min(100)) / 100;
+        if self.screen.line_provider.is_some() {
+            return line;
+        }
+        // Past the last line means the bottom of the text
+        self.lines_to_row_map
+            .get(line)
+            .copied()
+            .unwrap_or(usize::MAX - 1)
+    }
+
+    /// Whether the prompt shows the position in the text and has to be formatted after scrolling
+    pub(crate) const fn prompt_shows_position(&self) -> bool {
+        self.show_percentage || self.prompt_format.is_some() || self.data_finished
+    }
+
+    /// Replace the placeholders in the prompt `format` with their values
+    ///
+    /// See [`Pager::set_prompt_format`](crate::Pager::set_prompt_format) for the placeholders.
+    /// Anything starting with a `%` that isn't a placeholder is kept as is.
+    fn expand_prompt_format(&self, format: &str) -> String {
+        let visible_lines = self.visible_lines();
+        let total_lines = self.total_lines();
+
+        let mut expanded = String::with_capacity(format.len());
+        let mut rest = format;
+        while let Some(pos) = rest.find('%') {
+            expanded.push_str(&rest[..pos]);
+            rest = &rest[pos + 1..];
+            let name_len = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            let (name, after) = rest.split_at(name_len);
+            rest = after;
+            match name {
+                "" if rest.starts_with('%') => {
+                    expanded.push('%');
+                    rest = &rest[1..];
+                }
+                "prompt" => expanded.push_str(&self.prompt),
+                "line" => {
+                    let line = visible_lines.start + self.screen.first_line_number();
+                    expanded.push_str(&line.to_string());
+                }
+                "bottom" => {
+                    let line =
+                        (visible_lines.end + self.screen.first_line_number()).saturating_sub(1);
+                    expanded.push_str(&line.to_string());
+                }
+                "total" => expanded.push_str(&total_lines.to_string()),
+                "buffer" => expanded.push_str(self.buffers.loaded_name()),
+                "percent" => expanded.push_str(&self.scroll_percentage().to_string()),
+                #[cfg(feature = "search")]
+                "search" => {
+                    if !self.search_state.search_idx.is_empty() {
+                        expanded.push_str(&(self.search_state.search_mark + 1).to_string());
+                        expanded.push('/');
+                        expanded.push_str(&self.search_state.search_idx.len().to_string());
+                    }
+                }
+                #[cfg(not(feature = "search"))]
+                "search" => {}
+                _ => {
+                    expanded.push('%');
+                    expanded.push_str(name);
+                }
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+
+    /// Get the text that is currently displayed on the screen
+    ///
+    /// Each row of the screen is ended with a newline and the prompt, if it is shown, is on the
+    /// last row below the status area. ANSI escape sequences in the text like colors and search highlights are kept,
+    /// so the snapshot looks exactly like the screen when it is printed to a terminal.
+    ///
+    /// This can be used to save the view or log what the user saw from a
+    /// [key binding](crate::input), or for golden-file tests.
+    #[must_use]
+    pub fn screen_snapshot(&self) -> String {
+        let frame = Frame::new(self);
+        let mut snapshot = String::new();
+        for row in frame.rows() {
+            snapshot.push_str(row);
+            snapshot.push('\n');
+        }
+        if self.show_prompt && !self.minimal_layout() {
+            // Keep the prompt on the last row even if the text doesn't fill the screen
+            let blank_rows = self.writable_rows().saturating_sub(frame.rows().len());
+            snapshot.push_str(&"\n".repeat(blank_rows));
+            for line in self.displayed_status() {
+                snapshot.push_str(&line);
+                snapshot.push('\n');
+            }
+            snapshot.push_str(&self.displayed_prompt);
+            snapshot.push('\n');
+        }
+        snapshot
+    }
+
+    /// Get all of the text, formatted just like it would be displayed on a screen that is tall
+    /// enough to show all of it
+    ///
+    /// Line numbers, wrapping and search highlights are applied just like on the screen. If the
+    /// text is served by a [`LineProvider`](crate::LineProvider), its lines are returned as
+    /// they are.
+    #[must_use]
+    pub fn buffer_snapshot(&self) -> String {
+        let mut snapshot = String::new();
+        if let Some(provider) = self.screen.line_provider.as_deref() {
+            for line in provider.lines(0..provider.len()) {
+                snapshot.push_str(&line);
+                snapshot.push('\n');
+            }
+            return snapshot;
+        }
+        for row in &self.screen.formatted_lines {
+            snapshot.push_str(row);
+            snapshot.push('\n');
+        }
+        snapshot
+    }
+
+    /// Record why the pager quit and run the exit callbacks
+    pub(crate) fn exit(&mut self, reason: ExitReason) {
+        self.exit_reason = Some(reason);
         for func in &mut self.exit_callbacks {
             func();
         }
     }
 
+    /// Discard the oldest lines so that at most [`max_lines`](PagerState::max_lines) lines are kept
+    ///
+    /// The remaining lines keep their line numbers. Returns true if any lines were discarded.
+    pub(crate) fn enforce_max_lines(&mut self) -> bool {
+        if self.max_lines == 0 || self.screen.line_count() <= self.max_lines {
+            return false;
+        }
+        let count = self.screen.line_count() - self.max_lines;
+        // The lines of a fold whose first line is discarded are shown again
+        let fold_cut = self.screen.folds.is_hidden(count);
+        if !self.screen.orig_text.remove_first_lines(count) {
+            return false;
+        }
+        self.table.remove_first_rows(count);
+        self.diff.remove_first_rows(count);
+        self.sources.remove_first_lines(count);
+        self.screen.line_count -= count;
+        self.screen.discarded_lines += count;
+        self.screen.line_number_anchor = self.screen.line_number_anchor.saturating_sub(count);
+
+        let rows = self.lines_to_row_map.remove_first_lines(count);
+        self.screen.formatted_lines.drain(..rows);
+        self.upper_mark = self.upper_mark.saturating_sub(rows);
+        self.jump_list.shift_up(rows);
+        if let Some(split) = self.split.as_mut() {
+            split.other_mark = split.other_mark.saturating_sub(rows);
+        }
+        self.selected_line = self.selected_line.map(|line| line.saturating_sub(count));
+        self.line_highlights.retain_mut(|(lines, _)| {
+            *lines = lines.start.saturating_sub(count)..lines.end.saturating_sub(count);
+            lines.start < lines.end
+        });
+        self.screen.signs = self
+            .screen
+            .signs
+            .split_off(&count)
+            .into_iter()
+            .map(|(line, sign)| (line - count, sign))
+            .collect();
+        self.screen.folds.remove_first_lines(count);
+        self.sections = self
+            .sections
+            .split_off(&count)
+            .into_iter()
+            .map(|(line, label)| (line - count, label))
+            .collect();
+        #[cfg(feature = "search")]
+        {
+            let search_idx = self.search_state.search_idx.split_off(&rows);
+            let matches_removed = self.search_state.search_idx.len();
+            self.search_state.search_idx = search_idx.into_iter().map(|row| row - rows).collect();
+            self.search_state.search_mark = self
+                .search_state
+                .search_mark
+                .saturating_sub(matches_removed);
+        }
+        if fold_cut {
+            self.format_lines();
+        }
+        true
+    }
+
+    /// Discard all the lines after the first `count` ones
+    ///
+    /// Returns true if any lines were discarded. Text served by a
+    /// [line provider](crate::Pager::set_line_provider) can't be truncated.
+    pub(crate) fn truncate_lines(&mut self, count: usize) -> bool {
+        if self.screen.line_provider.is_some() || self.screen.line_count() <= count {
+            return false;
+        }
+        self.screen.orig_text.truncate_lines(count);
+        self.table.truncate(count);
+        self.diff.truncate(count);
+        self.sources.truncate(count);
+        self.screen.line_count = count;
+
+        self.selected_line = self
+            .selected_line
+            .map(|line| line.min(count.saturating_sub(1)));
+        self.line_highlights.retain_mut(|(lines, _)| {
+            lines.end = lines.end.min(count);
+            lines.start < lines.end
+        });
+        self.screen.signs.split_off(&count);
+        self.screen.folds.truncate(count);
+        self.sections.split_off(&count);
+        self.format_lines();
+        true
+    }
+
+    /// Replace the lines in `lines` with the lines of `text`
+    ///
+    /// `text` must be empty or end with a newline. Only the rows of the changed lines are
+    /// formatted again, along with the lines after them if their line numbers are displayed and
+    /// have changed. Returns false if nothing was changed, which is also the case if the text is
+    /// served by a [line provider](crate::Pager::set_line_provider), laid out as a
+    /// [table](crate::Pager::push_row) or a [diff](crate::Pager::set_diff) or made up of the lines
+    /// of [input sources](crate::Pager::push_source_str).
+    #[allow(clippy::too_many_lines)]
+    pub(crate) fn edit_lines(&mut self, lines: Range<usize>, text: &str) -> bool {
+        if self.screen.line_provider.is_some()
+            || !self.table.is_empty()
+            || !self.diff.is_empty()
+            || !self.sources.is_empty()
+        {
+            return false;
+        }
+        let old_count = self.screen.line_count();
+        let start = lines.start.min(old_count);
+        let end = lines.end.clamp(start, old_count);
+        let added_lines = text.lines().count();
+        if start == end && added_lines == 0 {
+            return false;
+        }
+        let new_count = old_count - (end - start) + added_lines;
+        // Position of a boundary between two lines once the lines have been replaced
+        let moved = |line: usize| {
+            if line <= start {
+                line
+            } else if line >= end {
+                line - end + start + added_lines
+            } else {
+                line.min(start + added_lines)
+            }
+        };
+
+        let old_digits = minus_core::utils::digits(self.screen.last_line_number());
+        let old_sign_column_width = self.screen.sign_column_width();
+        self.screen.signs = std::mem::take(&mut self.screen.signs)
+            .into_iter()
+            .filter(|(line, _)| *line < start || *line >= end || *line < start + added_lines)
+            .map(|(line, sign)| (if line >= end { moved(line) } else { line }, sign))
+            .collect();
+        self.screen.folds.replace_lines(start..end, added_lines);
+        self.sections = std::mem::take(&mut self.sections)
+            .into_iter()
+            .filter(|(line, _)| *line < start || *line >= end || *line < start + added_lines)
+            .map(|(line, label)| (if line >= end { moved(line) } else { line }, label))
+            .collect();
+        self.line_highlights.retain_mut(|(lines, _)| {
+            *lines = moved(lines.start)..moved(lines.end);
+            lines.start < lines.end
+        });
+        self.selected_line = self
+            .selected_line
+            .map(|line| moved(line).min(new_count.saturating_sub(1)));
+
+        // A line can't be added after the last line before that line is terminated
+        let unterminated_end = end == old_count
+            && !self.screen.orig_text.is_empty()
+            && !self.screen.orig_text.ends_with("\n");
+        if unterminated_end && start == end {
+            self.screen.orig_text.push_str("\n");
+            self.screen.orig_text.push_str(text);
+        } else {
+            self.screen.orig_text.replace_lines(start..end, text);
+        }
+        self.screen.line_count = new_count;
+
+        let line_numbers = self.displayed_line_numbers();
+        let numbers_changed = line_numbers.is_on() && added_lines != end - start;
+        if unterminated_end
+            || (line_numbers.is_relative() && numbers_changed)
+            || (line_numbers.is_on()
+                && old_digits != minus_core::utils::digits(self.screen.last_line_number()))
+            || old_sign_column_width != self.screen.sign_column_width()
+            // Only the formatting of the entire text leaves out the lines hidden in folds
+            || !self.screen.folds.all_open()
+        {
+            self.format_lines();
+            self.enforce_max_lines();
+            return true;
+        }
+
+        // The line numbers of all the following lines change when lines are added or removed
+        let (format_end, old_format_end) = if numbers_changed {
+            (new_count, old_count)
+        } else {
+            (start + added_lines, end)
+        };
+        let row_start = self
+            .lines_to_row_map
+            .get(start)
+            .copied()
+            .unwrap_or(self.screen.formatted_lines.len());
+        let old_row_end = self
+            .lines_to_row_map
+            .get(old_format_end)
+            .copied()
+            .unwrap_or(self.screen.formatted_lines.len());
+        #[cfg(feature = "search")]
+        let mut search_idx = BTreeSet::new();
+        let (rows, first_rows) = self.screen.format_line_range(
+            start..format_end,
+            row_start,
+            line_numbers,
+            self.text_cols(),
+            #[cfg(feature = "search")]
+            &mut search_idx,
+            #[cfg(feature = "search")]
+            &self.search_state.search_term,
+        );
+        let removed_rows = old_row_end - row_start;
+        let added_rows = rows.len();
+        if format_end == new_count && self.screen.unterminated > 0 {
+            self.screen.unterminated = row_start + added_rows - first_rows.last().unwrap();
+        }
+        self.screen
+            .formatted_lines
+            .splice(row_start..old_row_end, rows);
+        self.lines_to_row_map.replace_lines(
+            start..old_format_end,
+            first_rows,
+            removed_rows,
+            added_rows,
+        );
+        if self.upper_mark >= old_row_end {
+            self.upper_mark = self.upper_mark - removed_rows + added_rows;
+        }
+        #[cfg(feature = "search")]
+        {
+            let following = self.search_state.search_idx.split_off(&old_row_end);
+            self.search_state.search_idx.split_off(&row_start);
+            self.search_state.search_idx.append(&mut search_idx);
+            self.search_state.search_idx.extend(
+                following
+                    .into_iter()
+                    .map(|row| row - removed_rows + added_rows),
+            );
+            self.search_state.search_mark = self
+                .search_state
+                .search_mark
+                .min(self.search_state.search_idx.len().saturating_sub(1));
+        }
+        self.enforce_max_lines();
+        self.format_prompt();
+        true
+    }
+
+    /// Turn the `\r\n` line endings of `text` into `\n` unless they should be kept
+    pub(crate) fn normalize_line_endings_of(&self, text: &mut String) {
+        if !self.normalize_line_endings {
+            return;
+        }
+        if let Cow::Owned(normalized) = screen::normalize_line_endings(text) {
+            *text = normalized;
+        }
+    }
+
     pub(crate) fn append_str(&mut self, text: &str) -> AppendStyle {
-        let old_lc = self.screen.line_count();
-        let old_lc_dgts = minus_core::utils::digits(old_lc);
+        let text = if self.normalize_line_endings {
+            // The `\r` of a line ending which is split between two appends is part of the last
+            // line, which is formatted again along with the incoming text. It is left alone if the
+            // line has nothing else that could be formatted.
+            if text.starts_with('\n')
+                && self.screen.orig_text.ends_with("\r")
+                && self.screen.orig_text.len() > 1
+                && !self.screen.orig_text.ends_with("\n\r")
+            {
+                self.screen.orig_text.pop();
+            }
+            screen::normalize_line_endings(text)
+        } else {
+            Cow::Borrowed(text)
+        };
+        let text = &*text;
+        let old_lc_dgts = minus_core::utils::digits(self.screen.last_line_number());
+        // The rows of an unterminated last line are formatted again along with the incoming text
+        // so drop any search matches that were found in them.
+        #[cfg(feature = "search")]
+        self.search_state
+            .search_idx
+            .split_off(&(self.screen.formatted_lines_count() - self.screen.unterminated));
         let mut append_result = self.screen.push_screen_buf(
             text,
-            self.line_numbers,
-            self.cols.try_into().unwrap(),
+            self.displayed_line_numbers(),
+            self.text_cols().try_into().unwrap(),
             #[cfg(feature = "search")]
             &self.search_state.search_term,
         );
-        let new_lc = self.screen.line_count();
-        let new_lc_dgts = minus_core::utils::digits(new_lc);
+        let new_lc_dgts = minus_core::utils::digits(self.screen.last_line_number());
         #[cfg(feature = "search")]
         {
-            let mut append_search_idx = append_result.append_search_idx;
-            self.search_state.search_idx.append(&mut append_search_idx);
+            // Extending is cheaper than BTreeSet::append here as it does not need to rebuild the
+            // entire set, which would make every append take time proportional to all the matches
+            let append_search_idx = std::mem::take(&mut append_result.append_search_idx);
+            self.search_state.search_idx.extend(append_search_idx);
         }
         self.lines_to_row_map.append(
             &mut append_result.lines_to_row_map,
             append_result.clean_append,
         );
 
+        let lines_removed = self.enforce_max_lines();
+
         if self.line_numbers.is_on() && (new_lc_dgts != old_lc_dgts && old_lc_dgts != 0) {
             self.format_lines();
             return AppendStyle::FullRedraw;
         }
+        // Everything on the screen has moved up
+        if lines_removed {
+            return AppendStyle::FullRedraw;
+        }
 
         let total_rows = self.screen.formatted_lines_count();
         let fmt_lines = &self
@@ -2,12 +2,16 @@
 //!
 //! This module provides provides the [`page_all`] function to display static output via minus
 use crate::minus_core::init;
-use crate::{error::MinusError, Pager};
+use crate::{error::MinusError, ExitReason, Pager};
 
 /// Display static information to the screen
 ///
 /// Since it is sure that fed data will never change, minus can do some checks like:-
 /// * If stdout is not a tty, minus not start a pager. It will simply print all the data and quit
+/// * If running in a CI or under a terminal with `TERM=dumb`, minus will not start a pager either.
+///   If stdin is a tty, it will print the data in chunks that fit the terminal and wait for the user
+///   to press Enter after each chunk (or `q` and Enter to stop). Otherwise it will simply print all
+///   the data and quit
 /// * If there are more rows in the terminal than the number of lines of data to display
 /// minus will not start a pager and simply display all data on the main stdout screen.
 /// This behaviour can be turned off if
@@ -16,15 +20,27 @@ use crate::{error::MinusError, Pager};
 /// * Since any other event except user inputs will not occur, we can do some optimizations on
 /// matching events.
 ///
+/// Once the pager has quit, the [`ExitReason`] tells why it quit.
+///
 /// See [example](../index.html#static-output) on how to use this function.
 ///
 /// # Panics
-/// This function will panic if another instance of minus is already running.
+/// This function will panic if the pager is already running. Once the pager has quit, it can be
+/// started again. Remember to set the exit strategy to
+/// [`ExitStrategy::PagerQuit`](crate::ExitStrategy::PagerQuit) as the process is otherwise
+/// exited when the pager quits.
+///
+/// Separate pagers can run at the same time if each of them has its own output and input set
+/// with [`Pager::set_output`](crate::Pager::set_output) and
+/// [`Pager::set_input`](crate::Pager::set_input). Only one pager at a time can use the terminal
+/// of the process though, since raw mode is a setting of the whole terminal and a pager that
+/// quits turns it off for all the others. Starting a pager while another one is using the
+/// terminal panics.
 ///
 /// # Errors
 /// The function will return with an error if it encounters a error during paging.
 #[cfg_attr(docsrs, doc(cfg(feature = "static_output")))]
-#[allow(clippy::needless_pass_by_value)]
-pub fn page_all(pager: Pager) -> Result<(), MinusError> {
-    init::init_core(&pager, crate::RunMode::Static)
+pub fn page_all(pager: Pager) -> Result<ExitReason, MinusError> {
+    let runmode = init::mark_running(&pager, crate::RunMode::Static);
+    init::init_core(pager, runmode)
 }
@@ -0,0 +1,283 @@
+//! Lays out rows of cells into the columns of a table
+//!
+//! See [`Pager::push_row`](crate::Pager::push_row) for more info.
+
+use crossterm::style::Attribute;
+use textwrap::core::display_width;
+
+/// Columns are separated by this many spaces
+const COLUMN_GAP: usize = 2;
+
+/// How the text of a [`Cell`] is placed inside its column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alignment {
+    /// Place the text at the left edge of the column
+    #[default]
+    Left,
+    /// Place the text at the right edge of the column, usually for numbers
+    Right,
+    /// Place the text at the middle of the column
+    Center,
+}
+
+/// A cell of a row pushed with [`Pager::push_row`](crate::Pager::push_row)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Cell {
+    /// Text of the cell. Newlines are displayed as spaces
+    pub text: String,
+    /// Placement of the text inside its column
+    pub alignment: Alignment,
+}
+
+impl Cell {
+    /// Make a left aligned cell displaying `text`
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            alignment: Alignment::Left,
+        }
+    }
+
+    /// Set the placement of the text inside its column
+    #[must_use]
+    pub const fn align(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+/// The rows pushed with [`Pager::push_row`](crate::Pager::push_row)
+#[derive(Debug, Default)]
+pub struct Table {
+    rows: Vec<Vec<Cell>>,
+    /// Width of the widest cell of each column
+    natural_widths: Vec<usize>,
+    /// Width of each column when the table was last laid out
+    widths: Vec<usize>,
+}
+
+impl Table {
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Remove the first `count` rows
+    pub fn remove_first_rows(&mut self, count: usize) {
+        self.rows.drain(..count.min(self.rows.len()));
+        self.natural_widths = natural_widths(&self.rows);
+    }
+
+    /// Remove all the rows after the first `count` ones
+    pub fn truncate(&mut self, count: usize) {
+        self.rows.truncate(count);
+        self.natural_widths = natural_widths(&self.rows);
+    }
+
+    /// Add a row to the end of the table
+    ///
+    /// Returns the text of the row if it fits into the columns as they are laid out. Otherwise
+    /// the sizes of the columns have changed and the whole table has to be laid out again with
+    /// [`layout`](Table::layout).
+    pub fn push(&mut self, row: Vec<Cell>, max_width: Option<usize>) -> Option<String> {
+        for (idx, cell) in row.iter().enumerate() {
+            let width = cell_width(cell);
+            match self.natural_widths.get_mut(idx) {
+                Some(natural) => *natural = (*natural).max(width),
+                None => self.natural_widths.push(width),
+            }
+        }
+        self.rows.push(row);
+        if column_widths(&self.natural_widths, max_width) == self.widths {
+            self.rows.last().map(|row| layout_row(row, &self.widths))
+        } else {
+            None
+        }
+    }
+
+    /// Get the text of the whole table with each row on its own line
+    ///
+    /// If `max_width` is given, the widest columns are shrunk so that each row fits into that many
+    /// columns, truncating the text of their cells.
+    pub fn layout(&mut self, max_width: Option<usize>) -> String {
+        self.widths = column_widths(&self.natural_widths, max_width);
+        self.rows
+            .iter()
+            .map(|row| layout_row(row, &self.widths))
+            .collect()
+    }
+}
+
+fn cell_width(cell: &Cell) -> usize {
+    display_width(&cell.text)
+}
+
+fn natural_widths(rows: &[Vec<Cell>]) -> Vec<usize> {
+    let mut widths = Vec::new();
+    for row in rows {
+        for (idx, cell) in row.iter().enumerate() {
+            match widths.get_mut(idx) {
+                Some(width) => *width = cell_width(cell).max(*width),
+                None => widths.push(cell_width(cell)),
+            }
+        }
+    }
+    widths
+}
+
+/// Get the width of each column so that a row fits into `max_width` columns
+///
+/// Only the widest columns are shrunk, all down to the same width, so that narrow columns like
+/// identifiers are kept intact.
+fn column_widths(natural_widths: &[usize], max_width: Option<usize>) -> Vec<usize> {
+    let gaps = natural_widths.len().saturating_sub(1) * COLUMN_GAP;
+    let row_width = |limit: usize| {
+        natural_widths
+            .iter()
+            .map(|w| (*w).min(limit))
+            .sum::<usize>()
+    };
+    let Some(max_width) = max_width else {
+        return natural_widths.to_vec();
+    };
+    let available = max_width.saturating_sub(gaps);
+    let widest = natural_widths.iter().copied().max().unwrap_or(0);
+    if row_width(widest) <= available {
+        return natural_widths.to_vec();
+    }
+    // Find the largest limit for the widths of the columns that lets the row fit
+    let (mut low, mut high) = (1, widest);
+    while low < high {
+        let mid = (low + high + 1) / 2;
+        if row_width(mid) <= available {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    natural_widths.iter().map(|w| (*w).min(low)).collect()
+}
+
+/// Get the text of a row with each cell placed in its column
+fn layout_row(row: &[Cell], widths: &[usize]) -> String {
+    let mut text = String::new();
+    for (idx, (cell, width)) in row.iter().zip(widths).enumerate() {
+        if idx > 0 {
+            text.push_str(&" ".repeat(COLUMN_GAP));
+        }
+        let cell_text = fit(&cell.text.replace('\n', " "), *width);
+        let padding = width.saturating_sub(display_width(&cell_text));
+        let (before, after) = match cell.alignment {
+            Alignment::Left => (0, padding),
+            Alignment::Right => (padding, 0),
+            Alignment::Center => (padding / 2, padding - padding / 2),
+        };
+        text.push_str(&" ".repeat(before));
+        text.push_str(&cell_text);
+        text.push_str(&" ".repeat(after));
+    }
+    // Padding after the last cell would show up as trailing spaces
+    let len = text.trim_end_matches(' ').len();
+    text.truncate(len);
+    text.push('\n');
+    text
+}
+
+/// Truncate `text` with an ellipsis if it is wider than `width` columns
+fn fit(text: &str, width: usize) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+    let end = text
+        .char_indices()
+        .map(|(idx, _)| idx)
+        .take_while(|idx| display_width(&text[..*idx]) < width)
+        .last()
+        .unwrap_or(0);
+    // Colors started in the cell must not continue past it
+    let reset = if text[..end].contains('\x1b') {
+        Attribute::Reset.to_string()
+    } else {
+        String::new()
+    };
+    format!("{}…{reset}", &text[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Alignment, Cell, Table};
+
+    fn table(rows: &[&[Cell]]) -> Table {
+        let mut table = Table::default();
+        for row in rows {
+            table.push(row.to_vec(), None);
+        }
+        table
+    }
+
+    #[test]
+    fn aligned_columns() {
+        let mut table = table(&[
+            &["PID".into(), "NAME".into(), "STATE".into()],
+            &[
+                Cell::new("1").align(Alignment::Right),
+                Cell::new("init").align(Alignment::Center),
+                "S".into(),
+            ],
+            &[
+                Cell::new("4012").align(Alignment::Right),
+                "sshd".into(),
+                "R".into(),
+            ],
+        ]);
+        assert_eq!(
+            table.layout(None),
+            "PID   NAME  STATE\n   1  init  S\n4012  sshd  R\n"
+        );
+    }
+
+    #[test]
+    fn shrink_widest_columns() {
+        let mut table = table(&[
+            &["1".into(), "a long description".into(), "tag".into()],
+            &["2".into(), "short".into(), "a longer tag".into()],
+        ]);
+        // Without a limit, every column is as wide as its widest cell
+        assert_eq!(
+            table.layout(None),
+            "1  a long description  tag\n2  short               a longer tag\n"
+        );
+        assert_eq!(
+            table.layout(Some(24)),
+            "1  a long d…  tag\n2  short      a longer…\n"
+        );
+    }
+
+    #[test]
+    fn push_into_laid_out_columns() {
+        let mut table = table(&[&["a".into(), "bbb".into()]]);
+        table.layout(None);
+        assert_eq!(
+            table.push(vec!["c".into(), "d".into()], None),
+            Some("c  d\n".to_string())
+        );
+        // A wider cell changes the width of its column
+        assert_eq!(table.push(vec!["eee".into(), "f".into()], None), None);
+        assert_eq!(table.layout(None), "a    bbb\nc    d\neee  f\n");
+    }
+}
@@ -0,0 +1,856 @@
+//! Utilities for testing applications that use minus
+//!
+//! The [`run_script`] function runs the pager against an in-memory screen instead of the
+//! terminal and feeds it a scripted sequence of key presses. It returns every [`Frame`] that was
+//! displayed along the way so that applications can write integration tests for their pager
+//! flows, like custom key bindings, prompts or data that is sent while the pager is running.
+//!
+//! ```
+//! use crossterm::event::{KeyCode, KeyEvent};
+//! use minus::{testing, Pager};
+//!
+//! let pager = Pager::new();
+//! for i in 0..100 {
+//!     pager.push_str(format!("Line {i}\n")).unwrap();
+//! }
+//!
+//! let frames = testing::run_script(&pager, 20, 5, [KeyEvent::from(KeyCode::Char('j'))]).unwrap();
+//! assert_eq!(frames[0].rows()[0], "Line 0");
+//! assert_eq!(frames[1].rows()[0], "Line 1");
+//! ```
+
+use crate::{
+    error::MinusError,
+    input::EventSource,
+    minus_core::{
+        ev_handler::handle_event,
+        init::classify_event,
+        utils::{display, term},
+        CommandQueue, RunMode, RunModeGuard,
+    },
+    ExitStrategy, Pager, PagerState,
+};
+use crossterm::event::{Event, KeyEvent};
+use parking_lot::Mutex;
+use std::{
+    collections::VecDeque,
+    fmt, io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use parking_lot::Condvar;
+
+/// A snapshot of the screen
+///
+/// Each row holds the text displayed on it without any styling. Trailing whitespace is removed
+/// from the rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    rows: Vec<String>,
+}
+
+impl Frame {
+    /// Get the text of each row of the screen from top to bottom
+    #[must_use]
+    pub fn rows(&self) -> &[String] {
+        &self.rows
+    }
+
+    /// Returns `true` if any row of the screen contains `text`
+    #[must_use]
+    pub fn contains(&self, text: &str) -> bool {
+        self.rows.iter().any(|row| row.contains(text))
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rows.join("\n"))
+    }
+}
+
+/// Run the pager headlessly with scripted key presses
+///
+/// The pager is started with all the data and configuration that has been sent to `pager` and
+/// is drawn to an in-memory screen that is `cols` columns wide and `rows` rows high. Then each
+/// key in `keys` is pressed one after another. Data and configuration sent from other clones of
+/// `pager` are handled before each key press.
+///
+/// The first returned [`Frame`] is the screen right after the pager started and each following
+/// one is the screen after a key press. The script stops early if the pager quits. The
+/// [`ExitStrategy`] is always [`PagerQuit`](ExitStrategy::PagerQuit) so that the tests aren't
+/// stopped when a key quits the pager.
+///
+/// # Panics
+/// This function will panic if `pager` is already running.
+///
+/// # Errors
+/// This function returns an error if handling a key press fails. It also returns an error if the
+/// script ends while the pager is waiting for more input, like in the middle of a search prompt.
+pub fn run_script(
+    pager: &Pager,
+    cols: u16,
+    rows: u16,
+    keys: impl IntoIterator<Item = KeyEvent>,
+) -> Result<Vec<Frame>, MinusError> {
+    let runmode = RunModeGuard::set(&pager.running, RunMode::Dynamic);
+    assert!(
+        runmode.previous().is_uninitialized(),
+        "The pager is already running"
+    );
+    let script = Arc::new(Mutex::new(Script {
+        screen: Screen::new(cols, rows),
+        keys: keys.into_iter().collect(),
+        frames: Vec::new(),
+    }));
+
+    // Process everything as if the pager had not started yet and then as if it was running
+    let mut ps = PagerState::generate_initial_state(&pager.rx, &mut io::sink())?;
+    ps.running = pager.running.clone();
+
+    ps.output_size = Some((cols, rows));
+    if ps.viewport.is_none() {
+        ps.cols = cols.into();
+        ps.rows = rows.into();
+    }
+    ps.event_source = Arc::new(Mutex::new(Box::new(ScriptedEvents(script.clone()))));
+    ps.terminal_input = false;
+    ps.exit_strategy = ExitStrategy::PagerQuit;
+    ps.format_lines();
+    ps.format_prompt();
+
+    let mut out = ScreenWriter(script.clone());
+    let source = ps.event_source.clone();
+    let mut command_queue = CommandQueue::new();
+    let is_exited = Arc::new(AtomicBool::new(false));
+    let user_input_active = Arc::new((Mutex::new(true), Condvar::new()));
+
+    term::setup(&mut out, ps.term_options())?;
+    ps.go_to_initial_position();
+    display::draw_full(&mut out, &mut ps)?;
+    if ps.follow_output {
+        display::draw_for_change(&mut out, &mut ps, &mut (usize::MAX - 1))?;
+    }
+    for command in std::mem::take(&mut ps.pending_commands) {
+        command_queue.push_back(command);
+    }
+
+    loop {
+        if is_exited.load(Ordering::SeqCst) {
+            break;
+        }
+        // Handle everything that is waiting before the next key press
+        while let Some(command) = command_queue
+            .pop_front()
+            .or_else(|| pager.rx.try_recv().ok())
+        {
+            handle_event(
+                command,
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &is_exited,
+                &user_input_active,
+            )?;
+        }
+        if is_exited.load(Ordering::SeqCst) || script.lock().keys.is_empty() {
+            break;
+        }
+
+        let ev = source
+            .lock()
+            .next_event(Duration::ZERO)
+            .map_err(|e| MinusError::HandleEvent(e.into()))?;
+        if let Some(command) = ev.and_then(|ev| classify_event(ev, &mut ps)) {
+            handle_event(
+                command,
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &is_exited,
+                &user_input_active,
+            )?;
+        }
+    }
+
+    let mut script = script.lock();
+    let frame = script.screen.frame();
+    script.frames.push(frame);
+    Ok(std::mem::take(&mut script.frames))
+}
+
+/// State shared between the screen and the scripted key presses
+struct Script {
+    screen: Screen,
+    keys: VecDeque<KeyEvent>,
+    frames: Vec<Frame>,
+}
+
+/// Writes the output of the pager to the in-memory screen
+struct ScreenWriter(Arc<Mutex<Script>>);
+
+impl io::Write for ScreenWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().screen.feed(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Gives the scripted key presses to the pager
+///
+/// The screen is captured right before each key is given out, which is when the pager has
+/// finished handling the previous key.
+struct ScriptedEvents(Arc<Mutex<Script>>);
+
+impl EventSource for ScriptedEvents {
+    fn next_event(&mut self, _timeout: Duration) -> io::Result<Option<Event>> {
+        let mut script = self.0.lock();
+        let Some(key) = script.keys.pop_front() else {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the script ended while the pager was waiting for input",
+            ));
+        };
+        let frame = script.screen.frame();
+        script.frames.push(frame);
+        drop(script);
+        Ok(Some(Event::Key(key)))
+    }
+}
+
+/// A minimal terminal emulator which understands the escape sequences used by minus
+struct Screen {
+    cols: usize,
+    cells: Vec<Vec<String>>,
+    row: usize,
+    col: usize,
+    /// Bytes of an escape sequence or a UTF-8 character which has not been fully written yet
+    pending: Vec<u8>,
+}
+
+impl Screen {
+    fn new(cols: u16, rows: u16) -> Self {
+        let cols = usize::from(cols.max(1));
+        Self {
+            cols,
+            cells: vec![vec![String::new(); cols]; usize::from(rows.max(1))],
+            row: 0,
+            col: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    fn frame(&self) -> Frame {
+        let rows = self
+            .cells
+            .iter()
+            .map(|row| {
+                let text: String = row
+                    .iter()
+                    .map(|cell| if cell.is_empty() { " " } else { cell })
+                    .collect();
+                text.trim_end().to_string()
+            })
+            .collect();
+        Frame { rows }
+    }
+
+    fn feed(&mut self, buf: &[u8]) {
+        self.pending.extend_from_slice(buf);
+        let pending = std::mem::take(&mut self.pending);
+        let mut start = 0;
+        while start < pending.len() {
+            match self.process(&pending[start..]) {
+                Some(consumed) => start += consumed,
+                None => break,
+            }
+        }
+        self.pending = pending[start..].to_vec();
+    }
+
+    /// Process the first character or escape sequence in `bytes` and return its length
+    ///
+    /// Returns `None` if it is incomplete.
+    fn process(&mut self, bytes: &[u8]) -> Option<usize> {
+        match bytes[0] {
+            b'\x1b' => self.escape(bytes),
+            b'\r' => {
+                self.col = 0;
+                Some(1)
+            }
+            b'\n' => {
+                self.line_feed();
+                Some(1)
+            }
+            b'\x08' => {
+                self.col = self.col.saturating_sub(1);
+                Some(1)
+            }
+            b'\t' => {
+                self.col = ((self.col / 8 + 1) * 8).min(self.cols - 1);
+                Some(1)
+            }
+            b if b < 0x20 || b == 0x7f => Some(1),
+            b => {
+                let len = match b {
+                    0xf0..=0xf7 => 4,
+                    0xe0..=0xef => 3,
+                    0xc0..=0xdf => 2,
+                    _ => 1,
+                };
+                let bytes = bytes.get(..len)?;
+                let text = String::from_utf8_lossy(bytes);
+                self.print(&text);
+                Some(len)
+            }
+        }
+    }
+
+    fn print(&mut self, text: &str) {
+        let width = textwrap::core::display_width(text);
+        if width == 0 {
+            // Combine with the previous character
+            if let Some(cell) = self.cells[self.row].get_mut(self.col.saturating_sub(1)) {
+                cell.push_str(text);
+            }
+            return;
+        }
+        if self.col + width > self.cols {
+            self.col = 0;
+            self.line_feed();
+        }
+        let row = &mut self.cells[self.row];
+        row[self.col] = text.to_string();
+        // The cells covered by a wide character stay empty
+        for cell in row.iter_mut().skip(self.col + 1).take(width - 1) {
+            cell.clear();
+        }
+        self.col += width;
+    }
+
+    fn line_feed(&mut self) {
+        if self.row + 1 == self.cells.len() {
+            self.scroll_up(1);
+        } else {
+            self.row += 1;
+        }
+    }
+
+    fn scroll_up(&mut self, n: usize) {
+        for _ in 0..n.min(self.cells.len()) {
+            self.cells.remove(0);
+            self.cells.push(vec![String::new(); self.cols]);
+        }
+    }
+
+    fn scroll_down(&mut self, n: usize) {
+        for _ in 0..n.min(self.cells.len()) {
+            self.cells.pop();
+            self.cells.insert(0, vec![String::new(); self.cols]);
+        }
+    }
+
+    fn escape(&mut self, bytes: &[u8]) -> Option<usize> {
+        match *bytes.get(1)? {
+            b'[' => self.csi(bytes),
+            // Operating system commands end with BEL or ST
+            b']' => bytes
+                .windows(2)
+                .enumerate()
+                .skip(1)
+                .find_map(|(i, w)| match w {
+                    [b'\x07', _] => Some(i + 1),
+                    [b'\x1b', b'\\'] => Some(i + 2),
+                    _ => None,
+                }),
+            _ => Some(2),
+        }
+    }
+
+    fn csi(&mut self, bytes: &[u8]) -> Option<usize> {
+        let end = bytes
+            .iter()
+            .skip(2)
+            .position(|b| (0x40..=0x7e).contains(b))?
+            + 2;
+        let params = &bytes[2..end];
+        // Private sequences like switching to the alternate screen don't affect the contents
+        if params.first() == Some(&b'?') {
+            return Some(end + 1);
+        }
+        let params: Vec<usize> = String::from_utf8_lossy(params)
+            .split(';')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+        let param = |i: usize, default: usize| match params.get(i) {
+            Some(0) | None => default,
+            Some(&p) => p,
+        };
+        let last_row = self.cells.len() - 1;
+        match bytes[end] {
+            b'H' | b'f' => {
+                self.row = (param(0, 1) - 1).min(last_row);
+                self.col = (param(1, 1) - 1).min(self.cols - 1);
+            }
+            b'A' => self.row = self.row.saturating_sub(param(0, 1)),
+            b'B' => self.row = (self.row + param(0, 1)).min(last_row),
+            b'C' => self.col = (self.col + param(0, 1)).min(self.cols - 1),
+            b'D' => self.col = self.col.saturating_sub(param(0, 1)),
+            b'E' => {
+                self.row = (self.row + param(0, 1)).min(last_row);
+                self.col = 0;
+            }
+            b'F' => {
+                self.row = self.row.saturating_sub(param(0, 1));
+                self.col = 0;
+            }
+            b'G' => self.col = (param(0, 1) - 1).min(self.cols - 1),
+            b'd' => self.row = (param(0, 1) - 1).min(last_row),
+            b'J' => {
+                let (row, col) = (self.row, self.col);
+                let clear_row = |cells: &mut Vec<String>| cells.iter_mut().for_each(String::clear);
+                match param(0, 0) {
+                    0 => {
+                        self.cells[row][col..].iter_mut().for_each(String::clear);
+                        self.cells[row + 1..].iter_mut().for_each(clear_row);
+                    }
+                    1 => {
+                        self.cells[..row].iter_mut().for_each(clear_row);
+                        self.cells[row][..=col].iter_mut().for_each(String::clear);
+                    }
+                    _ => self.cells.iter_mut().for_each(clear_row),
+                }
+            }
+            b'K' => {
+                let row = &mut self.cells[self.row];
+                let cells = match param(0, 0) {
+                    0 => &mut row[self.col..],
+                    1 => &mut row[..=self.col],
+                    _ => &mut row[..],
+                };
+                cells.iter_mut().for_each(String::clear);
+            }
+            b'S' => self.scroll_up(param(0, 1)),
+            b'T' => self.scroll_down(param(0, 1)),
+            _ => {}
+        }
+        Some(end + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_script, Screen};
+    use crate::{
+        input::{InputEvent, PipeRange},
+        Alignment, Cell, ExitReason, InterruptStrategy, LineNumbers, Pager, Position,
+    };
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use parking_lot::Mutex;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn screen_escape_sequences() {
+        let mut screen = Screen::new(10, 3);
+        screen.feed(b"\x1b[2Jhello\r\n\x1b[1mworld\x1b[0m");
+        screen.feed(b"\x1b[3;");
+        screen.feed(b"2Hend\x1b[1;3H\x1b[K");
+        assert_eq!(screen.frame().rows(), ["he", "world", " end"]);
+
+        // Scrolling moves the contents
+        screen.feed(b"\x1b[1S");
+        assert_eq!(screen.frame().rows(), ["world", " end", ""]);
+    }
+
+    #[test]
+    fn scripted_keys() {
+        let pager = Pager::new();
+        pager
+            .set_text((0..20).map(|i| format!("Line {i}\n")).collect::<String>())
+            .unwrap();
+        pager.set_prompt("test").unwrap();
+
+        let keys = [KeyCode::Char('j'), KeyCode::Char('G'), KeyCode::Char('q')].map(KeyEvent::from);
+        let frames = run_script(&pager, 20, 5, keys).unwrap();
+        // The initial frame, one for each key and the frame after quitting
+        assert_eq!(frames.len(), 4);
+        assert_eq!(
+            frames[0].rows()[..4],
+            ["Line 0", "Line 1", "Line 2", "Line 3"]
+        );
+        assert!(frames[0].rows()[4].starts_with("test"));
+        assert_eq!(frames[1].rows()[0], "Line 1");
+        assert_eq!(frames[2].rows()[0], "Line 16");
+    }
+
+    #[test]
+    fn small_terminal() {
+        let pager = || {
+            let pager = Pager::new();
+            pager
+                .set_text((0..20).map(|i| format!("Line {i}\n")).collect::<String>())
+                .unwrap();
+            pager
+        };
+
+        // The text takes up all the rows if there are too few for the prompt
+        let frames = run_script(&pager(), 20, 2, []).unwrap();
+        assert_eq!(frames[0].rows(), ["Line 0", "Line 1"]);
+
+        let frames = run_script(&pager(), 3, 5, []).unwrap();
+        assert_eq!(frames[0].rows(), ["Te…", "", "", "", ""]);
+
+        // Line numbers that would leave too little room for the text are left out
+        let narrow = pager();
+        narrow.set_line_numbers(LineNumbers::Enabled).unwrap();
+        let frames = run_script(&narrow, 12, 5, []).unwrap();
+        assert_eq!(frames[0].rows()[0], "Line 0");
+
+        // The indicators that don't fit are left out of the prompt instead of wrapping it
+        let prompt = pager();
+        prompt.set_prompt("prompt").unwrap();
+        prompt.follow_output(true).unwrap();
+        prompt.show_percentage(true).unwrap();
+        let frames = run_script(&prompt, 8, 5, []).unwrap();
+        assert_eq!(frames[0].rows()[0], "Line 16");
+        assert_eq!(frames[0].rows()[4], "prom…[F]");
+    }
+
+    #[test]
+    fn input_sent_before_start() {
+        let pager = Pager::new();
+        pager
+            .set_text((0..20).map(|i| format!("Line {i}\n")).collect::<String>())
+            .unwrap();
+        pager.send_input(InputEvent::UpdateUpperMark(5)).unwrap();
+
+        let frames = run_script(&pager, 20, 5, []).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].rows()[0], "Line 5");
+    }
+
+    #[test]
+    fn initial_position() {
+        for (position, first_row) in [
+            (Position::Bottom, "Line 16"),
+            (Position::Line(7), "Line 7"),
+            (Position::Line(100), "Line 16"),
+            #[cfg(feature = "search")]
+            (Position::FirstMatch("e 1[2-9]".to_string()), "Line 12"),
+        ] {
+            let pager = Pager::new();
+            pager
+                .set_text((0..20).map(|i| format!("Line {i}\n")).collect::<String>())
+                .unwrap();
+            pager.set_initial_position(position).unwrap();
+            let frames = run_script(&pager, 20, 5, []).unwrap();
+            assert_eq!(frames[0].rows()[0], first_row);
+        }
+    }
+
+    #[test]
+    fn scripted_save_prompt() {
+        let text = "Line\twith a tab\n\x1b[1mbold\x1b[0m\n";
+        let path = std::env::temp_dir().join(format!("minus-save-{}", std::process::id()));
+        let pager = Pager::new();
+        pager.set_text(text).unwrap();
+
+        let keys = std::iter::once('s')
+            .chain(path.to_str().unwrap().chars())
+            .map(KeyCode::Char)
+            .chain([KeyCode::Enter])
+            .map(KeyEvent::from);
+        let frames = run_script(&pager, 80, 5, keys).unwrap();
+        assert!(frames[2].rows()[4].starts_with("Save to: /"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+        assert!(frames.last().unwrap().rows()[4].starts_with("Saved to"));
+
+        // The file is only overwritten once the user agrees to it
+        pager.set_text("changed\n").unwrap();
+        let save_again = |answer: char| {
+            std::iter::once('s')
+                .chain(path.to_str().unwrap().chars())
+                .map(KeyCode::Char)
+                .chain([KeyCode::Enter, KeyCode::Char(answer), KeyCode::Enter])
+                .map(KeyEvent::from)
+        };
+        let frames = run_script(&pager, 80, 5, save_again('n')).unwrap();
+        assert!(frames
+            .iter()
+            .any(|frame| frame.rows()[4].ends_with("exists. Overwrite it? (y/n) n")));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+        pager.set_text("changed\n").unwrap();
+        run_script(&pager, 80, 5, save_again('y')).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "changed\n");
+        std::fs::remove_file(&path).unwrap();
+
+        // The hook can refuse to save the text
+        pager
+            .set_save_path_hook(|_| Err(String::from("Not allowed")))
+            .unwrap();
+        let keys = [KeyCode::Char('s'), KeyCode::Char('a'), KeyCode::Enter].map(KeyEvent::from);
+        let frames = run_script(&pager, 80, 5, keys).unwrap();
+        assert_eq!(frames.last().unwrap().rows()[4], "Not allowed");
+        assert!(!std::path::Path::new("a").exists());
+    }
+
+    #[test]
+    fn scripted_exit_veto() {
+        let pager = Pager::new();
+        pager.set_text("Line\n").unwrap();
+        let mut vetoed = false;
+        pager
+            .set_exit_veto(move |reason| {
+                assert_eq!(reason, ExitReason::UserQuit);
+                if vetoed {
+                    return Ok(());
+                }
+                vetoed = true;
+                Err(String::from("Press q again to quit"))
+            })
+            .unwrap();
+
+        let keys = [KeyCode::Char('q'), KeyCode::Char('q'), KeyCode::Char('j')].map(KeyEvent::from);
+        let frames = run_script(&pager, 40, 5, keys).unwrap();
+        // The pager quits on the second key press so the last key is never pressed
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[1].rows()[4], "Press q again to quit");
+    }
+
+    #[test]
+    fn scripted_interrupt_forwarded() {
+        let pager = Pager::new();
+        pager
+            .set_text((0..20).map(|i| format!("Line {i}\n")).collect::<String>())
+            .unwrap();
+        let interrupts = Arc::new(AtomicUsize::new(0));
+        let interrupts2 = interrupts.clone();
+        pager
+            .set_interrupt_strategy(InterruptStrategy::Forward(Box::new(move || {
+                interrupts2.fetch_add(1, Ordering::SeqCst);
+            })))
+            .unwrap();
+
+        let keys = [
+            KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            KeyEvent::from(KeyCode::Char('j')),
+        ];
+        let frames = run_script(&pager, 20, 5, keys).unwrap();
+        // The pager keeps running after Ctrl+C
+        assert_eq!(frames.last().unwrap().rows()[0], "Line 1");
+        assert_eq!(interrupts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn scripted_unhandled_keys() {
+        let pager = Pager::new();
+        pager.set_text("Line\n").unwrap();
+        let keys = Arc::new(Mutex::new(Vec::new()));
+        let keys2 = keys.clone();
+        pager
+            .set_unhandled_key_callback(move |key| keys2.lock().push(key.code))
+            .unwrap();
+
+        let script = [KeyCode::Char('r'), KeyCode::Char('j'), KeyCode::F(5)].map(KeyEvent::from);
+        run_script(&pager, 20, 5, script).unwrap();
+        // Keys bound to an action are not forwarded
+        assert_eq!(*keys.lock(), [KeyCode::Char('r'), KeyCode::F(5)]);
+    }
+
+    #[test]
+    fn scripted_custom_command() {
+        let pager = Pager::new();
+        pager
+            .set_text((0..20).map(|i| format!("Line {i}\n")).collect::<String>())
+            .unwrap();
+        let pager2 = pager.clone();
+        pager
+            .add_command("s", "refresh", move |ps| {
+                pager2
+                    .set_text(format!("Refreshed at {}\n", ps.upper_mark))
+                    .unwrap();
+            })
+            .unwrap();
+
+        // The command takes precedence over saving the text
+        let keys = [KeyCode::Char('j'), KeyCode::Char('s')].map(KeyEvent::from);
+        let frames = run_script(&pager, 20, 5, keys).unwrap();
+        assert_eq!(frames.last().unwrap().rows()[0], "Refreshed at 1");
+    }
+
+    #[test]
+    fn scripted_cursor_line() {
+        let pager = Pager::new();
+        pager
+            .set_text((0..20).map(|i| format!("Line {i}\n")).collect::<String>())
+            .unwrap();
+        pager.set_cursor_line(true).unwrap();
+        let pager2 = pager.clone();
+        pager
+            .add_command("x", "show", move |ps| {
+                pager2
+                    .send_message(format!("At {:?}", ps.selected_line))
+                    .unwrap();
+            })
+            .unwrap();
+
+        // The text only scrolls once the cursor bar moves past the last row
+        let mut keys = vec![KeyEvent::from(KeyCode::Down); 6];
+        keys.extend(
+            [
+                KeyCode::Up,
+                KeyCode::Enter,
+                KeyCode::PageDown,
+                KeyCode::Char('x'),
+            ]
+            .map(KeyEvent::from),
+        );
+        let frames = run_script(&pager, 20, 5, keys).unwrap();
+        assert_eq!(frames[3].rows()[0], "Line 0");
+        assert_eq!(frames[5].rows()[0], "Line 2");
+        assert_eq!(frames[7].rows()[0], "Line 3");
+        // Enter only picks the line in picker mode
+        assert_eq!(frames[8].rows()[0], "Line 4");
+        // The cursor line is kept on the screen when the text is scrolled
+        assert_eq!(frames[9].rows()[0], "Line 8");
+        assert_eq!(frames[10].rows()[4], "At Some(8)");
+    }
+
+    #[test]
+    fn scripted_header_lines() {
+        let pager = Pager::new();
+        let text = (0..20).map(|i| format!("Row {i}\n")).collect::<String>();
+        pager.set_text(format!("NAME\n{text}")).unwrap();
+        pager.set_header_lines(1).unwrap();
+
+        let keys = [KeyCode::Down, KeyCode::PageDown, KeyCode::PageUp].map(KeyEvent::from);
+        let frames = run_script(&pager, 20, 5, keys).unwrap();
+        assert_eq!(frames[0].rows()[..2], ["NAME", "Row 0"]);
+        // The header stays at the top while the rest of the text scrolls beneath it
+        assert_eq!(frames[1].rows()[..4], ["NAME", "Row 1", "Row 2", "Row 3"]);
+        // A page only counts the rows below the header, so no row is skipped
+        assert_eq!(frames[2].rows()[..4], ["NAME", "Row 4", "Row 5", "Row 6"]);
+        assert_eq!(frames[3].rows()[..4], ["NAME", "Row 1", "Row 2", "Row 3"]);
+    }
+
+    #[test]
+    fn scripted_table() {
+        let pager = Pager::new();
+        pager
+            .push_row(&[Cell::new("ID"), Cell::new("DESCRIPTION")])
+            .unwrap();
+        pager
+            .push_row(&[
+                Cell::new("7").align(Alignment::Right),
+                Cell::new("A description that is too long"),
+            ])
+            .unwrap();
+
+        // Cells are truncated to fit on the screen instead of being wrapped
+        let frames = run_script(&pager, 20, 5, []).unwrap();
+        assert_eq!(
+            frames[0].rows()[..3],
+            ["ID  DESCRIPTION", " 7  A description t…", ""]
+        );
+    }
+
+    #[test]
+    fn scripted_command_prompt() {
+        // Every run takes the configuration out of the pager so a new one is needed each time
+        let run = |line: &str| {
+            let pager = Pager::new();
+            pager
+                .set_text((0..20).map(|i| format!("Line {i}\n")).collect::<String>())
+                .unwrap();
+            let pager2 = pager.clone();
+            pager
+                .add_command("F5", "refresh", move |_| {
+                    pager2.set_text("Refreshed\n").unwrap();
+                })
+                .unwrap();
+            let keys = std::iter::once(':')
+                .chain(line.chars())
+                .map(KeyCode::Char)
+                .chain([KeyCode::Enter])
+                .map(KeyEvent::from);
+            let frames = run_script(&pager, 20, 5, keys).unwrap();
+            frames.last().unwrap().rows().to_vec()
+        };
+
+        assert_eq!(run("5")[0], "Line 4");
+        assert!(run("set colors")[4].starts_with("Unknown option"));
+        assert_eq!(run("refresh")[0], "Refreshed");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn scripted_pipe_prompt() {
+        let text = (0..20).map(|i| format!("Line {i}\n")).collect::<String>();
+        let path = std::env::temp_dir().join(format!("minus-pipe-{}", std::process::id()));
+        let command = format!("cat > {}", path.display());
+        let pager = Pager::new();
+        pager.set_text(&text).unwrap();
+
+        let keys = |first: Option<char>| {
+            first
+                .into_iter()
+                .chain(command.chars())
+                .map(KeyCode::Char)
+                .chain([KeyCode::Enter, KeyCode::Char('x')])
+                .map(KeyEvent::from)
+                .collect::<Vec<_>>()
+        };
+        let frames = run_script(&pager, 20, 5, keys(Some('|'))).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+        // The pager is drawn again once a key is pressed after the command
+        assert_eq!(frames.last().unwrap().rows()[0], "Line 0");
+
+        // Only the lines on the screen
+        pager.set_text(&text).unwrap();
+        pager.send_input(InputEvent::UpdateUpperMark(3)).unwrap();
+        pager
+            .send_input(InputEvent::Pipe(PipeRange::Screen))
+            .unwrap();
+        run_script(&pager, 20, 5, keys(None)).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "Line 3\nLine 4\nLine 5\nLine 6\n"
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "search")]
+    fn scripted_search_prompt() {
+        let pager = Pager::new();
+        pager
+            .set_text((0..20).map(|i| format!("Line {i}\n")).collect::<String>())
+            .unwrap();
+
+        let keys = [
+            KeyCode::Char('/'),
+            KeyCode::Char('1'),
+            KeyCode::Char('2'),
+            KeyCode::Enter,
+        ]
+        .map(KeyEvent::from);
+        let frames = run_script(&pager, 20, 5, keys).unwrap();
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[3].rows()[4], "/12");
+        assert_eq!(frames[4].rows()[0], "Line 12");
+
+        // The prompt is still waiting for input when the script ends
+        assert!(run_script(&pager, 20, 5, [KeyEvent::from(KeyCode::Char('/'))]).is_err());
+    }
+}
@@ -26,6 +26,22 @@ mod fmt_write {
     }
 }
 
+mod push_lines {
+    use crate::{minus_core::commands::Command, Pager};
+
+    #[test]
+    fn join_into_block() {
+        let pager = Pager::new();
+        pager.push_lines(["one", "two"]).unwrap();
+        pager.push_lines(Vec::<String>::new()).unwrap();
+        assert_eq!(
+            pager.rx.try_recv(),
+            Ok(Command::AppendData("one\ntwo\n".to_string()))
+        );
+        assert_eq!(pager.rx.try_recv(), Ok(Command::AppendData(String::new())));
+    }
+}
+
 mod pager_append_str {
     use crate::PagerState;
 
@@ -194,7 +210,7 @@ mod pager_append_str {
                 .collect::<Vec<String>>()
         );
 
-        ps.screen.orig_text = TEST.to_string();
+        ps.screen.orig_text = TEST.into();
         ps.format_lines();
 
         assert_eq!(ps.screen.orig_text, TEST.to_owned());
@@ -223,6 +239,475 @@ mod pager_append_str {
     }
 }
 
+mod line_endings {
+    use crate::{ControlChars, PagerState};
+
+    #[test]
+    fn crlf_is_normalized() {
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.control_chars = ControlChars::Escape;
+        ps.append_str("a\r\nb\r");
+        // The line ending is split between two appends
+        ps.append_str("\nc\rd\r\n");
+        assert_eq!(ps.screen.orig_text, "a\nb\nc\rd\n");
+        assert_eq!(ps.screen.formatted_lines, vec!["a", "b", "c^Md"]);
+        assert_eq!(ps.screen.line_count(), 3);
+    }
+
+    #[test]
+    fn crlf_is_preserved() {
+        let mut ps = PagerState::new().unwrap();
+        ps.normalize_line_endings = false;
+        ps.append_str("a\r\nb\r");
+        ps.append_str("\n");
+        assert_eq!(ps.screen.orig_text, "a\r\nb\r\n");
+    }
+}
+
+mod incremental_format {
+    use crate::PagerState;
+
+    fn chunked_text() -> String {
+        (0..30)
+            .map(|i| format!("Line {i} {}\n", "word ".repeat(i % 7)))
+            .collect()
+    }
+
+    #[test]
+    fn chunked_append_matches_full_format() {
+        let text = chunked_text();
+        let mut ps = PagerState::new().unwrap();
+        ps.cols = 20;
+        for chunk in text.as_bytes().chunks(7) {
+            ps.append_str(std::str::from_utf8(chunk).unwrap());
+        }
+        let appended_rows = ps.screen.formatted_lines.clone();
+        let appended_map = ps.lines_to_row_map.clone();
+
+        ps.format_lines();
+        assert_eq!(ps.screen.formatted_lines, appended_rows);
+        assert_eq!(ps.lines_to_row_map, appended_map);
+    }
+
+    #[test]
+    #[cfg(feature = "search")]
+    fn chunked_append_keeps_search_matches() {
+        let text = chunked_text();
+        let mut ps = PagerState::new().unwrap();
+        ps.cols = 20;
+        // Matches the incomplete words at the end of unterminated lines
+        ps.search_state.search_term = Some(regex::Regex::new("wor\\b").unwrap());
+        for chunk in text.as_bytes().chunks(7) {
+            ps.append_str(std::str::from_utf8(chunk).unwrap());
+        }
+        assert!(ps.search_state.search_idx.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "search")]
+    fn large_text_formatted_in_chunks() {
+        // Enough text to be stored and formatted in several chunks
+        let text: String = (0..20_000).map(|i| format!("Line {i}\n")).collect();
+        let mut ps = PagerState::new().unwrap();
+        ps.line_numbers = crate::LineNumbers::Enabled;
+        ps.search_state.search_term = Some(regex::Regex::new("match").unwrap());
+        ps.append_str(&text);
+        ps.append_str("match\n");
+        let appended_rows = ps.screen.formatted_lines.clone();
+        let appended_search_idx = ps.search_state.search_idx.clone();
+        assert_eq!(
+            appended_search_idx.iter().copied().collect::<Vec<_>>(),
+            [20_000]
+        );
+
+        ps.format_lines();
+        assert_eq!(ps.screen.formatted_lines, appended_rows);
+        assert_eq!(ps.search_state.search_idx, appended_search_idx);
+    }
+}
+
+mod max_lines {
+    use crate::{LineNumbers, PagerState};
+
+    #[test]
+    fn keep_recent_lines() {
+        let mut ps = PagerState::new().unwrap();
+        ps.max_lines = 3;
+        ps.line_numbers = LineNumbers::Enabled;
+        ps.append_str("L1\nL2\nL3\n");
+        ps.upper_mark = 2;
+        ps.jump_list.record(1);
+        ps.append_str("L4\nL5\n");
+
+        assert_eq!(ps.screen.orig_text, "L3\nL4\nL5\n");
+        assert_eq!(ps.screen.line_count(), 3);
+        assert_eq!(
+            ps.screen.formatted_lines,
+            vec!["     3. L3", "     4. L4", "     5. L5"]
+        );
+        assert_eq!(ps.upper_mark, 0);
+        assert_eq!(ps.jump_list.back(0), Some(0));
+
+        // The line numbers stay the same after reformatting
+        ps.format_lines();
+        assert_eq!(ps.screen.formatted_lines[0], "     3. L3");
+    }
+
+    #[test]
+    #[cfg(feature = "search")]
+    fn search_matches_move_up() {
+        let mut ps = PagerState::new().unwrap();
+        ps.max_lines = 2;
+        ps.search_state.search_term = Some(regex::Regex::new("match").unwrap());
+        ps.append_str("match\nline\nmatch\n");
+
+        assert_eq!(ps.screen.orig_text, "line\nmatch\n");
+        assert_eq!(
+            ps.search_state
+                .search_idx
+                .iter()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+    }
+}
+
+mod truncate {
+    use crate::{PagerState, Sign};
+    use crossterm::style::ContentStyle;
+
+    #[test]
+    fn keep_first_lines() {
+        let mut ps = PagerState::new().unwrap();
+        ps.append_str("L1\nL2\nL3\nL4");
+        ps.selected_line = Some(3);
+        ps.line_highlights.push((1..4, ContentStyle::new()));
+        ps.screen
+            .signs
+            .insert(0, Sign::new("+", ContentStyle::new()));
+        ps.screen
+            .signs
+            .insert(2, Sign::new("!", ContentStyle::new()));
+
+        assert!(ps.truncate_lines(2));
+        assert_eq!(ps.screen.orig_text, "L1\nL2\n");
+        assert_eq!(ps.screen.line_count(), 2);
+        assert_eq!(ps.screen.formatted_lines, vec!["+ L1", "  L2"]);
+        assert_eq!(ps.selected_line, Some(1));
+        assert_eq!(ps.line_highlights[0].0, 1..2);
+        assert_eq!(ps.screen.signs.keys().copied().collect::<Vec<_>>(), vec![0]);
+
+        // Text can be appended after the remaining lines
+        ps.append_str("L5\n");
+        assert_eq!(ps.screen.formatted_lines, vec!["+ L1", "  L2", "  L5"]);
+
+        assert!(!ps.truncate_lines(3));
+        assert!(ps.truncate_lines(0));
+        assert!(ps.screen.orig_text.is_empty());
+        assert!(ps.screen.formatted_lines.is_empty());
+        assert!(ps.line_highlights.is_empty());
+    }
+}
+
+mod edit_lines {
+    use crate::{LineNumbers, PagerState};
+
+    /// Check that the incrementally formatted rows are the same as after formatting everything
+    fn assert_formatted_like_full(ps: &mut PagerState) {
+        let rows = ps.screen.formatted_lines.clone();
+        let map = ps.lines_to_row_map.clone();
+        let unterminated = ps.screen.unterminated;
+        #[cfg(feature = "search")]
+        let search_idx = ps.search_state.search_idx.clone();
+        ps.format_lines();
+        assert_eq!(rows, ps.screen.formatted_lines);
+        assert_eq!(map, ps.lines_to_row_map);
+        assert_eq!(unterminated, ps.screen.unterminated);
+        #[cfg(feature = "search")]
+        assert_eq!(search_idx, ps.search_state.search_idx);
+    }
+
+    #[test]
+    fn edit_wrapped_lines() {
+        let mut ps = PagerState::new().unwrap();
+        ps.cols = 10;
+        #[cfg(feature = "search")]
+        {
+            ps.search_state.search_term = Some(regex::Regex::new("match").unwrap());
+        }
+        ps.append_str("one\na line that wraps match\nthree match\nfour\nfive");
+
+        assert!(ps.edit_lines(1..2, "two\n"));
+        assert_eq!(ps.screen.orig_text, "one\ntwo\nthree match\nfour\nfive");
+        assert_formatted_like_full(&mut ps);
+
+        assert!(ps.edit_lines(1..1, "inserted match that wraps\nx\n"));
+        assert_eq!(ps.screen.line_count(), 7);
+        assert_formatted_like_full(&mut ps);
+
+        assert!(ps.edit_lines(0..3, ""));
+        assert_eq!(ps.screen.orig_text, "two\nthree match\nfour\nfive");
+        assert_formatted_like_full(&mut ps);
+
+        // The unterminated last line stays unterminated when a line is inserted before it
+        assert!(ps.edit_lines(3..3, "before five\n"));
+        assert_formatted_like_full(&mut ps);
+        ps.append_str(" and more");
+        assert_eq!(ps.screen.orig_text.lines().last(), Some("five and more"));
+
+        // Lines added after an unterminated line are placed on their own lines
+        assert!(ps.edit_lines(5..5, "last\n"));
+        assert_eq!(ps.screen.orig_text.lines().nth(4), Some("five and more"));
+        assert_eq!(ps.screen.orig_text.lines().nth(5), Some("last"));
+        assert_formatted_like_full(&mut ps);
+
+        assert!(!ps.edit_lines(9..9, ""));
+    }
+
+    #[test]
+    fn line_numbers_follow_edits() {
+        let mut ps = PagerState::new().unwrap();
+        ps.line_numbers = LineNumbers::Enabled;
+        ps.append_str("a\nb\nc\n");
+        ps.selected_line = Some(2);
+        ps.line_highlights
+            .push((2..3, crossterm::style::ContentStyle::new()));
+
+        assert!(ps.edit_lines(0..0, "z\n"));
+        assert_eq!(ps.screen.formatted_lines[3], "     4. c");
+        assert_eq!(ps.selected_line, Some(3));
+        assert_eq!(ps.line_highlights[0].0, 3..4);
+        assert_formatted_like_full(&mut ps);
+
+        // The gutter grows once there are ten lines
+        assert!(ps.edit_lines(1..2, "1\n2\n3\n4\n5\n6\n7\n"));
+        assert_eq!(ps.screen.line_count(), 10);
+        assert_formatted_like_full(&mut ps);
+    }
+}
+
+mod line_provider {
+    use crate::{minus_core::utils::display::write_from_pagerstate, LineProvider, PagerState};
+    use std::{
+        ops::Range,
+        sync::{Arc, Mutex},
+    };
+
+    struct Numbers(Arc<Mutex<Vec<Range<usize>>>>);
+
+    impl LineProvider for Numbers {
+        fn len(&self) -> usize {
+            1_000_000
+        }
+
+        fn lines(&self, range: Range<usize>) -> Vec<String> {
+            self.0.lock().unwrap().push(range.clone());
+            range.map(|n| format!("line {n}")).collect()
+        }
+    }
+
+    #[test]
+    fn fetches_only_displayed_lines() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.line_provider = Some(Box::new(Numbers(requests.clone())));
+        ps.format_lines();
+        assert_eq!(ps.screen.formatted_lines_count(), 1_000_000);
+
+        ps.upper_mark = 500_000;
+        let mut out = Vec::new();
+        write_from_pagerstate(&mut out, &mut ps).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("line 500000"));
+        assert!(out.contains("line 500008"));
+        assert!(!out.contains("line 500009"));
+
+        let fetched: usize = requests.lock().unwrap().iter().map(Range::len).sum();
+        assert!(fetched < 100);
+
+        // Moving within the fetched window does not fetch again
+        ps.upper_mark = 500_002;
+        write_from_pagerstate(&mut Vec::new(), &mut ps).unwrap();
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn shared_lines() {
+        let lines: Arc<[Arc<str>]> = (0..5).map(|n| Arc::from(format!("line {n}"))).collect();
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.line_provider = Some(Box::new(Arc::clone(&lines)));
+        ps.format_lines();
+        assert_eq!(ps.screen.formatted_lines_count(), 5);
+
+        let mut out = Vec::new();
+        write_from_pagerstate(&mut out, &mut ps).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("line 0"));
+        assert!(out.contains("line 4"));
+
+        static STATIC_LINES: &[&str] = &["a", "b", "c"];
+        assert_eq!(STATIC_LINES.lines(1..5), vec!["b", "c"]);
+        assert_eq!(LineProvider::len(&vec!["a".to_string()]), 1);
+    }
+}
+
+mod snapshot {
+    use crate::PagerState;
+
+    fn state() -> PagerState {
+        let mut ps = PagerState::new().unwrap();
+        ps.rows = 5;
+        ps.cols = 8;
+        ps.screen.orig_text = "first line\nsecond\nthird\n4\n5\n".into();
+        ps.format_lines();
+        ps.prompt = "prompt".to_string();
+        ps.format_prompt();
+        ps
+    }
+
+    #[test]
+    fn screen() {
+        let mut ps = state();
+        ps.upper_mark = 1;
+        let snapshot = ps.screen_snapshot();
+        let rows: Vec<&str> = snapshot.lines().collect();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[..4], ["line", "second", "third", "4"]);
+        assert!(rows[4].contains("prompt"));
+
+        ps.show_prompt = false;
+        assert_eq!(ps.screen_snapshot(), "line\nsecond\nthird\n4\n");
+    }
+
+    #[test]
+    fn screen_not_filled() {
+        let mut ps = state();
+        ps.screen.orig_text = "short\n".into();
+        ps.format_lines();
+        // The prompt is still on the last row
+        let snapshot = ps.screen_snapshot();
+        assert!(snapshot.starts_with("short\n\n\n\n"));
+        assert!(snapshot.lines().nth(4).unwrap().contains("prompt"));
+    }
+
+    #[test]
+    fn status_area() {
+        let mut ps = state();
+        ps.status_lines = vec!["a long status".to_string(), "two".to_string()];
+        ps.upper_mark = 1;
+        let snapshot = ps.screen_snapshot();
+        let rows: Vec<&str> = snapshot.lines().collect();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[..2], ["line", "second"]);
+        // The status lines are cut off and padded to the width of the screen
+        assert!(rows[2].contains("a long …"));
+        assert!(!rows[2].contains("status"));
+        assert!(rows[3].contains("two     "));
+        assert!(rows[4].contains("prompt"));
+
+        // At least one row is left for the text
+        ps.status_lines = vec!["status".to_string(); 5];
+        assert_eq!(ps.writable_rows(), 1);
+        assert_eq!(ps.screen_snapshot().lines().count(), 5);
+    }
+
+    #[test]
+    fn truncated_prompt() {
+        let mut ps = state();
+        ps.prompt = "a long prompt".to_string();
+        ps.format_prompt();
+        assert!(ps.displayed_prompt.contains("a long …"));
+
+        assert!(ps.show_full_prompt());
+        let snapshot = ps.screen_snapshot();
+        let rows: Vec<&str> = snapshot.lines().collect();
+        assert_eq!(rows[..2], ["first", "line"]);
+        assert!(rows[2].contains("a long  "));
+        assert!(rows[3].contains("prompt  "));
+
+        // Nothing is shown if the prompt isn't cut off
+        ps.prompt = "short".to_string();
+        assert!(!ps.show_full_prompt());
+        assert_eq!(ps.writable_rows(), 4);
+    }
+
+    #[test]
+    fn buffer() {
+        let mut ps = state();
+        ps.upper_mark = 2;
+        assert_eq!(ps.buffer_snapshot(), "first\nline\nsecond\nthird\n4\n5\n");
+    }
+}
+
+mod reflow {
+    use crate::PagerState;
+
+    #[test]
+    fn keep_top_line_on_resize() {
+        let mut ps = PagerState::new().unwrap();
+        let text = (0..20)
+            .map(|i| format!("Line {i} {}\n", "word ".repeat(10)))
+            .collect::<String>();
+        ps.append_str(&text);
+        // Each line spans exactly one row at 80 columns
+        ps.upper_mark = 5;
+
+        ps.cols = 20;
+        ps.format_lines();
+        assert!(ps.screen.formatted_lines[ps.upper_mark].starts_with("Line 5 "));
+
+        // Scroll to the second row of the line and resize back
+        ps.upper_mark += 1;
+        ps.cols = 80;
+        ps.format_lines();
+        assert_eq!(ps.upper_mark, 5);
+    }
+
+    #[test]
+    fn keep_top_text_on_resize() {
+        let mut ps = PagerState::new().unwrap();
+        ps.cols = 20;
+        let text = (0..20)
+            .map(|i| format!("Line {i} {}\n", "word ".repeat(10)))
+            .collect::<String>();
+        ps.append_str(&text);
+        // At 20 columns, line 5 spans the rows "Line 5 word word", "word word word word" and
+        // "word word word word". Scroll to its last row
+        let first_row = ps.lines_to_row_map.get(5).copied().unwrap();
+        ps.upper_mark = first_row + 2;
+
+        // At 40 columns, the last four words of line 5 are moved to its second row
+        ps.cols = 40;
+        ps.format_lines();
+        let first_row = ps.lines_to_row_map.get(5).copied().unwrap();
+        assert_eq!(ps.upper_mark, first_row + 1);
+        assert_eq!(
+            ps.screen.formatted_lines[ps.upper_mark],
+            "word word word word"
+        );
+    }
+
+    #[test]
+    fn keep_top_line_on_wrap_toggle() {
+        let mut ps = PagerState::new().unwrap();
+        ps.cols = 20;
+        let text = (0..20)
+            .map(|i| format!("Line {i} {}\n", "word ".repeat(10)))
+            .collect::<String>();
+        ps.append_str(&text);
+        ps.upper_mark = ps.lines_to_row_map.get(7).copied().unwrap();
+
+        ps.screen.line_wrapping = false;
+        ps.format_lines();
+        assert_eq!(ps.upper_mark, 7);
+
+        ps.screen.line_wrapping = true;
+        ps.format_lines();
+        assert!(ps.screen.formatted_lines[ps.upper_mark].starts_with("Line 7 "));
+    }
+}
+
 // Test exit callbacks function
 #[cfg(feature = "dynamic_output")]
 #[test]
@@ -237,14 +722,296 @@ fn exit_callback() {
     ps.exit_callbacks.push(Box::new(move || {
         exited_within_callback.store(true, Ordering::Relaxed);
     }));
-    ps.exit();
+    ps.exit(crate::ExitReason::UserQuit);
 
     assert!(exited.load(Ordering::Relaxed));
+    assert_eq!(ps.exit_reason, Some(crate::ExitReason::UserQuit));
+}
+
+#[cfg(feature = "dynamic_output")]
+mod quit_if_one_screen {
+    use crate::{minus_core::commands::Command, PagerState};
+
+    #[test]
+    fn wait_until_finished() {
+        let mut ps = PagerState::new().unwrap();
+        let (tx, rx) = crossbeam_channel::unbounded();
+        tx.send(Command::AppendData("short\n".to_string())).unwrap();
+        tx.send(Command::DataFinished).unwrap();
+
+        ps.wait_for_overflow(&rx, &mut std::io::stdout(), || true)
+            .unwrap();
+        assert!(ps.data_finished);
+        assert!(ps.fits_one_screen());
+    }
+
+    #[test]
+    fn stop_on_overflow() {
+        let mut ps = PagerState::new().unwrap();
+        let (tx, rx) = crossbeam_channel::unbounded();
+        for i in 0..20 {
+            tx.send(Command::AppendData(format!("{i}\n"))).unwrap();
+        }
+
+        // The data is never finished but this still returns as soon as it overflows
+        ps.wait_for_overflow(&rx, &mut std::io::stdout(), || true)
+            .unwrap();
+        assert!(!ps.data_finished);
+        assert_eq!(ps.screen.line_count(), 11);
+    }
+
+    #[test]
+    fn stop_when_no_sender_is_left() {
+        let mut ps = PagerState::new().unwrap();
+        let pager = crate::Pager::new();
+        let app_pager = pager.clone();
+        app_pager.push_str("short\n").unwrap();
+        drop(app_pager);
+
+        // The data is never finished and the channel stays connected through `pager`
+        ps.wait_for_overflow(&pager.rx, &mut std::io::stdout(), || {
+            pager.has_other_clones()
+        })
+        .unwrap();
+        assert!(ps.data_finished);
+        assert_eq!(ps.screen.line_count(), 1);
+    }
+}
+
+mod buffers {
+    use crate::{minus_core::commands::Command, Pager};
+
+    #[test]
+    fn forward_to_buffer() {
+        let pager = Pager::new();
+        let buffer = pager.add_buffer("logs").unwrap();
+        pager.push_str("first\n").unwrap();
+        buffer.push_str("text\n").unwrap();
+        pager.push_str("last\n").unwrap();
+        // The commands of both pagers arrive in the order they were sent
+        assert_eq!(
+            pager.rx.try_iter().collect::<Vec<_>>(),
+            vec![
+                Command::AddBuffer("logs".to_string()),
+                Command::AppendData("first\n".to_string()),
+                Command::InBuffer(
+                    "logs".to_string(),
+                    Box::new(Command::AppendData("text\n".to_string()))
+                ),
+                Command::AppendData("last\n".to_string()),
+            ]
+        );
+    }
+}
+
+mod read_from {
+    use crate::{minus_core::commands::Command, Pager};
+    use std::io::{self, Read};
+
+    /// A reader which returns its chunks one at a time
+    struct Chunks(Vec<&'static [u8]>);
+
+    impl Read for Chunks {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Ok(0);
+            }
+            let chunk = self.0.remove(0);
+            buf[..chunk.len()].copy_from_slice(chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    fn read_text(chunks: Vec<&'static [u8]>) -> Vec<String> {
+        let pager = Pager::new();
+        pager.read_from(Chunks(chunks)).join().unwrap().unwrap();
+        pager
+            .rx
+            .try_iter()
+            .map(|command| match command {
+                Command::AppendData(text) => text,
+                c => panic!("unexpected command {:?}", c),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn partial_lines() {
+        assert_eq!(
+            read_text(vec![b"first li", b"ne\nsecond\n"]),
+            vec!["first li", "ne\nsecond\n"]
+        );
+    }
+
+    #[test]
+    fn split_characters() {
+        // "é" is encoded as 0xC3 0xA9
+        assert_eq!(
+            read_text(vec![b"caf\xC3", b"\xA9\n"]),
+            vec!["caf", "\u{e9}\n"]
+        );
+    }
+
+    #[test]
+    fn source_terminates_last_line() {
+        let pager = Pager::new();
+        pager
+            .read_from_source("web", Chunks(vec![b"first\nla", b"st"]))
+            .join()
+            .unwrap()
+            .unwrap();
+        let commands: Vec<_> = pager.rx.try_iter().collect();
+        assert_eq!(
+            commands,
+            vec![
+                Command::AppendSourceData("web".into(), "first\nla".into()),
+                Command::AppendSourceData("web".into(), "st".into()),
+                Command::AppendSourceData("web".into(), "\n".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_bytes() {
+        assert_eq!(
+            read_text(vec![b"a\xFFb", b"\xC3"]),
+            vec!["a\u{fffd}b", "\u{fffd}"]
+        );
+    }
+}
+
+mod io_write {
+    use crate::{minus_core::commands::Command, Pager};
+    use std::io::{ErrorKind, Write};
+
+    fn appended(pager: &Pager) -> Vec<String> {
+        pager
+            .rx
+            .try_iter()
+            .map(|command| match command {
+                Command::AppendData(text) => text,
+                c => panic!("unexpected command {:?}", c),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_characters() {
+        let mut pager = Pager::new();
+        pager.write_all(b"caf\xC3").unwrap();
+        pager.write_all(b"\xA9\n").unwrap();
+        assert_eq!(appended(&pager), vec!["caf", "\u{e9}\n"]);
+    }
+
+    #[test]
+    fn lossy() {
+        let mut pager = Pager::new();
+        pager.write_all(b"a\xFFb\n").unwrap();
+        assert_eq!(appended(&pager), vec!["a\u{fffd}b\n"]);
+    }
+
+    #[test]
+    fn strict() {
+        let mut pager = Pager::new();
+        pager.set_lossy_utf8(false);
+        pager.write_all(b"ok\xC3").unwrap();
+        assert_eq!(
+            pager.write_all(b"\xFF").unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        // The incomplete character can still be completed
+        pager.write_all(b"\xA9").unwrap();
+        assert_eq!(appended(&pager), vec!["ok", "\u{e9}"]);
+    }
+
+    #[test]
+    fn flush_incomplete_character() {
+        let mut pager = Pager::new();
+        pager.write_all(b"caf\xC3").unwrap();
+        pager.flush().unwrap();
+        assert_eq!(appended(&pager), vec!["caf", "\u{fffd}"]);
+        // Flushing without anything held back appends nothing
+        pager.flush().unwrap();
+        assert!(appended(&pager).is_empty());
+    }
+
+    #[test]
+    fn push_bytes() {
+        let mut pager = Pager::new();
+        let text = "Grüße\n".as_bytes();
+        // The character is completed on a clone and through a different way of appending
+        pager.clone().push_bytes(&text[..3]).unwrap();
+        pager.write_all(&text[3..]).unwrap();
+        assert_eq!(appended(&pager), vec!["Gr", "üße\n"]);
+
+        pager.push_bytes(b"a\xFFb\xE2\x82").unwrap();
+        assert_eq!(appended(&pager), vec!["a\u{fffd}b"]);
+        pager.finish_data().unwrap();
+        assert_eq!(
+            pager.rx.try_iter().collect::<Vec<_>>(),
+            vec![
+                Command::AppendData("\u{fffd}".to_string()),
+                Command::DataFinished
+            ]
+        );
+    }
+}
+
+#[test]
+fn events_from_channel() {
+    use crate::input::EventSource;
+    use crossterm::event::{Event, KeyCode, KeyEvent};
+    use std::time::Duration;
+
+    let (events, mut source) = std::sync::mpsc::channel();
+    let ev = Event::Key(KeyEvent::from(KeyCode::Char('q')));
+    events.send(ev.clone()).unwrap();
+    assert_eq!(source.next_event(Duration::ZERO).unwrap(), Some(ev));
+    assert_eq!(source.next_event(Duration::ZERO).unwrap(), None);
+    // A closed channel just never has any events
+    drop(events);
+    assert_eq!(source.next_event(Duration::ZERO).unwrap(), None);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn async_writer() {
+    use crate::{minus_core::commands::Command, Pager};
+
+    let pager = Pager::new();
+    let mut writer = pager.async_writer();
+    let mut reader: &[u8] = b"first line\n\xC3\xA9\n";
+    tokio::io::copy(&mut reader, &mut writer).await.unwrap();
+
+    assert_eq!(
+        pager.rx.try_recv().unwrap(),
+        Command::AppendData("first line\n\u{e9}\n".to_string())
+    );
+
+    // An incomplete character is only appended once the writer is shut down
+    let mut reader: &[u8] = b"caf\xC3";
+    tokio::io::copy(&mut reader, &mut writer).await.unwrap();
+    assert_eq!(
+        pager.rx.try_recv().unwrap(),
+        Command::AppendData("caf".to_string())
+    );
+    assert!(pager.rx.try_recv().is_err());
+    tokio::io::AsyncWriteExt::shutdown(&mut writer)
+        .await
+        .unwrap();
+    assert_eq!(
+        pager.rx.try_recv().unwrap(),
+        Command::AppendData("\u{fffd}".to_string())
+    );
 }
 
 mod emit_events {
     // Check functions emit correct events on function calls
-    use crate::{minus_core::commands::Command, ExitStrategy, LineNumbers, Pager};
+    use crate::{
+        input::InputEvent, minus_core::commands::Command, ExitStrategy, LineNumbers, MessageLevel,
+        Pager,
+    };
+    use parking_lot::Mutex;
 
     const TEST_STR: &str = "This is sample text";
     #[test]
@@ -282,7 +1049,16 @@ mod emit_events {
         let pager = Pager::new();
         pager.send_message(TEST_STR).unwrap();
         assert_eq!(
-            Command::SendMessage(TEST_STR.to_string()),
+            Command::SendMessage(MessageLevel::Error, TEST_STR.to_string(), None),
+            pager.rx.try_recv().unwrap()
+        );
+
+        let timeout = Some(std::time::Duration::from_secs(1));
+        pager
+            .send_message_with(MessageLevel::Warning, TEST_STR, timeout)
+            .unwrap();
+        assert_eq!(
+            Command::SendMessage(MessageLevel::Warning, TEST_STR.to_string(), timeout),
             pager.rx.try_recv().unwrap()
         );
     }
@@ -298,6 +1074,36 @@ mod emit_events {
         );
     }
 
+    #[test]
+    fn send_input() {
+        let pager = Pager::new();
+        pager.send_input(InputEvent::UpdateUpperMark(10)).unwrap();
+        assert_eq!(
+            Command::UserInput(InputEvent::UpdateUpperMark(10)),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_output() {
+        let pager = Pager::new();
+        pager.set_output(std::io::sink(), 100, 30).unwrap();
+        assert_eq!(
+            Command::SetOutput(Mutex::new(Box::new(std::io::sink())), 100, 30),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_alternate_screen() {
+        let pager = Pager::new();
+        pager.set_alternate_screen(false).unwrap();
+        assert_eq!(
+            Command::SetAlternateScreen(false),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
     #[test]
     fn set_line_numbers() {
         let pager = Pager::new();
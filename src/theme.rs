@@ -0,0 +1,221 @@
+//! Colors and styles of the parts of the pager that minus draws around the text
+//!
+//! See [`Pager::set_theme`](crate::Pager::set_theme) for more info.
+
+use crate::PromptMode;
+use crossterm::style::{Color, ContentStyle, Stylize};
+#[cfg(feature = "search")]
+use crossterm::{
+    style::{Attribute, SetAttribute, SetBackgroundColor, SetForegroundColor, SetUnderlineColor},
+    Command,
+};
+
+/// Styles of everything that minus draws apart from the text itself
+///
+/// This lets the pager match the color scheme of the application. Start from the
+/// [default](Theme::default) theme and change the styles that should look different.
+///
+/// The colors are adapted to what the terminal can display like all other styles. See
+/// [`Pager::set_color_support`](crate::Pager::set_color_support).
+///
+/// # Example
+/// ```
+/// use minus::{Pager, Theme};
+/// use crossterm::style::{Color, ContentStyle, Stylize};
+///
+/// let mut theme = Theme::default();
+/// theme.prompt = ContentStyle::new().with(Color::White).on(Color::DarkBlue);
+/// theme.line_numbers = ContentStyle::new().with(Color::DarkGrey);
+///
+/// let pager = Pager::new();
+/// pager.set_theme(theme).expect("Failed to communicate with the pager");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Theme {
+    /// Style of the prompt line when it displays the prompt
+    pub prompt: ContentStyle,
+    /// Style of the lines of the status area above the prompt
+    ///
+    /// See [`Pager::set_status`](crate::Pager::set_status).
+    pub status: ContentStyle,
+    /// Style of the prompt line when it displays an [error](crate::MessageLevel::Error) message
+    pub message: ContentStyle,
+    /// Style of the prompt line when it displays an [info](crate::MessageLevel::Info) message
+    pub info_message: ContentStyle,
+    /// Style of the prompt line when it displays a [warning](crate::MessageLevel::Warning) message
+    pub warning_message: ContentStyle,
+    /// Style of the prompt line while a search query is typed
+    #[cfg(feature = "search")]
+    pub search_prompt: ContentStyle,
+    /// Style of the prompt line while the user types an input like the path to save the text to
+    pub input_prompt: ContentStyle,
+    /// Style of the line numbers
+    ///
+    /// It is not applied if the application formats the line numbers itself with
+    /// [`Pager::set_line_number_format`](crate::Pager::set_line_number_format).
+    pub line_numbers: ContentStyle,
+    /// Style of the search matches
+    #[cfg(feature = "search")]
+    pub search_match: ContentStyle,
+    /// Style of the search matches on the row that was last moved to with `n` or `p`
+    ///
+    /// This makes the current match stand out among the others. Set it to the same style as
+    /// [`search_match`](Theme::search_match) to draw all the matches alike.
+    #[cfg(feature = "search")]
+    pub current_search_match: ContentStyle,
+    /// Style of the selected line
+    pub selection: ContentStyle,
+    /// Style of the track of the scrollbar
+    ///
+    /// See [`Pager::show_scrollbar`](crate::Pager::show_scrollbar).
+    pub scrollbar: ContentStyle,
+    /// Style of the part of the scrollbar that shows where the screen is in the text
+    pub scrollbar_thumb: ContentStyle,
+    /// Style of the marks on the scrollbar for the rows with search matches
+    #[cfg(feature = "search")]
+    pub scrollbar_search_mark: ContentStyle,
+    /// Style of the marker on the rows past the end of the text
+    ///
+    /// See [`Pager::set_filler`](crate::Pager::set_filler).
+    pub filler: ContentStyle,
+    /// Style of the row between the panes of the split view
+    ///
+    /// See [`InputEvent::SplitView`](crate::input::InputEvent::SplitView).
+    pub split_separator: ContentStyle,
+    /// Style of the removed and changed lines of the old text of a diff
+    ///
+    /// The words that were changed are emphasized by reversing the style. See
+    /// [`Pager::set_diff`](crate::Pager::set_diff).
+    pub diff_removed: ContentStyle,
+    /// Style of the added and changed lines of the new text of a diff
+    pub diff_added: ContentStyle,
+    /// Style of the lines of a unified diff outside of its lines, like the names of the files and
+    /// the headers of the hunks
+    pub diff_header: ContentStyle,
+    /// Style of the marker after the first line of a folded region telling how many lines it hides
+    ///
+    /// See [`InputEvent::ToggleFold`](crate::input::InputEvent::ToggleFold).
+    pub fold: ContentStyle,
+}
+
+impl Theme {
+    pub(crate) const fn set_prompt_style(&mut self, mode: PromptMode, style: ContentStyle) {
+        match mode {
+            PromptMode::Normal => self.prompt = style,
+            PromptMode::Message => self.message = style,
+            #[cfg(feature = "search")]
+            PromptMode::Search => self.search_prompt = style,
+            PromptMode::Input => self.input_prompt = style,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            prompt: ContentStyle::new().with(Color::Grey).on(Color::Black).dim(),
+            status: ContentStyle::new().with(Color::Grey).on(Color::Black).dim(),
+            message: ContentStyle::new()
+                .with(Color::Black)
+                .on(Color::DarkRed)
+                .bold(),
+            info_message: ContentStyle::new().with(Color::Black).on(Color::DarkGreen),
+            warning_message: ContentStyle::new().with(Color::Black).on(Color::DarkYellow),
+            #[cfg(feature = "search")]
+            search_prompt: ContentStyle::new().with(Color::Black).on(Color::DarkYellow),
+            input_prompt: ContentStyle::new().with(Color::Black).on(Color::DarkCyan),
+            line_numbers: ContentStyle::new().bold(),
+            #[cfg(feature = "search")]
+            search_match: ContentStyle::new().reverse(),
+            #[cfg(feature = "search")]
+            current_search_match: ContentStyle::new().with(Color::Black).on(Color::Yellow),
+            selection: ContentStyle::new().reverse(),
+            scrollbar: ContentStyle::new().with(Color::DarkGrey),
+            scrollbar_thumb: ContentStyle::new().reverse(),
+            #[cfg(feature = "search")]
+            scrollbar_search_mark: ContentStyle::new().with(Color::Black).on(Color::DarkYellow),
+            filler: ContentStyle::new().with(Color::DarkBlue),
+            split_separator: ContentStyle::new().with(Color::DarkGrey),
+            diff_removed: ContentStyle::new().with(Color::Red),
+            diff_added: ContentStyle::new().with(Color::Green),
+            diff_header: ContentStyle::new().with(Color::Cyan),
+            fold: ContentStyle::new().with(Color::DarkCyan),
+        }
+    }
+}
+
+/// Get the escape sequences that turn on `style` and turn it back off
+///
+/// Unlike the reset used by [`ContentStyle::apply`], turning the style off only undoes what the
+/// style has set. This keeps the styles of the text surrounding a highlight intact.
+#[cfg(feature = "search")]
+pub fn style_escapes(style: ContentStyle) -> (String, String) {
+    let mut start = String::new();
+    let mut end = String::new();
+    // Writing to a String never fails
+    if let Some(color) = style.foreground_color {
+        SetForegroundColor(color).write_ansi(&mut start).unwrap();
+        SetForegroundColor(Color::Reset)
+            .write_ansi(&mut end)
+            .unwrap();
+    }
+    if let Some(color) = style.background_color {
+        SetBackgroundColor(color).write_ansi(&mut start).unwrap();
+        SetBackgroundColor(Color::Reset)
+            .write_ansi(&mut end)
+            .unwrap();
+    }
+    if let Some(color) = style.underline_color {
+        SetUnderlineColor(color).write_ansi(&mut start).unwrap();
+        SetUnderlineColor(Color::Reset)
+            .write_ansi(&mut end)
+            .unwrap();
+    }
+    for attribute in Attribute::iterator().filter(|a| style.attributes.has(*a)) {
+        SetAttribute(attribute).write_ansi(&mut start).unwrap();
+        if let Some(off) = attribute_off(attribute) {
+            SetAttribute(off).write_ansi(&mut end).unwrap();
+        }
+    }
+    (start, end)
+}
+
+/// The attribute that turns off `attribute`, if there is one
+#[cfg(feature = "search")]
+const fn attribute_off(attribute: Attribute) -> Option<Attribute> {
+    match attribute {
+        Attribute::Bold | Attribute::Dim => Some(Attribute::NormalIntensity),
+        Attribute::Italic | Attribute::Fraktur => Some(Attribute::NoItalic),
+        Attribute::Underlined
+        | Attribute::DoubleUnderlined
+        | Attribute::Undercurled
+        | Attribute::Underdotted
+        | Attribute::Underdashed => Some(Attribute::NoUnderline),
+        Attribute::SlowBlink | Attribute::RapidBlink => Some(Attribute::NoBlink),
+        Attribute::Reverse => Some(Attribute::NoReverse),
+        Attribute::Hidden => Some(Attribute::NoHidden),
+        Attribute::CrossedOut => Some(Attribute::NotCrossedOut),
+        Attribute::Framed | Attribute::Encircled => Some(Attribute::NotFramedOrEncircled),
+        Attribute::OverLined => Some(Attribute::NotOverLined),
+        _ => None,
+    }
+}
+
+#[cfg(all(test, feature = "search"))]
+mod tests {
+    use super::style_escapes;
+    use crossterm::style::{Attribute, Color, ContentStyle, Stylize};
+
+    #[test]
+    fn escapes_of_style() {
+        let (start, end) = style_escapes(ContentStyle::new().reverse());
+        assert_eq!(start, Attribute::Reverse.to_string());
+        assert_eq!(end, Attribute::NoReverse.to_string());
+
+        let (start, end) =
+            style_escapes(ContentStyle::new().with(Color::Red).on(Color::Blue).bold());
+        assert_eq!(start, "\x1b[38;5;9m\x1b[48;5;12m\x1b[1m");
+        assert_eq!(end, "\x1b[39m\x1b[49m\x1b[22m");
+    }
+}
@@ -0,0 +1,206 @@
+//! Provides following a file as it changes, like `tail -F` does
+//!
+//! See [`Pager::watch_file`] for more info.
+
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::mpsc,
+};
+
+use notify::{
+    event::{EventKind, ModifyKind},
+    RecommendedWatcher, RecursiveMode, Watcher,
+};
+
+use crate::{error::MinusError, Pager};
+
+/// Keeps the text of a pager in sync with a file
+///
+/// This is returned by [`Pager::watch_file`]. The file stops being watched once this is dropped.
+pub struct FileWatcher {
+    // Dropping the watcher closes the channel of events, which ends the thread reading the file
+    _watcher: RecommendedWatcher,
+}
+
+impl FileWatcher {
+    pub(crate) fn new(pager: Pager, path: &Path) -> Result<Self, MinusError> {
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::env::current_dir()
+                .map_err(MinusError::ReadInput)?
+                .join(path)
+        };
+        let mut follower = Follower {
+            pager,
+            file: Some(File::open(&path).map_err(MinusError::ReadInput)?),
+            path,
+            offset: 0,
+        };
+        follower.read_new()?;
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        // The directory is watched instead of the file itself so that the file being replaced,
+        // like when logs are rotated, is noticed too
+        let dir = follower.path.parent().unwrap_or(&follower.path);
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+        std::thread::spawn(move || {
+            for event in rx {
+                let Ok(event) = event else { continue };
+                if !event.paths.contains(&follower.path) {
+                    continue;
+                }
+                let result = match event.kind {
+                    EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_)) => {
+                        follower.reopen()
+                    }
+                    EventKind::Modify(_) => follower.read_new(),
+                    _ => Ok(()),
+                };
+                // The pager might have already quit so nothing can be done if sending fails
+                if let Err(MinusError::ReadInput(e)) = result {
+                    let _ = follower
+                        .pager
+                        .send_message(format!("Failed to read {}: {e}", follower.path.display()));
+                }
+            }
+        });
+        Ok(Self { _watcher: watcher })
+    }
+}
+
+/// Reads what gets written to a file into a pager
+struct Follower {
+    pager: Pager,
+    path: PathBuf,
+    /// The file that is being read. This is [`None`] while no file exists at `path`.
+    file: Option<File>,
+    /// Number of bytes that have been read from `file`
+    offset: u64,
+}
+
+impl Follower {
+    /// Append whatever was written to the file since it was last read
+    ///
+    /// If the file got shorter than what was read, it has been truncated and the text is
+    /// reloaded from the start of the file.
+    fn read_new(&mut self) -> Result<(), MinusError> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        let len = file.metadata().map_err(MinusError::ReadInput)?.len();
+        if len < self.offset {
+            file.seek(SeekFrom::Start(0))
+                .map_err(MinusError::ReadInput)?;
+            self.offset = 0;
+            self.pager.set_text("")?;
+        }
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf).map_err(MinusError::ReadInput)?;
+        if !buf.is_empty() {
+            self.offset += buf.len() as u64;
+            self.pager.push_bytes(&buf)?;
+        }
+        Ok(())
+    }
+
+    /// Switch over to the file which now exists at the path
+    ///
+    /// What was still written to the previous file is read first. The text of the new file is
+    /// appended after it.
+    fn reopen(&mut self) -> Result<(), MinusError> {
+        self.read_new()?;
+        self.offset = 0;
+        self.file = match File::open(&self.path) {
+            Ok(file) => Some(file),
+            // The file was moved away and wasn't replaced yet
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(MinusError::ReadInput(e)),
+        };
+        self.read_new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Follower;
+    use crate::{minus_core::commands::Command, Pager};
+    use std::{fs::File, io::Write, path::PathBuf};
+
+    fn follower(name: &str) -> (Follower, PathBuf) {
+        let path =
+            std::env::temp_dir().join(format!("minus-watch-test-{}-{name}", std::process::id()));
+        std::fs::write(&path, "first\n").unwrap();
+        let follower = Follower {
+            pager: Pager::new(),
+            file: Some(File::open(&path).unwrap()),
+            path: path.clone(),
+            offset: 0,
+        };
+        (follower, path)
+    }
+
+    fn commands(follower: &Follower) -> Vec<Command> {
+        follower.pager.rx.try_iter().collect()
+    }
+
+    fn append(path: &PathBuf, text: &str) {
+        let mut file = std::fs::OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn append_and_truncate() {
+        let (mut follower, path) = follower("truncate");
+        follower.read_new().unwrap();
+        append(&path, "second\n");
+        follower.read_new().unwrap();
+        follower.read_new().unwrap();
+        assert_eq!(
+            commands(&follower),
+            vec![
+                Command::AppendData("first\n".to_string()),
+                Command::AppendData("second\n".to_string()),
+            ]
+        );
+
+        std::fs::write(&path, "new\n").unwrap();
+        follower.read_new().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            commands(&follower),
+            vec![
+                Command::SetData(String::new()),
+                Command::AppendData("new\n".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn follow_replaced_file() {
+        let (mut follower, path) = follower("replace");
+        follower.read_new().unwrap();
+        append(&path, "last\n");
+        let rotated = path.with_extension("1");
+        std::fs::rename(&path, &rotated).unwrap();
+        follower.reopen().unwrap();
+        assert!(follower.file.is_none());
+
+        std::fs::write(&path, "rotated\n").unwrap();
+        follower.reopen().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&rotated).unwrap();
+        assert_eq!(
+            commands(&follower),
+            vec![
+                Command::AppendData("first\n".to_string()),
+                Command::AppendData("last\n".to_string()),
+                Command::AppendData("rotated\n".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,200 @@
+#![cfg(any(feature = "static_output", feature = "dynamic_output"))]
+
+use std::{
+    io::{self, Write},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use minus::{ExitReason, ExitStrategy, Pager};
+
+/// Output which is shared with the test so that it can be inspected afterwards
+#[derive(Clone, Default)]
+struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SharedOutput {
+    fn contains(&self, text: &str) -> bool {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).contains(text)
+    }
+}
+
+/// Page some text, quit right away and check that the text was displayed
+fn run_session(session: usize, run: fn(Pager) -> Result<ExitReason, minus::MinusError>) {
+    let output = SharedOutput::default();
+    let (events, source) = mpsc::channel();
+    events
+        .send(Event::Key(KeyEvent::from(KeyCode::Char('q'))))
+        .unwrap();
+
+    let pager = Pager::new();
+    pager.set_exit_strategy(ExitStrategy::PagerQuit).unwrap();
+    pager.set_output(output.clone(), 40, 10).unwrap();
+    pager.set_input(source).unwrap();
+    pager.set_text(format!("Session {session}\n")).unwrap();
+    #[cfg(feature = "static_output")]
+    pager.set_run_no_overflow(true).unwrap();
+    assert_eq!(run(pager).unwrap(), ExitReason::UserQuit);
+
+    let written = String::from_utf8_lossy(&output.0.lock().unwrap()).into_owned();
+    assert!(
+        written.contains(&format!("Session {session}")),
+        "{:?}",
+        written
+    );
+}
+
+#[test]
+fn one_after_another() {
+    for session in 0..3 {
+        #[cfg(feature = "static_output")]
+        run_session(session, minus::page_all);
+        #[cfg(feature = "dynamic_output")]
+        run_session(session, |pager| minus::dynamic_paging(pager).join());
+    }
+}
+
+#[test]
+#[cfg(feature = "dynamic_output")]
+fn at_the_same_time() {
+    let sessions: Vec<_> = (0..2)
+        .map(|session| {
+            let output = SharedOutput::default();
+            let (events, source) = mpsc::channel();
+            let pager = Pager::new();
+            pager.set_exit_strategy(ExitStrategy::PagerQuit).unwrap();
+            pager.set_output(output.clone(), 40, 10).unwrap();
+            pager.set_input(source).unwrap();
+            pager.set_text(format!("Session {session}\n")).unwrap();
+            let handle = minus::dynamic_paging(pager);
+            (output, events, handle)
+        })
+        .collect();
+
+    // Both pagers display their text before any of them quits
+    for (session, (output, ..)) in sessions.iter().enumerate() {
+        let text = format!("Session {session}");
+        for _ in 0..500 {
+            if output.contains(&text) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(output.contains(&text), "{:?} was never displayed", text);
+    }
+    for (_, events, handle) in sessions {
+        events
+            .send(Event::Key(KeyEvent::from(KeyCode::Char('q'))))
+            .unwrap();
+        assert_eq!(handle.join().unwrap(), ExitReason::UserQuit);
+    }
+}
+
+#[test]
+#[cfg(feature = "dynamic_output")]
+fn stopped_by_the_application() {
+    let output = SharedOutput::default();
+    let (_events, source) = mpsc::channel::<Event>();
+    let exited = Arc::new(Mutex::new(false));
+    let pager = Pager::new();
+    pager.set_output(output.clone(), 40, 10).unwrap();
+    pager.set_input(source).unwrap();
+    pager.set_text("Stop me\n").unwrap();
+    let exited2 = exited.clone();
+    pager
+        .add_exit_callback(Box::new(move || *exited2.lock().unwrap() = true))
+        .unwrap();
+
+    // The process isn't exited even though the exit strategy is ProcessQuit
+    let handle = minus::dynamic_paging(pager);
+    assert!(handle.is_running());
+    for _ in 0..500 {
+        if output.contains("Stop me") {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    handle.stop().unwrap();
+    assert_eq!(handle.join().unwrap(), ExitReason::Stopped);
+    assert!(*exited.lock().unwrap());
+}
+
+#[test]
+#[cfg(feature = "static_output")]
+fn text_fits_one_screen() {
+    let output = SharedOutput::default();
+    let pager = Pager::new();
+    pager.set_exit_strategy(ExitStrategy::PagerQuit).unwrap();
+    pager.set_output(output.clone(), 40, 10).unwrap();
+    pager.set_input(mpsc::channel::<Event>().1).unwrap();
+    pager.set_text("Short\n").unwrap();
+    assert_eq!(minus::page_all(pager).unwrap(), ExitReason::FitsOneScreen);
+    assert!(output.contains("Short"));
+}
+
+#[test]
+#[cfg(feature = "dynamic_output")]
+fn interrupted_by_the_user() {
+    let (events, source) = mpsc::channel();
+    events
+        .send(Event::Key(KeyEvent::new(
+            KeyCode::Char('c'),
+            KeyModifiers::CONTROL,
+        )))
+        .unwrap();
+    let pager = Pager::new();
+    pager.set_exit_strategy(ExitStrategy::PagerQuit).unwrap();
+    pager.set_output(SharedOutput::default(), 40, 10).unwrap();
+    pager.set_input(source).unwrap();
+    pager.set_text("Interrupt me\n").unwrap();
+    assert_eq!(
+        minus::dynamic_paging(pager).join().unwrap(),
+        ExitReason::Interrupted
+    );
+}
+
+#[test]
+#[cfg(feature = "testing")]
+fn line_picked_by_the_user() {
+    let pager = Pager::new();
+    pager.set_text("red\ngreen\nblue\nyellow\n").unwrap();
+    pager.set_picker_mode(true).unwrap();
+    let pager2 = pager.clone();
+    pager
+        .add_command("x", "show", move |ps| {
+            pager2
+                .send_message(format!("At {:?}", ps.selected_line))
+                .unwrap();
+        })
+        .unwrap();
+
+    // Each key is classified based on where the cursor bar was after the previous one
+    let keys = [
+        KeyCode::Down,
+        KeyCode::Down,
+        KeyCode::Down,
+        KeyCode::Up,
+        KeyCode::Char('x'),
+        KeyCode::Enter,
+        KeyCode::Enter,
+        KeyCode::Down,
+    ]
+    .map(KeyEvent::from);
+    let frames = minus::testing::run_script(&pager, 40, 10, keys).unwrap();
+    assert_eq!(frames[5].rows()[9], "At Some(2)");
+    // The first Enter clears the message and the second one picks the line and quits, so the
+    // last key is never pressed
+    assert_eq!(frames.len(), 8);
+}